@@ -0,0 +1,153 @@
+//! Minimal reader showing a few hardcoded items.
+//!
+//! Run with `cargo run --example static_items`.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use ratatui::crossterm::event::{self, Event as CrosstermEvent, KeyCode};
+use simple_rss_lib::{
+    Error,
+    app::{App, AppConfig},
+    data::{ContentLoader, Fetcher, Item, ItemStore, RefreshError},
+    event::{Event, EventBus, KeyboardEvent},
+};
+
+const TICK_FPS: u32 = 30;
+
+#[derive(Clone)]
+struct StaticLoader {
+    items: Arc<Mutex<Vec<Item>>>,
+    version: Arc<Mutex<u16>>,
+}
+
+impl ItemStore for StaticLoader {
+    type Guard<'a> = MutexGuard<'a, Vec<Item>>;
+
+    fn get_items(&self) -> Self::Guard<'_> {
+        self.items.lock().unwrap()
+    }
+
+    fn get_version(&self) -> u16 {
+        *self.version.lock().unwrap()
+    }
+
+    fn set_read_by_id(&mut self, id: &str, read: bool) {
+        let mut items = self.items.lock().unwrap();
+        if let Some(item) = items.iter_mut().find(|it| it.id == id) {
+            item.read = read;
+            *self.version.lock().unwrap() += 1;
+        }
+    }
+}
+
+impl Fetcher for StaticLoader {
+    async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+        Ok(())
+    }
+}
+
+impl ContentLoader for StaticLoader {
+    async fn load_item(&self, item: &Item) -> simple_rss_lib::Result<String> {
+        Ok(format!(
+            "<h1>Hello!</h1><p>This is the content of <a href=\"{}\">the item</a>.</p>",
+            item.link
+        ))
+    }
+
+    async fn download(
+        &self,
+        _item: &Item,
+        _dir: &Path,
+        _progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> simple_rss_lib::Result<PathBuf> {
+        Err(Error::Storage("downloads are not supported".to_string()))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let items = (1..=3)
+        .map(|idx| Item {
+            id: idx.to_string(),
+            channel_name: "Example".to_string(),
+            title: format!("Item number {idx}"),
+            author: None,
+            description: None,
+            pub_date: None,
+            link: format!("https://example.com/{idx}"),
+            enclosure: None,
+            alternate_links: vec![],
+            read: false,
+            starred: false,
+            tags: vec![],
+            hidden: None,
+        })
+        .collect();
+    let loader = StaticLoader {
+        items: Arc::new(Mutex::new(items)),
+        version: Arc::new(Mutex::new(0)),
+    };
+
+    let mut event_bus = EventBus::new();
+
+    // Forward keyboard input and resizes.
+    let sender = event_bus.get_sender();
+    thread::spawn(move || {
+        while let Ok(evt) = event::read() {
+            let key = match evt {
+                CrosstermEvent::Key(key) => key,
+                CrosstermEvent::Resize(width, height) => {
+                    sender.send(Event::Resize(width, height));
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let event = match key.code {
+                KeyCode::Up => KeyboardEvent::Up,
+                KeyCode::Down => KeyboardEvent::Down,
+                KeyCode::Left => KeyboardEvent::Left,
+                KeyCode::Right => KeyboardEvent::Right,
+                KeyCode::Enter => KeyboardEvent::Enter,
+                KeyCode::Char(' ') => KeyboardEvent::Space,
+                KeyCode::Esc => KeyboardEvent::Back,
+                _ => continue,
+            };
+            sender.send(Event::Keyboard(event));
+        }
+    });
+
+    // Ticks drive the animations.
+    let sender = event_bus.get_sender();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / TICK_FPS);
+        loop {
+            interval.tick().await;
+            sender.send(Event::Tick);
+        }
+    });
+
+    let config = AppConfig {
+        disable_browser_open: true,
+        ..Default::default()
+    };
+    let mut app = App::new(config, event_bus.get_sender(), loader, TICK_FPS);
+
+    let mut terminal = ratatui::init();
+    while let Some(event) = event_bus.next().await {
+        let state = app.handle_event(&event);
+        if app.is_dirty() {
+            terminal.draw(|f| app.draw(f)).unwrap();
+        }
+
+        if !state.is_handled() && event == Event::Keyboard(KeyboardEvent::Back) {
+            break;
+        }
+    }
+    ratatui::restore();
+}
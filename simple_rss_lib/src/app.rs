@@ -1,15 +1,47 @@
+//! The complete reader application.
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
-    widgets::Paragraph,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Paragraph, Wrap},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    components::*,
-    data::{Loader, RefreshStatus},
+    clipboard::Clipboard,
+    components::{help::key_label, item_list::Grouping, *},
+    data::{ContentLoader, Fetcher, Item, ItemStore, Loader},
     event::*,
+    render::{Format, RenderOptions},
 };
 
+// Width of the item list in percent of the screen.
+const DEFAULT_LIST_WIDTH: u16 = 33;
+const MIN_LIST_WIDTH: u16 = 15;
+const MAX_LIST_WIDTH: u16 = 85;
+const LIST_WIDTH_STEP: i16 = 5;
+
+// Below this terminal width only the focused pane is shown.
+const NARROW_WIDTH: u16 = 60;
+
+// Smaller terminals only show that they are too small.
+const MIN_WIDTH: u16 = 24;
+const MIN_HEIGHT: u16 = 6;
+
+// Opening more items in the browser at once has to be confirmed.
+const OPEN_ALL_CONFIRM: usize = 10;
+
+/// Actions that have to be confirmed before they are done.
+enum ConfirmAction {
+    MarkAllRead,
+    OpenAll,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Focus {
     ItemList,
@@ -17,88 +49,525 @@ enum Focus {
     Help,
 }
 
+/// Components that have to be drawn again on the next frame.
+#[derive(Debug, Clone, Copy)]
+struct Dirty {
+    item_list: bool,
+    content: bool,
+    status_bar: bool,
+    overlay: bool,
+}
+
+impl Dirty {
+    fn all() -> Self {
+        Self {
+            item_list: true,
+            content: true,
+            status_bar: true,
+            overlay: true,
+        }
+    }
+
+    fn none() -> Self {
+        Self {
+            item_list: false,
+            content: false,
+            status_bar: false,
+            overlay: false,
+        }
+    }
+}
+
+/// Part of the UI state that can be persisted between sessions.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// Id of the selected item in the item list.
+    pub selected_item: Option<String>,
+
+    /// Scroll offsets of opened items, by item id.
+    pub scroll_offsets: HashMap<String, usize>,
+
+    /// When the state was saved. Items published later are marked as new in the next session.
+    pub last_seen: Option<DateTime<Utc>>,
+
+    /// Width of the item list in percent, if it was changed with the keyboard.
+    pub list_width: Option<u16>,
+}
+
+/// Configuration of the [`App`]. Default enables all the core features.
 #[derive(Default)]
 pub struct AppConfig {
     pub item_list_custom_empty_msg: Option<Paragraph<'static>>,
     pub disable_read_status: bool,
     pub disable_channel_names: bool,
     pub disable_browser_open: bool,
+
+    /// Don't write links of the opened item as OSC 8 hyperlinks, for terminals that show
+    /// the escape sequences instead of making the links clickable.
+    pub disable_hyperlinks: bool,
+
+    /// Number of description lines shown under each item title. Descriptions are hidden if 0.
+    pub description_lines: usize,
+
+    /// Command used to open links, e.g. `firefox --new-tab {url}`. `{url}` is replaced
+    /// with the link, otherwise the link is appended. Default browser is used if not set.
+    pub open_command: Option<String>,
+
+    /// Open command is a program that runs in the terminal, like `w3m {url}`. Links are
+    /// then sent with [`Event::OpenInTerminal`] and the embedder has to hand the terminal
+    /// over to the command.
+    pub terminal_browser: bool,
+
+    /// Directory where item attachments are downloaded. Downloads are disabled if not set.
+    pub download_dir: Option<PathBuf>,
+
+    /// Shell command used to read articles aloud. Narration is disabled if not set.
+    pub narration_command: Option<String>,
+
+    /// Directory where opened articles are exported as Markdown. Exporting is disabled if not set.
+    pub export_dir: Option<PathBuf>,
+
+    /// Items published after this time are marked as new. If not set, items published
+    /// since the previous session are marked once the UI state is restored.
+    pub new_since: Option<DateTime<Utc>>,
+    pub disable_new_badge: bool,
+
+    /// Keys bound to each action, shown in help. Default bindings are shown if not set.
+    pub key_labels: Option<HashMap<KeyboardEvent, String>>,
+
+    /// Width of the item list in percent of the screen. Defaults to one third. When set, it's
+    /// used instead of the width restored with [`App::restore_ui_state`].
+    pub list_width: Option<u16>,
+
+    /// Sections the item list is grouped into, e.g. by day.
+    pub grouping: Grouping,
+
+    /// How long snoozed items are hidden. Defaults to one day.
+    pub snooze: Option<TimeDelta>,
+
+    /// How the opened item is rendered.
+    pub render_options: RenderOptions,
+
+    /// How long toast messages are shown.
+    pub toast: crate::components::toast::Config,
 }
 
-pub struct App<L: Loader> {
+/// Reader with an item list on the left and content of the opened item on the right.
+///
+/// Items are kept in the [`ItemStore`], refreshed with the [`Fetcher`] and their content is
+/// loaded with the [`ContentLoader`]. [`App::new`] takes a single [`Loader`] for all three.
+pub struct App<S: ItemStore, F = S, C: ContentLoader = S> {
     focus: Focus,
 
     // Focus before help is opened
     prev_focus: Option<Focus>,
 
-    item_list: ItemList<L>,
+    data_loader: S,
+    fetcher: F,
+    event_tx: EventSender,
+    refresh_error_message: String,
+    export_dir: Option<PathBuf>,
+    clipboard: Clipboard,
+    disable_read_status: bool,
+    disable_browser_open: bool,
+    // Whether the new badge is placed by the time of the previous session.
+    new_since_last_seen: bool,
+
+    // Width of the item list in percent. Resized width is kept in the UI state, but it's not
+    // restored if the width is configured.
+    list_width: u16,
+    list_resized: bool,
+    list_width_configured: bool,
+    // Whether the item list is hidden and content takes the whole width.
+    fullscreen: bool,
+
+    item_list: ItemList<S, C>,
     content: Content,
     toast: Toast,
     help: Help,
+    confirm: Confirm<ConfirmAction>,
+    error_panel: ErrorPanel,
+    stats_panel: StatsPanel,
+    channel_panel: ChannelPanel,
+    toc: Toc,
+    narrator: Narrator,
+    status_bar: StatusBar,
+
+    // Whether key presses are currently typed into the content, instead of triggering actions.
+    capturing_input: bool,
+
+    dirty: Dirty,
+    item_list_cache: DrawCache,
+    content_cache: DrawCache,
 }
 
-impl<L: Loader + Clone + Send + 'static> App<L> {
+impl<L: Loader + Clone + Send + Sync + 'static> App<L> {
     pub fn new(
         config: AppConfig,
         event_sender: EventSender,
         data_loader: L,
         tick_fps: u32,
     ) -> Self {
-        // Start refreshing
-        let mut loader = data_loader.clone();
-        let sender = event_sender.clone();
-        tokio::spawn(async move {
-            sender.send(Event::Toast(ToastEvent::Loading("Refreshing".to_string())));
-            let status = loader.refresh().await;
-            match status {
-                RefreshStatus::Ok => sender.send(Event::Toast(ToastEvent::Hide)),
-                RefreshStatus::Error => sender.send(Event::Toast(ToastEvent::Error(
-                    "Failed to refresh data!".to_string(),
-                ))),
+        let fetcher = data_loader.clone();
+        let content_loader = data_loader.clone();
+        Self::with_parts(
+            config,
+            event_sender,
+            data_loader,
+            fetcher,
+            content_loader,
+            tick_fps,
+        )
+    }
+}
+
+impl<S, F, C> App<S, F, C>
+where
+    S: ItemStore + Clone + Send + 'static,
+    F: Fetcher + Clone + Send + 'static,
+    C: ContentLoader + Clone + Send + Sync + 'static,
+{
+    /// Reader with the parts provided separately, e.g. an in-memory store refreshed by a
+    /// fetcher that writes to it.
+    pub fn with_parts(
+        config: AppConfig,
+        event_sender: EventSender,
+        data_loader: S,
+        fetcher: F,
+        content_loader: C,
+        tick_fps: u32,
+    ) -> Self {
+        let refresh_error_message =
+            match key_label(config.key_labels.as_ref(), KeyboardEvent::RefreshErrors) {
+                Some(key) => format!("Failed to refresh! See {key}"),
+                None => "Failed to refresh data!".to_string(),
             };
-        });
 
-        Self {
+        let app = Self {
             focus: Focus::ItemList,
             prev_focus: None,
             item_list: ItemList::new(
                 true,
-                event_sender,
+                event_sender.clone(),
                 data_loader.clone(),
+                content_loader,
                 crate::components::item_list::Config {
                     custom_empty_list_msg: config.item_list_custom_empty_msg,
                     disable_read_status: config.disable_read_status,
                     disable_channel_names: config.disable_channel_names,
                     disable_browser_open: config.disable_browser_open,
+                    description_lines: config.description_lines,
+                    open_command: config.open_command.clone(),
+                    terminal_browser: config.terminal_browser,
+                    download_dir: config.download_dir.clone(),
+                    new_since: config.new_since.filter(|_| !config.disable_new_badge),
+                    key_labels: config.key_labels.clone(),
+                    grouping: config.grouping,
+                    snooze: config.snooze.unwrap_or(TimeDelta::days(1)),
+                },
+            ),
+            content: Content::new(
+                false,
+                event_sender.clone(),
+                crate::components::content::Config {
+                    render_options: config.render_options,
+                    hyperlinks: !config.disable_hyperlinks,
                 },
             ),
-            content: Content::new(false),
-            toast: Toast::new(tick_fps),
-            help: Help::new(config.disable_read_status, config.disable_browser_open),
+            toast: Toast::new(tick_fps, config.toast),
+            help: Help::new(&crate::components::help::Config {
+                disable_read_status: config.disable_read_status,
+                disable_browser_open: config.disable_browser_open,
+                enable_download: config.download_dir.is_some(),
+                enable_narration: config.narration_command.is_some(),
+                enable_export: config.export_dir.is_some(),
+                key_labels: config.key_labels,
+            }),
+            confirm: Confirm::new(),
+            error_panel: ErrorPanel::new(),
+            stats_panel: StatsPanel::new(),
+            channel_panel: ChannelPanel::new(),
+            toc: Toc::new(),
+            status_bar: StatusBar::new(),
+            narrator: Narrator::new(config.narration_command, event_sender.clone()),
+            data_loader,
+            fetcher,
+            event_tx: event_sender,
+            refresh_error_message,
+            export_dir: config.export_dir,
+            clipboard: Clipboard::new(),
+            disable_read_status: config.disable_read_status,
+            disable_browser_open: config.disable_browser_open,
+            new_since_last_seen: config.new_since.is_none() && !config.disable_new_badge,
+            list_width: clamp_list_width(config.list_width.unwrap_or(DEFAULT_LIST_WIDTH)),
+            list_resized: false,
+            list_width_configured: config.list_width.is_some(),
+            fullscreen: false,
+            capturing_input: false,
+            dirty: Dirty::all(),
+            item_list_cache: DrawCache::default(),
+            content_cache: DrawCache::default(),
+        };
+        app.refresh();
+        app
+    }
+
+    /// Refresh all the channels in the background. Called on start.
+    pub fn refresh(&self) {
+        let mut loader = self.fetcher.clone();
+        let sender = self.event_tx.clone();
+        let error_message = self.refresh_error_message.clone();
+        tokio::spawn(async move {
+            sender.send(Event::Toast(ToastEvent::Loading("Refreshing".to_string())));
+            sender.send(Event::RefreshStarted);
+            let errors = match loader.refresh().await {
+                Ok(()) => {
+                    sender.send(Event::Toast(ToastEvent::Hide));
+                    vec![]
+                }
+                Err(errors) => {
+                    sender.send(Event::Toast(ToastEvent::Error(error_message)));
+                    errors
+                }
+            };
+            sender.send(Event::RefreshFinished { errors });
+        });
+    }
+
+    /// Select the next unread item in the list, if there is one.
+    pub fn select_next_unread(&mut self) {
+        if self.item_list.select_next_unread() {
+            self.dirty.item_list = true;
         }
     }
 
+    /// Open the selected item in the content pane, regardless of focus.
+    pub fn open_selected(&mut self) {
+        self.item_list.open_selected();
+    }
+
+    /// Current UI state. Scroll offsets are kept only for items that still exist.
+    pub fn ui_state(&mut self) -> UiState {
+        let mut scroll_offsets = self.content.scroll_offsets().clone();
+        {
+            let items = self.data_loader.get_items();
+            scroll_offsets.retain(|id, _| items.iter().any(|it| it.id == *id));
+        }
+
+        UiState {
+            selected_item: self.item_list.selected_id(),
+            scroll_offsets,
+            last_seen: Some(Utc::now()),
+            list_width: self.list_resized.then_some(self.list_width),
+        }
+    }
+
+    /// Restore UI state from a previous session.
+    pub fn restore_ui_state(&mut self, state: UiState) {
+        if let Some(id) = &state.selected_item {
+            self.item_list.select_id(id);
+        }
+        self.content.set_scroll_offsets(state.scroll_offsets);
+        if self.new_since_last_seen {
+            self.item_list.set_new_since(state.last_seen);
+        }
+        if let Some(width) = state.list_width
+            && !self.list_width_configured
+        {
+            self.list_width = clamp_list_width(width);
+            self.list_resized = true;
+        }
+        self.dirty = Dirty::all();
+    }
+
+    /// Item selected in the item list.
+    pub fn selected_item(&self) -> Option<Item> {
+        let id = self.item_list.selected_id()?;
+        let items = self.data_loader.get_items();
+        items.iter().find(|it| it.id == id).cloned()
+    }
+
+    /// Opened article exported in the given format.
+    pub fn opened_article(&self, format: Format) -> Option<String> {
+        self.content.export(format)
+    }
+
+    /// Draw everything again on the next frame, e.g. after the terminal was used by
+    /// another program.
+    pub fn invalidate(&mut self) {
+        self.dirty = Dirty::all();
+        self.item_list_cache = DrawCache::default();
+        self.content_cache = DrawCache::default();
+    }
+
+    /// Returns true if anything changed since the last draw and the app should be drawn again.
+    pub fn is_dirty(&self) -> bool {
+        let dirty = self.dirty;
+        dirty.item_list
+            || dirty.content
+            || dirty.status_bar
+            || dirty.overlay
+            || self.item_list.is_stale()
+    }
+
+    /// Returns true while something is animated or timed, in which case [`Event::Tick`]s
+    /// should be sent at the full rate. Otherwise they can be sent rarely, or not at all.
+    pub fn is_animating(&self) -> bool {
+        self.toast.is_active() || self.content.is_animating() || self.status_bar.is_animating()
+    }
+
     pub fn draw(&mut self, frame: &mut Frame) {
-        let layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-            .spacing(1)
-            .split(frame.area());
-
-        self.item_list.draw(frame, layout[0]);
-        self.content.draw(frame, layout[1]);
+        let area = frame.area();
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            draw_too_small(frame);
+            // Changes are not drawn, so the panes are drawn from scratch once it's larger.
+            self.item_list_cache = DrawCache::default();
+            self.content_cache = DrawCache::default();
+            self.dirty = Dirty::none();
+            return;
+        }
+
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+
+        let (list_area, content_area) = if self.fullscreen || main_area.width < NARROW_WIDTH {
+            // In narrow terminals columns would be too thin, so only the focused pane is shown.
+            let content_focused = self.focus == Focus::Content
+                || (self.focus == Focus::Help && self.prev_focus == Some(Focus::Content));
+            if self.fullscreen || content_focused {
+                (None, Some(main_area))
+            } else {
+                (Some(main_area), None)
+            }
+        } else {
+            let layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(self.list_width), Constraint::Fill(1)])
+                .spacing(1)
+                .split(main_area);
+            (Some(layout[0]), Some(layout[1]))
+        };
+
+        if let Some(area) = list_area {
+            let item_list_dirty = self.dirty.item_list || self.item_list.is_stale();
+            let item_list = &mut self.item_list;
+            self.item_list_cache
+                .draw(frame, area, item_list_dirty, |f, area| {
+                    item_list.draw(f, area)
+                });
+        }
+
+        if let Some(area) = content_area {
+            let content = &mut self.content;
+            self.content_cache
+                .draw(frame, area, self.dirty.content, |f, area| {
+                    content.draw(f, area)
+                });
+        }
+
+        {
+            let items = self.data_loader.get_items();
+            self.status_bar.draw(frame, status_area, &items);
+        }
+
         self.help.draw(frame);
+        self.confirm.draw(frame);
+        self.error_panel.draw(frame);
+        self.stats_panel.draw(frame);
+        self.channel_panel.draw(frame);
+        self.toc.draw(frame);
         self.toast.draw(frame);
+
+        self.dirty = Dirty::none();
     }
 
     pub fn handle_event(&mut self, event: &Event) -> EventState {
+        // Each key press is sent as a keyboard event followed by an input event.
+        // While text is being typed, keyboard events are ignored, otherwise input events are.
+        match event {
+            Event::Keyboard(_) => {
+                self.capturing_input = self.is_capturing_input();
+                if self.capturing_input {
+                    return EventState::Handled;
+                }
+            }
+            Event::Input(_) => {
+                let capturing = self.capturing_input;
+                let state = if !capturing {
+                    EventState::Ignored
+                } else if self.error_panel.is_open() {
+                    let state = self.error_panel.handle_event(event);
+                    self.dirty.overlay |= state.is_handled();
+                    state
+                } else if self.stats_panel.is_open() {
+                    let state = self.stats_panel.handle_event(event);
+                    self.dirty.overlay |= state.is_handled();
+                    state
+                } else if self.channel_panel.is_open() {
+                    let state = self.channel_panel.handle_event(event);
+                    self.dirty.overlay |= state.is_handled();
+                    if let Some((from, to)) = self.channel_panel.take_move() {
+                        self.data_loader.move_channel(from, to);
+                    }
+                    state
+                } else if self.toc.is_open() {
+                    let state = self.toc.handle_event(event);
+                    self.dirty.overlay |= state.is_handled();
+                    if let Some(line) = self.toc.take_selected() {
+                        self.content.scroll_to_line(line);
+                        self.set_focus(Focus::Content);
+                        self.dirty.content = true;
+                    }
+                    state
+                } else if self.confirm.is_open() {
+                    let state = self.confirm.handle_event(event);
+                    self.dirty.overlay |= state.is_handled();
+                    if let Some(action) = self.confirm.take_confirmed() {
+                        self.run_confirmed(action);
+                    }
+                    state
+                } else if self.item_list.is_capturing_input() {
+                    let state = self.item_list.handle_event(event);
+                    self.dirty.item_list |= state.is_handled();
+                    state
+                } else {
+                    let state = self.content.handle_event(event);
+                    self.dirty.content |= state.is_handled();
+                    state
+                };
+                self.capturing_input = self.is_capturing_input();
+
+                return state;
+            }
+            _ => (),
+        }
+
         // Component events
         let mut res_state = self.item_list.handle_event(event);
+        self.dirty.item_list |= res_state.is_handled();
 
         let state = self.content.handle_event(event);
+        self.dirty.content |= state.is_handled();
         res_state = res_state.or(&state);
 
         let state = self.toast.handle_event(event);
+        self.dirty.overlay |= state.is_handled();
+        res_state = res_state.or(&state);
+
+        let state = self.narrator.handle_event(event);
+        res_state = res_state.or(&state);
+
+        let state = self.error_panel.handle_event(event);
+        self.dirty.overlay |= state.is_handled();
+        res_state = res_state.or(&state);
+
+        let state = self.status_bar.handle_event(event);
+        self.dirty.status_bar |= state.is_handled();
+        res_state = res_state.or(&state);
+
+        let state = self.help.handle_event(event);
+        self.dirty.overlay |= state.is_handled();
         res_state = res_state.or(&state);
 
         // Move focus
@@ -133,9 +602,85 @@ impl<L: Loader + Clone + Send + 'static> App<L> {
                     self.set_focus(Focus::Help);
                     EventState::Handled
                 }
+                KeyboardEvent::Listen if self.narrator.enabled() => match self.content.text() {
+                    Some(text) => {
+                        self.narrator.enqueue(text);
+                        EventState::Handled
+                    }
+                    None => EventState::Ignored,
+                },
+                KeyboardEvent::Export if self.export_dir.is_some() => {
+                    self.export();
+                    EventState::Handled
+                }
+                KeyboardEvent::CopyArticle => {
+                    self.copy_article();
+                    EventState::Handled
+                }
+                KeyboardEvent::Fullscreen if self.focus != Focus::Help => {
+                    self.fullscreen = !self.fullscreen;
+                    // Focus changes and content is laid out for the new width.
+                    self.set_focus(if self.fullscreen {
+                        Focus::Content
+                    } else {
+                        self.focus
+                    });
+                    EventState::Handled
+                }
+                KeyboardEvent::MarkAllRead
+                    if self.focus == Focus::ItemList && !self.disable_read_status =>
+                {
+                    let unread = self.item_list.unread_count();
+                    if unread > 0 {
+                        self.confirm.open(
+                            format!("Mark {unread} items as read?"),
+                            ConfirmAction::MarkAllRead,
+                        );
+                        self.dirty.overlay = true;
+                    }
+                    EventState::Handled
+                }
+                KeyboardEvent::OpenAll
+                    if self.focus == Focus::ItemList && !self.disable_browser_open =>
+                {
+                    let count = self.item_list.shown_count();
+                    if count > OPEN_ALL_CONFIRM {
+                        self.confirm.open(
+                            format!("Open {count} items in browser?"),
+                            ConfirmAction::OpenAll,
+                        );
+                        self.dirty.overlay = true;
+                    } else {
+                        self.item_list.open_shown();
+                    }
+                    EventState::Handled
+                }
+                KeyboardEvent::RefreshErrors => {
+                    self.error_panel.open();
+                    self.dirty.overlay = true;
+                    EventState::Handled
+                }
+                KeyboardEvent::Stats => {
+                    self.stats_panel.open(&self.data_loader.get_items());
+                    self.dirty.overlay = true;
+                    EventState::Handled
+                }
+                KeyboardEvent::Channels => {
+                    self.channel_panel.open(&self.data_loader.get_channels());
+                    self.dirty.overlay = true;
+                    EventState::Handled
+                }
+                KeyboardEvent::Toc if self.focus != Focus::Help => {
+                    self.toc
+                        .open(self.content.headings(), self.content.current_heading());
+                    self.dirty.overlay = true;
+                    EventState::Handled
+                }
+                KeyboardEvent::ShrinkList => self.resize_list(-LIST_WIDTH_STEP),
+                KeyboardEvent::GrowList => self.resize_list(LIST_WIDTH_STEP),
                 _ => EventState::Ignored,
             },
-            Event::StartLoadingItem => match self.focus {
+            Event::StartLoadingItem(..) => match self.focus {
                 Focus::ItemList => {
                     self.set_focus(Focus::Content);
                     EventState::Handled
@@ -143,16 +688,94 @@ impl<L: Loader + Clone + Send + 'static> App<L> {
                 Focus::Content | Focus::Help => EventState::Ignored,
             },
             Event::Tick => EventState::Ignored,
-            Event::LoadedItem(_) => EventState::Ignored,
+            Event::Input(_) => EventState::Ignored,
+            Event::LoadedItem(..) => EventState::Ignored,
+            Event::RenderedItem { .. } => EventState::Ignored,
             Event::Toast(_) => EventState::Ignored,
+            Event::NarrationFinished(_) => EventState::Ignored,
+            Event::OpenInTerminal(_) => EventState::Ignored,
+            Event::Resize(..) => {
+                // Cached output has the old size, layout is computed again when drawn.
+                self.invalidate();
+                EventState::Handled
+            }
+            Event::RefreshStarted | Event::RefreshFinished { .. } => EventState::Ignored,
         };
 
         res_state.or(&state)
     }
 
+    /// Write the opened item into the export directory.
+    fn export(&self) {
+        let (Some(dir), Some(item), Some(text)) = (
+            &self.export_dir,
+            self.content.item(),
+            self.content.export(Format::Markdown),
+        ) else {
+            return;
+        };
+
+        let path = dir.join(format!("{}.md", file_stem(&item.title)));
+        let res = fs::create_dir_all(dir).and_then(|_| fs::write(&path, text));
+
+        let toast = match res {
+            Ok(_) => ToastEvent::Info("Article exported".to_string()),
+            Err(_) => ToastEvent::Error("Failed to export article!".to_string()),
+        };
+        self.event_tx.send(Event::Toast(toast));
+    }
+
+    /// Copy the opened item to the clipboard as Markdown.
+    fn copy_article(&mut self) {
+        let Some(text) = self.content.export(Format::Markdown) else {
+            return;
+        };
+
+        let toast = match self.clipboard.copy(&text) {
+            Ok(copied) => ToastEvent::Info(copied.message("Article")),
+            Err(_) => ToastEvent::Error("Failed to copy article!".to_string()),
+        };
+        self.event_tx.send(Event::Toast(toast));
+    }
+
+    /// Whether key presses are typed into a component, instead of triggering actions.
+    fn is_capturing_input(&self) -> bool {
+        self.confirm.is_open()
+            || self.error_panel.is_open()
+            || self.stats_panel.is_open()
+            || self.channel_panel.is_open()
+            || self.toc.is_open()
+            || self.item_list.is_capturing_input()
+            || self.content.is_capturing_input()
+    }
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::MarkAllRead => self.item_list.mark_all_read(),
+            ConfirmAction::OpenAll => self.item_list.open_shown(),
+        }
+    }
+
+    fn resize_list(&mut self, step: i16) -> EventState {
+        let width = clamp_list_width(self.list_width.saturating_add_signed(step));
+        if width == self.list_width {
+            return EventState::Ignored;
+        }
+
+        self.list_width = width;
+        self.list_resized = true;
+        self.dirty = Dirty::all();
+        EventState::Handled
+    }
+
     fn set_focus(&mut self, focus: Focus) {
+        // Focus changes borders of all the components.
+        self.dirty = Dirty::all();
+
         match focus {
             Focus::ItemList => {
+                // List can't be focused while hidden.
+                self.fullscreen = false;
                 self.item_list.set_focused(true);
                 self.content.set_focused(false);
                 self.help.close();
@@ -173,3 +796,43 @@ impl<L: Loader + Clone + Send + 'static> App<L> {
         self.focus = focus;
     }
 }
+
+/// Message that the terminal is too small, instead of a layout squeezed into it.
+fn draw_too_small(frame: &mut Frame) {
+    let area = frame.area();
+    let paragraph = Paragraph::new(vec![
+        Line::from("Terminal too small").bold(),
+        Line::from(format!(
+            "{}x{}, needs {MIN_WIDTH}x{MIN_HEIGHT}",
+            area.width, area.height
+        )),
+    ])
+    .centered()
+    .wrap(Wrap { trim: true });
+
+    let top = area.height.saturating_sub(2) / 2;
+    frame.render_widget(
+        paragraph,
+        Rect::new(area.x, area.y + top, area.width, area.height - top),
+    );
+}
+
+fn clamp_list_width(width: u16) -> u16 {
+    width.clamp(MIN_LIST_WIDTH, MAX_LIST_WIDTH)
+}
+
+/// File name without extension, made from the title.
+fn file_stem(title: &str) -> String {
+    let stem = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if stem.is_empty() {
+        "article".to_string()
+    } else {
+        stem
+    }
+}
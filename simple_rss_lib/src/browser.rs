@@ -0,0 +1,44 @@
+//! Opening links in the browser.
+
+use std::{io, process::Stdio};
+
+use tokio::process::Command;
+
+/// Opens the url with the command, or with the default browser if the command is not set.
+///
+/// `{url}` in the command is replaced with the url. If the command doesn't contain it,
+/// the url is appended as the last argument. Has to be called within a tokio runtime.
+pub(crate) fn open(url: &str, command: Option<&str>) -> io::Result<()> {
+    let Some(command) = command else {
+        return webbrowser::open(url);
+    };
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command_for(url, command))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Reap the process once the browser exits.
+    tokio::spawn(async move {
+        let _ = child.wait().await;
+    });
+
+    Ok(())
+}
+
+/// Shell command that opens the url with the open command.
+pub fn command_for(url: &str, command: &str) -> String {
+    let url = shell_quote(url);
+    if command.contains("{url}") {
+        command.replace("{url}", &url)
+    } else {
+        format!("{command} {url}")
+    }
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
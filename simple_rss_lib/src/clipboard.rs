@@ -0,0 +1,70 @@
+//! Copying text to the system clipboard.
+
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+/// System clipboard with OSC 52 fallback.
+///
+/// The system clipboard is connected on the first copy, so that sessions which never copy
+/// anything don't pay for it. On X11 the copied text is only available while the clipboard
+/// is alive, so it should be kept around for the whole session.
+pub(crate) struct Clipboard {
+    // Not connected yet if `None`, and not available if `Some(None)`.
+    system: Option<Option<arboard::Clipboard>>,
+}
+
+/// Where the copied text went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Copied {
+    Clipboard,
+    /// Sent to the terminal with OSC 52. The terminal doesn't report whether it was copied.
+    Terminal,
+}
+
+impl Copied {
+    /// Message shown after copying, like `Link copied`.
+    pub fn message(self, what: &str) -> String {
+        match self {
+            Copied::Clipboard => format!("{what} copied"),
+            Copied::Terminal => format!("{what} sent to terminal"),
+        }
+    }
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self { system: None }
+    }
+
+    pub fn copy(&mut self, text: &str) -> io::Result<Copied> {
+        // Over ssh the system clipboard is the one of the remote machine,
+        // which is not what the user wants.
+        let system = self
+            .system
+            .get_or_insert_with(|| (!is_ssh()).then(|| arboard::Clipboard::new().ok())?);
+        if let Some(system) = system
+            && system.set_text(text).is_ok()
+        {
+            return Ok(Copied::Clipboard);
+        }
+
+        copy_osc52(text)?;
+        Ok(Copied::Terminal)
+    }
+}
+
+fn is_ssh() -> bool {
+    env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Asks the terminal to set the clipboard with the OSC 52 escape sequence.
+/// Terminal doesn't report whether it's supported.
+fn copy_osc52(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
@@ -0,0 +1,127 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    data::Channel,
+    event::{Event, EventState, InputEvent},
+};
+
+/// Popup listing the channels in their order, where they can be rearranged.
+///
+/// While the panel is open it handles all [`Event::Input`]s: Up and Down select
+/// a channel, `K` and `J` move it up and down and Esc, Enter or `q` close the panel.
+/// Each move is returned by [`ChannelPanel::take_move`], to be applied to the store.
+#[derive(Default)]
+pub struct ChannelPanel {
+    open: bool,
+    names: Vec<String>,
+    list_state: ListState,
+    moved: Option<(usize, usize)>,
+}
+
+impl ChannelPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, channels: &[Channel]) {
+        self.open = true;
+        self.names = channels
+            .iter()
+            .map(|ch| ch.name.clone().unwrap_or_else(|| ch.url.clone()))
+            .collect();
+        self.list_state = ListState::default().with_selected(Some(0));
+        self.moved = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Move made since the last call, as indices of the channel before and after it.
+    pub fn take_move(&mut self) -> Option<(usize, usize)> {
+        self.moved.take()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::Input(input) if self.open => {
+                match input {
+                    InputEvent::Up => self.list_state.select_previous(),
+                    InputEvent::Down => self.list_state.select_next(),
+                    InputEvent::Char('K') => self.move_selected(-1),
+                    InputEvent::Char('J') => self.move_selected(1),
+                    InputEvent::Esc | InputEvent::Enter | InputEvent::Char('q') => {
+                        self.open = false
+                    }
+                    _ => (),
+                }
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    fn move_selected(&mut self, offset: isize) {
+        let Some(from) = self.list_state.selected() else {
+            return;
+        };
+        let Some(to) = from.checked_add_signed(offset) else {
+            return;
+        };
+        if from >= self.names.len() || to >= self.names.len() {
+            return;
+        }
+
+        self.names.swap(from, to);
+        self.list_state.select(Some(to));
+        self.moved = Some((from, to));
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if !self.open {
+            return;
+        }
+
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+
+        let instructions = Line::from(vec![
+            " Move ".into(),
+            "<K> / <J>  ".blue().bold(),
+            "Close ".into(),
+            "<Esc> ".blue().bold(),
+        ]);
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Blue)
+            .title("Channels")
+            .title_bottom(instructions.centered());
+
+        if self.names.is_empty() {
+            let paragraph = Paragraph::new(Line::from("No channels").centered()).block(block);
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        let list = List::new(
+            self.names
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| ListItem::new(format!(" {idx:>2}  {name}"))),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .block(block);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
@@ -0,0 +1,97 @@
+use ratatui::{
+    Frame,
+    style::{Color, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Clear, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::centered;
+use crate::event::{Event, EventState, InputEvent};
+
+const MIN_WIDTH: u16 = 30;
+
+/// Modal Yes/No question, guarding an action of type `A`.
+///
+/// While the dialog is open it handles all [`Event::Input`]s: `y` or Enter confirms
+/// the action and `n` or Esc cancels it. Confirmed action is returned by
+/// [`Confirm::take_confirmed`].
+pub struct Confirm<A> {
+    pending: Option<(String, A)>,
+    confirmed: Option<A>,
+}
+
+impl<A> Default for Confirm<A> {
+    fn default() -> Self {
+        Self {
+            pending: None,
+            confirmed: None,
+        }
+    }
+}
+
+impl<A> Confirm<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the question. Previously pending action is discarded.
+    pub fn open(&mut self, question: impl Into<String>, action: A) {
+        self.pending = Some((question.into(), action));
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Action confirmed since the last call.
+    pub fn take_confirmed(&mut self) -> Option<A> {
+        self.confirmed.take()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        let Event::Input(input) = event else {
+            return EventState::Ignored;
+        };
+        if !self.is_open() {
+            return EventState::Ignored;
+        }
+
+        match input {
+            InputEvent::Char('y' | 'Y') | InputEvent::Enter => {
+                self.confirmed = self.pending.take().map(|(_, action)| action);
+            }
+            InputEvent::Char('n' | 'N') | InputEvent::Esc => self.pending = None,
+            // Other keys are swallowed, so that nothing happens behind the dialog.
+            _ => (),
+        }
+
+        EventState::Handled
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let Some((question, _)) = &self.pending else {
+            return;
+        };
+
+        let width = (question.width() as u16 + 4).max(MIN_WIDTH);
+        let height = 4; // 2 border + question + answers
+        let area = centered(frame.area(), width, height);
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Yellow)
+            .title("Confirm");
+        let answers = Line::from(vec![
+            "<y>".blue().bold(),
+            " Yes   ".into(),
+            "<n>".blue().bold(),
+            " No".into(),
+        ]);
+        let paragraph = Paragraph::new(vec![Line::from(question.as_str()).bold(), answers])
+            .centered()
+            .block(block);
+        frame.render_widget(paragraph, area);
+    }
+}
@@ -1,17 +1,56 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+};
+
 use ratatui::{
     Frame,
+    buffer::Buffer,
     layout::Rect,
-    style::{Color, Stylize},
-    text::Line,
-    widgets::{Block, BorderType, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    style::{Color, Modifier, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
+    },
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    event::{Event, EventState, KeyboardEvent},
-    html_render::render,
+    Error,
+    data::Item,
+    event::{Event, EventSender, EventState, InputEvent, KeyboardEvent},
+    render::{
+        Document, Format, Heading, Link, RenderOptions, export_item, render_document,
+        render_to_lines,
+    },
 };
 
-use super::spinner_frame;
+use super::{
+    input::{Input, InputState},
+    search::Search,
+    spinner_frame,
+};
+
+/// Width used when rendering text that shouldn't be wrapped.
+const UNWRAPPED_WIDTH: usize = 100_000;
+
+/// Maximum number of items kept in the back history.
+const HISTORY_LIMIT: usize = 20;
+
+/// Maximum number of rendered articles kept in the [`Renderer`]'s cache.
+const RENDER_LRU_SIZE: usize = 16;
+
+/// Configuration of the [`Content`].
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    /// How the items are rendered, see [`RenderOptions`].
+    pub render_options: RenderOptions,
+    /// Whether links are drawn as OSC 8 hyperlinks, which can be clicked in terminals that
+    /// support them.
+    pub hyperlinks: bool,
+}
 
 #[derive(Default)]
 enum ContentState {
@@ -19,30 +58,123 @@ enum ContentState {
     Empty,
     Loading(u8),
     Data(ContentStateData),
+    Error(Error),
 }
 
 struct ContentStateData {
+    item: Option<Box<Item>>,
     raw_text: String,
+    // Hash of the raw text, so that rendered lines of outdated content aren't reused.
+    text_hash: u64,
     scroll_offset: usize,
+    search: Option<Search>,
+
+    // Indices of the expanded sections. Sections are collapsed by default.
+    expanded: HashSet<usize>,
+    // Index of the section that is toggled, if its summary is shown.
+    section_cursor: Option<usize>,
+    // Height of the text area when it was last drawn.
+    view_height: usize,
 
-    render_cache: Option<RenderCache>,
+    render_cache: Option<Box<RenderCache>>,
+    // Render running in the background, replacing the render cache when done.
+    pending_render: Option<PendingRender>,
+}
+
+struct PendingRender {
+    id: u64,
+    width: u16,
+    tick: u8,
 }
 
 struct RenderCache {
-    lines: Vec<Line<'static>>,
+    document: Arc<Document>,
     render_width: u16,
+
+    // Lines of the document with collapsed sections left out, as they are shown.
+    lines: Vec<Line<'static>>,
+    // Shown summary lines, with the index of their section.
+    summaries: Vec<(usize, usize)>,
+    // Shown headings, on the lines where they are shown.
+    headings: Vec<Heading>,
+    // Shown links, on the lines where they are shown.
+    links: Vec<Link>,
+}
+
+/// Renders items in the background and caches rendered lines of recently shown items,
+/// by item id and width. Cache makes switching between items and widths instant.
+struct Renderer {
+    event_tx: EventSender,
+    next_id: u64,
+    options: RenderOptions,
+
+    // Least recently used first.
+    entries: VecDeque<RenderEntry>,
 }
 
+struct RenderEntry {
+    id: String,
+    width: u16,
+    text_hash: u64,
+    document: Arc<Document>,
+}
+
+/// Pane showing the opened item.
 pub struct Content {
     focused: bool,
     state: ContentState,
+
+    // Id of the load that is in progress and the item being loaded.
+    loading_item: Option<(u64, Box<Item>)>,
+
+    // Scroll offsets of previously opened items, by item id.
+    scroll_offsets: HashMap<String, usize>,
+
+    // Marked positions of items, by item id and mark letter. Kept only for the session.
+    marks: HashMap<String, HashMap<char, usize>>,
+
+    // Mark action waiting for the letter to be typed.
+    pending_mark: Option<MarkAction>,
+
+    // Shared by all items, to keep the history of queries.
+    search_input: Input,
+
+    // Previously opened items, most recent last, and items to go forward to after
+    // going back.
+    back: Vec<ContentStateData>,
+    forward: Vec<ContentStateData>,
+
+    renderer: Renderer,
+
+    // Whether links are drawn as OSC 8 hyperlinks.
+    hyperlinks: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MarkAction {
+    Set,
+    Jump,
 }
 
 impl Content {
-    pub fn new(focused: bool) -> Self {
+    pub fn new(focused: bool, event_tx: EventSender, config: Config) -> Self {
         Self {
             focused,
             state: ContentState::default(),
+            loading_item: None,
+            scroll_offsets: HashMap::new(),
+            marks: HashMap::new(),
+            pending_mark: None,
+            search_input: Input::new(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            renderer: Renderer {
+                event_tx,
+                next_id: 0,
+                options: config.render_options,
+                entries: VecDeque::new(),
+            },
+            hyperlinks: config.hyperlinks,
         }
     }
 
@@ -53,38 +185,330 @@ impl Content {
     pub fn handle_event(&mut self, event: &Event) -> EventState {
         match event {
             Event::Keyboard(key_event) => self.handle_keyboard_event(*key_event),
-            Event::Tick => match self.state {
+            Event::Input(input) => {
+                if let Some(action) = self.pending_mark.take() {
+                    self.handle_mark(action, *input);
+                    return EventState::Handled;
+                }
+
+                match &mut self.state {
+                    ContentState::Data(data) if self.focused => {
+                        data.handle_input(&mut self.search_input, event)
+                    }
+                    _ => EventState::Ignored,
+                }
+            }
+            Event::Tick => match &mut self.state {
                 ContentState::Loading(tick) => {
-                    self.state = ContentState::Loading(tick.wrapping_add(1));
+                    *tick = tick.wrapping_add(1);
+                    EventState::Handled
+                }
+                ContentState::Data(ContentStateData {
+                    pending_render: Some(pending),
+                    ..
+                }) => {
+                    pending.tick = pending.tick.wrapping_add(1);
                     EventState::Handled
                 }
                 _ => EventState::Ignored,
             },
-            Event::StartLoadingItem => {
+            Event::StartLoadingItem(id, item) => {
+                self.save_scroll_offset();
+                if let ContentState::Data(data) = std::mem::take(&mut self.state) {
+                    self.push_back(data);
+                }
+                self.forward.clear();
+
                 self.state = ContentState::Loading(0);
+                self.loading_item = Some((*id, item.clone()));
                 EventState::Handled
             }
-            Event::LoadedItem(text) => {
+            Event::LoadedItem(id, result) => {
+                // Result of a load that was superseded by a newer one.
+                if self
+                    .loading_item
+                    .as_ref()
+                    .is_none_or(|(loading, _)| loading != id)
+                {
+                    return EventState::Ignored;
+                }
+                let item = self.loading_item.take().map(|(_, item)| item);
+
+                self.pending_mark = None;
+
+                let text = match result {
+                    Ok(text) => text,
+                    Err(err) => {
+                        self.state = ContentState::Error(err.clone());
+                        return EventState::Handled;
+                    }
+                };
+                let scroll_offset = item
+                    .as_ref()
+                    .and_then(|it| self.scroll_offsets.get(&it.id))
+                    .copied()
+                    .unwrap_or(0);
+
                 self.state = ContentState::Data(ContentStateData {
+                    item,
                     raw_text: text.clone(),
-                    scroll_offset: 0,
+                    text_hash: hash_text(text),
+                    scroll_offset,
+                    search: None,
+                    expanded: HashSet::new(),
+                    section_cursor: None,
+                    view_height: 0,
                     render_cache: None,
+                    pending_render: None,
                 });
 
                 EventState::Handled
             }
+            Event::RenderedItem { id, document } => {
+                let ContentState::Data(data) = &mut self.state else {
+                    return EventState::Ignored;
+                };
+                let Some(pending) = data.pending_render.take_if(|p| p.id == *id) else {
+                    return EventState::Ignored;
+                };
+
+                if let Some(item) = &data.item {
+                    self.renderer
+                        .insert(&item.id, pending.width, data.text_hash, document.clone());
+                }
+                data.set_render_cache(document.clone(), pending.width);
+
+                EventState::Handled
+            }
             Event::Toast(_) => EventState::Ignored,
+            Event::NarrationFinished(_) => EventState::Ignored,
+            Event::OpenInTerminal(_) => EventState::Ignored,
+            Event::Resize(..) => EventState::Ignored,
+            Event::RefreshStarted | Event::RefreshFinished { .. } => EventState::Ignored,
+        }
+    }
+
+    /// Returns true while a loading or rendering spinner is shown.
+    pub fn is_animating(&self) -> bool {
+        match &self.state {
+            ContentState::Loading(_) => true,
+            ContentState::Data(data) => data.pending_render.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Returns true while text is being typed into the content pane, in which case
+    /// keyboard shortcuts shouldn't be triggered.
+    pub fn is_capturing_input(&self) -> bool {
+        if self.pending_mark.is_some() {
+            return true;
+        }
+
+        match &self.state {
+            ContentState::Data(data) => data.search.as_ref().is_some_and(|s| s.editing),
+            _ => false,
+        }
+    }
+
+    fn handle_mark(&mut self, action: MarkAction, input: InputEvent) {
+        let InputEvent::Char(letter) = input else {
+            return;
+        };
+        if !letter.is_ascii_alphabetic() {
+            return;
+        }
+        let ContentState::Data(data) = &mut self.state else {
+            return;
+        };
+        let Some(item) = &data.item else {
+            return;
+        };
+
+        match action {
+            MarkAction::Set => {
+                self.marks
+                    .entry(item.id.clone())
+                    .or_default()
+                    .insert(letter, data.scroll_offset);
+            }
+            MarkAction::Jump => {
+                let offset = self.marks.get(&item.id).and_then(|m| m.get(&letter));
+                if let Some(offset) = offset {
+                    data.scroll_offset = *offset;
+                }
+            }
         }
     }
 
+    /// Scroll offsets of opened items, by item id.
+    pub fn scroll_offsets(&mut self) -> &HashMap<String, usize> {
+        self.save_scroll_offset();
+        &self.scroll_offsets
+    }
+
+    /// Restore scroll offsets, used when the item is opened again.
+    pub fn set_scroll_offsets(&mut self, offsets: HashMap<String, usize>) {
+        self.scroll_offsets = offsets;
+    }
+
+    fn push_back(&mut self, mut data: ContentStateData) {
+        data.search = None;
+        self.back.push(data);
+        if self.back.len() > HISTORY_LIMIT {
+            self.back.remove(0);
+        }
+    }
+
+    /// Show the previous item in the history. Loading of an item that is in progress
+    /// is abandoned.
+    fn history_back(&mut self) -> EventState {
+        let Some(data) = self.back.pop() else {
+            return EventState::Ignored;
+        };
+
+        self.save_scroll_offset();
+        if let ContentState::Data(mut current) = std::mem::take(&mut self.state) {
+            current.search = None;
+            self.forward.push(current);
+        }
+        self.show_from_history(data);
+        EventState::Handled
+    }
+
+    fn history_forward(&mut self) -> EventState {
+        let Some(data) = self.forward.pop() else {
+            return EventState::Ignored;
+        };
+
+        self.save_scroll_offset();
+        if let ContentState::Data(current) = std::mem::take(&mut self.state) {
+            self.push_back(current);
+        }
+        self.show_from_history(data);
+        EventState::Handled
+    }
+
+    fn show_from_history(&mut self, mut data: ContentStateData) {
+        // Result of the render was discarded while the item was in the history.
+        data.pending_render = None;
+        self.loading_item = None;
+        self.pending_mark = None;
+        self.state = ContentState::Data(data);
+    }
+
+    fn save_scroll_offset(&mut self) {
+        let ContentState::Data(data) = &self.state else {
+            return;
+        };
+        let Some(item) = &data.item else {
+            return;
+        };
+
+        if data.scroll_offset == 0 {
+            self.scroll_offsets.remove(&item.id);
+        } else {
+            self.scroll_offsets
+                .insert(item.id.clone(), data.scroll_offset);
+        }
+    }
+
+    /// Plain text of the opened item, without any styling or wrapping.
+    pub fn text(&self) -> Option<String> {
+        let ContentState::Data(data) = &self.state else {
+            return None;
+        };
+
+        let options = RenderOptions {
+            colorize: false,
+            ..self.renderer.options.clone()
+        };
+        let lines = render_to_lines(&data.raw_text, UNWRAPPED_WIDTH, &options);
+        let text = lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Some(text)
+    }
+
+    /// Opened item, if it's known.
+    pub fn item(&self) -> Option<&Item> {
+        match &self.state {
+            ContentState::Data(data) => data.item.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Headings of the opened item, on the lines where they are shown.
+    pub fn headings(&self) -> &[Heading] {
+        match &self.state {
+            ContentState::Data(ContentStateData {
+                render_cache: Some(cache),
+                ..
+            }) => &cache.headings,
+            _ => &[],
+        }
+    }
+
+    /// Index of the heading of the section that is scrolled to, if any.
+    pub fn current_heading(&self) -> Option<usize> {
+        let ContentState::Data(data) = &self.state else {
+            return None;
+        };
+        self.headings()
+            .iter()
+            .rposition(|h| h.line <= data.scroll_offset)
+    }
+
+    /// Scroll the opened item so that the line is at the top.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        if let ContentState::Data(data) = &mut self.state {
+            data.scroll_offset = line;
+        }
+    }
+
+    /// Opened item exported in the given format.
+    pub fn export(&self, format: Format) -> Option<String> {
+        let ContentState::Data(data) = &self.state else {
+            return None;
+        };
+        let item = data.item.as_ref()?;
+
+        Some(export_item(item, &data.raw_text, format))
+    }
+
     fn handle_keyboard_event(&mut self, event: KeyboardEvent) -> EventState {
+        // History works from both panes.
+        match event {
+            KeyboardEvent::HistoryBack => return self.history_back(),
+            KeyboardEvent::HistoryForward => return self.history_forward(),
+            _ => (),
+        }
+
         if !self.focused {
             return EventState::Ignored;
         }
 
-        match &mut self.state {
-            ContentState::Data(data) => data.handle_keyboard_event(event),
-            _ => EventState::Ignored,
+        let ContentState::Data(data) = &mut self.state else {
+            return EventState::Ignored;
+        };
+
+        match event {
+            KeyboardEvent::SetMark => {
+                self.pending_mark = Some(MarkAction::Set);
+                EventState::Handled
+            }
+            KeyboardEvent::JumpToMark => {
+                self.pending_mark = Some(MarkAction::Jump);
+                EventState::Handled
+            }
+            KeyboardEvent::Search => {
+                self.search_input.clear();
+                data.search = Some(Search::new());
+                EventState::Handled
+            }
+            _ => data.handle_keyboard_event(event),
         }
     }
 
@@ -92,34 +516,68 @@ impl Content {
         match self.state {
             ContentState::Empty => self.draw_empty(frame, area),
             ContentState::Loading(tick) => self.draw_loading(tick, frame, area),
-            ContentState::Data(ref mut data) => data.draw(frame, area, self.focused),
+            ContentState::Error(ref err) => self.draw_error(err, frame, area),
+            ContentState::Data(ref mut data) => data.draw(
+                frame,
+                area,
+                self.focused,
+                &self.search_input,
+                &mut self.renderer,
+                self.hyperlinks,
+            ),
         }
     }
 
-    fn draw_empty(&self, frame: &mut Frame, mut area: Rect) {
+    fn draw_empty(&self, frame: &mut Frame, area: Rect) {
         let block = basic_block(self.focused);
+        let inner = block.inner(area);
         frame.render_widget(block, area);
 
         let paragraph = Paragraph::new("Select an item to get started")
             .bold()
             .centered();
+        frame.render_widget(paragraph, center_line(inner));
+    }
 
-        area.y = area.height / 2;
+    fn draw_error(&self, err: &Error, frame: &mut Frame, area: Rect) {
+        let block = basic_block(self.focused);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let paragraph = Paragraph::new(vec![
+            Line::from("Failed loading item").bold(),
+            Line::from(err.to_string()),
+        ])
+        .fg(Color::Red)
+        .centered()
+        .wrap(Wrap { trim: true });
+        // Starts in the middle, long errors are wrapped below.
+        let top = (inner.height / 2).saturating_sub(1);
+        let area = Rect::new(inner.x, inner.y + top, inner.width, inner.height - top);
         frame.render_widget(paragraph, area);
     }
 
-    fn draw_loading(&self, tick: u8, frame: &mut Frame, mut area: Rect) {
+    fn draw_loading(&self, tick: u8, frame: &mut Frame, area: Rect) {
         let block = basic_block(self.focused);
+        let inner = block.inner(area);
         frame.render_widget(block, area);
 
         let ch = spinner_frame(tick as usize);
         let paragraph = Paragraph::new(format!("Loading {ch}")).centered();
-
-        area.y = area.height / 2;
-        frame.render_widget(paragraph, area);
+        frame.render_widget(paragraph, center_line(inner));
     }
 }
 
+/// Single line in the vertical middle of the area.
+fn center_line(area: Rect) -> Rect {
+    Rect::new(
+        area.x,
+        area.y + area.height / 2,
+        area.width,
+        area.height.min(1),
+    )
+}
+
 fn basic_block(selected: bool) -> Block<'static> {
     let mut block = Block::bordered().border_type(BorderType::Rounded);
     if !selected {
@@ -146,27 +604,219 @@ impl ContentStateData {
 
                 EventState::Handled
             }
+            KeyboardEvent::Space => self.next_section(),
+            KeyboardEvent::Enter => self.toggle_section(),
+            KeyboardEvent::PreviousHeading => {
+                let headings = self.render_cache.as_ref().map(|c| &c.headings);
+                let line = headings.and_then(|headings| {
+                    headings
+                        .iter()
+                        .rev()
+                        .find(|h| h.line < self.scroll_offset)
+                        .map(|h| h.line)
+                });
+                self.scroll_offset = line.unwrap_or(0);
+                EventState::Handled
+            }
+            KeyboardEvent::NextHeading => {
+                let headings = self.render_cache.as_ref().map(|c| &c.headings);
+                let line = headings.and_then(|headings| {
+                    headings
+                        .iter()
+                        .find(|h| h.line > self.scroll_offset)
+                        .map(|h| h.line)
+                });
+                if let Some(line) = line {
+                    self.scroll_offset = line;
+                }
+                EventState::Handled
+            }
+            KeyboardEvent::SearchNext | KeyboardEvent::SearchPrevious => {
+                let Some(search) = &mut self.search else {
+                    return EventState::Ignored;
+                };
+
+                if key == KeyboardEvent::SearchNext {
+                    search.next();
+                } else {
+                    search.previous();
+                }
+                self.scroll_to_match();
+
+                EventState::Handled
+            }
             _ => EventState::Ignored,
         }
     }
 
-    fn draw(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
-        let scroll_offset = self.scroll_offset;
-        let cache = self.get_render_cache(area);
+    /// Line and index of the section under the cursor, or of the first section shown in the
+    /// pane if the cursor's section isn't shown.
+    fn selected_section(&self) -> Option<(usize, usize)> {
+        let cache = self.render_cache.as_ref()?;
+        let shown = self.scroll_offset..self.scroll_offset + self.view_height;
+        let mut summaries = cache
+            .summaries
+            .iter()
+            .filter(|(line, _)| shown.contains(line));
+        let first = summaries.clone().next();
+        summaries
+            .find(|(_, section)| Some(*section) == self.section_cursor)
+            .or(first)
+            .copied()
+    }
 
-        let block = basic_block(focused);
-        frame.render_widget(block, area);
+    /// Move the cursor to the next section, scrolling to it if it's not shown. After the
+    /// last section the cursor wraps to the first one.
+    fn next_section(&mut self) -> EventState {
+        let Some(cache) = &self.render_cache else {
+            return EventState::Ignored;
+        };
+        let after = self
+            .selected_section()
+            .map_or(self.scroll_offset, |(line, _)| line + 1);
+        let Some(&(line, section)) = cache
+            .summaries
+            .iter()
+            .find(|(line, _)| *line >= after)
+            .or(cache.summaries.first())
+        else {
+            return EventState::Ignored;
+        };
+
+        self.section_cursor = Some(section);
+        if !(self.scroll_offset..self.scroll_offset + self.view_height).contains(&line) {
+            self.scroll_offset = line;
+        }
+        EventState::Handled
+    }
+
+    /// Expand or collapse the section under the cursor.
+    fn toggle_section(&mut self) -> EventState {
+        let Some((_, section)) = self.selected_section() else {
+            return EventState::Ignored;
+        };
+        let Some(cache) = &self.render_cache else {
+            return EventState::Ignored;
+        };
+
+        self.section_cursor = Some(section);
+        if !self.expanded.remove(&section) {
+            self.expanded.insert(section);
+        }
+        let document = cache.document.clone();
+        let width = cache.render_width;
+        self.set_render_cache(document, width);
+
+        EventState::Handled
+    }
+
+    fn handle_input(&mut self, search_input: &mut Input, event: &Event) -> EventState {
+        let Some(search) = &mut self.search else {
+            return EventState::Ignored;
+        };
+        if !search.editing {
+            return EventState::Ignored;
+        }
+
+        match search_input.handle_event(event) {
+            InputState::Ignored => return EventState::Ignored,
+            InputState::Moved => return EventState::Handled,
+            InputState::Changed => search.query = search_input.value().to_string(),
+            InputState::Submitted => search.editing = false,
+            InputState::Cancelled => {
+                self.search = None;
+                return EventState::Handled;
+            }
+        }
+
+        self.update_search();
+        EventState::Handled
+    }
+
+    /// Find matches of the search query and scroll to the first one.
+    fn update_search(&mut self) {
+        let (Some(search), Some(cache)) = (&mut self.search, &self.render_cache) else {
+            return;
+        };
+
+        search.update(&cache.lines, self.scroll_offset);
+        self.scroll_to_match();
+    }
+
+    fn scroll_to_match(&mut self) {
+        if let Some(line) = self.search.as_ref().and_then(|s| s.current_line()) {
+            // Keep some context above the match.
+            self.scroll_offset = line.saturating_sub(2);
+        }
+    }
+
+    fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        focused: bool,
+        search_input: &Input,
+        renderer: &mut Renderer,
+        hyperlinks: bool,
+    ) {
+        let mut block = basic_block(focused);
+        if let Some(search) = &self.search {
+            if search.editing {
+                block = block.title_bottom(search_input.line(" /"));
+            } else {
+                block = block.title(
+                    Line::from(format!(" /{} [{}] ", search.query, search.counter()))
+                        .right_aligned(),
+                );
+            }
+        }
+        let inner = block.inner(area);
+        self.view_height = inner.height as usize;
+
+        if !self.update_render_cache(area, renderer) {
+            let tick = self.pending_render.as_ref().map_or(0, |p| p.tick);
+            frame.render_widget(block, area);
+
+            let ch = spinner_frame(tick as usize);
+            let paragraph = Paragraph::new(format!("Rendering {ch}")).centered();
+            frame.render_widget(paragraph, center_line(inner));
+            return;
+        }
+        let Some(cache) = &self.render_cache else {
+            return;
+        };
+
+        // Restored offset can be out of range if the width changed.
+        self.scroll_offset = self.scroll_offset.min(cache.lines.len().saturating_sub(5));
+        let scroll_offset = self.scroll_offset;
 
-        let lines = cache
+        // Only the scrolled-to region is handed to the paragraph, the rest of the
+        // article doesn't have to be cloned or laid out.
+        let end = (scroll_offset + inner.height as usize).min(cache.lines.len());
+        let mut visible: Vec<_> = cache
             .lines
+            .get(scroll_offset..end)
+            .unwrap_or_default()
             .iter()
-            .skip(scroll_offset + 1)
-            .take((area.height as usize) - 2);
-        for (idx, line) in lines.enumerate() {
-            frame.render_widget(
-                line,
-                Rect::new(area.x + 1, area.y + idx as u16 + 1, area.width - 2, 1),
-            );
+            .enumerate()
+            .map(|(idx, line)| match &self.search {
+                Some(search) => search.highlight(scroll_offset + idx, line.clone()),
+                None => line.clone(),
+            })
+            .collect();
+        // Marker of the section that Enter toggles.
+        if focused && let Some((line, section)) = self.selected_section() {
+            let marker = cache.document.sections[section].marker;
+            if let Some(span) = visible
+                .get_mut(line - scroll_offset)
+                .and_then(|line| line.spans.get_mut(marker))
+            {
+                span.style = span.style.add_modifier(Modifier::REVERSED);
+            }
+        }
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+        if hyperlinks {
+            draw_hyperlinks(frame.buffer_mut(), inner, cache, scroll_offset..end);
         }
 
         // Scrollbar
@@ -176,26 +826,279 @@ impl ContentStateData {
         frame.render_stateful_widget(scroll_bar, area, &mut bar_state);
     }
 
-    fn get_render_cache(&mut self, area: Rect) -> &RenderCache {
-        let Some(render_cache) = &self.render_cache else {
-            return self.recalculate_render_cache(area);
-        };
+    /// Make sure the render cache matches the area. Returns false if the item is still
+    /// being rendered in the background.
+    fn update_render_cache(&mut self, area: Rect, renderer: &mut Renderer) -> bool {
+        if self
+            .render_cache
+            .as_ref()
+            .is_some_and(|c| c.render_width == area.width)
+        {
+            return true;
+        }
 
-        if render_cache.render_width != area.width {
-            return self.recalculate_render_cache(area);
+        let cached = self
+            .item
+            .as_ref()
+            .and_then(|item| renderer.get(&item.id, area.width, self.text_hash));
+        if let Some(document) = cached {
+            self.pending_render = None;
+            self.set_render_cache(document, area.width);
+            return true;
         }
 
-        self.render_cache.as_ref().unwrap()
+        if self
+            .pending_render
+            .as_ref()
+            .is_none_or(|p| p.width != area.width)
+        {
+            let id = renderer.spawn(self.item.clone(), self.raw_text.clone(), area.width);
+            self.pending_render = Some(PendingRender {
+                id,
+                width: area.width,
+                tick: 0,
+            });
+        }
+        false
     }
 
-    fn recalculate_render_cache(&mut self, area: Rect) -> &RenderCache {
-        let lines = render(&self.raw_text, area.width as usize - 2, true);
+    fn set_render_cache(&mut self, document: Arc<Document>, width: u16) {
+        let cache = fold(document, width, &self.expanded);
 
-        self.render_cache = Some(RenderCache {
-            lines,
-            render_width: area.width,
+        // Wrapping changed, so the matches are on different lines.
+        if let Some(search) = &mut self.search {
+            search.update(&cache.lines, self.scroll_offset);
+        }
+
+        self.render_cache = Some(Box::new(cache));
+    }
+}
+
+impl Renderer {
+    /// Render the item in the background. Lines are sent with [`Event::RenderedItem`]
+    /// with the returned id.
+    fn spawn(&mut self, item: Option<Box<Item>>, raw_text: String, area_width: u16) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let sender = self.event_tx.clone();
+        let options = self.options.clone();
+        tokio::task::spawn_blocking(move || {
+            let width = (area_width as usize).saturating_sub(2).max(1);
+            let mut document = render_document(&raw_text, width, &options);
+            if let Some(item) = &item {
+                let (mut lines, mut links) = render_header(item, width);
+                let offset = lines.len();
+                for section in &mut document.sections {
+                    section.summary += offset;
+                    section.body = section.body.start + offset..section.body.end + offset;
+                }
+                for heading in &mut document.headings {
+                    heading.line += offset;
+                }
+                for link in &mut document.links {
+                    link.line += offset;
+                }
+                lines.append(&mut document.lines);
+                document.lines = lines;
+                links.append(&mut document.links);
+                document.links = links;
+            }
+
+            sender.send(Event::RenderedItem {
+                id,
+                document: Arc::new(document),
+            });
         });
 
-        self.render_cache.as_ref().unwrap()
+        id
+    }
+
+    fn get(&mut self, id: &str, width: u16, text_hash: u64) -> Option<Arc<Document>> {
+        let idx = self
+            .entries
+            .iter()
+            .position(|e| e.id == id && e.width == width && e.text_hash == text_hash)?;
+
+        // Move to the back, as the most recently used.
+        let entry = self.entries.remove(idx)?;
+        let document = entry.document.clone();
+        self.entries.push_back(entry);
+        Some(document)
+    }
+
+    fn insert(&mut self, id: &str, width: u16, text_hash: u64, document: Arc<Document>) {
+        if self.entries.len() >= RENDER_LRU_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(RenderEntry {
+            id: id.to_string(),
+            width,
+            text_hash,
+            document,
+        });
+    }
+}
+
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Render cache of the document as it is shown, with the bodies of collapsed sections
+/// left out.
+fn fold(document: Arc<Document>, render_width: u16, expanded: &HashSet<usize>) -> RenderCache {
+    let mut lines = Vec::with_capacity(document.lines.len());
+    let mut summaries = vec![];
+    let mut headings = vec![];
+    let mut links = vec![];
+    let mut hidden = 0..0;
+    let mut sections = document.sections.iter().enumerate().peekable();
+    let mut doc_headings = document.headings.iter().peekable();
+    let mut doc_links = document.links.iter().peekable();
+
+    for (idx, line) in document.lines.iter().enumerate() {
+        if hidden.contains(&idx) {
+            continue;
+        }
+        // Sections and headings inside a collapsed section aren't shown.
+        while sections.next_if(|(_, s)| s.summary < idx).is_some() {}
+        while doc_headings.next_if(|h| h.line < idx).is_some() {}
+        while doc_links.next_if(|l| l.line < idx).is_some() {}
+
+        if let Some(heading) = doc_headings.next_if(|h| h.line == idx) {
+            headings.push(Heading {
+                line: lines.len(),
+                ..heading.clone()
+            });
+        }
+        while let Some(link) = doc_links.next_if(|l| l.line == idx) {
+            links.push(Link {
+                line: lines.len(),
+                ..link.clone()
+            });
+        }
+
+        let mut line = line.clone();
+        if let Some((section, s)) = sections.next_if(|(_, s)| s.summary == idx) {
+            let is_expanded = expanded.contains(&section);
+            if let Some(span) = line.spans.get_mut(s.marker) {
+                span.content = if is_expanded { "▾ " } else { "▸ " }.into();
+            }
+            if !is_expanded {
+                hidden = s.body.clone();
+            }
+            summaries.push((lines.len(), section));
+        }
+        lines.push(line);
     }
+
+    RenderCache {
+        document,
+        render_width,
+        lines,
+        summaries,
+        headings,
+        links,
+    }
+}
+
+/// Turns the text of the links on the shown lines into OSC 8 hyperlinks.
+///
+/// Ratatui counts the escape sequences into the width of the cell's symbol and skips the
+/// cell after a wide symbol, so each hyperlink is written by the first of two columns:
+/// a pair of narrow characters or a wide one. Narrow characters that can't be paired, like
+/// the last one of a link with odd width, are left as they are.
+fn draw_hyperlinks(buf: &mut Buffer, area: Rect, cache: &RenderCache, shown: Range<usize>) {
+    for link in &cache.links {
+        if !shown.contains(&link.line) {
+            continue;
+        }
+        let Some(line) = cache.lines.get(link.line) else {
+            continue;
+        };
+
+        let width = |spans: &[Span]| spans.iter().map(Span::width).sum::<usize>();
+        let start = area.x + width(&line.spans[..link.spans.start]) as u16;
+        let end = (start + width(&line.spans[link.spans.clone()]) as u16).min(area.right());
+        let y = area.y + (link.line - shown.start) as u16;
+
+        let mut x = start;
+        while x + 1 < end {
+            let first = buf[(x, y)].symbol().to_string();
+            let text = match first.width() {
+                2 => first,
+                1 if buf[(x + 1, y)].symbol().width() == 1 => first + buf[(x + 1, y)].symbol(),
+                _ => {
+                    x += 1;
+                    continue;
+                }
+            };
+            let url = osc8_url(&link.url);
+            buf[(x, y)].set_symbol(&format!("\x1B]8;;{url}\x07{text}\x1B]8;;\x07"));
+            x += 2;
+        }
+    }
+}
+
+/// Url with everything except printable ASCII percent-encoded, so that links of the feed
+/// can't end the escape sequence and write their own.
+fn osc8_url(url: &str) -> String {
+    let mut encoded = String::with_capacity(url.len());
+    for byte in url.bytes() {
+        if byte.is_ascii_graphic() {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Renders information about the item and a rule separating it from the article, with the
+/// item's link.
+fn render_header(item: &Item, width: usize) -> (Vec<Line<'static>>, Vec<Link>) {
+    let opts = textwrap::Options::new(width).break_words(true);
+    let mut lines: Vec<Line> = textwrap::wrap(&item.title, &opts)
+        .iter()
+        .map(|s| Line::from(s.to_string()).bold().fg(Color::LightGreen))
+        .collect();
+
+    let mut source = item.channel_name.clone();
+    if let Some(author) = &item.author {
+        source.push_str(" · ");
+        source.push_str(author);
+    }
+    lines.extend(
+        textwrap::wrap(&source, &opts)
+            .iter()
+            .map(|s| Line::from(s.to_string()).bold().fg(Color::Gray)),
+    );
+
+    if let Some(date) = &item.pub_date {
+        lines.push(Line::from(date.format("%Y-%m-%d %H:%M").to_string()).fg(Color::Gray));
+    }
+
+    let mut links = vec![];
+    for part in textwrap::wrap(&item.link, &opts) {
+        links.push(Link {
+            line: lines.len(),
+            spans: 0..1,
+            url: item.link.clone(),
+        });
+        lines.push(Line::from(part.to_string()).fg(Color::LightBlue));
+    }
+    for link in &item.alternate_links {
+        lines.extend(
+            textwrap::wrap(&format!("{}: {}", link.kind.label(), link.url), &opts)
+                .iter()
+                .map(|s| Line::from(s.to_string()).fg(Color::Gray)),
+        );
+    }
+
+    lines.push(Line::from("─".repeat(width)).fg(Color::DarkGray));
+    lines.push(Line::default());
+
+    (lines, links)
 }
@@ -0,0 +1,41 @@
+use ratatui::{Frame, buffer::Buffer, layout::Rect};
+
+/// Output of the last draw of a component.
+///
+/// When component's state didn't change since the last frame, the cached cells are copied
+/// to the frame instead of drawing the component again.
+#[derive(Default)]
+pub(crate) struct DrawCache {
+    buffer: Option<Buffer>,
+}
+
+impl DrawCache {
+    /// Draw component into the area with `draw`, unless the component is not dirty
+    /// and output for the same area is cached.
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        dirty: bool,
+        draw: impl FnOnce(&mut Frame, Rect),
+    ) {
+        if let Some(buffer) = &self.buffer
+            && !dirty
+            && buffer.area == area
+        {
+            frame.buffer_mut().merge(buffer);
+            return;
+        }
+
+        draw(frame, area);
+
+        let mut buffer = Buffer::empty(area);
+        let frame_buffer = frame.buffer_mut();
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buffer[(x, y)] = frame_buffer[(x, y)].clone();
+            }
+        }
+        self.buffer = Some(buffer);
+    }
+}
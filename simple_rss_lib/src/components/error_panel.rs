@@ -0,0 +1,104 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Stylize},
+    text::{Line, Text},
+    widgets::{Block, BorderType, Clear, Paragraph, Wrap},
+};
+
+use crate::{
+    data::RefreshError,
+    event::{Event, EventState, InputEvent},
+};
+
+/// Popup listing the channels that failed in the last failed refresh, with their errors.
+///
+/// While the panel is open it handles all [`Event::Input`]s: Up and Down scroll
+/// the list and Esc, Enter or `q` close it.
+#[derive(Default)]
+pub struct ErrorPanel {
+    open: bool,
+    errors: Vec<RefreshError>,
+    scroll: u16,
+}
+
+impl ErrorPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.scroll = 0;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::RefreshFinished { errors } if !errors.is_empty() => {
+                self.errors = errors.clone();
+                self.scroll = 0;
+                if self.open {
+                    EventState::Handled
+                } else {
+                    EventState::Ignored
+                }
+            }
+            Event::Input(input) if self.open => {
+                match input {
+                    InputEvent::Up => self.scroll = self.scroll.saturating_sub(1),
+                    InputEvent::Down => self.scroll = self.scroll.saturating_add(1),
+                    InputEvent::Esc | InputEvent::Enter | InputEvent::Char('q') => {
+                        self.open = false
+                    }
+                    _ => (),
+                }
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if !self.open {
+            return;
+        }
+
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Red)
+            .title("Refresh errors")
+            .title_bottom(Line::from(" Close <Esc> ").centered());
+
+        let mut text = Text::default();
+        if self.errors.is_empty() {
+            text.push_line(Line::from("No refresh errors").bold().centered());
+        }
+        for error in &self.errors {
+            text.push_line(Line::from(error.channel.clone()).bold());
+            text.push_line(Line::from(error.url.clone()).fg(Color::Blue));
+            text.push_line(Line::from(error.error.to_string()).fg(Color::Red));
+            text.push_line("");
+        }
+
+        // Keep at least the last line visible.
+        self.scroll = self.scroll.min(text.height().saturating_sub(1) as u16);
+
+        let paragraph = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(block);
+        frame.render_widget(paragraph, area);
+    }
+}
@@ -1,121 +1,468 @@
+use std::collections::HashMap;
+
 use ratatui::{
     Frame,
-    layout::Rect,
-    style::Stylize,
+    style::{Color, Stylize},
+    text::{Line, Span},
     widgets::{Block, BorderType, Clear, Paragraph},
 };
+use unicode_width::UnicodeWidthStr;
+
+use super::centered;
+use crate::event::{Event, EventState, KeyboardEvent};
+
+const SPACING: usize = 3;
+
+/// Configuration of the [`Help`].
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub disable_read_status: bool,
+    pub disable_browser_open: bool,
+    pub enable_download: bool,
+    pub enable_narration: bool,
+    pub enable_export: bool,
+
+    /// Keys bound to each action, e.g. `<Up> / <k>`. Default bindings are shown if not set.
+    pub key_labels: Option<HashMap<KeyboardEvent, String>>,
+}
+
+/// Pane the actions of an entry apply to. Entries are shown grouped by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    General,
+    ItemList,
+    Content,
+    Narration,
+}
+
+/// Feature that has to be enabled for an entry to be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Requires {
+    Nothing,
+    ReadStatus,
+    Browser,
+    Download,
+    Export,
+    Narration,
+    // Actions handled by the embedder are listed only if their keys are given.
+    EmbedderKey,
+}
+
+struct Entry {
+    group: Group,
+    events: &'static [KeyboardEvent],
+    desc: &'static str,
+    requires: Requires,
+}
 
-const SPACING: u16 = 3;
-const NR_ENTRIES: u16 = 6;
+const fn entry(
+    group: Group,
+    events: &'static [KeyboardEvent],
+    desc: &'static str,
+    requires: Requires,
+) -> Entry {
+    Entry {
+        group,
+        events,
+        desc,
+        requires,
+    }
+}
+
+/// All the actions with their descriptions, in the order they are shown.
+const ENTRIES: &[Entry] = {
+    use Group as G;
+    use KeyboardEvent as K;
+    use Requires as R;
+
+    &[
+        entry(G::General, &[K::Back], "Go Back / Exit", R::Nothing),
+        entry(G::General, &[K::Help], "Show this help", R::Nothing),
+        entry(
+            G::General,
+            &[K::Left, K::Right],
+            "Change focus between item list and content",
+            R::Nothing,
+        ),
+        entry(
+            G::General,
+            &[K::HistoryBack, K::HistoryForward],
+            "Previous / Next opened item",
+            R::Nothing,
+        ),
+        entry(G::General, &[K::Open], "Open in browser", R::Browser),
+        entry(
+            G::General,
+            &[K::OpenComments],
+            "Open comments in browser",
+            R::Browser,
+        ),
+        entry(G::General, &[K::CopyLink], "Copy link", R::Nothing),
+        entry(
+            G::General,
+            &[K::CopyArticle],
+            "Copy article as Markdown",
+            R::Nothing,
+        ),
+        entry(
+            G::General,
+            &[K::Download],
+            "Download attachment",
+            R::Download,
+        ),
+        entry(
+            G::General,
+            &[K::ShrinkList, K::GrowList],
+            "Make item list narrower / wider",
+            R::Nothing,
+        ),
+        entry(
+            G::General,
+            &[K::Fullscreen],
+            "Toggle full screen content",
+            R::Nothing,
+        ),
+        entry(
+            G::General,
+            &[K::RefreshErrors],
+            "Show errors of the last refresh",
+            R::Nothing,
+        ),
+        entry(
+            G::General,
+            &[K::Stats],
+            "Show channel statistics",
+            R::Nothing,
+        ),
+        entry(G::General, &[K::Channels], "Reorder channels", R::Nothing),
+        entry(
+            G::General,
+            &[K::Pager],
+            "View opened item in pager",
+            R::EmbedderKey,
+        ),
+        entry(
+            G::General,
+            &[K::ReadLater],
+            "Save item to read-later services",
+            R::EmbedderKey,
+        ),
+        entry(
+            G::General,
+            &[K::Suspend],
+            "Suspend to the shell",
+            R::EmbedderKey,
+        ),
+        entry(
+            G::General,
+            &[K::Changelog],
+            "Show changelog",
+            R::EmbedderKey,
+        ),
+        entry(G::ItemList, &[K::Up, K::Down], "Move up / down", R::Nothing),
+        entry(G::ItemList, &[K::Enter], "Open item", R::Nothing),
+        entry(
+            G::ItemList,
+            &[K::OpenAll],
+            "Open all shown items in browser",
+            R::Browser,
+        ),
+        entry(
+            G::ItemList,
+            &[K::Space],
+            "Mark/Unmark item as read",
+            R::ReadStatus,
+        ),
+        entry(
+            G::ItemList,
+            &[K::MarkAllRead],
+            "Mark all items as read",
+            R::ReadStatus,
+        ),
+        entry(
+            G::ItemList,
+            &[K::Hide, K::Snooze],
+            "Hide / Snooze item",
+            R::Nothing,
+        ),
+        entry(
+            G::ItemList,
+            &[K::ShowHidden],
+            "Show hidden items, to unhide them",
+            R::Nothing,
+        ),
+        entry(
+            G::ItemList,
+            &[K::ToggleStar],
+            "Star/Unstar item",
+            R::Nothing,
+        ),
+        entry(
+            G::ItemList,
+            &[K::Undo],
+            "Undo marking as read or hiding",
+            R::Nothing,
+        ),
+        entry(
+            G::Content,
+            &[K::Up, K::Down],
+            "Scroll up / down",
+            R::Nothing,
+        ),
+        entry(
+            G::Content,
+            &[K::Search, K::SearchNext, K::SearchPrevious],
+            "Search / Next / Previous match",
+            R::Nothing,
+        ),
+        entry(
+            G::Content,
+            &[K::SetMark, K::JumpToMark],
+            "Set mark / Jump to mark (+ letter)",
+            R::Nothing,
+        ),
+        entry(
+            G::Content,
+            &[K::Space, K::Enter],
+            "Next section / Expand or collapse it",
+            R::Nothing,
+        ),
+        entry(G::Content, &[K::Toc], "Table of contents", R::Nothing),
+        entry(
+            G::Content,
+            &[K::PreviousHeading, K::NextHeading],
+            "Previous / Next heading",
+            R::Nothing,
+        ),
+        entry(
+            G::Content,
+            &[K::Export],
+            "Export item as Markdown",
+            R::Export,
+        ),
+        entry(
+            G::Narration,
+            &[K::Listen],
+            "Listen to opened item",
+            R::Narration,
+        ),
+        entry(
+            G::Narration,
+            &[K::NarrationPause, K::NarrationNext, K::NarrationStop],
+            "Pause / Skip / Stop narration",
+            R::Narration,
+        ),
+    ]
+};
+
+impl Group {
+    fn title(self) -> &'static str {
+        match self {
+            Group::General => "General",
+            Group::ItemList => "Item list",
+            Group::Content => "Content",
+            Group::Narration => "Narration",
+        }
+    }
+}
+
+impl Requires {
+    fn is_met(self, event: KeyboardEvent, config: &Config) -> bool {
+        match self {
+            Requires::Nothing => true,
+            Requires::ReadStatus => !config.disable_read_status,
+            Requires::Browser => !config.disable_browser_open,
+            Requires::Download => config.enable_download,
+            Requires::Export => config.enable_export,
+            Requires::Narration => config.enable_narration,
+            Requires::EmbedderKey => config
+                .key_labels
+                .as_ref()
+                .is_some_and(|l| l.contains_key(&event)),
+        }
+    }
+}
 
+/// Popup listing the keyboard shortcuts, grouped by pane. Scrolled with Up and Down
+/// when it doesn't fit on the screen.
 pub struct Help {
     open: bool,
-    keys: Paragraph<'static>,
-    descs: Paragraph<'static>,
-
-    keys_width: u16,
-    descs_width: u16,
+    lines: Vec<Line<'static>>,
+    width: u16,
+    scroll: u16,
+    scroll_hint: String,
 }
 
 impl Help {
-    pub fn new(disable_read_status: bool, disable_browser_open: bool) -> Self {
-        let (keys, descs) = build_paragraph(disable_read_status, disable_browser_open);
-        Self {
-            open: false,
-            keys,
-            descs,
-            keys_width: 28,
-            descs_width: 42,
-        }
+    pub fn new(config: &Config) -> Self {
+        build(config)
     }
 
     pub fn open(&mut self) {
         self.open = true;
+        self.scroll = 0;
     }
 
     pub fn close(&mut self) {
         self.open = false;
     }
 
-    pub fn draw(&self, frame: &mut Frame) {
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        if !self.open {
+            return EventState::Ignored;
+        }
+
+        match event {
+            Event::Keyboard(KeyboardEvent::Up) => {
+                self.scroll = self.scroll.saturating_sub(1);
+                EventState::Handled
+            }
+            Event::Keyboard(KeyboardEvent::Down) => {
+                self.scroll = self.scroll.saturating_add(1);
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
         if !self.open {
             return;
         }
 
-        let width = self.keys_width + self.descs_width + SPACING + 2 + 2; // 2 border + 2 space
-        let height = NR_ENTRIES + 2 + 1; // 2  border + 1  title
-        let area = Rect::new(
-            (frame.area().width - width) / 2,
-            (frame.area().height - height) / 2,
-            width,
-            height,
-        );
+        let height = self.lines.len() as u16 + 2; // 2 border
+        let area = centered(frame.area(), self.width, height);
         frame.render_widget(Clear, area);
 
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .border_type(BorderType::Rounded)
             .title("Help");
-        frame.render_widget(block, area);
-
-        frame.render_widget(
-            Paragraph::new("Key:").centered().bold(),
-            Rect::new(area.x + 2, area.y + 1, self.keys_width, 1),
-        );
-        frame.render_widget(
-            &self.keys,
-            Rect::new(area.x + 2, area.y + 2, self.keys_width, NR_ENTRIES),
-        );
-
-        frame.render_widget(
-            Paragraph::new("Description:").bold(),
-            Rect::new(
-                area.x + 2 + self.keys_width + SPACING,
-                area.y + 1,
-                self.descs_width,
-                1,
-            ),
-        );
-        frame.render_widget(
-            &self.descs,
-            Rect::new(
-                area.x + 2 + self.keys_width + SPACING,
-                area.y + 2,
-                self.descs_width,
-                NR_ENTRIES,
-            ),
-        );
+        let visible = area.height.saturating_sub(2);
+        let overflow = (self.lines.len() as u16).saturating_sub(visible);
+        if overflow > 0 {
+            block = block.title_bottom(Line::from(self.scroll_hint.clone()).centered());
+        }
+        self.scroll = self.scroll.min(overflow);
+
+        // Descriptions are cut off on narrow screens.
+        let paragraph = Paragraph::new(self.lines.clone())
+            .scroll((self.scroll, 0))
+            .block(block);
+        frame.render_widget(paragraph, area);
     }
 }
 
-fn build_paragraph(
-    disable_read_status: bool,
-    disable_browser_open: bool,
-) -> (Paragraph<'static>, Paragraph<'static>) {
-    let mut keys = vec!["<Enter>".into(), "<Esc> / <q>".into()];
-    if !disable_browser_open {
-        keys.push("<o>".into());
-    }
-    if !disable_read_status {
-        keys.push("<Space>".into());
+fn build(config: &Config) -> Help {
+    let labels = config.key_labels.as_ref();
+
+    // Skip actions without any bound keys.
+    let entries: Vec<_> = ENTRIES
+        .iter()
+        .filter(|e| e.events.iter().all(|ev| e.requires.is_met(*ev, config)))
+        .filter_map(|e| {
+            let keys = e
+                .events
+                .iter()
+                .filter_map(|event| key_label(labels, *event))
+                .collect::<Vec<_>>()
+                .join(" / ");
+            (!keys.is_empty()).then_some((e.group, keys, e.desc))
+        })
+        .collect();
+
+    let keys_width = entries.iter().map(|(_, k, _)| k.width()).max().unwrap_or(0);
+    let descs_width = entries.iter().map(|(_, _, d)| d.width()).max().unwrap_or(0);
+
+    let mut lines = vec![];
+    let mut group = None;
+    for (entry_group, keys, desc) in entries {
+        if group != Some(entry_group) {
+            if group.is_some() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(entry_group.title()).bold().fg(Color::Yellow));
+            group = Some(entry_group);
+        }
+
+        lines.push(Line::from(vec![
+            Span::from(format!(" {keys:^keys_width$}")).blue().bold(),
+            Span::from(" ".repeat(SPACING)),
+            Span::from(desc),
+        ]));
     }
-    keys.extend_from_slice(&[
-        "<Up> / <Down> / <j> / <k>".into(),
-        "<Left> / <Right> / <h> / <l>".into(),
-    ]);
-    let keys = Paragraph::new(keys).centered().blue().bold();
-
-    let mut descs = vec!["Select".into(), "Go Back / Exit".into()];
-    if !disable_browser_open {
-        descs.push("Open in browser".into());
+
+    let scroll_keys = [KeyboardEvent::Up, KeyboardEvent::Down]
+        .into_iter()
+        .filter_map(|event| key_label(labels, event))
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    Help {
+        open: false,
+        lines,
+        // 2 border + 2 space
+        width: (1 + keys_width + SPACING + descs_width + 4) as u16,
+        scroll: 0,
+        scroll_hint: format!(" Scroll {scroll_keys} "),
     }
-    if !disable_read_status {
-        descs.push("Mark/Unmark item in list as read".into());
+}
+
+/// Keys bound to the action, falling back to the default bindings if labels are not given.
+pub(crate) fn key_label(
+    labels: Option<&HashMap<KeyboardEvent, String>>,
+    event: KeyboardEvent,
+) -> Option<String> {
+    if let Some(labels) = labels {
+        return labels.get(&event).cloned();
     }
-    descs.extend_from_slice(&[
-        "Scroll up / down".into(),
-        "Change focus between item list and content".into(),
-    ]);
-    let descs = Paragraph::new(descs);
 
-    (keys, descs)
+    let label = match event {
+        KeyboardEvent::Left => "<Left> / <h>",
+        KeyboardEvent::Right => "<Right> / <l>",
+        KeyboardEvent::Up => "<Up> / <k>",
+        KeyboardEvent::Down => "<Down> / <j>",
+        KeyboardEvent::Back => "<Esc> / <q>",
+        KeyboardEvent::Enter => "<Enter>",
+        KeyboardEvent::Space => "<Space>",
+        KeyboardEvent::Open => "<o>",
+        KeyboardEvent::OpenComments => "<c>",
+        KeyboardEvent::OpenAll => "<A>",
+        KeyboardEvent::Download => "<D>",
+        KeyboardEvent::Help => "<?>",
+        KeyboardEvent::Listen => "<s>",
+        KeyboardEvent::NarrationNext => "<.>",
+        KeyboardEvent::NarrationPause => "<p>",
+        KeyboardEvent::NarrationStop => "<x>",
+        KeyboardEvent::Search => "</>",
+        KeyboardEvent::SearchNext => "<n>",
+        KeyboardEvent::SearchPrevious => "<N>",
+        KeyboardEvent::SetMark => "<m>",
+        KeyboardEvent::JumpToMark => "<'>",
+        KeyboardEvent::Toc => "<t>",
+        KeyboardEvent::PreviousHeading => "<{>",
+        KeyboardEvent::NextHeading => "<}>",
+        KeyboardEvent::CopyLink => "<y>",
+        KeyboardEvent::CopyArticle => "<Y>",
+        KeyboardEvent::Export => "<E>",
+        KeyboardEvent::ShrinkList => "<<>",
+        KeyboardEvent::GrowList => "<>>",
+        KeyboardEvent::Fullscreen => "<f>",
+        KeyboardEvent::MarkAllRead => "<R>",
+        KeyboardEvent::Undo => "<u>",
+        KeyboardEvent::Hide => "<d>",
+        KeyboardEvent::Snooze => "<z>",
+        KeyboardEvent::ShowHidden => "<H>",
+        KeyboardEvent::ToggleStar => "<*>",
+        KeyboardEvent::RefreshErrors => "<e>",
+        KeyboardEvent::Stats => "<S>",
+        KeyboardEvent::Channels => "<M>",
+        KeyboardEvent::HistoryBack => "<[>",
+        KeyboardEvent::HistoryForward => "<]>",
+        KeyboardEvent::Changelog
+        | KeyboardEvent::Pager
+        | KeyboardEvent::ReadLater
+        | KeyboardEvent::Suspend => return None,
+    };
+
+    Some(label.to_string())
 }
@@ -0,0 +1,210 @@
+use ratatui::{Frame, layout::Rect, text::Line, widgets::Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use crate::event::{Event, InputEvent, Modifiers};
+
+const CURSOR: &str = "▏";
+
+// Number of submitted values that are remembered.
+const HISTORY_LIMIT: usize = 50;
+
+/// Result of handling an event by the [`Input`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputState {
+    Ignored,
+    /// Cursor moved, but the value is the same.
+    Moved,
+    Changed,
+    /// Enter was pressed. Value is added to the history.
+    Submitted,
+    /// Esc was pressed.
+    Cancelled,
+}
+
+impl InputState {
+    pub fn is_handled(&self) -> bool {
+        *self != InputState::Ignored
+    }
+}
+
+/// Single line text input with a cursor and history of submitted values.
+///
+/// Handles [`Event::Input`]s: characters are inserted at the cursor, Left/Right/Home/End
+/// move the cursor and Up/Down go through the history. Like in shells, Ctrl-W deletes the
+/// word before the cursor and Ctrl-U everything before it.
+#[derive(Debug, Default)]
+pub struct Input {
+    value: String,
+    // Position of the cursor in characters.
+    cursor: usize,
+
+    history: Vec<String>,
+    // Position in history while browsing it, and the value typed before browsing.
+    history_idx: Option<usize>,
+    draft: String,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the value and move the cursor to the end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.chars().count();
+        self.history_idx = None;
+    }
+
+    /// Clear the value, keeping the history.
+    pub fn clear(&mut self) {
+        self.set_value(String::new());
+    }
+
+    /// Previously submitted values, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> InputState {
+        let Event::Input(input) = event else {
+            return InputState::Ignored;
+        };
+
+        match input {
+            InputEvent::Char(ch) => {
+                let idx = self.byte_idx(self.cursor);
+                self.value.insert(idx, *ch);
+                self.cursor += 1;
+                self.history_idx = None;
+                InputState::Changed
+            }
+            InputEvent::Shortcut('w', Modifiers { ctrl: true, .. }) => {
+                let before: Vec<_> = self.value.chars().take(self.cursor).collect();
+                let spaces = before
+                    .iter()
+                    .rev()
+                    .take_while(|ch| ch.is_whitespace())
+                    .count();
+                let word = before[..before.len() - spaces]
+                    .iter()
+                    .rev()
+                    .take_while(|ch| !ch.is_whitespace())
+                    .count();
+                self.delete_before(spaces + word)
+            }
+            InputEvent::Shortcut('u', Modifiers { ctrl: true, .. }) => {
+                self.delete_before(self.cursor)
+            }
+            InputEvent::Shortcut(..) => InputState::Ignored,
+            InputEvent::Backspace => {
+                if self.cursor == 0 {
+                    return InputState::Moved;
+                }
+                self.cursor -= 1;
+                self.value.remove(self.byte_idx(self.cursor));
+                self.history_idx = None;
+                InputState::Changed
+            }
+            InputEvent::Delete => {
+                if self.cursor == self.value.chars().count() {
+                    return InputState::Moved;
+                }
+                self.value.remove(self.byte_idx(self.cursor));
+                self.history_idx = None;
+                InputState::Changed
+            }
+            InputEvent::Left => {
+                self.cursor = self.cursor.saturating_sub(1);
+                InputState::Moved
+            }
+            InputEvent::Right => {
+                self.cursor = (self.cursor + 1).min(self.value.chars().count());
+                InputState::Moved
+            }
+            InputEvent::Home => {
+                self.cursor = 0;
+                InputState::Moved
+            }
+            InputEvent::End => {
+                self.cursor = self.value.chars().count();
+                InputState::Moved
+            }
+            InputEvent::Up => self.browse_history(true),
+            InputEvent::Down => self.browse_history(false),
+            InputEvent::Enter => {
+                self.history_idx = None;
+                if !self.value.is_empty() && self.history.last() != Some(&self.value) {
+                    if self.history.len() == HISTORY_LIMIT {
+                        self.history.remove(0);
+                    }
+                    self.history.push(self.value.clone());
+                }
+                InputState::Submitted
+            }
+            InputEvent::Esc => {
+                self.history_idx = None;
+                InputState::Cancelled
+            }
+        }
+    }
+
+    /// Delete `count` characters before the cursor.
+    fn delete_before(&mut self, count: usize) -> InputState {
+        if count == 0 {
+            return InputState::Moved;
+        }
+        let start = self.byte_idx(self.cursor - count);
+        let end = self.byte_idx(self.cursor);
+        self.value.replace_range(start..end, "");
+        self.cursor -= count;
+        self.history_idx = None;
+        InputState::Changed
+    }
+
+    fn browse_history(&mut self, older: bool) -> InputState {
+        let idx = match (self.history_idx, older) {
+            (None, true) if !self.history.is_empty() => {
+                self.draft = self.value.clone();
+                Some(self.history.len() - 1)
+            }
+            (None, _) => return InputState::Moved,
+            (Some(idx), true) => Some(idx.saturating_sub(1)),
+            (Some(idx), false) if idx + 1 < self.history.len() => Some(idx + 1),
+            (Some(_), false) => None,
+        };
+
+        let value = match idx {
+            Some(idx) => self.history[idx].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.set_value(value);
+        self.history_idx = idx;
+        InputState::Changed
+    }
+
+    fn byte_idx(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(idx, _)| idx)
+    }
+
+    /// Value with the cursor, after the given prefix.
+    pub fn line(&self, prefix: &str) -> Line<'static> {
+        let (before, after) = self.value.split_at(self.byte_idx(self.cursor));
+        Line::from(format!("{prefix}{before}{CURSOR}{after}"))
+    }
+
+    /// Draw the value, scrolled so that the cursor is visible.
+    pub fn draw(&self, frame: &mut Frame, area: Rect) {
+        let before = &self.value[..self.byte_idx(self.cursor)];
+        let scroll = (before.width() + 1).saturating_sub(area.width as usize);
+        let paragraph = Paragraph::new(self.line("")).scroll((0, scroll as u16));
+        frame.render_widget(paragraph, area);
+    }
+}
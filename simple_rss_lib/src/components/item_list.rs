@@ -1,3 +1,9 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Days, Local, NaiveDate, TimeDelta, Utc};
 use ratatui::{
     Frame,
     layout::Rect,
@@ -8,21 +14,84 @@ use ratatui::{
         ScrollbarState,
     },
 };
+use serde::Deserialize;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    data::{Item, Loader},
-    event::{Event, EventSender, EventState, KeyboardEvent},
+    browser,
+    clipboard::Clipboard,
+    data::{ContentLoader, Hidden, Item, ItemStore},
+    event::{Event, EventSender, EventState, KeyboardEvent, ToastEvent, next_load_id},
+    index::SearchIndex,
+    render::{self, Format},
 };
 
+use super::{
+    help::key_label,
+    input::{Input, InputState},
+};
+
+/// Configuration of the [`ItemList`].
 pub struct Config {
     pub custom_empty_list_msg: Option<Paragraph<'static>>,
     pub disable_read_status: bool,
     pub disable_channel_names: bool,
     pub disable_browser_open: bool,
+    /// Number of description lines shown under the title. Description is hidden if 0.
+    pub description_lines: usize,
+    /// Command used to open links, see [`AppConfig::open_command`](crate::app::AppConfig).
+    pub open_command: Option<String>,
+    /// Open command runs in the terminal, see [`AppConfig::terminal_browser`](crate::app::AppConfig).
+    pub terminal_browser: bool,
+    pub download_dir: Option<PathBuf>,
+
+    /// Items published after this time are marked as new.
+    pub new_since: Option<DateTime<Utc>>,
+
+    /// Keys bound to each action, shown in the instructions. Default bindings are shown if not set.
+    pub key_labels: Option<HashMap<KeyboardEvent, String>>,
+
+    /// Sections the items are grouped into.
+    pub grouping: Grouping,
+
+    /// How long [`KeyboardEvent::Snooze`] hides the item for.
+    pub snooze: TimeDelta,
+}
+
+/// How the items are grouped into sections, each under a header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grouping {
+    /// Items in the order of the store, without headers.
+    #[default]
+    None,
+    /// By publication day, newest first: "Today", "Yesterday", then the dates.
+    Day,
+    /// By channel. Channels are in the order of their first items in the store.
+    Channel,
 }
 
-pub struct ItemList<L: Loader> {
+const NEW_BADGE: &str = "NEW";
+/// Shown before titles of starred items.
+const STAR: &str = "★";
+
+// Number of changes that can be undone.
+const UNDO_LIMIT: usize = 20;
+
+// Maximum number of items opened at once with [`ItemList::open_shown`], so that a long list
+// doesn't start hundreds of browser tabs.
+const MAX_OPEN_ALL: usize = 30;
+
+// Number of loaded articles kept for the search. The oldest ones are dropped first.
+const MAX_ARTICLES: usize = 100;
+
+// Number of items rendered above and below the visible ones, so that scrolling by a few
+// items doesn't require rendering.
+const RENDER_MARGIN: usize = 20;
+
+/// Scrollable list of all the items provided by the [`ItemStore`]. Opened items and
+/// attachments are loaded with the [`ContentLoader`].
+pub struct ItemList<S: ItemStore, C: ContentLoader> {
     config: Config,
 
     focused: bool,
@@ -30,21 +99,207 @@ pub struct ItemList<L: Loader> {
     list_state: ListState,
 
     event_tx: EventSender,
-    data_loader: L,
+    data_loader: S,
+    content_loader: C,
+
+    clipboard: Clipboard,
+
+    undo_stack: Vec<Change>,
 
     render_cache: Option<RenderCache>,
+    layout: Option<Layout>,
+    // Hidden and snoozed items are listed too, so that they can be unhidden.
+    show_hidden: bool,
+
+    // Ids of the items after the previous refresh, to find the ones the next one adds.
+    known_ids: HashSet<String>,
+    // Items added by the last refresh that added any, marked as new.
+    arrived: HashSet<String>,
 
     empty_list_message: Paragraph<'static>,
+
+    search: Option<ItemSearch>,
+    search_input: Input,
+    search_index: Option<IndexCache>,
+    // Articles loaded in this session by item id, searched together with the descriptions.
+    articles: HashMap<String, String>,
+    // Ids of the kept articles, oldest first.
+    article_order: VecDeque<String>,
+    // Id of the latest load and of its item, to know which item the loaded article is for.
+    loading: Option<(u64, String)>,
+}
+
+/// Change of the items that can be undone, with their previous state.
+enum Change {
+    /// Previous read status of the items, by item id.
+    Read(Vec<(String, bool)>),
+    /// Item with the id was hidden or unhidden.
+    Hidden(String, Option<Hidden>),
+}
+
+/// Ranked search over the items, started with [`KeyboardEvent::Search`].
+#[derive(Debug, Default)]
+struct ItemSearch {
+    query: String,
+    /// True while the query is being typed.
+    editing: bool,
+
+    // Indices of the matching items, best first.
+    hits: Vec<usize>,
+    current: usize,
+}
+
+impl ItemSearch {
+    /// Counter shown in the border, e.g. `3/12`.
+    fn counter(&self) -> String {
+        if self.hits.is_empty() {
+            "0/0".to_string()
+        } else {
+            format!("{}/{}", self.current + 1, self.hits.len())
+        }
+    }
+}
+
+/// Search index with the ids of the indexed items. Index is rebuilt when the items change,
+/// but not when only their read status does.
+struct IndexCache {
+    ids: Vec<String>,
+    index: SearchIndex,
+}
+
+/// Rows of the list: items in the order they are shown, with the headers of their sections.
+/// Hidden items have no row, unless they are shown too.
+///
+/// Selection is kept as the index of the item in the store, the offset of the list
+/// is a row.
+struct Layout {
+    rows: Vec<Row>,
+    // Row of each item, by item index.
+    item_rows: Vec<Option<usize>>,
+    // End of the earliest snooze, when the layout has to be computed again.
+    expires: Option<DateTime<Utc>>,
+    version: u16,
+}
+
+enum Row {
+    Header(String),
+    Item(usize),
+}
+
+impl Row {
+    fn is_item(&self) -> bool {
+        matches!(self, Row::Item(_))
+    }
+
+    fn item(&self) -> Option<usize> {
+        match self {
+            Row::Item(idx) => Some(*idx),
+            Row::Header(_) => None,
+        }
+    }
+}
+
+impl Layout {
+    fn new(items: &[Item], grouping: Grouping, show_hidden: bool, version: u16) -> Self {
+        let now = Utc::now();
+        let mut order: Vec<usize> = (0..items.len())
+            .filter(|idx| show_hidden || !items[*idx].is_hidden(now))
+            .collect();
+        let header: fn(&Item, NaiveDate) -> String = match grouping {
+            Grouping::None => |_, _| String::new(),
+            Grouping::Day => {
+                // Newest day first, items without a date at the end.
+                order.sort_by_key(|idx| std::cmp::Reverse(local_day(&items[*idx])));
+                |it, today| match local_day(it) {
+                    Some(day) if day == today => "Today".to_string(),
+                    Some(day) if Some(day) == today.checked_sub_days(Days::new(1)) => {
+                        "Yesterday".to_string()
+                    }
+                    Some(day) => day.format("%A, %Y-%m-%d").to_string(),
+                    None => "No date".to_string(),
+                }
+            }
+            Grouping::Channel => {
+                let mut channels: HashMap<&str, usize> = HashMap::new();
+                for it in items {
+                    let next = channels.len();
+                    channels.entry(&it.channel_name).or_insert(next);
+                }
+                order.sort_by_key(|idx| channels[items[*idx].channel_name.as_str()]);
+                |it, _| it.channel_name.clone()
+            }
+        };
+
+        let today = Local::now().date_naive();
+        let mut rows = Vec::with_capacity(items.len());
+        let mut item_rows = vec![None; items.len()];
+        let mut previous = None;
+        for idx in order {
+            if grouping != Grouping::None {
+                let title = header(&items[idx], today);
+                if previous.as_ref() != Some(&title) {
+                    rows.push(Row::Header(title.clone()));
+                    previous = Some(title);
+                }
+            }
+
+            item_rows[idx] = Some(rows.len());
+            rows.push(Row::Item(idx));
+        }
+
+        let expires = items
+            .iter()
+            .filter_map(|it| match it.hidden {
+                Some(Hidden::Until(until)) if until > now => Some(until),
+                _ => None,
+            })
+            .min();
+
+        Self {
+            rows,
+            item_rows,
+            expires,
+            version,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Visible item closest to the given one, preferring the ones after it in the store.
+    fn nearest_visible(&self, idx: usize) -> Option<usize> {
+        let is_visible = |idx: &usize| self.item_rows[*idx].is_some();
+        (idx..self.item_rows.len())
+            .find(is_visible)
+            .or_else(|| (0..idx.min(self.item_rows.len())).rev().find(is_visible))
+    }
 }
 
+fn local_day(it: &Item) -> Option<NaiveDate> {
+    it.pub_date
+        .map(|date| date.with_timezone(&Local).date_naive())
+}
+
+/// Rendered items, by index. Only the items around the visible ones are rendered.
 struct RenderCache {
-    list: List<'static>,
+    items: Vec<Option<ListItem<'static>>>,
     width: u16,
     version: u16,
 }
 
-impl<L: Loader> ItemList<L> {
-    pub fn new(focused: bool, event_tx: EventSender, data_loader: L, config: Config) -> Self {
+impl<S, C> ItemList<S, C>
+where
+    S: ItemStore,
+    C: ContentLoader + Clone + Send + Sync + 'static,
+{
+    pub fn new(
+        focused: bool,
+        event_tx: EventSender,
+        data_loader: S,
+        content_loader: C,
+        config: Config,
+    ) -> Self {
         let empty_list_message = config.custom_empty_list_msg.clone().unwrap_or_else(|| {
             Paragraph::new(vec![
                 Line::from("Add channels to get started").bold(),
@@ -52,6 +307,11 @@ impl<L: Loader> ItemList<L> {
             ])
             .centered()
         });
+        let known_ids = data_loader
+            .get_items()
+            .iter()
+            .map(|it| it.id.clone())
+            .collect();
 
         Self {
             config,
@@ -59,8 +319,21 @@ impl<L: Loader> ItemList<L> {
             list_state: ListState::default(),
             event_tx,
             data_loader,
+            content_loader,
+            clipboard: Clipboard::new(),
+            undo_stack: Vec::new(),
             render_cache: None,
+            layout: None,
+            show_hidden: false,
+            known_ids,
+            arrived: HashSet::new(),
             empty_list_message,
+            search: None,
+            search_input: Input::new(),
+            search_index: None,
+            articles: HashMap::new(),
+            article_order: VecDeque::new(),
+            loading: None,
         }
     }
 
@@ -68,26 +341,142 @@ impl<L: Loader> ItemList<L> {
         self.focused = focused;
     }
 
+    /// Id of the selected item.
+    pub fn selected_id(&self) -> Option<String> {
+        let selected = self.list_state.selected()?;
+        let data = self.data_loader.get_items();
+        data.get(selected).map(|it| it.id.clone())
+    }
+
+    /// Select item with the given id, if it exists and is listed.
+    pub fn select_id(&mut self, id: &str) {
+        let data = self.data_loader.get_items();
+        let now = Utc::now();
+        if let Some(idx) = data
+            .iter()
+            .position(|it| it.id == id && (self.show_hidden || !it.is_hidden(now)))
+        {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    /// Mark items published after the given time as new.
+    pub fn set_new_since(&mut self, new_since: Option<DateTime<Utc>>) {
+        self.config.new_since = new_since;
+        self.render_cache = None;
+    }
+
+    /// Returns true if the data changed since the list was last drawn, or a snoozed item
+    /// should be shown again.
+    pub fn is_stale(&self) -> bool {
+        self.render_cache
+            .as_ref()
+            .is_none_or(|cache| cache.version != self.data_loader.get_version())
+            || self.layout.as_ref().is_some_and(Layout::is_expired)
+    }
+
+    /// Returns true while the search query is being typed, in which case keyboard
+    /// shortcuts shouldn't be triggered.
+    pub fn is_capturing_input(&self) -> bool {
+        self.search.as_ref().is_some_and(|s| s.editing)
+    }
+
     pub fn handle_event(&mut self, event: &Event) -> EventState {
         match event {
             Event::Keyboard(key_event) => self.handle_keyboard_event(*key_event),
+            Event::Input(_) => self.handle_input(event),
+            Event::RefreshFinished { .. } => self.mark_arrived(),
+            Event::StartLoadingItem(id, item) => {
+                self.loading = Some((*id, item.id.clone()));
+                EventState::Ignored
+            }
+            Event::LoadedItem(id, Ok(html)) => {
+                if let Some((_, item_id)) = self.loading.take_if(|(load_id, _)| load_id == id) {
+                    self.add_article(item_id, html.clone());
+                }
+                EventState::Ignored
+            }
             _ => EventState::Ignored,
         }
     }
 
+    /// Mark items added since the previous refresh as new and let the user know how many
+    /// there are. Items are not marked when the list was empty, since everything is new.
+    fn mark_arrived(&mut self) -> EventState {
+        let ids: HashSet<_> = self
+            .data_loader
+            .get_items()
+            .iter()
+            .map(|it| it.id.clone())
+            .collect();
+        let known_ids = std::mem::replace(&mut self.known_ids, ids);
+        let arrived: HashSet<_> = self.known_ids.difference(&known_ids).cloned().collect();
+        if arrived.is_empty() || known_ids.is_empty() {
+            return EventState::Ignored;
+        }
+
+        let message = match arrived.len() {
+            1 => "1 new item".to_string(),
+            count => format!("{count} new items"),
+        };
+        self.event_tx.send(Event::Toast(ToastEvent::Info(message)));
+
+        self.arrived = arrived;
+        self.render_cache = None;
+        EventState::Handled
+    }
+
     fn handle_keyboard_event(&mut self, event: KeyboardEvent) -> EventState {
         //  Handle open browser separately, because it's independent of focus.
         if event == KeyboardEvent::Open && !self.config.disable_browser_open {
-            if let Some(selected) = self.list_state.selected() {
-                let data = self.data_loader.get_items();
+            let selected = self.list_state.selected();
+            let item = selected.and_then(|idx| self.data_loader.get_items().get(idx).cloned());
+            if let Some(item) = item {
+                self.open_in_browser(&item);
+            }
+
+            return EventState::Handled;
+        }
+
+        if event == KeyboardEvent::OpenComments && !self.config.disable_browser_open {
+            let selected = self.list_state.selected();
+            let item = selected.and_then(|idx| self.data_loader.get_items().get(idx).cloned());
+            match item.as_ref().and_then(Item::comments) {
+                Some(comments) => self.open_url(comments),
+                None => self.event_tx.send(Event::Toast(ToastEvent::Warning(
+                    "Item has no comments".to_string(),
+                ))),
+            }
+
+            return EventState::Handled;
+        }
 
-                let url = &data[selected].link;
-                let _ = webbrowser::open(url);
+        // Copying the link is also independent of focus.
+        if event == KeyboardEvent::CopyLink {
+            let selected = self.list_state.selected();
+            let link =
+                selected.and_then(|idx| Some(self.data_loader.get_items().get(idx)?.link.clone()));
+            if let Some(link) = link {
+                let toast = match self.clipboard.copy(&link) {
+                    Ok(copied) => ToastEvent::Info(copied.message("Link")),
+                    Err(_) => ToastEvent::Error("Failed to copy link!".to_string()),
+                };
+                self.event_tx.send(Event::Toast(toast));
+            }
+
+            return EventState::Handled;
+        }
 
-                // Set to read
-                if !self.config.disable_read_status {
-                    drop(data); // Drop lock to avoid race condition
-                    self.data_loader.set_read(selected, true);
+        // Downloads are also independent of focus.
+        if let (KeyboardEvent::Download, Some(dir)) = (event, &self.config.download_dir) {
+            let selected = self.list_state.selected();
+            let data = self.data_loader.get_items();
+            if let Some(item) = selected.and_then(|idx| data.get(idx)) {
+                match item.enclosure {
+                    Some(_) => self.start_download(item.clone(), dir.clone()),
+                    None => self.event_tx.send(Event::Toast(ToastEvent::Warning(
+                        "Item has no attachment!".to_string(),
+                    ))),
                 }
             }
 
@@ -100,64 +489,581 @@ impl<L: Loader> ItemList<L> {
 
         match event {
             KeyboardEvent::Up => {
-                self.list_state.select_previous();
+                self.move_selection(false);
                 EventState::Handled
             }
             KeyboardEvent::Down => {
-                self.list_state.select_next();
+                self.move_selection(true);
                 EventState::Handled
             }
             KeyboardEvent::Enter => {
-                if let Some(selected) = self.list_state.selected() {
-                    let data = self.data_loader.get_items();
-
-                    // Start loading item
-                    let url = data[selected].link.clone();
-                    let sender = self.event_tx.clone();
-                    tokio::spawn(async move {
-                        let text = L::load_item(&url).await;
-                        sender.send(Event::LoadedItem(text));
-                    });
-
-                    self.event_tx.send(Event::StartLoadingItem);
-
-                    // Set to read
-                    if !self.config.disable_read_status {
-                        drop(data); // Drop lock to avoid race condition
-                        self.data_loader.set_read(selected, true);
-                    }
+                self.open_selected();
+                EventState::Handled
+            }
+            KeyboardEvent::Space => {
+                let selected = self.list_state.selected();
+                let item = selected.and_then(|idx| {
+                    let it = self.data_loader.get_items().get(idx)?.clone();
+                    Some((it.id, it.read))
+                });
+
+                if let Some((id, read)) = item
+                    && !self.config.disable_read_status
+                {
+                    self.set_read_undoable(vec![(id, read)], !read);
                 }
 
                 EventState::Handled
             }
-            KeyboardEvent::Space => {
-                if let Some(selected) = self.list_state.selected() {
-                    let data = self.data_loader.get_items();
-                    let new_read = !data[selected].read;
+            KeyboardEvent::Hide | KeyboardEvent::Snooze => {
+                self.hide_selected(event == KeyboardEvent::Snooze);
+                EventState::Handled
+            }
+            KeyboardEvent::ShowHidden => {
+                self.show_hidden = !self.show_hidden;
+                self.layout = None;
+                self.render_cache = None;
+                if self.search.is_some() {
+                    self.update_search();
+                }
+                EventState::Handled
+            }
+            KeyboardEvent::ToggleStar => {
+                let selected = self.list_state.selected();
+                let item = selected.and_then(|idx| {
+                    let it = self.data_loader.get_items().get(idx)?.clone();
+                    Some((it.id, it.starred))
+                });
 
-                    if !self.config.disable_read_status {
-                        drop(data); // Drop to avoid race condition
-                        self.data_loader.set_read(selected, new_read);
-                    }
+                if let Some((id, starred)) = item {
+                    self.data_loader.set_starred_by_id(&id, !starred);
+                    let message = if starred {
+                        "Item unstarred"
+                    } else {
+                        "Item starred"
+                    };
+                    self.event_tx
+                        .send(Event::Toast(ToastEvent::Info(message.to_string())));
                 }
 
                 EventState::Handled
             }
+            KeyboardEvent::Undo => {
+                self.undo();
+                EventState::Handled
+            }
+            KeyboardEvent::Search => {
+                self.search_input.clear();
+                self.search = Some(ItemSearch {
+                    editing: true,
+                    ..Default::default()
+                });
+                EventState::Handled
+            }
+            KeyboardEvent::SearchNext | KeyboardEvent::SearchPrevious => {
+                let Some(search) = &mut self.search else {
+                    return EventState::Ignored;
+                };
+
+                let len = search.hits.len().max(1);
+                search.current = if event == KeyboardEvent::SearchNext {
+                    (search.current + 1) % len
+                } else {
+                    (search.current + len - 1) % len
+                };
+                self.select_hit();
+                EventState::Handled
+            }
+            KeyboardEvent::Back if self.search.is_some() => {
+                self.search = None;
+                EventState::Handled
+            }
             _ => EventState::Ignored,
         }
     }
 
+    fn handle_input(&mut self, event: &Event) -> EventState {
+        let Some(search) = &mut self.search else {
+            return EventState::Ignored;
+        };
+        if !self.focused || !search.editing {
+            return EventState::Ignored;
+        }
+
+        match self.search_input.handle_event(event) {
+            InputState::Ignored => return EventState::Ignored,
+            InputState::Moved => return EventState::Handled,
+            InputState::Changed => search.query = self.search_input.value().to_string(),
+            InputState::Submitted => {
+                search.editing = false;
+                return EventState::Handled;
+            }
+            InputState::Cancelled => {
+                self.search = None;
+                return EventState::Handled;
+            }
+        }
+
+        self.update_search();
+        EventState::Handled
+    }
+
+    /// Rank the items by the search query and select the best match.
+    fn update_search(&mut self) {
+        let Some(query) = self.search.as_ref().map(|s| s.query.clone()) else {
+            return;
+        };
+
+        let hits = self.get_search_index().search(&query);
+        let data = self.data_loader.get_items();
+        let now = Utc::now();
+        let hits = hits
+            .into_iter()
+            .map(|hit| hit.item)
+            .filter(|idx| {
+                data.get(*idx)
+                    .is_some_and(|it| self.show_hidden || !it.is_hidden(now))
+            })
+            .collect();
+        drop(data);
+
+        if let Some(search) = &mut self.search {
+            search.hits = hits;
+            search.current = 0;
+        }
+        self.select_hit();
+    }
+
+    fn select_hit(&mut self) {
+        let hit = self
+            .search
+            .as_ref()
+            .and_then(|s| s.hits.get(s.current).copied());
+        if let Some(idx) = hit {
+            self.list_state.select(Some(idx));
+        }
+    }
+
+    /// Index of the current items, rebuilt if they changed since it was built.
+    fn get_search_index(&mut self) -> &SearchIndex {
+        let data = self.data_loader.get_items();
+        let is_current = self.search_index.as_ref().is_some_and(|cache| {
+            cache.ids.len() == data.len()
+                && cache
+                    .ids
+                    .iter()
+                    .zip(data.iter())
+                    .all(|(id, it)| *id == it.id)
+        });
+
+        if !is_current {
+            let mut index = SearchIndex::new(&data);
+            for (idx, it) in data.iter().enumerate() {
+                if let Some(html) = self.articles.get(&it.id) {
+                    index.add_html(idx, html);
+                }
+            }
+            let ids = data.iter().map(|it| it.id.clone()).collect();
+            self.search_index = Some(IndexCache { ids, index });
+        }
+        drop(data);
+
+        &self.search_index.as_ref().unwrap().index
+    }
+
+    /// Remember the loaded article of the item, so that it's found by the search. Only the
+    /// latest [`MAX_ARTICLES`] articles are kept.
+    fn add_article(&mut self, item_id: String, html: String) {
+        if self.articles.contains_key(&item_id) {
+            self.article_order.retain(|id| *id != item_id);
+        } else if let Some(cache) = &mut self.search_index
+            && let Some(idx) = cache.ids.iter().position(|id| *id == item_id)
+        {
+            cache.index.add_html(idx, &html);
+        }
+        self.article_order.push_back(item_id.clone());
+        self.articles.insert(item_id, html);
+
+        if self.article_order.len() > MAX_ARTICLES {
+            if let Some(oldest) = self.article_order.pop_front() {
+                self.articles.remove(&oldest);
+            }
+            // Rebuild the index without the dropped article.
+            self.search_index = None;
+        }
+    }
+
+    /// Start loading the selected item and mark it as read.
+    pub fn open_selected(&mut self) {
+        let selected = self.list_state.selected();
+        let data = self.data_loader.get_items();
+        let Some(item) = selected.and_then(|idx| data.get(idx)).cloned() else {
+            return;
+        };
+        drop(data); // Drop lock to avoid race condition
+
+        let settings = self
+            .data_loader
+            .get_channels()
+            .into_iter()
+            .find(|ch| ch.contains(&item))
+            .map(|ch| ch.settings)
+            .unwrap_or_default();
+        if settings.open_in_browser == Some(true) && !self.config.disable_browser_open {
+            self.open_in_browser(&item);
+            return;
+        }
+
+        // Start loading item
+        let item_id = item.id.clone();
+        let description = item
+            .description
+            .clone()
+            .filter(|_| settings.use_description == Some(true));
+        let readability = settings.readability == Some(true);
+        let id = next_load_id();
+        self.event_tx
+            .send(Event::StartLoadingItem(id, Box::new(item.clone())));
+
+        let sender = self.event_tx.clone();
+        let loader = self.content_loader.clone();
+        tokio::spawn(async move {
+            let text = match description {
+                Some(description) => Ok(description),
+                None => loader.load_item(&item).await,
+            };
+            let text = text.map(|html| {
+                let content = render::main_content(&html).filter(|_| readability);
+                content.unwrap_or(html)
+            });
+            sender.send(Event::LoadedItem(id, text));
+        });
+
+        // Set to read
+        if !self.config.disable_read_status {
+            self.data_loader.set_read_by_id(&item_id, true);
+        }
+    }
+
+    /// Number of the items [`ItemList::open_shown`] opens.
+    pub fn shown_count(&mut self) -> usize {
+        self.shown_items().len().min(MAX_OPEN_ALL)
+    }
+
+    /// Open the shown items in the browser, in the shown order, and mark them as read.
+    /// While searching, only the matches are opened. At most [`MAX_OPEN_ALL`] items are
+    /// opened, the first ones in the list.
+    pub fn open_shown(&mut self) {
+        if self.config.disable_browser_open {
+            return;
+        }
+
+        let mut items = self.shown_items();
+        let shown = items.len();
+        items.truncate(MAX_OPEN_ALL);
+        for item in &items {
+            self.open_url(&item.link);
+        }
+        if !self.config.disable_read_status {
+            let ids: Vec<_> = items.iter().map(|it| it.id.clone()).collect();
+            self.data_loader.set_read_by_ids(&ids, true);
+        }
+
+        if items.len() < shown {
+            self.event_tx.send(Event::Toast(ToastEvent::Info(format!(
+                "Opened the first {} of {shown} items",
+                items.len()
+            ))));
+        } else if items.len() > 1 {
+            self.event_tx.send(Event::Toast(ToastEvent::Info(format!(
+                "Opened {} items",
+                items.len()
+            ))));
+        }
+    }
+
+    /// Items that aren't hidden, in the shown order, or the search matches while searching.
+    fn shown_items(&mut self) -> Vec<Item> {
+        let hits = self
+            .search
+            .as_ref()
+            .filter(|s| !s.query.is_empty())
+            .map(|s| s.hits.clone());
+        let order = match hits {
+            Some(hits) => hits,
+            None => self.layout().rows.iter().filter_map(Row::item).collect(),
+        };
+
+        let data = self.data_loader.get_items();
+        order
+            .into_iter()
+            .filter_map(|idx| data.get(idx).cloned())
+            .collect()
+    }
+
+    fn open_in_browser(&mut self, item: &Item) {
+        self.open_url(&item.link);
+
+        if !self.config.disable_read_status {
+            self.data_loader.set_read_by_id(&item.id, true);
+        }
+    }
+
+    fn open_url(&self, url: &str) {
+        if self.config.terminal_browser && self.config.open_command.is_some() {
+            self.event_tx.send(Event::OpenInTerminal(url.to_string()));
+            return;
+        }
+
+        if browser::open(url, self.config.open_command.as_deref()).is_err() {
+            self.event_tx.send(Event::Toast(ToastEvent::Error(
+                "Failed to open browser!".to_string(),
+            )));
+        }
+    }
+
+    /// Select the next or the previous item in the order they are shown, skipping
+    /// the headers.
+    fn move_selection(&mut self, forward: bool) {
+        let selected = self.list_state.selected();
+        let layout = self.layout();
+        let Some(last) = layout.item_rows.len().checked_sub(1) else {
+            return;
+        };
+
+        let row = selected.and_then(|idx| layout.item_rows[idx.min(last)]);
+        let next = match (row, forward) {
+            (None, true) => layout.rows.iter().position(Row::is_item),
+            (None, false) => layout.rows.iter().rposition(Row::is_item),
+            (Some(row), true) => layout.rows[row + 1..]
+                .iter()
+                .position(Row::is_item)
+                .map(|offset| row + 1 + offset),
+            (Some(row), false) => layout.rows[..row].iter().rposition(Row::is_item),
+        };
+        let next = next.and_then(|row| layout.rows[row].item());
+
+        if next.is_some() {
+            self.list_state.select(next);
+        }
+    }
+
+    /// Select the first unread item shown after the selected one. Returns false if
+    /// there is none.
+    pub fn select_next_unread(&mut self) -> bool {
+        let selected = self.list_state.selected();
+        self.layout();
+        let Some(layout) = &self.layout else {
+            return false;
+        };
+
+        let data = self.data_loader.get_items();
+        let start = selected
+            .and_then(|idx| *layout.item_rows.get(idx)?)
+            .map_or(0, |row| row + 1);
+        let next = layout.rows[start.min(layout.rows.len())..]
+            .iter()
+            .filter_map(Row::item)
+            .find(|idx| data.get(*idx).is_some_and(|it| !it.read));
+        drop(data);
+
+        match next {
+            Some(idx) => {
+                self.list_state.select(Some(idx));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rows of the current items, computed again if they changed.
+    fn layout(&mut self) -> &Layout {
+        let version = self.data_loader.get_version();
+        let data = self.data_loader.get_items();
+        let is_current = self.layout.as_ref().is_some_and(|l| {
+            l.version == version && l.item_rows.len() == data.len() && !l.is_expired()
+        });
+        if !is_current {
+            let layout = Layout::new(&data, self.config.grouping, self.show_hidden, version);
+            self.layout = Some(layout);
+        }
+        drop(data);
+
+        self.layout.as_ref().unwrap()
+    }
+
+    /// Number of the unread items [`ItemList::mark_all_read`] marks as read.
+    pub fn unread_count(&self) -> usize {
+        let now = Utc::now();
+        self.data_loader
+            .get_items()
+            .iter()
+            .filter(|it| !it.read && (self.show_hidden || !it.is_hidden(now)))
+            .count()
+    }
+
+    /// Mark all the listed items as read. Can be undone.
+    pub fn mark_all_read(&mut self) {
+        if self.config.disable_read_status {
+            return;
+        }
+
+        let unread: Vec<_> = {
+            let data = self.data_loader.get_items();
+            let now = Utc::now();
+            data.iter()
+                .filter(|it| !it.read && (self.show_hidden || !it.is_hidden(now)))
+                .map(|it| (it.id.clone(), it.read))
+                .collect()
+        };
+
+        if !unread.is_empty() {
+            let count = unread.len();
+            self.set_read_undoable(unread, true);
+            self.event_tx.send(Event::Toast(ToastEvent::Info(format!(
+                "Marked {count} items as read"
+            ))));
+        }
+    }
+
+    /// Set read status of items, given with their current status by id, so that it can
+    /// be undone.
+    fn set_read_undoable(&mut self, previous: Vec<(String, bool)>, read: bool) {
+        let ids: Vec<_> = previous.iter().map(|(id, _)| id.clone()).collect();
+        self.data_loader.set_read_by_ids(&ids, read);
+        self.push_undo(Change::Read(previous));
+    }
+
+    /// Hide the selected item, or snooze it for the configured time, and select the next
+    /// one. Shown hidden items are unhidden instead. Can be undone.
+    fn hide_selected(&mut self, snooze: bool) {
+        let selected = self.list_state.selected();
+        let now = Utc::now();
+        let item = selected.and_then(|idx| {
+            let it = self.data_loader.get_items().get(idx)?.clone();
+            let is_hidden = it.is_hidden(now);
+            Some((it.id, it.hidden, is_hidden))
+        });
+        let Some((id, previous, is_hidden)) = item else {
+            return;
+        };
+
+        if is_hidden {
+            self.data_loader.set_hidden_by_id(&id, None);
+            self.push_undo(Change::Hidden(id, previous));
+            self.event_tx
+                .send(Event::Toast(ToastEvent::Info("Item unhidden".to_string())));
+            return;
+        }
+
+        // Snooze too long to represent hides the item for good.
+        let until = now
+            .checked_add_signed(self.config.snooze)
+            .filter(|_| snooze);
+        let (hidden, message) = match until {
+            Some(until) => {
+                let local = until.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+                (Hidden::Until(until), format!("Snoozed until {local}"))
+            }
+            None => (Hidden::Always, "Item hidden".to_string()),
+        };
+
+        // Selection moves while the item is still shown, to the previous item if it's
+        // the last one.
+        self.move_selection(true);
+        if self.list_state.selected() == selected {
+            self.move_selection(false);
+        }
+
+        self.data_loader.set_hidden_by_id(&id, Some(hidden));
+        self.push_undo(Change::Hidden(id, previous));
+        self.event_tx.send(Event::Toast(ToastEvent::Info(message)));
+    }
+
+    fn push_undo(&mut self, change: Change) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(change);
+    }
+
+    fn undo(&mut self) {
+        match self.undo_stack.pop() {
+            None => (),
+            Some(Change::Read(previous)) => {
+                let (read, unread): (Vec<_>, Vec<_>) =
+                    previous.into_iter().partition(|(_, read)| *read);
+                let ids = |items: Vec<(String, bool)>| -> Vec<_> {
+                    items.into_iter().map(|(id, _)| id).collect()
+                };
+                self.data_loader.set_read_by_ids(&ids(read), true);
+                self.data_loader.set_read_by_ids(&ids(unread), false);
+            }
+            Some(Change::Hidden(id, previous)) => {
+                self.data_loader.set_hidden_by_id(&id, previous);
+                self.select_id(&id);
+            }
+        }
+    }
+
+    fn start_download(&self, item: Item, dir: PathBuf) {
+        let sender = self.event_tx.clone();
+        let loader = self.content_loader.clone();
+        tokio::spawn(async move {
+            sender.send(Event::Toast(ToastEvent::Loading("Downloading".to_string())));
+
+            let progress_sender = sender.clone();
+            let progress = move |downloaded: u64, total: Option<u64>| {
+                let message = match total {
+                    Some(total) if total > 0 => {
+                        format!("Downloading {}%", downloaded * 100 / total)
+                    }
+                    _ => format!("Downloading {} MB", downloaded / 1_000_000),
+                };
+                progress_sender.send(Event::Toast(ToastEvent::Loading(message)));
+            };
+
+            match loader.download(&item, &dir, progress).await {
+                Ok(_) => sender.send(Event::Toast(ToastEvent::Hide)),
+                Err(err) => sender.send(Event::Toast(ToastEvent::Error(format!(
+                    "Failed to download: {err}"
+                )))),
+            }
+        });
+    }
+
     pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let labels = self.config.key_labels.as_ref();
+        let exit = key_label(labels, KeyboardEvent::Back).unwrap_or_default();
+        let help = key_label(labels, KeyboardEvent::Help).unwrap_or_default();
         let instructions = Line::from(vec![
             "Exit ".into(),
-            "<Esc> / <q>  ".blue().bold(),
+            format!("{exit}  ").blue().bold(),
             "Help ".into(),
-            "<?>".blue().bold(),
+            help.blue().bold(),
         ]);
+        let title = if self.show_hidden {
+            "Items (with hidden)"
+        } else {
+            "Items"
+        };
         let mut block = Block::bordered()
             .border_type(BorderType::Rounded)
-            .title(Line::from("Items"))
-            .title_bottom(instructions.centered());
+            .title(Line::from(title));
+        match &self.search {
+            Some(search) if search.editing => {
+                block = block.title_bottom(self.search_input.line(" /"));
+            }
+            Some(search) => {
+                block = block
+                    .title(
+                        Line::from(format!(" /{} [{}] ", search.query, search.counter()))
+                            .right_aligned(),
+                    )
+                    .title_bottom(instructions.centered());
+            }
+            None => block = block.title_bottom(instructions.centered()),
+        }
         if !self.focused {
             block = block.border_style(Color::Gray)
         }
@@ -165,40 +1071,69 @@ impl<L: Loader> ItemList<L> {
         frame.render_widget(block, area);
 
         // List
-        let mut list_state = self.list_state.clone();
-        let list = self.get_render_cache(list_area);
-        let nr_items = list.list.len();
-
-        if nr_items == 0 {
-            self.draw_empty(frame, list_area);
+        self.get_render_cache(list_area);
+        let selected = self.list_state.selected();
+        let layout = self.layout();
+        let nr_items = layout.item_rows.len();
+        let nr_rows = layout.rows.len();
+        // Selected item could have been hidden, e.g. by another client.
+        let selected =
+            selected.and_then(|sel| layout.nearest_visible(sel.min(nr_items.saturating_sub(1))));
+        let selected_row = selected.and_then(|sel| layout.item_rows[sel]);
+        // Header of the section is scrolled into view together with its first item.
+        let top_row = selected_row.map(|row| match row.checked_sub(1) {
+            Some(above) if !layout.rows[above].is_item() => above,
+            _ => row,
+        });
+        if nr_rows == 0 {
+            self.draw_empty(frame, list_area, nr_items > 0);
             return;
         }
 
-        frame.render_stateful_widget(&list.list, list_area, &mut list_state);
-        self.list_state = list_state;
+        // Every row takes at least one line, so no more than `height` rows are visible
+        // after the offset. The offset is moved so that the selected item is among them.
+        let height = list_area.height.max(1) as usize;
+        let mut offset = self.list_state.offset().min(nr_rows - 1);
+        if let (Some(row), Some(top)) = (selected_row, top_row) {
+            if top < offset {
+                offset = top;
+            } else if row >= offset + height {
+                offset = row + 1 - height;
+            }
+        }
+
+        let start = offset.saturating_sub(RENDER_MARGIN);
+        let end = (offset + height + RENDER_MARGIN).min(nr_rows);
+        let list = List::new(self.render_window(start, end))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+
+        let mut window_state = ListState::default()
+            .with_offset(offset - start)
+            .with_selected(selected_row.map(|row| row - start));
+        frame.render_stateful_widget(list, list_area, &mut window_state);
+        *self.list_state.offset_mut() = window_state.offset() + start;
+        self.list_state.select(selected);
 
         // Scrollbar
         let scroll_bar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
-        let mut bar_state =
-            ScrollbarState::new(nr_items).position(self.list_state.selected().unwrap_or(0));
+        let mut bar_state = ScrollbarState::new(nr_rows).position(selected_row.unwrap_or(0));
         frame.render_stateful_widget(scroll_bar, area, &mut bar_state);
     }
 
-    fn draw_empty(&self, frame: &mut Frame, mut area: Rect) {
+    fn draw_empty(&self, frame: &mut Frame, mut area: Rect, all_hidden: bool) {
         area.y = area.height / 2;
-        frame.render_widget(&self.empty_list_message, area);
+        if all_hidden {
+            let message = Paragraph::new(Line::from("All items are hidden").bold()).centered();
+            frame.render_widget(message, area);
+        } else {
+            frame.render_widget(&self.empty_list_message, area);
+        }
     }
 
     fn recalculate_render_cache(&mut self, area: Rect) -> &RenderCache {
-        let data = self.data_loader.get_items();
-        let list = List::new(
-            data.iter()
-                .map(|it| item_to_list_item(it, area.width as usize, &self.config)),
-        )
-        .highlight_style(Style::default().bg(Color::DarkGray));
-
+        let nr_items = self.data_loader.get_items().len();
         self.render_cache = Some(RenderCache {
-            list,
+            items: vec![None; nr_items],
             width: area.width,
             version: self.data_loader.get_version(),
         });
@@ -206,6 +1141,47 @@ impl<L: Loader> ItemList<L> {
         self.render_cache.as_ref().unwrap()
     }
 
+    /// Rendered rows in the given range, rendering the items that aren't cached yet.
+    fn render_window(&mut self, start: usize, end: usize) -> Vec<ListItem<'static>> {
+        let (Some(cache), Some(layout)) = (&mut self.render_cache, &self.layout) else {
+            return vec![];
+        };
+
+        let end = end.min(layout.rows.len());
+        let start = start.min(end);
+        let width = cache.width as usize;
+
+        // Data could have changed since the cache was checked, it's updated on next draw.
+        let data = self.data_loader.get_items();
+        let now = Utc::now();
+        layout.rows[start..end]
+            .iter()
+            .filter_map(|row| {
+                let idx = match row {
+                    Row::Header(title) => return Some(header_list_item(title, width)),
+                    Row::Item(idx) => *idx,
+                };
+                let (rendered, it) = (cache.items.get_mut(idx)?, data.get(idx)?);
+                let rendered = rendered
+                    .get_or_insert_with(|| {
+                        let is_new = self.arrived.contains(&it.id)
+                            || self
+                                .config
+                                .new_since
+                                .is_some_and(|since| it.pub_date.is_some_and(|date| date > since));
+                        let rendered = item_to_list_item(it, width, is_new, &self.config);
+                        if it.is_hidden(now) {
+                            rendered.dim()
+                        } else {
+                            rendered
+                        }
+                    })
+                    .clone();
+                Some(rendered)
+            })
+            .collect()
+    }
+
     fn get_render_cache(&mut self, area: Rect) -> &RenderCache {
         let Some(render_cache) = &self.render_cache else {
             return self.recalculate_render_cache(area);
@@ -221,9 +1197,9 @@ impl<L: Loader> ItemList<L> {
     }
 }
 
-fn item_to_list_item(it: &Item, width: usize, config: &Config) -> ListItem<'static> {
+fn item_to_list_item(it: &Item, width: usize, is_new: bool, config: &Config) -> ListItem<'static> {
     // Title
-    let mut opts = textwrap::Options::new(width - 1).break_words(true);
+    let mut opts = textwrap::Options::new(width.saturating_sub(1).max(1)).break_words(true);
     if !config.disable_read_status {
         opts = opts.subsequent_indent("    ");
 
@@ -236,14 +1212,31 @@ fn item_to_list_item(it: &Item, width: usize, config: &Config) -> ListItem<'stat
 
     let mut text = Text::default();
 
-    let title = textwrap::wrap(&it.title, &opts);
-    text.extend(
-        title
-            .iter()
-            .map(|s| Line::from(s.to_string()).bold().fg(Color::LightGreen)),
-    );
+    let mut title = it.title.clone();
+    if it.starred {
+        title = format!("{STAR} {title}");
+    }
+    if is_new {
+        title = format!("{NEW_BADGE} {title}");
+    }
 
-    let mut opts = textwrap::Options::new(width - 2).break_words(true);
+    let title = textwrap::wrap(&title, &opts);
+    text.extend(title.iter().enumerate().map(|(idx, s)| {
+        if idx == 0 && is_new {
+            badge_line(s)
+        } else {
+            Line::from(s.to_string()).bold().fg(Color::LightGreen)
+        }
+    }));
+
+    // Description
+    if config.description_lines > 0
+        && let Some(description) = &it.description
+    {
+        text.extend(description_lines(description, width, config));
+    }
+
+    let mut opts = textwrap::Options::new(width.saturating_sub(2).max(1)).break_words(true);
     if !config.disable_read_status {
         opts = opts.initial_indent("    ").subsequent_indent("    ");
     }
@@ -284,7 +1277,7 @@ fn item_to_list_item(it: &Item, width: usize, config: &Config) -> ListItem<'stat
     }
 
     // Everything can fit on one line, we can do the nice formatting.
-    if total_width < width - 3 {
+    if total_width < width.saturating_sub(3) {
         // 3 = Some buffer to have space around things
         let mut line = if config.disable_read_status {
             Line::default()
@@ -319,3 +1312,54 @@ fn item_to_list_item(it: &Item, width: usize, config: &Config) -> ListItem<'stat
     text.push_line("");
     ListItem::from(text)
 }
+
+/// Header of a section, e.g. `Today ───`.
+fn header_list_item(title: &str, width: usize) -> ListItem<'static> {
+    let fill = width.saturating_sub(title.width() + 2);
+    ListItem::from(Line::from(vec![
+        Span::from(title.to_string()).bold().fg(Color::Yellow),
+        Span::from(format!(" {}", "─".repeat(fill))).fg(Color::DarkGray),
+    ]))
+}
+
+/// First lines of the description without markup, with an ellipsis if it doesn't fit.
+fn description_lines(description: &str, width: usize, config: &Config) -> Vec<Line<'static>> {
+    let description = render::export(description, Format::Text);
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let mut opts = textwrap::Options::new(width.saturating_sub(2).max(1)).break_words(true);
+    if !config.disable_read_status {
+        opts = opts.initial_indent("    ").subsequent_indent("    ");
+    }
+
+    let mut lines = textwrap::wrap(&description, &opts);
+    let truncated = lines.len() > config.description_lines;
+    lines.truncate(config.description_lines);
+
+    let last = lines.len().saturating_sub(1);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            let mut line = s.to_string();
+            if truncated && idx == last {
+                line.push('…');
+            }
+            Line::from(line).fg(Color::Gray)
+        })
+        .collect()
+}
+
+/// First line of the title, with the new badge highlighted.
+fn badge_line(line: &str) -> Line<'static> {
+    let Some((before, after)) = line.split_once(NEW_BADGE) else {
+        return Line::from(line.to_string()).bold().fg(Color::LightGreen);
+    };
+
+    Line::from(vec![
+        Span::from(before.to_string()).fg(Color::LightGreen),
+        Span::from(NEW_BADGE).fg(Color::Black).bg(Color::Yellow),
+        Span::from(after.to_string()).fg(Color::LightGreen),
+    ])
+    .bold()
+}
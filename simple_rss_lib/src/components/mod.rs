@@ -1,12 +1,41 @@
+//! UI components of the reader.
+//!
+//! Each component handles [`Event`](crate::event::Event)s passed to its `handle_event` and
+//! draws itself with `draw`. [`App`](crate::app::App) wires them together, but they can
+//! also be used on their own.
+
+pub mod channel_panel;
+pub mod confirm;
 pub mod content;
+pub mod error_panel;
 pub mod help;
+pub mod input;
 pub mod item_list;
+pub mod narrator;
+pub mod stats_panel;
+pub mod status_bar;
 pub mod toast;
+pub mod toc;
+
+use ratatui::layout::Rect;
+
+mod draw_cache;
+mod search;
 
+pub use channel_panel::ChannelPanel;
+pub use confirm::Confirm;
 pub use content::Content;
+pub use error_panel::ErrorPanel;
 pub use help::Help;
+pub use input::Input;
 pub use item_list::ItemList;
+pub use narrator::Narrator;
+pub use stats_panel::StatsPanel;
+pub use status_bar::StatusBar;
 pub use toast::Toast;
+pub use toc::Toc;
+
+pub(crate) use draw_cache::DrawCache;
 
 const SPINNER_FRAMES: [u32; 10] = [
     0x280B, // ⠋
@@ -26,3 +55,15 @@ fn spinner_frame(tick: usize) -> char {
     // Safe because chars are hardcoded
     unsafe { char::from_u32_unchecked(ch) }
 }
+
+/// Area of the given size in the middle of `area`, shrunk to fit into it.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
@@ -0,0 +1,214 @@
+use std::{collections::VecDeque, process::Stdio};
+
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::event::{Event, EventSender, EventState, KeyboardEvent, ToastEvent};
+
+/// Reads articles aloud with an external text-to-speech command.
+///
+/// Articles are queued and played one after another. The command is run by the shell
+/// and receives the article text on stdin.
+pub struct Narrator {
+    command: Option<String>,
+    event_tx: EventSender,
+
+    queue: VecDeque<String>,
+    current: Option<Playback>,
+    next_id: u64,
+}
+
+struct Playback {
+    id: u64,
+    pid: Option<u32>,
+    paused: bool,
+}
+
+impl Narrator {
+    pub fn new(command: Option<String>, event_tx: EventSender) -> Self {
+        Self {
+            command,
+            event_tx,
+            queue: VecDeque::new(),
+            current: None,
+            next_id: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.command.is_some()
+    }
+
+    /// Adds text to the end of the queue. Playback starts immediately if nothing is playing.
+    pub fn enqueue(&mut self, text: String) {
+        self.queue.push_back(text);
+
+        if self.current.is_none() {
+            self.play_next();
+        } else {
+            self.show_status();
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::Keyboard(KeyboardEvent::NarrationNext) if self.enabled() => {
+                self.stop_current();
+                self.play_next();
+                EventState::Handled
+            }
+            Event::Keyboard(KeyboardEvent::NarrationPause) if self.enabled() => {
+                self.toggle_pause();
+                EventState::Handled
+            }
+            Event::Keyboard(KeyboardEvent::NarrationStop) if self.enabled() => {
+                self.queue.clear();
+                self.stop_current();
+                self.event_tx.send(Event::Toast(ToastEvent::Hide));
+                EventState::Handled
+            }
+            Event::NarrationFinished(id) => {
+                // Ignore narrations that were stopped or skipped.
+                if self.current.as_ref().is_some_and(|p| p.id == *id) {
+                    self.current = None;
+                    self.play_next();
+                }
+
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    fn play_next(&mut self) {
+        let (Some(command), Some(text)) = (&self.command, self.queue.pop_front()) else {
+            self.event_tx.send(Event::Toast(ToastEvent::Hide));
+            return;
+        };
+
+        let mut child = match shell_command(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => {
+                self.queue.clear();
+                self.event_tx.send(Event::Toast(ToastEvent::Error(
+                    "Failed to start narration!".to_string(),
+                )));
+                return;
+            }
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.current = Some(Playback {
+            id,
+            pid: child.id(),
+            paused: false,
+        });
+        self.show_status();
+
+        let sender = self.event_tx.clone();
+        let stdin = child.stdin.take();
+        tokio::spawn(async move {
+            if let Some(mut stdin) = stdin {
+                // Command is allowed to exit without reading everything.
+                let _ = stdin.write_all(text.as_bytes()).await;
+            }
+
+            let _ = child.wait().await;
+            sender.send(Event::NarrationFinished(id));
+        });
+    }
+
+    fn stop_current(&mut self) {
+        let Some(playback) = self.current.take() else {
+            return;
+        };
+
+        if let Some(pid) = playback.pid {
+            signal(pid, Signal::Terminate);
+            if playback.paused {
+                signal(pid, Signal::Continue);
+            }
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        let Some(playback) = &mut self.current else {
+            return;
+        };
+        let Some(pid) = playback.pid else {
+            return;
+        };
+
+        if playback.paused {
+            signal(pid, Signal::Continue);
+        } else {
+            signal(pid, Signal::Stop);
+        }
+        playback.paused = !playback.paused;
+
+        self.show_status();
+    }
+
+    fn show_status(&self) {
+        let Some(playback) = &self.current else {
+            return;
+        };
+
+        let mut message = if playback.paused {
+            "Narration paused".to_string()
+        } else {
+            "Narrating".to_string()
+        };
+        if !self.queue.is_empty() {
+            message.push_str(&format!(" ({} queued)", self.queue.len()));
+        }
+
+        self.event_tx
+            .send(Event::Toast(ToastEvent::Loading(message)));
+    }
+}
+
+enum Signal {
+    Stop,
+    Continue,
+    Terminate,
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    // Run in separate process group, so that the whole pipeline can be paused and stopped.
+    cmd.arg("-c").arg(command).process_group(0);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, signal: Signal) {
+    let signal = match signal {
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+        Signal::Terminate => libc::SIGTERM,
+    };
+
+    // Negative pid sends the signal to the whole process group.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn signal(_pid: u32, _signal: Signal) {
+    // Pausing and stopping is only supported on unix.
+}
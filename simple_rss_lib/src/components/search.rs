@@ -0,0 +1,176 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+/// Case insensitive search over rendered lines.
+#[derive(Debug, Default)]
+pub struct Search {
+    pub query: String,
+
+    /// True while the query is being typed.
+    pub editing: bool,
+
+    matches: Vec<Match>,
+    current: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    line: usize,
+    range: Range<usize>,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Default::default()
+        }
+    }
+
+    /// Find all the matches of the query. Current match is set to the first
+    /// match at or after the given line.
+    pub fn update(&mut self, lines: &[Line], from_line: usize) {
+        self.matches.clear();
+        self.current = 0;
+
+        if self.query.is_empty() {
+            return;
+        }
+
+        let query = self.query.to_lowercase();
+        for (idx, line) in lines.iter().enumerate() {
+            let text = line.to_string();
+            for range in find_all(&text, &query) {
+                self.matches.push(Match { line: idx, range });
+            }
+        }
+
+        self.current = self
+            .matches
+            .iter()
+            .position(|m| m.line >= from_line)
+            .unwrap_or(0);
+    }
+
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    /// Line of the current match.
+    pub fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).map(|m| m.line)
+    }
+
+    /// Counter shown in the border, e.g. `3/12`.
+    pub fn counter(&self) -> String {
+        if self.matches.is_empty() {
+            "0/0".to_string()
+        } else {
+            format!("{}/{}", self.current + 1, self.matches.len())
+        }
+    }
+
+    /// Returns the line with matches highlighted.
+    pub fn highlight(&self, idx: usize, line: Line<'static>) -> Line<'static> {
+        let ranges: Vec<_> = self
+            .matches
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.line == idx)
+            .map(|(i, m)| (m.range.clone(), i == self.current))
+            .collect();
+
+        if ranges.is_empty() {
+            return line;
+        }
+
+        highlight_ranges(line, &ranges)
+    }
+}
+
+/// Byte ranges of all case insensitive occurrences of lowercase `query` in `text`.
+fn find_all(text: &str, query: &str) -> Vec<Range<usize>> {
+    // Lowercasing can change the length of characters, so keep track of where
+    // each lowercase character came from.
+    let mut lower = String::new();
+    let mut offsets = vec![];
+    for (idx, ch) in text.char_indices() {
+        for lower_ch in ch.to_lowercase() {
+            for _ in 0..lower_ch.len_utf8() {
+                offsets.push(idx);
+            }
+            lower.push(lower_ch);
+        }
+    }
+    offsets.push(text.len());
+
+    let mut ranges = vec![];
+    let mut start = 0;
+    while let Some(pos) = lower[start..].find(query) {
+        let from = start + pos;
+        let to = from + query.len();
+        ranges.push(offsets[from]..offsets[to]);
+        start = to;
+    }
+
+    ranges
+}
+
+fn highlight_ranges(mut line: Line<'static>, ranges: &[(Range<usize>, bool)]) -> Line<'static> {
+    let mut spans = vec![];
+    let mut offset = 0;
+
+    for span in std::mem::take(&mut line.spans) {
+        let content = span.content.as_ref();
+        let span_range = offset..offset + content.len();
+
+        // Split points inside this span
+        let mut points = vec![0, content.len()];
+        for (range, _) in ranges {
+            for point in [range.start, range.end] {
+                if span_range.contains(&point) {
+                    points.push(point - offset);
+                }
+            }
+        }
+        points.sort_unstable();
+        points.dedup();
+
+        for part in points.windows(2) {
+            let (from, to) = (part[0], part[1]);
+            let Some(text) = content.get(from..to) else {
+                continue;
+            };
+
+            let matched = ranges
+                .iter()
+                .find(|(range, _)| range.start <= offset + from && offset + to <= range.end);
+            let style = match matched {
+                Some((_, true)) => span
+                    .style
+                    .patch(Style::default().bg(Color::Yellow).fg(Color::Black)),
+                Some((_, false)) => span.style.patch(Style::default().bg(Color::DarkGray)),
+                None => span.style,
+            };
+
+            spans.push(Span::styled(text.to_string(), style));
+        }
+
+        offset += content.len();
+    }
+
+    line.spans = spans;
+    line
+}
@@ -0,0 +1,133 @@
+use chrono::Utc;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Stylize},
+    text::{Line, Text},
+    widgets::{Block, BorderType, Clear, Paragraph},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    data::{Item, ItemStats},
+    event::{Event, EventState, InputEvent},
+};
+
+const COLUMNS: [&str; 5] = ["Channel", "Items", "Unread", "Frequency", "Avg. age"];
+
+/// Popup with statistics of each channel: item and unread counts, posting frequency and
+/// average age of the items. Statistics are taken when the panel is opened.
+///
+/// While the panel is open it handles all [`Event::Input`]s: Up and Down scroll
+/// the list and Esc, Enter or `q` close it.
+#[derive(Default)]
+pub struct StatsPanel {
+    open: bool,
+    channels: Vec<(String, ItemStats)>,
+    scroll: u16,
+}
+
+impl StatsPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, items: &[Item]) {
+        self.open = true;
+        self.channels = ItemStats::by_channel(items, Utc::now());
+        self.scroll = 0;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::Input(input) if self.open => {
+                match input {
+                    InputEvent::Up => self.scroll = self.scroll.saturating_sub(1),
+                    InputEvent::Down => self.scroll = self.scroll.saturating_add(1),
+                    InputEvent::Esc | InputEvent::Enter | InputEvent::Char('q') => {
+                        self.open = false
+                    }
+                    _ => (),
+                }
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if !self.open {
+            return;
+        }
+
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Green)
+            .title("Channel statistics")
+            .title_bottom(Line::from(" Close <Esc> ").centered());
+
+        let rows: Vec<[String; 5]> = self
+            .channels
+            .iter()
+            .map(|(name, stats)| {
+                [
+                    name.clone(),
+                    stats.items.to_string(),
+                    stats.unread.to_string(),
+                    stats.frequency_label(),
+                    stats.age_label(),
+                ]
+            })
+            .collect();
+        let mut widths = COLUMNS.map(|title| title.width());
+        for row in &rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.width());
+            }
+        }
+        // Channel names take whatever is left after the numbers.
+        let numbers_width: usize = widths[1..].iter().map(|w| w + 2).sum();
+        widths[0] = widths[0].min((area.width as usize).saturating_sub(numbers_width + 4));
+
+        let mut text = Text::default();
+        text.push_line(Line::from(format_row(&COLUMNS.map(String::from), &widths)).bold());
+        if rows.is_empty() {
+            text.push_line(Line::from("No items").centered());
+        }
+        for row in &rows {
+            text.push_line(format_row(row, &widths));
+        }
+
+        // Keep at least the last line visible.
+        self.scroll = self.scroll.min(text.height().saturating_sub(1) as u16);
+
+        let paragraph = Paragraph::new(text).scroll((self.scroll, 0)).block(block);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/// Channel name aligned to the left and numbers to the right.
+fn format_row(row: &[String; 5], widths: &[usize; 5]) -> String {
+    let mut name = row[0].clone();
+    while name.width() > widths[0] {
+        name.pop();
+    }
+
+    let mut line = format!(" {name}{}", " ".repeat(widths[0] - name.width()));
+    for (value, width) in row[1..].iter().zip(&widths[1..]) {
+        line.push_str(&format!("  {value:>width$}"));
+    }
+    line
+}
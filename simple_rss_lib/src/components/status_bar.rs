@@ -0,0 +1,98 @@
+use chrono::{DateTime, Local, Utc};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    text::Line,
+};
+
+use crate::{
+    data::Item,
+    event::{Event, EventState},
+};
+
+use super::spinner_frame;
+
+/// One line bar with item counts, active filter and refresh status.
+#[derive(Default)]
+pub struct StatusBar {
+    filter: Option<String>,
+
+    // Number of refreshes in progress.
+    refreshing: usize,
+    last_refresh: Option<DateTime<Local>>,
+    tick: usize,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the description of the active filter, or nothing if `None`.
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter = filter;
+    }
+
+    /// Returns true while the refresh spinner is shown.
+    pub fn is_animating(&self) -> bool {
+        self.refreshing > 0
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::RefreshStarted => {
+                self.refreshing += 1;
+                EventState::Handled
+            }
+            Event::RefreshFinished { errors } => {
+                self.refreshing = self.refreshing.saturating_sub(1);
+                if errors.is_empty() {
+                    self.last_refresh = Some(Local::now());
+                }
+                EventState::Handled
+            }
+            Event::Tick if self.refreshing > 0 => {
+                self.tick = self.tick.wrapping_add(1);
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, area: Rect, items: &[Item]) {
+        let now = Utc::now();
+        let (hidden, shown): (Vec<_>, Vec<_>) = items.iter().partition(|it| it.is_hidden(now));
+        let unread = shown.iter().filter(|it| !it.read).count();
+        let mut counts = Line::from(vec![
+            format!(" {unread}").bold(),
+            " unread / ".into(),
+            format!("{}", shown.len()).bold(),
+            " items".into(),
+        ]);
+        if !hidden.is_empty() {
+            counts.push_span(format!(" ({} hidden)", hidden.len()));
+        }
+
+        let filter = match &self.filter {
+            Some(filter) => Line::from(vec!["Filter: ".into(), filter.clone().bold()]),
+            None => Line::default(),
+        }
+        .centered();
+
+        let refresh = if self.refreshing > 0 {
+            Line::from(format!("Refreshing {} ", spinner_frame(self.tick)))
+        } else {
+            match self.last_refresh {
+                Some(time) => Line::from(format!("Refreshed at {} ", time.format("%H:%M"))),
+                None => Line::default(),
+            }
+        }
+        .right_aligned();
+
+        let [left, middle, right] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(area);
+        frame.render_widget(counts.fg(Color::Gray), left);
+        frame.render_widget(filter.fg(Color::Gray), middle);
+        frame.render_widget(refresh.fg(Color::Gray), right);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{collections::VecDeque, time::Duration};
+
 use ratatui::{
     Frame,
     layout::Rect,
@@ -9,118 +11,202 @@ use crate::event::{Event, EventState, ToastEvent};
 
 use super::spinner_frame;
 
-#[derive(Default)]
-enum ToastState {
-    #[default]
-    Hidden,
-    Loading {
-        message: String,
-        ticks: u32,
-    },
-    Error {
-        error: String,
-        ticks: u32,
-    },
+const WIDTH: u16 = 30;
+const HEIGHT: u16 = 3;
+
+// Number of messages shown at once. The rest wait in the queue.
+const MAX_VISIBLE: usize = 3;
+
+/// Configuration of the [`Toast`].
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    /// How long each kind of message is shown.
+    pub info_timeout: Duration,
+    pub warning_timeout: Duration,
+    pub error_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            info_timeout: Duration::from_secs(5),
+            warning_timeout: Duration::from_secs(8),
+            error_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+struct Message {
+    severity: Severity,
+    text: String,
+    ticks: u32,
 }
 
+struct Loading {
+    message: String,
+    ticks: u32,
+}
+
+/// Small popups in the bottom right corner, showing progress and messages.
+///
+/// Progress is shown with a single loading toast, until it's replaced or hidden with
+/// [`ToastEvent::Hide`]. Messages are stacked above it and hidden after the timeout
+/// of their severity. Messages that don't fit wait in a queue.
 pub struct Toast {
-    state: ToastState,
+    loading: Option<Loading>,
+    messages: VecDeque<Message>,
+
+    config: Config,
     tick_fps: u32,
 }
 
 impl Toast {
-    pub fn new(tick_fps: u32) -> Self {
+    pub fn new(tick_fps: u32, config: Config) -> Self {
         Self {
-            state: ToastState::default(),
+            loading: None,
+            messages: VecDeque::new(),
+            config,
             tick_fps,
         }
     }
 
+    /// Returns true while any toast is shown. Toasts are animated and time out on ticks.
+    pub fn is_active(&self) -> bool {
+        self.loading.is_some() || !self.messages.is_empty()
+    }
+
     pub fn handle_event(&mut self, event: &Event) -> EventState {
         match event {
             Event::Toast(ToastEvent::Loading(msg)) => {
-                self.state = ToastState::Loading {
+                let ticks = self.loading.as_ref().map_or(0, |l| l.ticks);
+                self.loading = Some(Loading {
                     message: msg.to_string(),
-                    ticks: 0,
-                };
+                    ticks,
+                });
                 EventState::Handled
             }
+            Event::Toast(ToastEvent::Info(msg)) => self.push(Severity::Info, msg),
+            Event::Toast(ToastEvent::Warning(msg)) => self.push(Severity::Warning, msg),
             Event::Toast(ToastEvent::Error(msg)) => {
-                self.state = ToastState::Error {
-                    error: msg.to_string(),
-                    ticks: 0,
-                };
-                EventState::Handled
+                // Errors end the progress they are reported for.
+                self.loading = None;
+                self.push(Severity::Error, msg)
             }
             Event::Toast(ToastEvent::Hide) => {
-                self.state = ToastState::Hidden;
+                self.loading = None;
                 EventState::Handled
             }
-            Event::Tick => match &mut self.state {
-                ToastState::Error { ticks, .. } => {
-                    if *ticks > self.tick_fps * 5 {
-                        self.state = ToastState::Hidden;
-                    } else {
-                        *ticks += 1;
-                    }
-
-                    EventState::Handled
-                }
-                ToastState::Loading { ticks, .. } => {
-                    *ticks += 1;
-                    EventState::Handled
-                }
-                ToastState::Hidden => EventState::Ignored,
-            },
+            Event::Tick => self.tick(),
             Event::Keyboard(_) => EventState::Ignored,
-            Event::StartLoadingItem => EventState::Ignored,
-            Event::LoadedItem(_) => EventState::Ignored,
+            Event::Input(_) => EventState::Ignored,
+            Event::StartLoadingItem(..) => EventState::Ignored,
+            Event::LoadedItem(..) => EventState::Ignored,
+            Event::RenderedItem { .. } => EventState::Ignored,
+            Event::NarrationFinished(_) => EventState::Ignored,
+            Event::OpenInTerminal(_) => EventState::Ignored,
+            Event::Resize(..) => EventState::Ignored,
+            Event::RefreshStarted | Event::RefreshFinished { .. } => EventState::Ignored,
         }
     }
 
-    pub fn draw(&mut self, frame: &mut Frame) {
-        if self.hidden() {
-            return;
+    fn push(&mut self, severity: Severity, text: &str) -> EventState {
+        // Repeated message is shown once, for the full time.
+        if let Some(msg) = self
+            .messages
+            .iter_mut()
+            .find(|m| m.severity == severity && m.text == text)
+        {
+            msg.ticks = 0;
+        } else {
+            self.messages.push_back(Message {
+                severity,
+                text: text.to_string(),
+                ticks: 0,
+            });
         }
 
-        let area = frame.area();
-
-        let width = 30;
-        let height = 3;
+        EventState::Handled
+    }
 
-        let x = area.width - width - 2;
-        let y = area.height - height - 1;
+    fn tick(&mut self) -> EventState {
+        if self.loading.is_none() && self.messages.is_empty() {
+            return EventState::Ignored;
+        }
 
-        let area = Rect::new(x, y, width, height);
-        frame.render_widget(Clear, area);
+        if let Some(loading) = &mut self.loading {
+            loading.ticks += 1;
+        }
 
-        let color = match &self.state {
-            ToastState::Loading { .. } => Color::Cyan,
-            ToastState::Error { .. } => Color::Red,
-            ToastState::Hidden => unreachable!(),
-        };
+        for msg in self.messages.iter_mut().take(MAX_VISIBLE) {
+            msg.ticks += 1;
+        }
 
-        let block = Block::bordered()
-            .border_type(BorderType::Rounded)
-            .border_style(color);
-        frame.render_widget(block, area);
+        let config = self.config;
+        let tick_fps = self.tick_fps as f64;
+        self.messages.retain(|msg| {
+            let timeout = match msg.severity {
+                Severity::Info => config.info_timeout,
+                Severity::Warning => config.warning_timeout,
+                Severity::Error => config.error_timeout,
+            };
+            (msg.ticks as f64) < timeout.as_secs_f64() * tick_fps
+        });
+
+        EventState::Handled
+    }
 
-        let paragraph = match &self.state {
-            ToastState::Loading { message, ticks } => {
-                let ch = spinner_frame(*ticks as usize);
-                Paragraph::new(format!("{ch} {message}"))
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let width = WIDTH.min(area.width);
+        let x = area.width.saturating_sub(width + 2);
+
+        // Loading is at the bottom, messages are stacked above it, oldest on top.
+        let loading = self.loading.as_ref().map(|loading| {
+            let ch = spinner_frame(loading.ticks as usize);
+            (format!("{ch} {}", loading.message), Color::Cyan)
+        });
+        let messages = self.messages.iter().take(MAX_VISIBLE).rev().map(|msg| {
+            let color = match msg.severity {
+                Severity::Info => Color::Green,
+                Severity::Warning => Color::Yellow,
+                Severity::Error => Color::Red,
+            };
+            (msg.text.clone(), color)
+        });
+
+        let mut bottom = area.height.saturating_sub(1);
+        for (text, color) in loading.into_iter().chain(messages) {
+            if bottom < HEIGHT {
+                break;
             }
-            ToastState::Error { error, .. } => Paragraph::new(error.to_string()),
-            ToastState::Hidden => unreachable!(),
-        };
-
-        frame.render_widget(
-            paragraph.style(color).bold(),
-            Rect::new(x + 2, y + 1, width - 4, height - 2),
-        );
-    }
+            bottom -= HEIGHT;
 
-    fn hidden(&self) -> bool {
-        matches!(self.state, ToastState::Hidden)
+            let area = Rect::new(x, bottom, width, HEIGHT).intersection(area);
+            draw_toast(frame, area, text, color);
+        }
     }
 }
+
+fn draw_toast(frame: &mut Frame, area: Rect, text: String, color: Color) {
+    frame.render_widget(Clear, area);
+
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .border_style(color);
+    frame.render_widget(block, area);
+
+    let inner = Rect::new(
+        area.x + 2,
+        area.y + 1,
+        area.width.saturating_sub(4),
+        area.height.saturating_sub(2),
+    );
+    frame.render_widget(Paragraph::new(text).style(color).bold(), inner);
+}
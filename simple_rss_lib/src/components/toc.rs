@@ -0,0 +1,113 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    event::{Event, EventState, InputEvent},
+    render::Heading,
+};
+
+/// Popup listing the headings of the opened item, indented by their level.
+///
+/// While the popup is open it handles all [`Event::Input`]s: Up and Down select a heading,
+/// Enter jumps to it and Esc or `q` close the popup. The line of the chosen heading is
+/// returned by [`Toc::take_selected`].
+#[derive(Default)]
+pub struct Toc {
+    open: bool,
+    headings: Vec<Heading>,
+    list_state: ListState,
+    selected: Option<usize>,
+}
+
+impl Toc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open with the headings, selecting the one at index `current`.
+    pub fn open(&mut self, headings: &[Heading], current: Option<usize>) {
+        self.open = true;
+        self.headings = headings.to_vec();
+        self.list_state = ListState::default().with_selected(Some(current.unwrap_or(0)));
+        self.selected = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Line of the heading chosen since the last call.
+    pub fn take_selected(&mut self) -> Option<usize> {
+        self.selected.take()
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> EventState {
+        match event {
+            Event::Input(input) if self.open => {
+                match input {
+                    InputEvent::Up => self.list_state.select_previous(),
+                    InputEvent::Down => self.list_state.select_next(),
+                    InputEvent::Enter => {
+                        self.open = false;
+                        self.selected = self
+                            .list_state
+                            .selected()
+                            .and_then(|idx| self.headings.get(idx))
+                            .map(|h| h.line);
+                    }
+                    InputEvent::Esc | InputEvent::Char('q') => self.open = false,
+                    _ => (),
+                }
+                EventState::Handled
+            }
+            _ => EventState::Ignored,
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if !self.open {
+            return;
+        }
+
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+
+        let instructions = Line::from(vec![
+            " Jump ".into(),
+            "<Enter>  ".blue().bold(),
+            "Close ".into(),
+            "<Esc> ".blue().bold(),
+        ]);
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Blue)
+            .title("Contents")
+            .title_bottom(instructions.centered());
+
+        if self.headings.is_empty() {
+            let paragraph = Paragraph::new(Line::from("No headings").centered()).block(block);
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        // Headings are indented relative to the highest level in the item.
+        let top = self.headings.iter().map(|h| h.level).min().unwrap_or(1);
+        let list = List::new(self.headings.iter().map(|h| {
+            let indent = "  ".repeat((h.level - top) as usize);
+            ListItem::new(format!(" {indent}{}", h.title))
+        }))
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .block(block);
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+}
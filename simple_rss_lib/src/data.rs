@@ -1,38 +1,326 @@
-use std::ops::Deref;
+//! Items, channels and the [`Loader`] trait used to access them.
 
-use chrono::{DateTime, FixedOffset};
+use std::{
+    collections::BTreeMap,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::Error;
+
+/// Single entry of a feed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Item {
+    /// Unique id of the item, stable across refreshes.
     pub id: String,
     pub channel_name: String,
     pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
     pub description: Option<String>,
     pub pub_date: Option<DateTime<FixedOffset>>,
+    /// Url of the item's web page.
     pub link: String,
 
+    /// Url of the attached media file (podcast episode, video, ...).
+    #[serde(default)]
+    pub enclosure: Option<String>,
+
+    /// Other pages of the item, like its discussion on Hacker News or Reddit.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_links: Vec<AlternateLink>,
+
     pub read: bool,
+
+    /// Item was starred by the user, e.g. to read it later.
+    #[serde(default)]
+    pub starred: bool,
+
+    /// Labels set by the channel's script, e.g. to find the item later.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Item was hidden from the list by the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<Hidden>,
+}
+
+impl Item {
+    /// Url of the item's discussion, if it has one.
+    pub fn comments(&self) -> Option<&str> {
+        self.alternate_links
+            .iter()
+            .find(|link| link.kind == LinkKind::Comments)
+            .map(|link| link.url.as_str())
+    }
+
+    /// Replace the url of the item's discussion, or remove it with `None`.
+    pub fn set_comments(&mut self, url: Option<String>) {
+        self.alternate_links
+            .retain(|link| link.kind != LinkKind::Comments);
+        if let Some(url) = url {
+            self.alternate_links.insert(0, AlternateLink::comments(url));
+        }
+    }
+
+    /// Whether the item is hidden from the list at the given time.
+    pub fn is_hidden(&self, now: DateTime<Utc>) -> bool {
+        match self.hidden {
+            None => false,
+            Some(Hidden::Always) => true,
+            Some(Hidden::Until(until)) => now < until,
+        }
+    }
+}
+
+/// Page of an item other than its [`Item::link`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlternateLink {
+    pub kind: LinkKind,
+    pub url: String,
+}
+
+impl AlternateLink {
+    pub fn comments(url: String) -> Self {
+        Self {
+            kind: LinkKind::Comments,
+            url,
+        }
+    }
+}
+
+/// What an [`AlternateLink`] leads to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    /// Discussion of the item, like the comments of a Hacker News or Reddit post.
+    Comments,
+    /// Page related to the item, like an Atom entry's `related` link.
+    Related,
+}
+
+impl LinkKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LinkKind::Comments => "Comments",
+            LinkKind::Related => "Related",
+        }
+    }
+}
+
+/// How long an item is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hidden {
+    /// Until it's shown again by the user.
+    Always,
+    /// Snoozed until the time, then it's shown again.
+    Until(DateTime<Utc>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Subscribed feed.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Channel {
+    /// Custom name of the channel. Feed's title is used if not set.
     pub name: Option<String>,
+    /// Url of the RSS or Atom feed, or of a site read from its API, like Hacker News or
+    /// Mastodon.
     pub url: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Time of the last successful fetch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_fetched: Option<DateTime<Utc>>,
+
+    /// Authentication of the requests for the feed and its items.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+
+    /// Extra headers sent with the requests for the feed and its items. Values can be
+    /// secret references, like [`Auth`] secrets.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    /// Settings overriding the defaults for this channel.
+    #[serde(default, skip_serializing_if = "ChannelSettings::is_empty")]
+    pub settings: ChannelSettings,
+}
+
+impl Channel {
+    /// Returns true if the item was fetched from this channel.
+    pub fn contains(&self, item: &Item) -> bool {
+        item.id
+            .strip_prefix(&self.url)
+            .is_some_and(|rest| rest.starts_with(':'))
+    }
 }
 
+/// Per-channel overrides of the defaults. Unset settings use the default behavior.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelSettings {
+    /// Interval of automatic refresh in minutes, instead of the global one. Zero disables
+    /// automatic refresh of the channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<u64>,
+
+    /// Show the item's description instead of fetching its web page, for feeds with
+    /// full articles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_description: Option<bool>,
+
+    /// Show only the main content of fetched web pages, without navigation and sidebars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readability: Option<bool>,
+
+    /// Items older than this many days are dropped on refresh.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u64>,
+
+    /// Open items in the browser instead of the reader.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_in_browser: Option<bool>,
+
+    /// Path of a script that filters and transforms the items on refresh. Embedders decide
+    /// how it is run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
+impl ChannelSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// HTTP authentication of a channel.
+///
+/// Secrets are stored as references, `env:NAME` for an environment variable or
+/// `keyring:NAME` for an entry in the system keyring. Other values are used as they are.
+/// Embedders resolve the references when making requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Auth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+/// All the stored data.
 #[derive(Default)]
 pub struct Data {
     pub channels: Vec<Channel>,
     pub items: Vec<Item>,
 }
 
-pub enum RefreshStatus {
-    Ok,
-    Error,
+impl Data {
+    /// Move the channel at index `from` to index `to`. Returns false if an index is invalid.
+    pub fn move_channel(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.channels.len() || to >= self.channels.len() {
+            return false;
+        }
+
+        let channel = self.channels.remove(from);
+        self.channels.insert(to, channel);
+        true
+    }
+}
+
+/// Statistics of a group of items, e.g. of a channel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemStats {
+    pub items: usize,
+    pub unread: usize,
+    /// Average number of items published per week. `None` if fewer than two items
+    /// have a publication date.
+    pub per_week: Option<f64>,
+    /// Average age of the items with a publication date.
+    pub average_age: Option<TimeDelta>,
 }
 
-pub trait Loader {
+impl ItemStats {
+    pub fn new<'a>(items: impl IntoIterator<Item = &'a Item>, now: DateTime<Utc>) -> Self {
+        let mut stats = Self::default();
+        let mut dates = vec![];
+        for item in items {
+            stats.items += 1;
+            stats.unread += !item.read as usize;
+            dates.extend(item.pub_date.map(|date| date.with_timezone(&Utc)));
+        }
+
+        if let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) {
+            let span =
+                (*last - *first).num_seconds() as f64 / TimeDelta::weeks(1).num_seconds() as f64;
+            if dates.len() > 1 && span > 0.0 {
+                stats.per_week = Some((dates.len() - 1) as f64 / span);
+            }
+
+            let total: TimeDelta = dates.iter().map(|date| now - *date).sum();
+            stats.average_age = Some(total / dates.len() as i32);
+        }
+
+        stats
+    }
+
+    /// Statistics of each channel, by channel name in alphabetical order.
+    pub fn by_channel(items: &[Item], now: DateTime<Utc>) -> Vec<(String, Self)> {
+        let mut channels: BTreeMap<&str, Vec<&Item>> = BTreeMap::new();
+        for item in items {
+            channels.entry(&item.channel_name).or_default().push(item);
+        }
+
+        channels
+            .into_iter()
+            .map(|(name, items)| (name.to_string(), Self::new(items, now)))
+            .collect()
+    }
+
+    /// Posting frequency, like `2.5/week`, or `-` if unknown.
+    pub fn frequency_label(&self) -> String {
+        match self.per_week {
+            Some(per_week) if per_week >= 10.0 => format!("{per_week:.0}/week"),
+            Some(per_week) => format!("{per_week:.1}/week"),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Average age, like `3 days`, or `-` if unknown.
+    pub fn age_label(&self) -> String {
+        let Some(age) = self.average_age else {
+            return "-".to_string();
+        };
+
+        match (age.num_days(), age.num_hours()) {
+            (0, hours) => format!("{hours} hours"),
+            (1, _) => "1 day".to_string(),
+            (days, _) => format!("{days} days"),
+        }
+    }
+}
+
+/// Failed refresh of a single channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshError {
+    /// Name of the channel, or its url if it has no name.
+    pub channel: String,
+    pub url: String,
+    pub error: Error,
+}
+
+/// Everything the UI needs from the data: storage, fetching of the feeds and loading
+/// of the items' content.
+///
+/// Implemented for every type that implements [`ItemStore`], [`Fetcher`] and
+/// [`ContentLoader`], so each part can be provided separately.
+pub trait Loader: ItemStore + Fetcher + ContentLoader {}
+
+impl<T: ItemStore + Fetcher + ContentLoader> Loader for T {}
+
+/// Storage of the items shown in the UI.
+pub trait ItemStore {
     type Guard<'a>: Deref<Target = Vec<Item>> + 'a
     where
         Self: 'a;
@@ -42,14 +330,68 @@ pub trait Loader {
     fn get_items<'a>(&'a self) -> Self::Guard<'a>;
 
     /// Version of the data. Used by items to know when data is changed
-    /// and re-render is needed. It is the loader's implementation responsibility
+    /// and re-render is needed. It is the store's implementation responsibility
     /// to increase the version each time the data is changed.
     fn get_version(&self) -> u16;
 
-    fn refresh(&mut self) -> impl Future<Output = RefreshStatus> + Send;
+    /// Set read status of the item with the given id. Items are addressed by id, because
+    /// a refresh can move or remove them while the UI holds their indices. Unknown ids
+    /// are ignored.
+    fn set_read_by_id(&mut self, id: &str, read: bool);
+
+    /// Set read status of all the items with the given ids at once. Stores that save or
+    /// send each change should override it to do that once for the whole batch.
+    fn set_read_by_ids(&mut self, ids: &[String], read: bool) {
+        for id in ids {
+            self.set_read_by_id(id, read);
+        }
+    }
+
+    /// Hide the item with the given id from the list, or show it again with `None`.
+    /// Unknown ids are ignored, as well as everything by stores that can't keep it.
+    fn set_hidden_by_id(&mut self, _id: &str, _hidden: Option<Hidden>) {}
+
+    /// Star or unstar the item with the given id. Unknown ids are ignored, as well as
+    /// everything by stores that can't keep it.
+    fn set_starred_by_id(&mut self, _id: &str, _starred: bool) {}
+
+    /// Star or unstar all the items with the given ids at once. Stores that save or send
+    /// each change should override it to do that once for the whole batch.
+    fn set_starred_by_ids(&mut self, ids: &[String], starred: bool) {
+        for id in ids {
+            self.set_starred_by_id(id, starred);
+        }
+    }
+
+    /// Subscribed channels, in the order the user arranged them. Stores whose channels
+    /// are managed elsewhere, e.g. by a sync server, return none.
+    fn get_channels(&self) -> Vec<Channel> {
+        vec![]
+    }
+
+    /// Move the channel at index `from` to index `to`, shifting the channels in between.
+    /// Invalid indices are ignored.
+    fn move_channel(&mut self, _from: usize, _to: usize) {}
+}
+
+/// Fetches the feeds and updates the stored items.
+pub trait Fetcher {
+    /// Fetch all the channels and update the items. Returns errors of the channels that
+    /// failed to refresh.
+    fn refresh(&mut self) -> impl Future<Output = Result<(), Vec<RefreshError>>> + Send;
+}
 
-    /// Set item at given index to read.
-    fn set_read(&mut self, index: usize, read: bool);
+/// Loads content linked from the items.
+pub trait ContentLoader {
+    /// Fetch the content (HTML) of the item's web page, at its link.
+    fn load_item(&self, item: &Item) -> impl Future<Output = crate::Result<String>> + Send;
 
-    fn load_item(url: &str) -> impl Future<Output = String> + Send;
+    /// Download the item's attachment into the directory and return path of the downloaded
+    /// file. Progress is reported with number of downloaded bytes and total size, if known.
+    fn download(
+        &self,
+        item: &Item,
+        dir: &Path,
+        progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> impl Future<Output = crate::Result<PathBuf>> + Send;
 }
@@ -0,0 +1,40 @@
+//! Errors reported by [`Loader`](crate::data::Loader)s.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Error of loading or storing feed data. Each variant carries a human readable description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Error {
+    /// Request failed or the server responded with an error.
+    Network(String),
+    /// Response is not a valid feed or document.
+    Parse(String),
+    /// Reading or writing local files failed.
+    Io(String),
+    /// Storage of the loader failed, e.g. a database.
+    Storage(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network(msg) => write!(f, "Network error: {msg}"),
+            Error::Parse(msg) => write!(f, "Invalid content: {msg}"),
+            Error::Io(msg) => write!(f, "I/O error: {msg}"),
+            Error::Storage(msg) => write!(f, "Storage error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
@@ -1,17 +1,70 @@
+//! Events passed between the components.
+//!
+//! Events are sent with an [`EventSender`] into the [`EventBus`], which delivers them
+//! to the [`App`](crate::app::App) in order.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+use crate::{
+    Error,
+    data::{Item, RefreshError},
+    render::Document,
+};
+
+/// Events are serializable for [`record`](crate::record), except [`Event::RenderedItem`],
+/// whose document is rendered again when the events are replayed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Event {
+    /// Emitted regularly, used for animations.
     Tick,
     Keyboard(KeyboardEvent),
+    /// Raw key press, used for text input. Sent after the corresponding [`Event::Keyboard`].
+    Input(InputEvent),
+    /// Terminal was resized to the given width and height. Everything is drawn again.
+    Resize(u16, u16),
+
+    /// Item was selected and its content is being loaded. Carries the id of the load,
+    /// obtained with [`next_load_id`].
+    StartLoadingItem(u64, Box<Item>),
+    /// Content of the load with the given id was loaded, or loading failed. Only the
+    /// latest started load is shown, results of the older ones are discarded.
+    LoadedItem(u64, Result<String, Error>),
+    /// Opened item was rendered in the background. Id is assigned by the content pane.
+    #[serde(skip)]
+    RenderedItem {
+        id: u64,
+        document: Arc<Document>,
+    },
+
+    /// Show or hide the toast.
+    Toast(ToastEvent),
 
-    StartLoadingItem,
-    LoadedItem(String),
+    /// Link has to be opened with the open command, which runs in the terminal. Handled
+    /// by the embedder, see [`AppConfig::terminal_browser`](crate::app::AppConfig).
+    OpenInTerminal(String),
 
-    Toast(ToastEvent),
+    /// Narration with the given id has finished playing.
+    NarrationFinished(u64),
+
+    /// Refreshing of the items started.
+    RefreshStarted,
+    /// Refresh started with [`Event::RefreshStarted`] finished. Refresh succeeded
+    /// if there are no errors.
+    RefreshFinished {
+        errors: Vec<RefreshError>,
+    },
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+/// Keyboard actions, independent of the actual key binding.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Copy, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum KeyboardEvent {
     Left,
     Right,
@@ -21,13 +74,93 @@ pub enum KeyboardEvent {
     Enter,
     Space,
     Open,
+    OpenComments,
+    OpenAll,
+    Download,
     Help,
+
+    Listen,
+    NarrationNext,
+    NarrationPause,
+    NarrationStop,
+
+    Search,
+    SearchNext,
+    SearchPrevious,
+
+    SetMark,
+    JumpToMark,
+
+    Toc,
+    PreviousHeading,
+    NextHeading,
+
+    CopyLink,
+    CopyArticle,
+    Export,
+    Changelog,
+    Pager,
+    ReadLater,
+    Suspend,
+
+    ShrinkList,
+    GrowList,
+    Fullscreen,
+
+    MarkAllRead,
+    Undo,
+    Hide,
+    Snooze,
+    ShowHidden,
+    ToggleStar,
+
+    RefreshErrors,
+    Stats,
+    Channels,
+
+    HistoryBack,
+    HistoryForward,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Key press as typed, independent of what action it is bound to.
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum InputEvent {
+    Char(char),
+    /// Character pressed with Ctrl or Alt, which doesn't type it.
+    Shortcut(char, Modifiers),
+    Backspace,
+    Delete,
+    Enter,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+}
+
+/// Modifiers a key was pressed with. Shift is not a modifier of characters, since it's
+/// already part of the character.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Copy, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum ToastEvent {
+    /// Progress of a running task. Replaces the previous progress.
     Loading(String),
+    /// Failure, also ends the progress.
     Error(String),
+    /// Something the user should know about, but nothing failed.
+    Warning(String),
+    /// Short confirmation, hidden after a few seconds.
+    Info(String),
+    /// Hide the progress.
     Hide,
 }
 
@@ -54,6 +187,12 @@ impl EventState {
     }
 }
 
+/// Id for a new [`Event::StartLoadingItem`], unique in the process.
+pub fn next_load_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 /// Send events to event bus.
 #[derive(Debug, Clone)]
 pub struct EventSender(mpsc::UnboundedSender<Event>);
@@ -0,0 +1,176 @@
+//! Full-text search over items.
+//!
+//! [`SearchIndex`] is an inverted index over titles, descriptions and any article text
+//! added to it. Results are ranked with BM25, with matches in the title weighted more
+//! than matches in the text.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    data::Item,
+    render::{self, Format},
+};
+
+// BM25 parameters.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Occurrence of a term in the title counts as this many occurrences in the text.
+const TITLE_WEIGHT: f64 = 3.0;
+
+/// Terms that only start with the query term are scored with this fraction of the score,
+/// so that exact matches rank first.
+const PREFIX_WEIGHT: f64 = 0.5;
+
+/// Item matching the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Index of the item in the indexed slice.
+    pub item: usize,
+    pub score: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Posting {
+    item: usize,
+    title: u32,
+    text: u32,
+}
+
+/// Inverted index over items, searched with [`SearchIndex::search`].
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    // Postings of each term, ordered by item.
+    terms: BTreeMap<String, Vec<Posting>>,
+    // Weighted number of terms of each item.
+    lengths: Vec<f64>,
+}
+
+impl SearchIndex {
+    /// Index titles and descriptions of the items.
+    pub fn new(items: &[Item]) -> Self {
+        let mut index = Self {
+            terms: BTreeMap::new(),
+            lengths: vec![0.0; items.len()],
+        };
+        for (idx, item) in items.iter().enumerate() {
+            index.add_title(idx, &item.title);
+            if let Some(description) = &item.description {
+                index.add_html(idx, description);
+            }
+        }
+        index
+    }
+
+    /// Number of indexed items.
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// Index the HTML content of the item, like its fetched article.
+    pub fn add_html(&mut self, item: usize, html: &str) {
+        self.add_text(item, &render::export(html, Format::Text));
+    }
+
+    /// Index plain text of the item.
+    pub fn add_text(&mut self, item: usize, text: &str) {
+        for term in tokenize(text) {
+            self.posting(item, term).text += 1;
+            self.lengths[item] += 1.0;
+        }
+    }
+
+    fn add_title(&mut self, item: usize, title: &str) {
+        for term in tokenize(title) {
+            self.posting(item, term).title += 1;
+            self.lengths[item] += TITLE_WEIGHT;
+        }
+    }
+
+    fn posting(&mut self, item: usize, term: String) -> &mut Posting {
+        if self.lengths.len() <= item {
+            self.lengths.resize(item + 1, 0.0);
+        }
+
+        let postings = self.terms.entry(term).or_default();
+        let pos = match postings.binary_search_by_key(&item, |p| p.item) {
+            Ok(pos) => pos,
+            Err(pos) => {
+                postings.insert(
+                    pos,
+                    Posting {
+                        item,
+                        ..Default::default()
+                    },
+                );
+                pos
+            }
+        };
+        &mut postings[pos]
+    }
+
+    /// Items containing all the words of the query, best first. Words match terms that
+    /// start with them, so results are shown while the last word is being typed.
+    pub fn search(&self, query: &str) -> Vec<Hit> {
+        let words = tokenize(query);
+        if words.is_empty() || self.is_empty() {
+            return vec![];
+        }
+
+        let avg_length = self.lengths.iter().sum::<f64>() / self.len() as f64;
+        let mut scores: HashMap<usize, (f64, usize)> = HashMap::new();
+        for (nr, word) in words.iter().enumerate() {
+            let mut word_scores: HashMap<usize, f64> = HashMap::new();
+            for (term, postings) in self.terms.range(word.clone()..) {
+                if !term.starts_with(word.as_str()) {
+                    break;
+                }
+
+                let weight = if term == word { 1.0 } else { PREFIX_WEIGHT };
+                let idf = self.idf(postings.len());
+                for posting in postings {
+                    let tf = posting.title as f64 * TITLE_WEIGHT + posting.text as f64;
+                    let norm = 1.0 - B + B * self.lengths[posting.item] / avg_length.max(1.0);
+                    let score = idf * tf * (K1 + 1.0) / (tf + K1 * norm);
+                    *word_scores.entry(posting.item).or_default() += weight * score;
+                }
+            }
+
+            // Keep only the items that matched all the previous words.
+            for (item, score) in word_scores {
+                let entry = scores.entry(item).or_default();
+                if entry.1 == nr {
+                    entry.0 += score;
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let mut hits: Vec<_> = scores
+            .into_iter()
+            .filter(|(_, (_, matched))| *matched == words.len())
+            .map(|(item, (score, _))| Hit { item, score })
+            .collect();
+        // Ties are ordered by index, which is newest first.
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.item.cmp(&b.item)));
+        hits
+    }
+
+    fn idf(&self, nr_items: usize) -> f64 {
+        let total = self.len() as f64;
+        let nr_items = nr_items as f64;
+        ((total - nr_items + 0.5) / (nr_items + 0.5) + 1.0).ln()
+    }
+}
+
+/// Lowercase alphanumeric words of the text.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
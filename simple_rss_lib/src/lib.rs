@@ -1,6 +1,47 @@
+//! Building blocks of the [simple-rss](https://github.com/viddrobnic/simple-rss) terminal
+//! feed reader.
+//!
+//! The crate is split into:
+//!
+//! - [`data`]: feed items, channels and the traits through which the UI accesses them:
+//!   [`ItemStore`](data::ItemStore), [`Fetcher`](data::Fetcher) and
+//!   [`ContentLoader`](data::ContentLoader), together called [`Loader`](data::Loader).
+//! - [`event`]: events passed between components and the [`EventBus`](event::EventBus)
+//!   delivering them.
+//! - [`components`]: individual ratatui components (item list, content pane, toast, ...).
+//! - [`index`]: ranked full-text search over items.
+//! - [`render`]: HTML to styled text renderer used by the content pane, which other ratatui
+//!   apps can use on its own. Also available as `html_render`, its previous name.
+//! - [`app`]: the complete reader, combining all the components.
+//! - [`record`]: recording of the events passed to the app and their headless replay, for
+//!   reproducing UI bugs.
+//! - [`browser`]: opening links with the default browser or a configured command.
+//! - [`Error`]: errors reported by loaders.
+//! - `testing`: mock HTTP server for testing loaders, an in-memory loader and a harness
+//!   driving the app on a test backend with snapshots of the screen, enabled by the
+//!   `testing` feature.
+//!
+//! Embedders implement the three traits, feed terminal input into the event bus and
+//! let [`App`](app::App) handle the rest. The traits can be implemented by one type, or by
+//! separate ones passed to [`App::with_parts`](app::App::with_parts). See `examples/static_items.rs` for a minimal
+//! reader with hardcoded items.
+
 pub mod app;
+pub mod browser;
+pub mod components;
 pub mod data;
+pub mod error;
 pub mod event;
-pub mod html_render;
+pub mod index;
+pub mod record;
+pub mod render;
+
+mod clipboard;
+
+pub use error::{Error, Result};
+
+#[cfg(feature = "testing")]
+pub mod testing;
 
-mod components;
+/// Previous name of the [`render`] module.
+pub use render as html_render;
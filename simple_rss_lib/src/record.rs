@@ -0,0 +1,441 @@
+//! Recording of the events passed to the [`App`], and their headless replay.
+//!
+//! Embedders pass every event they give to the app to a [`Recorder`] as well. The recording
+//! is a file with one JSON entry per line. [`Replay`] passes the recorded events to a new
+//! app drawn on a [`TestBackend`], so UI bugs can be reproduced without the terminal or
+//! the network, and the recordings turned into regression tests.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU16, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer, layout::Size};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    app::{App, AppConfig, UiState},
+    data::{Channel, ContentLoader, Fetcher, Hidden, Item, ItemStore, RefreshError},
+    event::{Event, EventBus},
+};
+
+/// How long the replay waits for an item to be rendered again.
+const RENDER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Entry {
+    /// State of the app when the recording started.
+    Start {
+        width: u16,
+        height: u16,
+        tick_fps: u32,
+        items: Vec<Item>,
+        channels: Vec<Channel>,
+        ui_state: UiState,
+    },
+    /// Event passed to the app, `at` milliseconds after the start.
+    Event { at: u64, event: Event },
+    /// Number of [`Event::Tick`]s passed to the app since the previous entry.
+    Ticks { at: u64, count: u32 },
+    /// Item rendered with the id was passed to the app. Its document isn't recorded,
+    /// the item is rendered again when replaying.
+    Rendered { at: u64, id: u64 },
+    /// Stored items after a refresh.
+    Items { at: u64, items: Vec<Item> },
+}
+
+/// Writes the events passed to the [`App`] into a recording.
+pub struct Recorder<W: Write = BufWriter<File>> {
+    writer: W,
+    started: Instant,
+    // Ticks since the last entry, written with the next one.
+    ticks: u32,
+}
+
+impl Recorder {
+    /// Create the recording file. See [`Recorder::new`].
+    pub fn create(
+        path: &Path,
+        size: Size,
+        tick_fps: u32,
+        store: &impl ItemStore,
+        ui_state: &UiState,
+    ) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Self::new(BufWriter::new(file), size, tick_fps, store, ui_state)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Start recording an app of the terminal size and tick rate, showing the store's
+    /// items, with the restored UI state. Credentials and headers of the channels are not
+    /// recorded, so that the recording can be shared.
+    pub fn new(
+        writer: W,
+        size: Size,
+        tick_fps: u32,
+        store: &impl ItemStore,
+        ui_state: &UiState,
+    ) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer,
+            started: Instant::now(),
+            ticks: 0,
+        };
+        let channels = store
+            .get_channels()
+            .into_iter()
+            .map(|channel| Channel {
+                auth: None,
+                headers: Default::default(),
+                ..channel
+            })
+            .collect();
+        // Items are locked until the end of the statement, channels may need the same lock.
+        let items = store.get_items().clone();
+        recorder.write(&Entry::Start {
+            width: size.width,
+            height: size.height,
+            tick_fps,
+            items,
+            channels,
+            ui_state: ui_state.clone(),
+        })?;
+
+        Ok(recorder)
+    }
+
+    /// Record the event, before it's passed to the app. Items are recorded with the
+    /// finished refresh that changed them. Ticks are only counted, and written before the
+    /// next other event.
+    pub fn record(&mut self, event: &Event, store: &impl ItemStore) -> io::Result<()> {
+        if *event == Event::Tick {
+            self.ticks += 1;
+            return Ok(());
+        }
+
+        let at = self.started.elapsed().as_millis() as u64;
+        if self.ticks > 0 {
+            let count = std::mem::take(&mut self.ticks);
+            self.write(&Entry::Ticks { at, count })?;
+        }
+
+        match event {
+            Event::RenderedItem { id, .. } => self.write(&Entry::Rendered { at, id: *id }),
+            Event::RefreshFinished { .. } => {
+                let items = store.get_items().clone();
+                self.write(&Entry::Items { at, items })?;
+                self.write(&Entry::Event {
+                    at,
+                    event: event.clone(),
+                })
+            }
+            event => self.write(&Entry::Event {
+                at,
+                event: event.clone(),
+            }),
+        }
+    }
+
+    /// Stop recording and return the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, entry: &Entry) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")?;
+        // Recording is mostly useful when something goes wrong, so nothing is buffered.
+        self.writer.flush()
+    }
+}
+
+/// Recorded events, replayed against a new [`App`].
+#[derive(Debug)]
+pub struct Replay {
+    size: Size,
+    tick_fps: u32,
+    items: Vec<Item>,
+    channels: Vec<Channel>,
+    ui_state: UiState,
+    entries: Vec<Entry>,
+}
+
+impl Replay {
+    /// Read the recording file.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read the recording written by a [`Recorder`].
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines().enumerate();
+        let Some((_, first)) = lines.next() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Recording is empty",
+            ));
+        };
+        let Entry::Start {
+            width,
+            height,
+            tick_fps,
+            items,
+            channels,
+            ui_state,
+        } = parse_entry(&first?, 1)?
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Recording doesn't start with the state of the app",
+            ));
+        };
+
+        let mut entries = vec![];
+        for (idx, line) in lines {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(parse_entry(&line, idx + 1)?);
+            }
+        }
+
+        Ok(Self {
+            size: Size::new(width, height),
+            tick_fps,
+            items,
+            channels,
+            ui_state,
+            entries,
+        })
+    }
+
+    /// Number of recorded events.
+    pub fn len(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| !matches!(entry, Entry::Items { .. }))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keep only the first `count` events, to see the screen at an earlier point.
+    pub fn truncate(&mut self, count: usize) {
+        let mut events = 0;
+        let end = self.entries.iter().position(|entry| {
+            if !matches!(entry, Entry::Items { .. }) {
+                events += 1;
+            }
+            events > count
+        });
+        if let Some(end) = end {
+            self.entries.truncate(end);
+        }
+    }
+
+    /// Pass the recorded events to a new app and return the screen after the last one.
+    ///
+    /// Events sent by the app itself are replaced by the recorded ones, only the items are
+    /// rendered again. Links are not opened, narration plays nothing and exported
+    /// articles are written to the temporary directory. Has to be called within a tokio
+    /// runtime.
+    pub async fn run(self, mut config: AppConfig) -> Buffer {
+        config.terminal_browser = true;
+        config.open_command.get_or_insert_default();
+        config.narration_command = config.narration_command.map(|_| "true".to_string());
+        config.export_dir = config.export_dir.map(|_| std::env::temp_dir());
+
+        let loader = ReplayLoader::new(self.items, self.channels);
+        let mut bus = EventBus::new();
+        let mut app = App::new(config, bus.get_sender(), loader.clone(), self.tick_fps);
+        app.restore_ui_state(self.ui_state);
+
+        let backend = TestBackend::new(self.size.width, self.size.height);
+        let mut terminal = Terminal::new(backend).expect("test backend doesn't fail");
+        let _ = terminal.draw(|f| app.draw(f));
+
+        let mut rendered = HashMap::new();
+        for entry in self.entries {
+            match entry {
+                Entry::Start { .. } => continue,
+                Entry::Items { items, .. } => {
+                    loader.set_items(items);
+                    continue;
+                }
+                Entry::Ticks { count, .. } => {
+                    for _ in 0..count {
+                        app.handle_event(&Event::Tick);
+                    }
+                }
+                Entry::Event { event, .. } => {
+                    if let Event::Resize(width, height) = event {
+                        terminal.backend_mut().resize(width, height);
+                    }
+                    app.handle_event(&event);
+                }
+                Entry::Rendered { id, .. } => {
+                    if let Some(event) = wait_for_render(&mut bus, &mut rendered, id).await {
+                        app.handle_event(&event);
+                    }
+                }
+            }
+
+            if app.is_dirty() {
+                let _ = terminal.draw(|f| app.draw(f));
+            }
+        }
+
+        terminal.backend().buffer().clone()
+    }
+}
+
+fn parse_entry(line: &str, line_nr: usize) -> io::Result<Entry> {
+    serde_json::from_str(line).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid entry on line {line_nr}: {err}"),
+        )
+    })
+}
+
+/// Wait until the item with the id is rendered again. Other events sent by the app are
+/// skipped, they are recorded.
+async fn wait_for_render(
+    bus: &mut EventBus,
+    rendered: &mut HashMap<u64, Event>,
+    id: u64,
+) -> Option<Event> {
+    while !rendered.contains_key(&id) {
+        match tokio::time::timeout(RENDER_TIMEOUT, bus.next()).await {
+            Ok(Some(event)) => {
+                if let Event::RenderedItem { id, .. } = &event {
+                    rendered.insert(*id, event.clone());
+                }
+            }
+            Ok(None) | Err(_) => return None,
+        }
+    }
+
+    rendered.remove(&id)
+}
+
+/// Store of the recorded items. Refreshing and loading do nothing, their results
+/// are recorded.
+#[derive(Clone)]
+struct ReplayLoader {
+    state: Arc<Mutex<ReplayState>>,
+    version: Arc<AtomicU16>,
+}
+
+struct ReplayState {
+    items: Vec<Item>,
+    channels: Vec<Channel>,
+}
+
+struct ReplayGuard<'a>(MutexGuard<'a, ReplayState>);
+
+impl std::ops::Deref for ReplayGuard<'_> {
+    type Target = Vec<Item>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.items
+    }
+}
+
+impl ReplayLoader {
+    fn new(items: Vec<Item>, channels: Vec<Channel>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReplayState { items, channels })),
+            version: Arc::default(),
+        }
+    }
+
+    fn set_items(&self, items: Vec<Item>) {
+        self.state.lock().unwrap().items = items;
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl ItemStore for ReplayLoader {
+    type Guard<'a> = ReplayGuard<'a>;
+
+    fn get_items(&self) -> Self::Guard<'_> {
+        ReplayGuard(self.state.lock().unwrap())
+    }
+
+    fn get_version(&self) -> u16 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    fn set_read_by_id(&mut self, id: &str, read: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.read = read;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn set_hidden_by_id(&mut self, id: &str, hidden: Option<Hidden>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.hidden = hidden;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn set_starred_by_id(&mut self, id: &str, starred: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.starred = starred;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn get_channels(&self) -> Vec<Channel> {
+        self.state.lock().unwrap().channels.clone()
+    }
+
+    fn move_channel(&mut self, from: usize, to: usize) {
+        let mut state = self.state.lock().unwrap();
+        if from < state.channels.len() && to < state.channels.len() {
+            let channel = state.channels.remove(from);
+            state.channels.insert(to, channel);
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Fetcher for ReplayLoader {
+    async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+        Ok(())
+    }
+}
+
+impl ContentLoader for ReplayLoader {
+    async fn load_item(&self, item: &Item) -> crate::Result<String> {
+        Err(Error::Network(format!(
+            "Content of {} is recorded",
+            item.link
+        )))
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        _dir: &Path,
+        _progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> crate::Result<PathBuf> {
+        Err(Error::Io(format!("Replay can't download {}", item.title)))
+    }
+}
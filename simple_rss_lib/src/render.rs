@@ -0,0 +1,1622 @@
+//! Renderer of HTML documents. The document is written by a [`Backend`], into styled,
+//! wrapped lines of text or into Markdown.
+//!
+//! The renderer doesn't depend on the rest of the reader, so other ratatui apps can show HTML
+//! with [`render_to_lines`], or print it with [`render_to_string`]:
+//!
+//! ```
+//! use simple_rss_lib::render::{RenderOptions, render_to_string};
+//!
+//! let html = "<h2>News</h2><p>Hello, <strong>world</strong>.</p>";
+//! let options = RenderOptions {
+//!     markup: false,
+//!     ..Default::default()
+//! };
+//! assert_eq!(render_to_string(html, 80, &options), "News\n\nHello, world.\n");
+//! ```
+
+use std::{borrow::Cow, ops::Range};
+
+use ego_tree::{NodeRef, iter::Children};
+use ratatui::{
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+};
+use scraper::{Html, Node, Selector};
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::data::Item;
+
+/// Widest line that is drawn on a screen. Wider lines are text that isn't wrapped, where
+/// rules are written as `---` instead of spanning the line.
+const MAX_SCREEN_WIDTH: usize = 1_000;
+
+/// Invisible character marking where a word can be hyphenated.
+const SOFT_HYPHEN: char = '\u{ad}';
+
+/// Space that doesn't break the line. Words joined by it are wrapped together.
+const NO_BREAK_SPACE: char = '\u{a0}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StackableModifier {
+    InsideRawBlock = 1 << 0,
+    InsideList = 1 << 1,
+    Superscript = 1 << 2,
+    Subscript = 1 << 3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ExclusiveModifier {
+    #[default]
+    Inline,
+    RequiresSpace,
+    NewLine,
+    NewParagraph,
+    NewHeading,
+    UnorderedList,
+    OrderedList(u16),
+    ForcedInline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StackableStyle {
+    Bold = 1 << 0,
+    Italic = 1 << 1,
+    CrossedOut = 1 << 2,
+    Underlined = 1 << 3,
+    Highlighted = 1 << 4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ExclusiveStyle {
+    #[default]
+    Default,
+    Code,
+    Link,
+    Heading,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Context {
+    exclusive_modifier: ExclusiveModifier,
+    stackable_modifiers: u8,
+
+    exclusive_style: ExclusiveStyle,
+    stackable_styles: u8,
+
+    // Column at which lines of the block start. Inside a list it's the column of the item's
+    // marker, while the following lines of the item are indented by the marker's width.
+    indent: u16,
+    marker_width: u16,
+    // Columns by which lists and sections are indented.
+    tab_size: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderStatus {
+    NotRendered,
+    Rendered,
+    RenderedRequiresSpace,
+}
+
+impl ExclusiveModifier {
+    fn precedence(&self) -> u8 {
+        match self {
+            ExclusiveModifier::Inline => 0,
+            ExclusiveModifier::RequiresSpace => 1,
+            ExclusiveModifier::NewLine => 2,
+            ExclusiveModifier::NewParagraph => 3,
+            ExclusiveModifier::NewHeading => 4,
+            ExclusiveModifier::UnorderedList => 5,
+            ExclusiveModifier::OrderedList(_) => 5,
+            ExclusiveModifier::ForcedInline => 6,
+        }
+    }
+}
+
+impl ExclusiveStyle {
+    fn precedence(&self) -> u8 {
+        match self {
+            ExclusiveStyle::Default => 0,
+            ExclusiveStyle::Code => 1,
+            ExclusiveStyle::Link => 2,
+            ExclusiveStyle::Heading => 3,
+        }
+    }
+}
+
+impl Context {
+    fn merge_exclusive_modifier(mut self, modifier: ExclusiveModifier) -> Self {
+        if self.exclusive_modifier.precedence() > modifier.precedence() {
+            self
+        } else {
+            if let Some(marker) = list_marker(modifier) {
+                // Nested list starts where the text of the item containing it starts.
+                self.indent = if self.has_stackable_modifier(StackableModifier::InsideList) {
+                    self.text_indent()
+                } else {
+                    self.indent + self.tab_size
+                };
+                self.marker_width = marker.width() as u16;
+            }
+
+            self.exclusive_modifier = modifier;
+            self
+        }
+    }
+
+    /// Column at which lines start, after the first line of a list item.
+    fn text_indent(&self) -> u16 {
+        if self.has_stackable_modifier(StackableModifier::InsideList) {
+            self.indent + self.marker_width
+        } else {
+            self.indent
+        }
+    }
+
+    fn set_exclusive_modifier(mut self, modifier: ExclusiveModifier) -> Self {
+        self.exclusive_modifier = modifier;
+        self
+    }
+
+    fn has_stackable_modifier(&self, modifier: StackableModifier) -> bool {
+        self.stackable_modifiers & modifier as u8 > 0
+    }
+
+    fn add_stackable_modifier(mut self, modifier: StackableModifier) -> Self {
+        self.stackable_modifiers |= modifier as u8;
+        self
+    }
+
+    fn remove_stackable_modifier(mut self, modifier: StackableModifier) -> Self {
+        self.stackable_modifiers &= !(modifier as u8);
+        self
+    }
+
+    fn merge_exclusive_style(mut self, style: ExclusiveStyle) -> Self {
+        if self.exclusive_style.precedence() <= style.precedence() {
+            self.exclusive_style = style;
+        }
+
+        self
+    }
+
+    fn set_exclusive_style(mut self, style: ExclusiveStyle) -> Self {
+        self.exclusive_style = style;
+        self
+    }
+
+    fn has_stackable_style(&self, style: StackableStyle) -> bool {
+        self.stackable_styles & style as u8 > 0
+    }
+
+    fn add_stackable_style(mut self, style: StackableStyle) -> Self {
+        self.stackable_styles |= style as u8;
+        self
+    }
+
+    fn style(&self, options: &RenderOptions) -> Style {
+        let mut style = match self.exclusive_style {
+            ExclusiveStyle::Default => Style::default(),
+            ExclusiveStyle::Code => options.code_style,
+            ExclusiveStyle::Link => options.link_style,
+            ExclusiveStyle::Heading => options.heading_style,
+        };
+
+        if self.has_stackable_style(StackableStyle::Bold) {
+            style = style.bold();
+        }
+        if self.has_stackable_style(StackableStyle::Italic) {
+            style = style.italic();
+        }
+        if self.has_stackable_style(StackableStyle::CrossedOut) {
+            style = style.crossed_out();
+        }
+        if self.has_stackable_style(StackableStyle::Underlined) {
+            style = style.underlined();
+        }
+        if self.has_stackable_style(StackableStyle::Highlighted) {
+            style = style.reversed();
+        }
+
+        style
+    }
+}
+
+impl RenderStatus {
+    fn is_rendered(&self) -> bool {
+        *self != RenderStatus::NotRendered
+    }
+}
+
+#[derive(Debug)]
+struct Renderer<B> {
+    backend: B,
+    // Index of the current line and the number of spans written to it.
+    line: usize,
+    spans: usize,
+    // Whether only whitespace has been written so far.
+    blank: bool,
+    last_line_width: usize,
+
+    max_width: usize,
+    options: RenderOptions,
+    markup: bool,
+    // Targets of the links written as footnotes, numbered from 1.
+    footnotes: Vec<String>,
+
+    // Collapsible sections, collected only when rendering a document.
+    sections: Option<Vec<Section>>,
+    headings: Vec<Heading>,
+
+    // Start of the link being rendered, as line and span, and the rendered links with
+    // their start, end and target.
+    link_start: Option<Position>,
+    links: Vec<(Position, Position, String)>,
+}
+
+/// Position in the rendered lines, as line and span.
+type Position = (usize, usize);
+
+/// Rendered document with its collapsible sections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub lines: Vec<Line<'static>>,
+    /// Sections in the order of their summaries.
+    pub sections: Vec<Section>,
+    pub headings: Vec<Heading>,
+    /// Links with absolute targets, ordered by line.
+    pub links: Vec<Link>,
+}
+
+/// Heading of the document, for the table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 for `h1` to 6 for `h6`.
+    pub level: u8,
+    pub title: String,
+    /// Line on which the heading starts.
+    pub line: usize,
+}
+
+/// Text of a link on a line of the document, e.g. to make it clickable. Links broken over
+/// several lines have a part on each of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub line: usize,
+    /// Spans of the line with the link text.
+    pub spans: Range<usize>,
+    pub url: String,
+}
+
+/// Collapsible section, rendered from a `<details>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Line on which the summary starts.
+    pub summary: usize,
+    /// Index of the span on the summary line containing the expanded/collapsed marker.
+    pub marker: usize,
+    /// Lines hidden when the section is collapsed.
+    pub body: Range<usize>,
+}
+
+/// Options of how documents are rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Whether text is styled. Without it, the styles below aren't applied.
+    pub colorize: bool,
+    /// Whether words are broken at soft hyphens, and words broken at the end of the line
+    /// end with a hyphen.
+    pub hyphenate: bool,
+    /// Whether markup, like `**` around bold text, is written.
+    pub markup: bool,
+    pub links: LinkFormat,
+
+    pub heading_style: Style,
+    pub link_style: Style,
+    pub code_style: Style,
+
+    /// Columns by which lists and collapsible sections are indented.
+    pub indent: u16,
+    /// Blank lines between paragraphs. Headings are preceded by one more.
+    pub paragraph_spacing: u16,
+}
+
+/// How targets of the links are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkFormat {
+    /// After the link text, like `[text](target)`.
+    #[default]
+    Inline,
+    /// In numbered footnotes at the end of the document, like `[text][1]`.
+    Footnote,
+    /// Not at all, only the link text is written.
+    Hidden,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            colorize: true,
+            hyphenate: false,
+            markup: true,
+            links: LinkFormat::Inline,
+            heading_style: Style::default().fg(Color::Green).bold(),
+            link_style: Style::default().fg(Color::LightBlue),
+            code_style: Style::default().fg(Color::Gray),
+            indent: 2,
+            paragraph_spacing: 1,
+        }
+    }
+}
+
+/// Format of an exported document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Markdown,
+    /// Text without any markup. Links are written in parentheses after the link text.
+    Text,
+}
+
+/// Output of the renderer. The renderer walks the HTML document and decides what is
+/// written on which line, while the backend decides how it is written.
+pub trait Backend {
+    /// Whether the backend writes Markdown. Markdown is written with markup, blank lines
+    /// between paragraphs and lists indented by less than four columns, which would make
+    /// them code, regardless of the [`RenderOptions`].
+    fn is_markdown(&self) -> bool {
+        false
+    }
+
+    /// Appends the text to the current line. Literal text is markup, code or whitespace
+    /// and must be written as is, while other text might have to be escaped.
+    fn push(&mut self, text: &str, style: Style, literal: bool);
+
+    /// Starts a new line.
+    fn new_line(&mut self);
+
+    /// Removes everything written to the current line.
+    fn clear_line(&mut self);
+
+    /// Marks the current line as ending with a line break within the paragraph. Called
+    /// right before the new line is started.
+    fn line_break(&mut self) {}
+}
+
+/// Backend writing styled lines, to be shown in the terminal.
+#[derive(Debug)]
+pub struct LineBackend {
+    lines: Vec<Line<'static>>,
+}
+
+impl LineBackend {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![Line::default()],
+        }
+    }
+
+    pub fn into_lines(self) -> Vec<Line<'static>> {
+        self.lines
+    }
+}
+
+impl Default for LineBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for LineBackend {
+    fn push(&mut self, text: &str, style: Style, _literal: bool) {
+        self.lines
+            .last_mut()
+            .unwrap()
+            .push_span(Span::styled(text.to_string(), style));
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push(Line::default());
+    }
+
+    fn clear_line(&mut self) {
+        *self.lines.last_mut().unwrap() = Line::default();
+    }
+}
+
+/// Backend writing CommonMark. Styles are ignored and the text is escaped, so that it
+/// isn't read as markup.
+#[derive(Debug)]
+pub struct MarkdownBackend {
+    lines: Vec<String>,
+    // Whether only whitespace has been written to the current line.
+    line_start: bool,
+    // Line ending with a line break. The break is written once the next line has some text,
+    // since a break at the end of a paragraph would be shown as a backslash.
+    line_break: Option<usize>,
+}
+
+impl MarkdownBackend {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![String::new()],
+            line_start: true,
+            line_break: None,
+        }
+    }
+
+    /// Written text, without whitespace at the end of the lines.
+    pub fn into_string(self) -> String {
+        let mut text = self
+            .lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.push('\n');
+        text
+    }
+}
+
+impl Default for MarkdownBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for MarkdownBackend {
+    fn is_markdown(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, text: &str, _style: Style, literal: bool) {
+        if text.trim().is_empty() {
+            self.lines.last_mut().unwrap().push_str(text);
+            return;
+        }
+
+        if let Some(idx) = self.line_break.take()
+            && idx + 2 == self.lines.len()
+        {
+            self.lines[idx].push('\\');
+        }
+
+        let text = if literal {
+            text.to_string()
+        } else {
+            escape_markdown(text, self.line_start)
+        };
+        self.lines.last_mut().unwrap().push_str(&text);
+        self.line_start = false;
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push(String::new());
+        self.line_start = true;
+    }
+
+    fn clear_line(&mut self) {
+        self.lines.last_mut().unwrap().clear();
+        self.line_start = true;
+    }
+
+    fn line_break(&mut self) {
+        if !self.line_start {
+            self.line_break = Some(self.lines.len() - 1);
+        }
+    }
+}
+
+/// Render HTML document into styled lines no wider than `max_width`, to be shown with
+/// a ratatui widget like `Paragraph`.
+pub fn render_to_lines(
+    html: &str,
+    max_width: usize,
+    options: &RenderOptions,
+) -> Vec<Line<'static>> {
+    render_with(html, max_width, LineBackend::new(), options).into_lines()
+}
+
+/// Render HTML document into text, wrapped at `max_width`. Styles are dropped, but markup
+/// is written unless it's disabled in the options.
+pub fn render_to_string(html: &str, max_width: usize, options: &RenderOptions) -> String {
+    let mut text = render_to_lines(html, max_width, options)
+        .iter()
+        .map(|line| line.to_string().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.push('\n');
+    text
+}
+
+/// Render HTML document like [`render_to_lines`], keeping track of its headings and collapsible
+/// sections. Sections are rendered expanded.
+pub fn render_document(html: &str, max_width: usize, options: &RenderOptions) -> Document {
+    let mut renderer = Renderer::new(LineBackend::new(), max_width, options);
+    renderer.sections = Some(vec![]);
+    renderer.render(html);
+
+    let lines = renderer.backend.into_lines();
+    let links = split_links(&lines, renderer.links);
+    Document {
+        lines,
+        sections: renderer.sections.unwrap_or_default(),
+        headings: renderer.headings,
+        links,
+    }
+}
+
+/// Links given by their start and end, as line and span, split into a link on each line.
+/// Whitespace around the link text, like indentation of wrapped lines, is left out.
+fn split_links(lines: &[Line<'static>], links: Vec<(Position, Position, String)>) -> Vec<Link> {
+    let mut result = vec![];
+    for (start, end, url) in links {
+        for (line_idx, line) in lines.iter().enumerate().take(end.0 + 1).skip(start.0) {
+            let from = if line_idx == start.0 { start.1 } else { 0 };
+            let to = if line_idx == end.0 {
+                end.1
+            } else {
+                line.spans.len()
+            }
+            .min(line.spans.len());
+            let is_text = |idx: &usize| !line.spans[*idx].content.trim().is_empty();
+            let Some(first) = (from..to).find(is_text) else {
+                continue;
+            };
+            let last = (from..to).rev().find(is_text).unwrap_or(first);
+            result.push(Link {
+                line: line_idx,
+                spans: first..last + 1,
+                url: url.clone(),
+            });
+        }
+    }
+    result.sort_by_key(|link| link.line);
+    result
+}
+
+/// Render HTML document with the given backend, breaking lines at `max_width`.
+pub fn render_with<B: Backend>(
+    html: &str,
+    max_width: usize,
+    backend: B,
+    options: &RenderOptions,
+) -> B {
+    let mut renderer = Renderer::new(backend, max_width, options);
+    renderer.render(html);
+    renderer.backend
+}
+
+/// Render HTML document into unwrapped text in the given format.
+pub fn export(html: &str, format: Format) -> String {
+    if format == Format::Markdown {
+        let options = RenderOptions::default();
+        return render_with(html, usize::MAX, MarkdownBackend::new(), &options).into_string();
+    }
+
+    let options = RenderOptions {
+        markup: false,
+        ..Default::default()
+    };
+    render_to_string(html, usize::MAX, &options)
+}
+
+/// Main content of the web page: the longest `article`, or else `main` element, without
+/// the navigation, headers and sidebars around it. Returns `None` if the page has neither,
+/// in which case the whole page should be used.
+pub fn main_content(html: &str) -> Option<String> {
+    let tree = Html::parse_document(html);
+    ["article", "main", "[role=main]"]
+        .iter()
+        .find_map(|selector| {
+            let selector = Selector::parse(selector).unwrap();
+            tree.select(&selector)
+                .max_by_key(|element| element.text().map(str::len).sum::<usize>())
+                .map(|element| element.html())
+        })
+}
+
+/// Export the article with title and link of the item at the top.
+pub fn export_item(item: &Item, html: &str, format: Format) -> String {
+    let title = match format {
+        Format::Markdown => format!("# {}", item.title),
+        Format::Text => item.title.clone(),
+    };
+
+    format!("{title}\n\n{}\n\n{}", item.link, export(html, format))
+}
+
+impl<B: Backend> Renderer<B> {
+    fn new(backend: B, max_width: usize, options: &RenderOptions) -> Self {
+        let mut options = options.clone();
+        if backend.is_markdown() {
+            options.markup = true;
+            options.paragraph_spacing = options.paragraph_spacing.max(1);
+            options.indent = options.indent.min(3);
+        }
+
+        Self {
+            markup: options.markup,
+            backend,
+            line: 0,
+            spans: 0,
+            blank: true,
+            last_line_width: 0,
+            max_width,
+            options,
+            footnotes: vec![],
+            sections: None,
+            headings: vec![],
+            link_start: None,
+            links: vec![],
+        }
+    }
+
+    fn render(&mut self, html: &str) {
+        let tree = Html::parse_document(html);
+        self.render_node(self.root_context(), tree.tree.root());
+        self.render_footnotes();
+    }
+
+    fn root_context(&self) -> Context {
+        Context {
+            tab_size: self.options.indent,
+            ..Default::default()
+        }
+    }
+
+    fn render_node(&mut self, ctx: Context, node: NodeRef<'_, Node>) -> RenderStatus {
+        match node.value() {
+            Node::Document => self.render_children(ctx, node.children()),
+            Node::Fragment => self.render_children(ctx, node.children()),
+            Node::Text(text) => self.render_text(ctx, &text.text),
+            Node::Element(element) => match element.name() {
+                "script" | "head" | "noscript" | "img" | "picutre" | "audio" | "video"
+                | "source" | "svg" => RenderStatus::NotRendered, // ignore
+                "span" | "button" => {
+                    self.render_context(ctx, first_char(node));
+                    self.render_children(
+                        ctx.set_exclusive_modifier(ExclusiveModifier::Inline),
+                        node.children(),
+                    );
+
+                    RenderStatus::RenderedRequiresSpace
+                }
+                "a" => {
+                    let ctx = self.render_link_start(ctx, first_char(node));
+                    self.render_children(ctx, node.children());
+                    self.render_link_target(ctx, element.attr("href").unwrap_or(""));
+
+                    RenderStatus::RenderedRequiresSpace
+                }
+                "iframe" | "embed" => self.render_embed(ctx, element.attr("src")),
+                "strong" => self.render_styled(ctx, StackableStyle::Bold, "**", node),
+                "em" => self.render_styled(ctx, StackableStyle::Italic, "_", node),
+                "del" | "s" => self.render_styled(ctx, StackableStyle::CrossedOut, "~~", node),
+                // Markdown has no syntax for underlined and highlighted text.
+                "ins" | "u" => self.render_styled(ctx, StackableStyle::Underlined, "", node),
+                "mark" => self.render_styled(ctx, StackableStyle::Highlighted, "", node),
+                "sup" => self.render_script(ctx, StackableModifier::Superscript, node),
+                "sub" => self.render_script(ctx, StackableModifier::Subscript, node),
+                "ul" => {
+                    let mut status = RenderStatus::NotRendered;
+                    let ctx = ctx
+                        .merge_exclusive_modifier(ExclusiveModifier::UnorderedList)
+                        .add_stackable_modifier(StackableModifier::InsideList);
+
+                    for child in node.children() {
+                        let st = self.render_node(ctx, child);
+                        if st.is_rendered() {
+                            status = RenderStatus::Rendered;
+                        }
+                    }
+
+                    status
+                }
+                "ol" => {
+                    let mut status = RenderStatus::NotRendered;
+                    let mut count = 1;
+                    for child in node.children() {
+                        let st = self.render_node(
+                            ctx.merge_exclusive_modifier(ExclusiveModifier::OrderedList(count))
+                                .add_stackable_modifier(StackableModifier::InsideList),
+                            child,
+                        );
+                        if st.is_rendered() {
+                            status = RenderStatus::Rendered;
+                            count += 1;
+                        }
+                    }
+
+                    status
+                }
+                "br" => {
+                    self.backend.line_break();
+                    self.render_new_line(ctx);
+                    RenderStatus::Rendered
+                }
+                "hr" => self.render_rule(ctx),
+                "details" => self.render_details(ctx, node),
+                "h1" => self.render_header(ctx, 1, node),
+                "h2" => self.render_header(ctx, 2, node),
+                "h3" => self.render_header(ctx, 3, node),
+                "h4" => self.render_header(ctx, 4, node),
+                "h5" => self.render_header(ctx, 5, node),
+                "h6" => self.render_header(ctx, 6, node),
+                "code" => {
+                    let is_block = node.parent().is_some_and(|p| match p.value() {
+                        Node::Element(elt) => elt.name() == "pre",
+                        _ => false,
+                    });
+
+                    let ctx = ctx.merge_exclusive_style(ExclusiveStyle::Code);
+                    if !is_block {
+                        self.render_markup(
+                            ctx.merge_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+                            "`",
+                            first_char(node),
+                        );
+
+                        let ctx = ctx.set_exclusive_modifier(ExclusiveModifier::Inline);
+                        self.render_children(ctx, node.children());
+                        self.render_markup(ctx, "`", None);
+
+                        RenderStatus::RenderedRequiresSpace
+                    } else {
+                        let context = ctx
+                            .set_exclusive_modifier(ExclusiveModifier::Inline)
+                            .add_stackable_modifier(StackableModifier::InsideRawBlock);
+
+                        if self.markup {
+                            self.render_literal(
+                                ctx.merge_exclusive_modifier(ExclusiveModifier::NewLine),
+                                "```",
+                            );
+                            self.render_new_line(context);
+                        } else {
+                            self.render_context(
+                                ctx.merge_exclusive_modifier(ExclusiveModifier::NewLine),
+                                None,
+                            );
+                        }
+
+                        for child in node.children() {
+                            self.render_node(context, child);
+                        }
+
+                        if self.markup {
+                            self.render_literal(
+                                ctx.set_exclusive_modifier(ExclusiveModifier::NewLine),
+                                "```",
+                            );
+                        }
+
+                        if matches!(
+                            ctx.exclusive_modifier,
+                            ExclusiveModifier::Inline | ExclusiveModifier::RequiresSpace
+                        ) {
+                            self.render_new_line(ctx);
+                        }
+
+                        RenderStatus::Rendered
+                    }
+                }
+                _ => {
+                    // Handle roles that are not content
+                    if element
+                        .attr("role")
+                        .is_some_and(|role| role == "dialog" || role == "navigation")
+                    {
+                        return RenderStatus::NotRendered;
+                    }
+
+                    self.render_block(ctx, node.children())
+                }
+            },
+            Node::Comment(_) => RenderStatus::NotRendered,
+            Node::Doctype(_) => RenderStatus::NotRendered,
+            Node::ProcessingInstruction(_) => RenderStatus::NotRendered,
+        }
+    }
+
+    fn render_header(
+        &mut self,
+        ctx: Context,
+        heading: u8,
+        node: NodeRef<'_, Node>,
+    ) -> RenderStatus {
+        self.render_context(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::NewHeading),
+            Some('#'),
+        );
+
+        let title = node
+            .descendants()
+            .filter_map(|n| n.value().as_text())
+            .map(|text| normalize_text(text))
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !title.is_empty() {
+            self.headings.push(Heading {
+                level: heading,
+                title,
+                line: self.line,
+            });
+        }
+
+        let ctx = ctx.set_exclusive_style(ExclusiveStyle::Heading);
+        if !self.markup {
+            self.render_children(
+                ctx.set_exclusive_modifier(ExclusiveModifier::Inline),
+                node.children(),
+            );
+            return RenderStatus::Rendered;
+        }
+
+        for _ in 0..heading {
+            self.render_literal(ctx.set_exclusive_modifier(ExclusiveModifier::Inline), "#");
+        }
+
+        self.render_children(
+            ctx.set_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+            node.children(),
+        );
+
+        RenderStatus::Rendered
+    }
+
+    /// Renders the start of the link and returns the context of the link text.
+    fn render_link_start(&mut self, ctx: Context, first_char: Option<char>) -> Context {
+        let ctx = ctx.merge_exclusive_style(ExclusiveStyle::Link);
+        let markup = match self.options.links {
+            LinkFormat::Hidden => "",
+            _ => "[",
+        };
+        self.render_markup(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+            markup,
+            first_char,
+        );
+        self.link_start = Some((self.line, self.spans));
+
+        ctx.set_exclusive_modifier(ExclusiveModifier::ForcedInline)
+    }
+
+    /// Remembers the link from its start to the current position, if the target is
+    /// absolute.
+    fn end_link(&mut self, href: &str) {
+        let Some(start) = self.link_start.take() else {
+            return;
+        };
+        if href.contains("://") || href.starts_with("mailto:") {
+            self.links
+                .push((start, (self.line, self.spans), href.to_string()));
+        }
+    }
+
+    /// Renders the end of the link, after the link text, with the target of the link.
+    fn render_link_target(&mut self, ctx: Context, href: &str) {
+        self.end_link(href);
+
+        // Link target is not written in superscript, even if the text is.
+        let ctx = ctx
+            .remove_stackable_modifier(StackableModifier::Superscript)
+            .remove_stackable_modifier(StackableModifier::Subscript);
+
+        if self.options.links == LinkFormat::Hidden {
+            return;
+        }
+
+        if self.options.links == LinkFormat::Footnote && !href.is_empty() {
+            let number = self.footnote(href);
+            if self.markup {
+                self.render_literal(ctx, &format!("][{number}]"));
+            } else {
+                self.render_literal(
+                    ctx.set_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+                    &format!("[{number}]"),
+                );
+            }
+        } else if self.markup {
+            self.render_literal(ctx, "]");
+            self.render_literal(ctx, "(");
+            self.render_literal(ctx, href);
+            self.render_literal(ctx, ")");
+        } else if !href.is_empty() {
+            self.render_literal(
+                ctx.set_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+                &format!("({href})"),
+            );
+        }
+    }
+
+    /// Renders embedded content, like a video player, as a link to it on its own paragraph.
+    fn render_embed(&mut self, ctx: Context, src: Option<&str>) -> RenderStatus {
+        let Some(src) = src.map(str::trim).filter(|src| !src.is_empty()) else {
+            return RenderStatus::NotRendered;
+        };
+
+        let url = embed_url(src);
+        let ctx = self.render_link_start(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::NewParagraph),
+            Some('['),
+        );
+        self.render_text(ctx, embed_label(&url));
+        self.render_link_target(ctx, &url);
+
+        RenderStatus::Rendered
+    }
+
+    /// Number of the footnote with the link target, added if the target isn't in the
+    /// footnotes yet.
+    fn footnote(&mut self, href: &str) -> usize {
+        match self.footnotes.iter().position(|footnote| footnote == href) {
+            Some(idx) => idx + 1,
+            None => {
+                self.footnotes.push(href.to_string());
+                self.footnotes.len()
+            }
+        }
+    }
+
+    /// Renders the targets of the links written as footnotes, at the end of the document.
+    fn render_footnotes(&mut self) {
+        let ctx = self.root_context();
+        for (idx, href) in std::mem::take(&mut self.footnotes).iter().enumerate() {
+            let modifier = if idx == 0 {
+                ExclusiveModifier::NewParagraph
+            } else {
+                ExclusiveModifier::NewLine
+            };
+            let label = if self.markup {
+                format!("[{}]:", idx + 1)
+            } else {
+                format!("[{}]", idx + 1)
+            };
+            self.render_literal(ctx.set_exclusive_modifier(modifier), &label);
+            // Space before the target is left out of the link.
+            self.link_start = Some((self.line, self.spans));
+            self.render_literal(
+                ctx.set_exclusive_modifier(ExclusiveModifier::RequiresSpace)
+                    .set_exclusive_style(ExclusiveStyle::Link),
+                href,
+            );
+            self.end_link(href);
+        }
+    }
+
+    /// Renders a horizontal rule across the whole width. Unwrapped documents have no width,
+    /// so they get a markdown rule instead.
+    fn render_rule(&mut self, ctx: Context) -> RenderStatus {
+        self.render_context(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::NewParagraph),
+            None,
+        );
+
+        let rule = if self.max_width > MAX_SCREEN_WIDTH {
+            "---".to_string()
+        } else {
+            "─".repeat(self.max_width.saturating_sub(self.last_line_width))
+        };
+        self.write(&rule, Style::default().fg(Color::Gray), true);
+
+        RenderStatus::Rendered
+    }
+
+    /// Renders children of a block element, starting on a new paragraph.
+    fn render_block<'a>(
+        &mut self,
+        ctx: Context,
+        children: impl Iterator<Item = NodeRef<'a, Node>>,
+    ) -> RenderStatus {
+        let mut status = RenderStatus::NotRendered;
+        for child in children {
+            let context = match status {
+                RenderStatus::NotRendered => {
+                    ctx.merge_exclusive_modifier(ExclusiveModifier::NewParagraph)
+                }
+                RenderStatus::Rendered => ctx.set_exclusive_modifier(ExclusiveModifier::Inline),
+                RenderStatus::RenderedRequiresSpace => {
+                    ctx.set_exclusive_modifier(ExclusiveModifier::RequiresSpace)
+                }
+            };
+
+            let st = self.render_node(context, child);
+            if st.is_rendered() {
+                status = st
+            }
+        }
+
+        if status.is_rendered() {
+            RenderStatus::Rendered
+        } else {
+            RenderStatus::NotRendered
+        }
+    }
+
+    /// Renders the summary followed by the indented body. When sections are collected,
+    /// the summary starts with a marker showing whether the section is expanded.
+    fn render_details(&mut self, ctx: Context, node: NodeRef<'_, Node>) -> RenderStatus {
+        let is_summary = |child: &NodeRef<'_, Node>| matches!(child.value(), Node::Element(elt) if elt.name() == "summary");
+        let summary = node.children().find(is_summary);
+
+        self.render_context(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::NewParagraph),
+            None,
+        );
+        let (summary_line, marker) = (self.line, self.spans);
+        let section = self.sections.as_mut().map(|sections| {
+            sections.push(Section {
+                summary: summary_line,
+                marker,
+                body: 0..0,
+            });
+            sections.len() - 1
+        });
+        if section.is_some() {
+            self.write("▾ ", Style::default(), true);
+        }
+
+        let summary_ctx = ctx
+            .set_exclusive_modifier(ExclusiveModifier::Inline)
+            .add_stackable_style(StackableStyle::Bold);
+        let rendered = summary.is_some_and(|summary| {
+            self.render_children(summary_ctx, summary.children())
+                .is_rendered()
+        });
+        if !rendered {
+            self.render_text(summary_ctx, "Details");
+        }
+
+        let body_start = self.line + 1;
+        let mut body_ctx = ctx;
+        body_ctx.indent += self.options.indent;
+        self.render_block(body_ctx, node.children().filter(|child| !is_summary(child)));
+
+        if let Some(idx) = section
+            && let Some(sections) = &mut self.sections
+        {
+            sections[idx].body = body_start..self.line + 1;
+        }
+
+        RenderStatus::Rendered
+    }
+
+    /// Renders inline element with the style, surrounded by the markup.
+    fn render_styled(
+        &mut self,
+        ctx: Context,
+        style: StackableStyle,
+        markup: &str,
+        node: NodeRef<'_, Node>,
+    ) -> RenderStatus {
+        let ctx = ctx.add_stackable_style(style);
+        self.render_markup(
+            ctx.merge_exclusive_modifier(ExclusiveModifier::RequiresSpace),
+            markup,
+            first_char(node),
+        );
+
+        let ctx = ctx.set_exclusive_modifier(ExclusiveModifier::Inline);
+        self.render_children(ctx, node.children());
+        self.render_markup(ctx, markup, None);
+
+        RenderStatus::RenderedRequiresSpace
+    }
+
+    /// Renders superscript or subscript, attached to the text around it unless it is
+    /// separated by whitespace.
+    fn render_script(
+        &mut self,
+        ctx: Context,
+        script: StackableModifier,
+        node: NodeRef<'_, Node>,
+    ) -> RenderStatus {
+        self.render_context(ctx, first_char(node));
+        self.render_children(
+            ctx.set_exclusive_modifier(ExclusiveModifier::Inline)
+                .add_stackable_modifier(script),
+            node.children(),
+        );
+
+        let space_after = node.next_sibling().is_some_and(|next| match next.value() {
+            Node::Text(text) => text.starts_with(char::is_whitespace),
+            _ => false,
+        });
+        if space_after {
+            RenderStatus::RenderedRequiresSpace
+        } else {
+            RenderStatus::Rendered
+        }
+    }
+
+    fn render_children(&mut self, ctx: Context, children: Children<'_, Node>) -> RenderStatus {
+        let mut status = RenderStatus::NotRendered;
+
+        for child in children {
+            let st = self.render_node(ctx, child);
+            if st.is_rendered() {
+                status = st;
+            }
+        }
+
+        status
+    }
+
+    fn render_text(&mut self, ctx: Context, text: &str) -> RenderStatus {
+        let literal = ctx.exclusive_style == ExclusiveStyle::Code;
+        self.render_words(ctx, text, literal)
+    }
+
+    /// Renders markup, or other text that is written as is.
+    fn render_literal(&mut self, ctx: Context, text: &str) -> RenderStatus {
+        self.render_words(ctx, text, true)
+    }
+
+    fn render_words(&mut self, ctx: Context, text: &str, literal: bool) -> RenderStatus {
+        let stripped = strip_control(text);
+        let text = stripped.as_ref();
+        if ctx.has_stackable_modifier(StackableModifier::InsideRawBlock) {
+            return self.render_raw_text(ctx, text);
+        }
+
+        let text = if literal {
+            Cow::Borrowed(text)
+        } else {
+            normalize_text(text)
+        };
+        let txt = text.trim();
+        if txt.is_empty() {
+            return RenderStatus::NotRendered;
+        }
+
+        let first_char = txt.chars().next();
+        self.render_context(ctx, first_char);
+
+        let style = self.style(ctx);
+
+        for (idx, word) in words(txt).enumerate() {
+            let word = script_word(ctx, word).replace(NO_BREAK_SPACE, " ");
+            self.render_word(ctx, &word, style, literal, idx > 0);
+        }
+
+        RenderStatus::Rendered
+    }
+
+    /// Renders the word, preceded by a space unless it starts a new line. Soft hyphens in
+    /// the word aren't shown, but with hyphenation the word is broken at them. URLs and
+    /// email addresses are never hyphenated, since the hyphen would read as part of them.
+    fn render_word(
+        &mut self,
+        ctx: Context,
+        word: &str,
+        style: Style,
+        literal: bool,
+        mut space: bool,
+    ) {
+        let hyphenate = self.options.hyphenate && !is_url_like(word);
+        let mut word = word;
+        loop {
+            let visible = word.replace(SOFT_HYPHEN, "");
+            let width = visible.width();
+
+            // Fits, keeping + 1 for space
+            if self.max_width > self.last_line_width + width {
+                self.render_space(space, style);
+                self.write(&visible, style, literal);
+                return;
+            }
+
+            if let Some((head, tail)) = hyphenate.then(|| self.split_at_soft_hyphen(word)).flatten()
+            {
+                self.render_space(space, style);
+                let head = format!("{}-", head.replace(SOFT_HYPHEN, ""));
+                self.write(&head, style, literal);
+                self.render_new_line(ctx);
+
+                word = tail;
+                space = false;
+                continue;
+            }
+
+            // Words that don't fit on a line of their own, like sentences in languages
+            // written without spaces, are broken wherever the line ends.
+            if self.max_width < ctx.text_indent() as usize + width + 1 {
+                self.render_space(space, style);
+                self.render_long_word(ctx, &visible, style, literal, hyphenate);
+            } else {
+                self.render_new_line(ctx);
+                self.write(&visible, style, literal);
+            }
+            return;
+        }
+    }
+
+    /// Longest start of the word, ending at a soft hyphen, that fits on the current line
+    /// followed by a hyphen, and the rest of the word.
+    fn split_at_soft_hyphen<'a>(&self, word: &'a str) -> Option<(&'a str, &'a str)> {
+        word.rmatch_indices(SOFT_HYPHEN)
+            .map(|(idx, _)| (&word[..idx], &word[idx + SOFT_HYPHEN.len_utf8()..]))
+            .find(|(head, _)| {
+                let width = head.replace(SOFT_HYPHEN, "").width();
+                // Space, hyphen and the space left at the end of the line.
+                width > 0 && self.max_width >= self.last_line_width + width + 3
+            })
+    }
+
+    fn render_space(&mut self, space: bool, style: Style) {
+        if space && self.last_line_width != 0 {
+            self.write(" ", style, true);
+        }
+    }
+
+    /// Writes the text to the current line, without wrapping it.
+    fn write(&mut self, text: &str, style: Style, literal: bool) {
+        self.backend.push(text, style, literal);
+        self.spans += 1;
+        self.blank &= text.trim().is_empty();
+        self.last_line_width += text.width();
+    }
+
+    /// Renders the word over as many lines as needed, breaking it between graphemes.
+    /// With `hyphenate`, broken parts end with a hyphen.
+    fn render_long_word(
+        &mut self,
+        ctx: Context,
+        word: &str,
+        style: Style,
+        literal: bool,
+        hyphenate: bool,
+    ) {
+        let hyphen = if hyphenate { "-" } else { "" };
+        let mut part = String::new();
+        for grapheme in word.graphemes(true) {
+            let width = self.last_line_width + part.width() + grapheme.width();
+            let line_empty = part.is_empty() && self.last_line_width <= ctx.text_indent() as usize;
+            if !line_empty && self.max_width < width + hyphen.len() + 1 {
+                if !part.is_empty() {
+                    part.push_str(hyphen);
+                }
+                self.write(&std::mem::take(&mut part), style, literal);
+                self.render_new_line(ctx);
+            }
+
+            part.push_str(grapheme);
+        }
+
+        self.write(&part, style, literal);
+    }
+
+    /// Renders markup characters, like `**` around bold text. Without markup only
+    /// the spacing required by the context is rendered.
+    fn render_markup(&mut self, ctx: Context, markup: &str, first_char: Option<char>) {
+        if self.markup && !markup.is_empty() {
+            self.render_literal(ctx, markup);
+        } else {
+            self.render_context(ctx, first_char);
+        }
+    }
+
+    fn render_raw_text(&mut self, ctx: Context, text: &str) -> RenderStatus {
+        let style = self.style(ctx);
+
+        for (idx, line) in text
+            .replace('\r', "")
+            .replace('\t', "    ")
+            .lines()
+            .enumerate()
+        {
+            if idx > 0 {
+                self.render_new_line(ctx);
+            }
+
+            self.write(line, style, true);
+        }
+
+        if text.ends_with('\n') {
+            self.render_new_line(ctx);
+        }
+
+        RenderStatus::Rendered
+    }
+
+    fn render_context(&mut self, ctx: Context, first_char: Option<char>) {
+        match ctx.exclusive_modifier {
+            ExclusiveModifier::Inline | ExclusiveModifier::ForcedInline => (),
+            ExclusiveModifier::RequiresSpace => {
+                if first_char.is_none_or(|c| c != '.' && c != ',' && c != ';') {
+                    self.write(" ", Style::default(), true);
+                }
+            }
+            ExclusiveModifier::NewLine => {
+                self.render_new_line(ctx);
+            }
+            ExclusiveModifier::NewParagraph => {
+                for _ in 0..=self.options.paragraph_spacing {
+                    self.render_new_line(ctx);
+                }
+            }
+            ExclusiveModifier::NewHeading => {
+                for _ in 0..=self.options.paragraph_spacing + 1 {
+                    self.render_new_line(ctx);
+                }
+            }
+            ExclusiveModifier::UnorderedList | ExclusiveModifier::OrderedList(_) => {
+                // We have to remove inside list modifier when rendering the first line of the
+                // element.
+                self.render_new_line(ctx.remove_stackable_modifier(StackableModifier::InsideList));
+
+                let marker = list_marker(ctx.exclusive_modifier).unwrap_or_default();
+                self.write(&marker, Style::default().fg(Color::Gray), true);
+            }
+        }
+    }
+
+    fn render_new_line(&mut self, ctx: Context) {
+        // If we are at the beginning of file, skip adding new line, but indent the first one.
+        if self.blank {
+            self.backend.clear_line();
+        } else {
+            self.backend.new_line();
+            self.line += 1;
+        }
+        self.spans = 0;
+        self.last_line_width = 0;
+
+        let indent = ctx.text_indent() as usize;
+        if indent > 0 {
+            self.write(&" ".repeat(indent), Style::default(), true);
+        }
+    }
+
+    fn style(&self, ctx: Context) -> Style {
+        if self.options.colorize {
+            ctx.style(&self.options)
+        } else {
+            Style::default()
+        }
+    }
+}
+
+/// Words of the text, separated by whitespace other than non-breaking spaces.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| ch.is_whitespace() && ch != NO_BREAK_SPACE)
+        .map(|word| word.trim_matches(NO_BREAK_SPACE))
+        .filter(|word| !word.is_empty())
+}
+
+/// Whether the word looks like a URL or an email address, which shouldn't be hyphenated.
+fn is_url_like(word: &str) -> bool {
+    let word = word.trim_start_matches(['(', '<', '"', '\'']);
+    word.contains("://")
+        || word.starts_with("www.")
+        || word.starts_with("mailto:")
+        || word
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+/// Text without control characters, which the terminal would interpret, e.g. as escape
+/// sequences. Line breaks and tabs are kept.
+fn strip_control(text: &str) -> Cow<'_, str> {
+    let is_stripped = |ch: char| ch.is_control() && !matches!(ch, '\n' | '\t');
+    if text.chars().any(is_stripped) {
+        Cow::Owned(text.chars().filter(|ch| !is_stripped(*ch)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Text without zero-width characters and with runs of no-break spaces collapsed. Entities
+/// are already decoded by the parser, so the rest is kept as written.
+fn normalize_text(text: &str) -> Cow<'_, str> {
+    let is_zero_width = |ch| matches!(ch, '\u{200b}' | '\u{2060}' | '\u{feff}');
+    let is_no_break_space = |ch| matches!(ch, NO_BREAK_SPACE | '\u{2007}' | '\u{202f}');
+    if !text
+        .chars()
+        .any(|ch| is_zero_width(ch) || is_no_break_space(ch))
+    {
+        return Cow::Borrowed(text);
+    }
+
+    let mut normalized = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if is_zero_width(ch) {
+            continue;
+        }
+        if is_no_break_space(ch) {
+            if !normalized.ends_with(NO_BREAK_SPACE) {
+                normalized.push(NO_BREAK_SPACE);
+            }
+            continue;
+        }
+        normalized.push(ch);
+    }
+
+    Cow::Owned(normalized)
+}
+
+/// Word written in unicode superscript or subscript characters, if the context requires it
+/// and all the characters have a variant. Otherwise the word is returned as is.
+fn script_word(ctx: Context, word: &str) -> Cow<'_, str> {
+    let convert = if ctx.has_stackable_modifier(StackableModifier::Superscript) {
+        superscript
+    } else if ctx.has_stackable_modifier(StackableModifier::Subscript) {
+        subscript
+    } else {
+        return Cow::Borrowed(word);
+    };
+
+    match word.chars().map(convert).collect::<Option<String>>() {
+        Some(converted) => Cow::Owned(converted),
+        None => Cow::Borrowed(word),
+    }
+}
+
+fn superscript(ch: char) -> Option<char> {
+    let res = match ch {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'i' => 'ⁱ',
+        'n' => 'ⁿ',
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn subscript(ch: char) -> Option<char> {
+    let res = match ch {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'x' => 'ₓ',
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// Text with backslashes before the characters that could be read as markdown. Some
+/// characters are special only at the start of a line.
+fn escape_markdown(text: &str, line_start: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (idx, ch) in text.char_indices() {
+        let special = match ch {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~' => true,
+            '#' | '>' | '-' | '+' | '=' => line_start && idx == 0,
+            // Number followed by a dot or parenthesis starts an ordered list.
+            '.' | ')' => line_start && idx > 0 && text[..idx].bytes().all(|b| b.is_ascii_digit()),
+            _ => false,
+        };
+        if special {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+/// Host of the URL, without the `www.` prefix.
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("//").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// Address of the embedded content that can be opened in the browser. Players of YouTube and
+/// Vimeo are replaced with the pages of their videos.
+fn embed_url(src: &str) -> String {
+    let src = match src.strip_prefix("//") {
+        Some(rest) => format!("https://{rest}"),
+        None => src.to_string(),
+    };
+
+    let host = url_host(&src);
+    let path = src.split_once(host).map_or("", |(_, path)| path);
+    let id = |prefix: &str| {
+        path.strip_prefix(prefix)
+            .and_then(|rest| rest.split(['/', '?', '#']).next())
+            .filter(|id| !id.is_empty())
+    };
+
+    match host {
+        "youtube.com" | "youtube-nocookie.com" => match id("/embed/") {
+            Some(id) => format!("https://www.youtube.com/watch?v={id}"),
+            None => src,
+        },
+        "player.vimeo.com" => match id("/video/") {
+            Some(id) => format!("https://vimeo.com/{id}"),
+            None => src,
+        },
+        _ => src,
+    }
+}
+
+/// Text of the link to embedded content, by the site it's embedded from.
+fn embed_label(url: &str) -> &'static str {
+    let host = url_host(url);
+    let is_site = |site: &str| host == site || host.ends_with(&format!(".{site}"));
+
+    if [
+        "youtube.com",
+        "youtube-nocookie.com",
+        "youtu.be",
+        "vimeo.com",
+        "dailymotion.com",
+    ]
+    .into_iter()
+    .any(is_site)
+    {
+        "embedded video"
+    } else if ["twitter.com", "x.com"].into_iter().any(is_site) {
+        "embedded tweet"
+    } else {
+        "embedded content"
+    }
+}
+
+/// Marker of the list item, or `None` if the modifier isn't a list.
+fn list_marker(modifier: ExclusiveModifier) -> Option<String> {
+    match modifier {
+        ExclusiveModifier::UnorderedList => Some("- ".to_string()),
+        ExclusiveModifier::OrderedList(idx) => Some(format!("{idx}. ")),
+        _ => None,
+    }
+}
+
+fn first_char(node: NodeRef<'_, Node>) -> Option<char> {
+    match node.value() {
+        Node::Document | Node::Fragment => node.first_child().and_then(first_char),
+        Node::Text(text) => text.chars().next(),
+        Node::Element(element) => match element.name() {
+            "script" | "head" | "noscript" => None,
+            "a" | "iframe" | "embed" => Some('['),
+            _ => node.first_child().and_then(first_char),
+        },
+        Node::Comment(_) => None,
+        Node::Doctype(_) => None,
+        Node::ProcessingInstruction(_) => None,
+    }
+}
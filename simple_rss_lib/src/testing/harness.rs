@@ -0,0 +1,205 @@
+use std::{env, fs, path::Path, time::Duration};
+
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+
+use crate::{
+    app::{App, AppConfig},
+    data::RefreshError,
+    event::{Event, EventBus, EventState, InputEvent, KeyboardEvent},
+    testing::MemoryLoader,
+};
+
+/// How long the app has to be quiet for [`Harness::settle`] to return.
+const SETTLE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Environment variable which makes [`Harness::assert_snapshot`] write the snapshots,
+/// also the missing ones, instead of comparing them.
+pub const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+/// [`App`] drawn on a [`TestBackend`], driven like an embedder drives it.
+///
+/// Every event is passed to the app and the app is drawn again if it changed. Events the
+/// app sends itself, like loaded articles, are passed on by [`Harness::settle`] and
+/// [`Harness::wait_for`]. Has to be used within a tokio runtime.
+///
+/// ```
+/// use simple_rss_lib::{
+///     app::AppConfig,
+///     event::KeyboardEvent,
+///     testing::{Harness, MemoryLoader},
+/// };
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut harness = Harness::new(MemoryLoader::new(vec![]), AppConfig::default(), 80, 20);
+/// harness.wait_for_refresh().await;
+/// harness.key(KeyboardEvent::Help);
+/// assert!(harness.screen().contains("Show this help"));
+/// # }
+/// ```
+pub struct Harness {
+    app: App<MemoryLoader>,
+    bus: EventBus,
+    terminal: Terminal<TestBackend>,
+    loader: MemoryLoader,
+}
+
+impl Harness {
+    /// Start the app with the loader on a terminal of the given size. The app starts
+    /// refreshing right away, see [`Harness::wait_for_refresh`].
+    pub fn new(loader: MemoryLoader, config: AppConfig, width: u16, height: u16) -> Self {
+        let bus = EventBus::new();
+        let app = App::new(config, bus.get_sender(), loader.clone(), 30);
+        let terminal = Terminal::new(TestBackend::new(width, height)).expect("test backend");
+
+        let mut harness = Self {
+            app,
+            bus,
+            terminal,
+            loader,
+        };
+        harness.draw();
+        harness
+    }
+
+    pub fn app(&mut self) -> &mut App<MemoryLoader> {
+        &mut self.app
+    }
+
+    pub fn loader(&self) -> &MemoryLoader {
+        &self.loader
+    }
+
+    /// Pass the event to the app and draw it.
+    pub fn send(&mut self, event: Event) -> EventState {
+        if let Event::Resize(width, height) = event {
+            self.terminal.backend_mut().resize(width, height);
+        }
+
+        let state = self.app.handle_event(&event);
+        if self.app.is_dirty() {
+            self.draw();
+        }
+        state
+    }
+
+    /// Press the key bound to the action.
+    pub fn key(&mut self, key: KeyboardEvent) -> EventState {
+        self.send(Event::Keyboard(key))
+    }
+
+    /// Type the text, e.g. into the search.
+    pub fn type_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.send(Event::Input(InputEvent::Char(ch)));
+        }
+    }
+
+    /// Resize the terminal, like the embedder does when the real terminal is resized.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.send(Event::Resize(width, height));
+    }
+
+    /// Pass events sent by the app to it until the refresh finishes. Returns errors of the
+    /// refresh.
+    pub async fn wait_for_refresh(&mut self) -> Vec<RefreshError> {
+        match self
+            .wait_for(|event| matches!(event, Event::RefreshFinished { .. }))
+            .await
+        {
+            Event::RefreshFinished { errors } => errors,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Pass events sent by the app to it until one matches. Returns the matching event,
+    /// after it was passed to the app.
+    ///
+    /// Panics if the app stops sending events before a match.
+    pub async fn wait_for(&mut self, matches: impl Fn(&Event) -> bool) -> Event {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), self.bus.next())
+                .await
+                .ok()
+                .flatten()
+                .expect("app didn't send the awaited event");
+            self.send(event.clone());
+            if matches(&event) {
+                return event;
+            }
+        }
+    }
+
+    /// Pass events sent by the app to it until it stops sending them, e.g. until the
+    /// opened article is loaded and rendered.
+    pub async fn settle(&mut self) {
+        while let Ok(Some(event)) = tokio::time::timeout(SETTLE_TIMEOUT, self.bus.next()).await {
+            self.send(event);
+        }
+    }
+
+    /// Draw the app, even if it didn't change.
+    pub fn draw(&mut self) {
+        let app = &mut self.app;
+        self.terminal
+            .draw(|f| app.draw(f))
+            .expect("test backend doesn't fail");
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// Text on the screen, see [`screen`].
+    pub fn screen(&self) -> String {
+        screen(self.buffer())
+    }
+
+    /// Compare the screen with the snapshot in the file, ignoring styles and trailing
+    /// spaces. Snapshot is written instead if the [`UPDATE_SNAPSHOTS`] environment variable
+    /// is set.
+    ///
+    /// Panics with both screens if they differ, and if the snapshot doesn't exist, so that
+    /// a snapshot missing on CI fails the test.
+    #[track_caller]
+    pub fn assert_snapshot(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual: String = self
+            .screen()
+            .lines()
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect();
+
+        if env::var_os(UPDATE_SNAPSHOTS).is_some() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).expect("failed to create the snapshot directory");
+            }
+            fs::write(path, &actual).expect("failed to write the snapshot");
+            return;
+        }
+
+        let Ok(expected) = fs::read_to_string(path) else {
+            panic!(
+                "Snapshot {} can't be read, set {UPDATE_SNAPSHOTS}=1 to write it.\n\
+                 Actual:\n{actual}",
+                path.display()
+            );
+        };
+        if expected != actual {
+            panic!(
+                "Screen doesn't match snapshot {}, set {UPDATE_SNAPSHOTS}=1 to update it.\n\
+                 Expected:\n{expected}\nActual:\n{actual}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Text of the buffer, one line per row, without styles.
+pub fn screen(buffer: &Buffer) -> String {
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n")
+        .collect()
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU16, Ordering},
+    },
+};
+
+use crate::{
+    Error,
+    data::{Channel, ContentLoader, Fetcher, Hidden, Item, ItemStore, RefreshError},
+};
+
+/// [`Loader`](crate::data::Loader) keeping the items in memory, for driving the
+/// [`App`](crate::app::App) in tests without network or filesystem.
+///
+/// Results of refreshes are queued with [`MemoryLoader::push_refresh`] and
+/// [`MemoryLoader::fail_refresh`]. A refresh without a queued result succeeds and keeps
+/// the items. Clones share the same items and content.
+///
+/// ```
+/// use simple_rss_lib::{Error, data::RefreshError, testing::MemoryLoader};
+///
+/// let loader = MemoryLoader::new(vec![]);
+/// loader.fail_refresh(vec![RefreshError {
+///     channel: "Example".to_string(),
+///     url: "https://example.com/feed.xml".to_string(),
+///     error: Error::Network("connection refused".to_string()),
+/// }]);
+/// loader.set_content("https://example.com/1", Ok("<p>Hello</p>".to_string()));
+/// ```
+#[derive(Clone, Default)]
+pub struct MemoryLoader {
+    state: Arc<Mutex<State>>,
+    // Separate from the state, because the version is read while the items are locked.
+    version: Arc<AtomicU16>,
+}
+
+#[derive(Default)]
+struct State {
+    items: Vec<Item>,
+    channels: Vec<Channel>,
+    refreshes: VecDeque<Result<Vec<Item>, Vec<RefreshError>>>,
+    refresh_count: usize,
+    // Content returned by `load_item`, by url.
+    content: HashMap<String, crate::Result<String>>,
+}
+
+/// Read lock of the [`MemoryLoader`]'s items.
+pub struct MemoryGuard<'a>(MutexGuard<'a, State>);
+
+impl std::ops::Deref for MemoryGuard<'_> {
+    type Target = Vec<Item>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.items
+    }
+}
+
+impl MemoryLoader {
+    pub fn new(items: Vec<Item>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                items,
+                ..Default::default()
+            })),
+            version: Arc::default(),
+        }
+    }
+
+    /// Queue a successful refresh, replacing the items. Read status of the items with
+    /// the same id is kept, like a real refresh does.
+    pub fn push_refresh(&self, items: Vec<Item>) {
+        let mut state = self.state.lock().unwrap();
+        state.refreshes.push_back(Ok(items));
+    }
+
+    /// Queue a failed refresh. Items are not changed.
+    pub fn fail_refresh(&self, errors: Vec<RefreshError>) {
+        let mut state = self.state.lock().unwrap();
+        state.refreshes.push_back(Err(errors));
+    }
+
+    /// Set the channels returned by [`ItemStore::get_channels`].
+    pub fn set_channels(&self, channels: Vec<Channel>) {
+        self.state.lock().unwrap().channels = channels;
+    }
+
+    /// Number of refreshes done so far.
+    pub fn refresh_count(&self) -> usize {
+        self.state.lock().unwrap().refresh_count
+    }
+
+    /// Set the result of [`ContentLoader::load_item`] for the url. Urls without content
+    /// return [`Error::Network`].
+    pub fn set_content(&self, url: &str, content: crate::Result<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.content.insert(url.to_string(), content);
+    }
+}
+
+impl ItemStore for MemoryLoader {
+    type Guard<'a> = MemoryGuard<'a>;
+
+    fn get_items(&self) -> Self::Guard<'_> {
+        MemoryGuard(self.state.lock().unwrap())
+    }
+
+    fn get_version(&self) -> u16 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    fn set_read_by_id(&mut self, id: &str, read: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.read = read;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn set_hidden_by_id(&mut self, id: &str, hidden: Option<Hidden>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.hidden = hidden;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn set_starred_by_id(&mut self, id: &str, starred: bool) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(item) = state.items.iter_mut().find(|it| it.id == id) {
+            item.starred = starred;
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn get_channels(&self) -> Vec<Channel> {
+        self.state.lock().unwrap().channels.clone()
+    }
+
+    fn move_channel(&mut self, from: usize, to: usize) {
+        let mut state = self.state.lock().unwrap();
+        if from < state.channels.len() && to < state.channels.len() {
+            let channel = state.channels.remove(from);
+            state.channels.insert(to, channel);
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Fetcher for MemoryLoader {
+    async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+        let mut state = self.state.lock().unwrap();
+        state.refresh_count += 1;
+
+        match state.refreshes.pop_front() {
+            None => Ok(()),
+            Some(Err(errors)) => Err(errors),
+            Some(Ok(mut items)) => {
+                let old_items: HashMap<_, _> = state
+                    .items
+                    .iter()
+                    .map(|it| (it.id.clone(), (it.read, it.hidden)))
+                    .collect();
+                for it in items.iter_mut() {
+                    if let Some(&(read, hidden)) = old_items.get(&it.id) {
+                        it.read |= read;
+                        it.hidden = it.hidden.or(hidden);
+                    }
+                }
+
+                state.items = items;
+                self.version.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ContentLoader for MemoryLoader {
+    async fn load_item(&self, item: &Item) -> crate::Result<String> {
+        let url = &item.link;
+        self.state
+            .lock()
+            .unwrap()
+            .content
+            .get(url)
+            .cloned()
+            .unwrap_or_else(|| Err(Error::Network(format!("404 Not Found: {url}"))))
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        _dir: &Path,
+        _progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> crate::Result<PathBuf> {
+        Err(Error::Io(format!(
+            "MemoryLoader can't download {}",
+            item.title
+        )))
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Response returned by the [`MockServer`] for a path.
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// `200 OK` with the given body. `{base}` in the body is replaced with the
+    /// server's base url, so feeds can link to other served paths.
+    Body(String),
+
+    /// `200 OK` with the bodies returned in order, one per request, e.g. for APIs that
+    /// receive all the calls on the same path. The last body is returned once the others
+    /// are used up.
+    Sequence(Vec<String>),
+
+    /// Empty response with the given status code, e.g. `500`.
+    Status(u16),
+
+    /// `429 Too Many Requests` with `Retry-After: retry_after` for the first request, then
+    /// `200 OK` with the body.
+    RateLimited { retry_after: u64, body: String },
+
+    /// Empty response with the status code for the first request, then `200 OK` with the
+    /// body, e.g. `401` for an expired session.
+    FailOnce { status: u16, body: String },
+
+    /// `302 Found` to the location. `{base}` in it is replaced with the server's base url.
+    Redirect(String),
+
+    /// `200 OK` with a body that is not valid XML.
+    MalformedXml,
+
+    /// Connection is accepted, but the server never responds.
+    Timeout,
+
+    /// `200 OK` announcing a longer body than the given one, and the connection is closed
+    /// after it, like a download that breaks off.
+    Truncated(String),
+
+    /// `200 OK` with the body sent in `chunks` parts, waiting `delay` before each of them.
+    Slow {
+        body: String,
+        chunks: usize,
+        delay: Duration,
+    },
+}
+
+/// HTTP server running on a random local port, serving configured responses.
+///
+/// Every connection is handled on its own thread, so faults like [`MockResponse::Timeout`]
+/// don't block other requests. Paths are matched with the query string first and then
+/// without it. Paths without a configured response return `404`.
+///
+/// ```no_run
+/// use simple_rss_lib::testing::{MockResponse, MockServer};
+///
+/// let server = MockServer::start();
+/// server.serve("/feed.xml", MockResponse::Body("<rss>...</rss>".to_string()));
+/// server.serve("/broken.xml", MockResponse::Status(500));
+///
+/// let url = server.url("/feed.xml");
+/// ```
+#[derive(Clone)]
+pub struct MockServer {
+    base: String,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: HashMap<String, MockResponse>,
+    hits: HashMap<String, usize>,
+    // Headers of the last request for each path, by lowercase name.
+    headers: HashMap<String, HashMap<String, String>>,
+    // Body of the last request for each path.
+    bodies: HashMap<String, String>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = Self {
+            base,
+            state: Arc::default(),
+        };
+
+        let srv = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let srv = srv.clone();
+                thread::spawn(move || srv.handle(stream));
+            }
+        });
+
+        server
+    }
+
+    /// Set the response for the path. Replaces previously configured response.
+    pub fn serve(&self, path: &str, response: MockResponse) {
+        let mut state = self.state.lock().unwrap();
+        state.routes.insert(path.to_string(), response);
+    }
+
+    /// Full url of the path on this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    /// Number of requests received for the path.
+    pub fn hits(&self, path: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state.hits.get(path).copied().unwrap_or(0)
+    }
+
+    /// Value of the header, case insensitively, in the last request for the path.
+    pub fn header(&self, path: &str, name: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.headers.get(path)?.get(&name.to_lowercase()).cloned()
+    }
+
+    /// Body of the last request for the path.
+    pub fn body(&self, path: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.bodies.get(path).cloned()
+    }
+
+    fn handle(&self, mut stream: TcpStream) {
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+
+        let mut headers = HashMap::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+            line.clear();
+        }
+
+        let length = headers
+            .get("content-length")
+            .and_then(|len| len.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let path = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string();
+
+        let response = {
+            let mut state = self.state.lock().unwrap();
+            let hits = state.hits.entry(path.clone()).or_default();
+            *hits += 1;
+            let hits = *hits;
+            state.headers.insert(path.clone(), headers);
+            state
+                .bodies
+                .insert(path.clone(), String::from_utf8_lossy(&body).into_owned());
+
+            let without_query = path.split('?').next().unwrap_or(&path);
+            let response = state
+                .routes
+                .get(&path)
+                .or_else(|| state.routes.get(without_query))
+                .cloned();
+            match response {
+                Some(MockResponse::Sequence(bodies)) => {
+                    let idx = (hits - 1).min(bodies.len().saturating_sub(1));
+                    bodies.get(idx).cloned().map(MockResponse::Body)
+                }
+                Some(
+                    MockResponse::RateLimited { body, .. } | MockResponse::FailOnce { body, .. },
+                ) if hits > 1 => Some(MockResponse::Body(body)),
+                response => response,
+            }
+        };
+
+        let _ = match response {
+            None => respond(&mut stream, 404, "not found"),
+            Some(MockResponse::Body(body)) => {
+                respond(&mut stream, 200, &body.replace("{base}", &self.base))
+            }
+            Some(MockResponse::Sequence(_)) => unreachable!("Sequence is resolved to a body"),
+            Some(MockResponse::Status(status)) => respond(&mut stream, status, ""),
+            Some(MockResponse::RateLimited { retry_after, .. }) => write!(
+                stream,
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: {retry_after}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            ),
+            Some(MockResponse::FailOnce { status, .. }) => respond(&mut stream, status, ""),
+            Some(MockResponse::Redirect(location)) => write!(
+                stream,
+                "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                location.replace("{base}", &self.base)
+            ),
+            Some(MockResponse::MalformedXml) => {
+                respond(&mut stream, 200, "<rss><channel><title>Broken</titl")
+            }
+            Some(MockResponse::Timeout) => {
+                // Keep the connection open until the client gives up.
+                let mut buf = String::new();
+                let _ = reader.read_line(&mut buf);
+                Ok(())
+            }
+            Some(MockResponse::Truncated(body)) => write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len() * 2
+            ),
+            Some(MockResponse::Slow {
+                body,
+                chunks,
+                delay,
+            }) => respond_slow(
+                &mut stream,
+                &body.replace("{base}", &self.base),
+                chunks,
+                delay,
+            ),
+        };
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    )
+}
+
+fn respond_slow(
+    stream: &mut TcpStream,
+    body: &str,
+    chunks: usize,
+    delay: Duration,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.flush()?;
+
+    let chunk_size = body.len().div_ceil(chunks.max(1)).max(1);
+    for chunk in body.as_bytes().chunks(chunk_size) {
+        thread::sleep(delay);
+        stream.write_all(chunk)?;
+        stream.flush()?;
+    }
+
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
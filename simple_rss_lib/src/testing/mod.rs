@@ -0,0 +1,40 @@
+//! Utilities for testing [`Loader`](crate::data::Loader) implementations and embedders.
+//!
+//! Available with the `testing` feature.
+
+use crate::data::Item;
+
+mod harness;
+mod memory_loader;
+mod mock_server;
+
+pub use harness::{Harness, UPDATE_SNAPSHOTS, screen};
+pub use memory_loader::{MemoryGuard, MemoryLoader};
+pub use mock_server::{MockResponse, MockServer};
+
+/// Unread item titled `Item number {idx}`, linking to `https://memory.example/{idx}`. Other
+/// fields can be set with the struct update syntax:
+///
+/// ```
+/// use simple_rss_lib::{data::Item, testing::item};
+///
+/// let read = Item { read: true, ..item(2) };
+/// assert_eq!(read.link, "https://memory.example/2");
+/// ```
+pub fn item(idx: usize) -> Item {
+    Item {
+        id: idx.to_string(),
+        channel_name: "Example".to_string(),
+        title: format!("Item number {idx}"),
+        author: None,
+        description: None,
+        pub_date: None,
+        link: format!("https://memory.example/{idx}"),
+        enclosure: None,
+        alternate_links: vec![],
+        read: false,
+        starred: false,
+        tags: vec![],
+        hidden: None,
+    }
+}
@@ -0,0 +1,914 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{Local, TimeDelta, Utc};
+use ratatui::{Terminal, backend::TestBackend};
+use simple_rss_lib::{
+    Error,
+    app::{App, AppConfig, UiState},
+    components::item_list::Grouping,
+    data::{Channel, ContentLoader, Hidden, Item, ItemStore, RefreshError},
+    event::{Event, EventBus, InputEvent, KeyboardEvent, ToastEvent, next_load_id},
+    testing::{self, Harness, MemoryLoader, item},
+};
+
+fn screen(terminal: &Terminal<TestBackend>) -> String {
+    testing::screen(terminal.backend().buffer())
+}
+
+/// Draw the app, waiting for the opened item to be rendered in the background.
+async fn draw(app: &mut App<MemoryLoader>, bus: &mut EventBus) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    while screen(&terminal).contains("Rendering") {
+        let event = bus.next().await.unwrap();
+        app.handle_event(&event);
+        if matches!(event, Event::RenderedItem { .. }) {
+            terminal.draw(|f| app.draw(f)).unwrap();
+        }
+    }
+    screen(&terminal)
+}
+
+/// Pass events to the app until the startup refresh finishes.
+async fn wait_for_refresh(app: &mut App<MemoryLoader>, bus: &mut EventBus) -> Vec<RefreshError> {
+    while let Some(event) = bus.next().await {
+        app.handle_event(&event);
+        if let Event::RefreshFinished { errors } = event {
+            return errors;
+        }
+    }
+    unreachable!("event bus closed");
+}
+
+#[tokio::test]
+async fn refresh_replaces_items() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+    loader.push_refresh(vec![item(2), item(3)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    let errors = wait_for_refresh(&mut app, &mut bus).await;
+    assert!(errors.is_empty());
+    assert_eq!(loader.refresh_count(), 1);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen = screen(&terminal);
+    assert!(!screen.contains("Item number 1"));
+    assert!(screen.contains("Item number 2"));
+    assert!(screen.contains("Item number 3"));
+}
+
+#[tokio::test]
+async fn refresh_marks_arrived_items_new() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+    loader.push_refresh(vec![item(2), item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+    let event = bus.next().await.unwrap();
+    assert_eq!(
+        event,
+        Event::Toast(ToastEvent::Info("1 new item".to_string()))
+    );
+    app.handle_event(&event);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen = screen(&terminal);
+    assert!(screen.contains("NEW Item number 2"));
+    assert!(screen.contains("] Item number 1"));
+    assert!(screen.contains("1 new item"));
+}
+
+#[tokio::test]
+async fn failed_refresh_keeps_items() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+    loader.fail_refresh(vec![RefreshError {
+        channel: "Example".to_string(),
+        url: "https://memory.example/feed.xml".to_string(),
+        error: Error::Network("connection refused".to_string()),
+    }]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    let errors = wait_for_refresh(&mut app, &mut bus).await;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(loader.get_items().len(), 1);
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::RefreshErrors));
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    assert!(screen(&terminal).contains("Network error: connection refused"));
+}
+
+#[tokio::test]
+async fn stats_panel_shows_channels() {
+    let mut read = item(2);
+    read.read = true;
+    read.channel_name = "Other".to_string();
+    let loader = MemoryLoader::new(vec![item(1), read, item(3)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Stats));
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen = screen(&terminal);
+    assert!(screen.contains("Channel statistics"));
+    let row = |name: &str| -> Vec<String> {
+        let line = screen
+            .lines()
+            .find(|l| l.contains(&format!(" {name} ")))
+            .unwrap();
+        line.split_whitespace().map(String::from).collect()
+    };
+    assert!(row("Example").join(" ").contains("Example 2 2 - -"));
+    assert!(row("Other").join(" ").contains("Other 1 0 - -"));
+}
+
+#[tokio::test]
+async fn channel_panel_moves_channels() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+    let channel = |name: &str| Channel {
+        name: Some(name.to_string()),
+        url: format!("https://{name}.example/feed.xml"),
+        ..Default::default()
+    };
+    loader.set_channels(vec![channel("first"), channel("second"), channel("third")]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Channels));
+    app.handle_event(&Event::Input(InputEvent::Char('J')));
+    app.handle_event(&Event::Input(InputEvent::Char('J')));
+    // Already the last one.
+    app.handle_event(&Event::Input(InputEvent::Char('J')));
+    let names: Vec<_> = loader
+        .get_channels()
+        .into_iter()
+        .filter_map(|ch| ch.name)
+        .collect();
+    assert_eq!(names, ["second", "third", "first"]);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen = screen(&terminal);
+    assert!(screen.contains(" 2  first"));
+
+    app.handle_event(&Event::Input(InputEvent::Esc));
+    app.handle_event(&Event::Input(InputEvent::Char('K')));
+    assert_eq!(loader.get_channels()[2].name.as_deref(), Some("first"));
+}
+
+#[tokio::test]
+async fn space_marks_item_read() {
+    let loader = MemoryLoader::new(vec![item(1), item(2)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Space));
+    assert!(loader.get_items()[0].read);
+    assert!(!loader.get_items()[1].read);
+}
+
+#[tokio::test]
+async fn stale_load_is_discarded() {
+    let loader = MemoryLoader::new(vec![item(1), item(2)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let first = next_load_id();
+    let second = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(first, Box::new(item(1))));
+    app.handle_event(&Event::StartLoadingItem(second, Box::new(item(2))));
+    app.handle_event(&Event::LoadedItem(
+        second,
+        Ok("<p>Newer article</p>".to_string()),
+    ));
+    app.handle_event(&Event::LoadedItem(
+        first,
+        Ok("<p>Older article</p>".to_string()),
+    ));
+
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("Newer article"));
+    assert!(!screen.contains("Older article"));
+}
+
+#[tokio::test]
+async fn item_search_finds_opened_articles() {
+    let loader = MemoryLoader::new(vec![item(1), item(2), item(3)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let id = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(id, Box::new(item(3))));
+    app.handle_event(&Event::LoadedItem(
+        id,
+        Ok("<p>The borrow checker</p>".to_string()),
+    ));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Left));
+
+    // Typed keys are also sent as keyboard events, which don't move the selection.
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Search));
+    for ch in "number 2".chars() {
+        app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+        app.handle_event(&Event::Input(InputEvent::Char(ch)));
+    }
+    app.handle_event(&Event::Input(InputEvent::Enter));
+    assert_eq!(app.ui_state().selected_item.as_deref(), Some("2"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Search));
+    for ch in "BORROW".chars() {
+        app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+        app.handle_event(&Event::Input(InputEvent::Char(ch)));
+    }
+    app.handle_event(&Event::Input(InputEvent::Enter));
+    assert_eq!(app.ui_state().selected_item.as_deref(), Some("3"));
+    assert!(draw(&mut app, &mut bus).await.contains("/BORROW [1/1]"));
+}
+
+#[tokio::test]
+async fn configured_list_width_wins_over_restored() {
+    let state = UiState {
+        list_width: Some(60),
+        ..Default::default()
+    };
+
+    let bus = EventBus::new();
+    let loader = MemoryLoader::new(vec![item(1)]);
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    app.restore_ui_state(state.clone());
+    assert_eq!(app.ui_state().list_width, Some(60));
+
+    let config = AppConfig {
+        list_width: Some(40),
+        ..Default::default()
+    };
+    let loader = MemoryLoader::new(vec![item(1)]);
+    let mut app = App::new(config, bus.get_sender(), loader, 30);
+    app.restore_ui_state(state);
+    assert_eq!(app.ui_state().list_width, None);
+
+    // Width resized in the session is still saved.
+    app.handle_event(&Event::Keyboard(KeyboardEvent::GrowList));
+    assert_eq!(app.ui_state().list_width, Some(45));
+}
+
+#[tokio::test]
+async fn history_back_and_forward() {
+    let loader = MemoryLoader::new(vec![item(1), item(2)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    for (idx, text) in [(1, "First article"), (2, "Second article")] {
+        let id = next_load_id();
+        app.handle_event(&Event::StartLoadingItem(id, Box::new(item(idx))));
+        app.handle_event(&Event::LoadedItem(id, Ok(format!("<p>{text}</p>"))));
+    }
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::HistoryBack));
+    assert!(draw(&mut app, &mut bus).await.contains("First article"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::HistoryForward));
+    assert!(draw(&mut app, &mut bus).await.contains("Second article"));
+}
+
+#[tokio::test]
+async fn links_are_drawn_as_hyperlinks() {
+    for disable_hyperlinks in [false, true] {
+        let loader = MemoryLoader::new(vec![item(1)]);
+
+        let mut bus = EventBus::new();
+        let config = AppConfig {
+            disable_hyperlinks,
+            ..Default::default()
+        };
+        let mut app = App::new(config, bus.get_sender(), loader, 30);
+        wait_for_refresh(&mut app, &mut bus).await;
+
+        let id = next_load_id();
+        app.handle_event(&Event::StartLoadingItem(id, Box::new(item(1))));
+        app.handle_event(&Event::LoadedItem(
+            id,
+            Ok("<p>Read <a href=\"https://example.com/post\">the post</a>.</p>".to_string()),
+        ));
+
+        let screen = draw(&mut app, &mut bus).await;
+        let article = "\x1B]8;;https://example.com/post\x07th\x1B]8;;\x07";
+        let header = "\x1B]8;;https://memory.example/1\x07ht\x1B]8;;\x07";
+        assert_eq!(screen.contains(article), !disable_hyperlinks);
+        assert_eq!(screen.contains(header), !disable_hyperlinks);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn narrated_text_has_short_rules() {
+    let out = std::env::temp_dir().join(format!("simple-rss-{}-rule", std::process::id()));
+    let _ = std::fs::remove_file(&out);
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let config = AppConfig {
+        narration_command: Some(format!("cat > {}", out.display())),
+        ..Default::default()
+    };
+    let mut app = App::new(config, bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let id = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(id, Box::new(item(1))));
+    app.handle_event(&Event::LoadedItem(
+        id,
+        Ok(include_str!("../../tests/fixtures/article.html").to_string()),
+    ));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Listen));
+    while let Some(event) = bus.next().await {
+        app.handle_event(&event);
+        if matches!(event, Event::NarrationFinished(_)) {
+            break;
+        }
+    }
+
+    let text = std::fs::read_to_string(&out).unwrap();
+    let _ = std::fs::remove_file(&out);
+    assert!(text.ends_with("~~old~~ new.\n\n---\n\nThe end."));
+    assert!(text.lines().all(|line| line.chars().count() < 100));
+}
+
+#[tokio::test]
+async fn hyperlinks_escape_control_characters() {
+    let mut harness = Harness::new(
+        MemoryLoader::new(vec![item(1)]),
+        AppConfig::default(),
+        80,
+        20,
+    );
+    harness.wait_for_refresh().await;
+
+    let id = next_load_id();
+    harness.send(Event::StartLoadingItem(id, Box::new(item(1))));
+    harness.send(Event::LoadedItem(
+        id,
+        Ok("<p><a href=\"https://example.com/&#x1b;]0;pwned&#x7;é\">the post</a></p>".to_string()),
+    ));
+    harness.settle().await;
+
+    let screen = harness.screen();
+    assert!(screen.contains("\x1B]8;;https://example.com/%1B]0;pwned%07%C3%A9\x07th"));
+    assert!(!screen.contains("\x1B]0;"));
+}
+
+#[tokio::test]
+async fn terminal_browser_is_left_to_embedder() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let config = AppConfig {
+        open_command: Some("w3m {url}".to_string()),
+        terminal_browser: true,
+        ..Default::default()
+    };
+    let mut app = App::new(config, bus.get_sender(), loader.clone(), 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Open));
+    while let Some(event) = bus.next().await {
+        if let Event::OpenInTerminal(url) = event {
+            assert_eq!(url, "https://memory.example/1");
+            break;
+        }
+    }
+    assert!(loader.get_items()[0].read);
+}
+
+#[tokio::test]
+async fn enter_expands_collapsed_section() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let id = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(id, Box::new(item(1))));
+    app.handle_event(&Event::LoadedItem(
+        id,
+        Ok(
+            "<details><summary>Changes</summary><p>Hidden text</p></details><p>After</p>"
+                .to_string(),
+        ),
+    ));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Right));
+
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("▸ Changes"));
+    assert!(!screen.contains("Hidden text"));
+    assert!(screen.contains("After"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Enter));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("▾ Changes"));
+    assert!(screen.contains("Hidden text"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Enter));
+    assert!(!draw(&mut app, &mut bus).await.contains("Hidden text"));
+}
+
+#[tokio::test]
+async fn space_selects_section_to_toggle() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let id = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(id, Box::new(item(1))));
+    app.handle_event(&Event::LoadedItem(
+        id,
+        Ok(concat!(
+            "<details><summary>First</summary><p>First text</p></details>",
+            "<details><summary>Second</summary><p>Second text</p></details>",
+        )
+        .to_string()),
+    ));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Right));
+    assert!(draw(&mut app, &mut bus).await.contains("▸ Second"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Space));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Enter));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("▸ First"));
+    assert!(screen.contains("▾ Second"));
+    assert!(screen.contains("Second text"));
+    assert!(!screen.contains("First text"));
+
+    // Cursor wraps around to the first section.
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Space));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Enter));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("▾ First"));
+    assert!(screen.contains("First text"));
+    assert!(screen.contains("Second text"));
+}
+
+#[tokio::test]
+async fn toc_jumps_to_heading() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    let paragraphs = (1..=10)
+        .map(|idx| format!("<p>Paragraph {idx}</p>"))
+        .collect::<String>();
+    let html = ["Alpha", "Beta", "Gamma"]
+        .iter()
+        .map(|title| format!("<h2>{title} section</h2>{paragraphs}"))
+        .collect::<String>();
+    let id = next_load_id();
+    app.handle_event(&Event::StartLoadingItem(id, Box::new(item(1))));
+    app.handle_event(&Event::LoadedItem(id, Ok(html)));
+    assert!(draw(&mut app, &mut bus).await.contains("Alpha section"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Toc));
+    // Input event of the same key press.
+    app.handle_event(&Event::Input(InputEvent::Char('t')));
+    app.handle_event(&Event::Input(InputEvent::Down));
+    app.handle_event(&Event::Input(InputEvent::Down));
+    app.handle_event(&Event::Input(InputEvent::Enter));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("Gamma section"));
+    assert!(!screen.contains("Beta section"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::PreviousHeading));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("Beta section"));
+    assert!(!screen.contains("Alpha section"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::NextHeading));
+    assert!(!draw(&mut app, &mut bus).await.contains("Beta section"));
+}
+
+#[tokio::test]
+async fn large_list_scrolls_to_selection() {
+    let loader = MemoryLoader::new((1..=5000).map(item).collect());
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    for _ in 0..2500 {
+        app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+    }
+
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(screen.contains("Item number 2500"));
+    assert!(!screen.contains("Item number 1 "));
+}
+
+#[tokio::test]
+async fn help_scrolls_when_it_does_not_fit() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Help));
+    let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen_top = screen(&terminal);
+    assert!(screen_top.contains("General"));
+    assert!(screen_top.contains("Scroll"));
+    assert!(!screen_top.contains("Set mark"));
+
+    for _ in 0..50 {
+        app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+    }
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let screen_bottom = screen(&terminal);
+    assert!(screen_bottom.contains("Set mark"));
+    assert!(!screen_bottom.contains("Go Back"));
+}
+
+#[tokio::test]
+async fn tiny_terminal_shows_it_is_too_small() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+    app.handle_event(&Event::Toast(ToastEvent::Info("Saved".to_string())));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Help));
+
+    // No size may panic, however small.
+    for width in 0..40 {
+        for height in 0..12 {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| app.draw(f)).unwrap();
+        }
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(20, 4)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    let small = screen(&terminal);
+    assert!(small.contains("Terminal too small"));
+    assert!(small.contains("20x4, needs 24x6"));
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Help));
+    let screen = draw(&mut app, &mut bus).await;
+    assert!(!screen.contains("Terminal too small"));
+    assert!(screen.contains("Item number 1"));
+}
+
+#[tokio::test]
+async fn resize_redraws_everything() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader, 30);
+    wait_for_refresh(&mut app, &mut bus).await;
+    draw(&mut app, &mut bus).await;
+    assert!(!app.is_dirty());
+
+    assert!(app.handle_event(&Event::Resize(40, 10)).is_handled());
+    assert!(app.is_dirty());
+    let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+    terminal.draw(|f| app.draw(f)).unwrap();
+    assert!(screen(&terminal).contains("Item number 1"));
+}
+
+/// Ids of the items selected by pressing the key repeatedly.
+fn selections(harness: &mut Harness, key: KeyboardEvent, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            harness.key(key);
+            harness.app().selected_item().unwrap().id
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn items_are_grouped_by_channel() {
+    let mut items: Vec<_> = (1..=3).map(item).collect();
+    items[1].channel_name = "Other".to_string();
+    let config = AppConfig {
+        grouping: Grouping::Channel,
+        ..Default::default()
+    };
+    let mut harness = Harness::new(MemoryLoader::new(items), config, 120, 20);
+    harness.wait_for_refresh().await;
+
+    let screen = harness.screen();
+    let pos = |text: &str| screen.find(text).unwrap();
+    assert!(pos("Example ─") < pos("Item number 1"));
+    assert!(pos("Item number 1") < pos("Item number 3"));
+    assert!(pos("Item number 3") < pos("Other ─"));
+    assert!(pos("Other ─") < pos("Item number 2"));
+
+    // Selection moves in the shown order and skips the headers.
+    let down = selections(&mut harness, KeyboardEvent::Down, 4);
+    assert_eq!(down, ["1", "3", "2", "2"]);
+    let up = selections(&mut harness, KeyboardEvent::Up, 3);
+    assert_eq!(up, ["3", "1", "1"]);
+}
+
+#[tokio::test]
+async fn items_are_grouped_by_day() {
+    let now = Local::now().fixed_offset();
+    let mut items: Vec<_> = (1..=4).map(item).collect();
+    items[1].pub_date = Some(now - TimeDelta::days(1));
+    items[2].pub_date = Some(now);
+    items[3].pub_date = Some(now - TimeDelta::days(10));
+    let config = AppConfig {
+        grouping: Grouping::Day,
+        ..Default::default()
+    };
+    let mut harness = Harness::new(MemoryLoader::new(items), config, 120, 30);
+    harness.wait_for_refresh().await;
+
+    let screen = harness.screen();
+    let pos = |text: &str| screen.find(text).unwrap();
+    let older = (now - TimeDelta::days(10)).format("%Y-%m-%d").to_string();
+    assert!(pos("Today ─") < pos("Item number 3"));
+    assert!(pos("Item number 3") < pos("Yesterday ─"));
+    assert!(pos("Yesterday ─") < pos("Item number 2"));
+    assert!(pos("Item number 2") < pos(&format!("{older} ─")));
+    assert!(pos(&format!("{older} ─")) < pos("Item number 4"));
+    assert!(pos("Item number 4") < pos("No date ─"));
+    assert!(pos("No date ─") < pos("Item number 1"));
+
+    harness.app().select_next_unread();
+    assert_eq!(harness.app().selected_item().unwrap().id, "3");
+    harness.app().select_next_unread();
+    assert_eq!(harness.app().selected_item().unwrap().id, "2");
+}
+
+#[tokio::test]
+async fn hidden_items_are_not_shown() {
+    let mut items: Vec<_> = (1..=4).map(item).collect();
+    // Snooze ended, so the item is shown again.
+    items[3].hidden = Some(Hidden::Until(Utc::now() - TimeDelta::hours(1)));
+    let loader = MemoryLoader::new(items);
+    let mut harness = Harness::new(loader.clone(), AppConfig::default(), 120, 30);
+    harness.wait_for_refresh().await;
+    assert!(harness.screen().contains("Item number 4"));
+
+    // Next item is selected after hiding.
+    selections(&mut harness, KeyboardEvent::Down, 2);
+    harness.key(KeyboardEvent::Hide);
+    assert_eq!(harness.app().selected_item().unwrap().id, "3");
+    assert!(!harness.screen().contains("Item number 2"));
+    assert_eq!(loader.get_items()[1].hidden, Some(Hidden::Always));
+
+    harness.key(KeyboardEvent::Snooze);
+    assert_eq!(harness.app().selected_item().unwrap().id, "4");
+    assert!(!harness.screen().contains("Item number 3"));
+    assert!(matches!(
+        loader.get_items()[2].hidden,
+        Some(Hidden::Until(until)) if until > Utc::now() + TimeDelta::hours(23)
+    ));
+
+    let up = selections(&mut harness, KeyboardEvent::Up, 2);
+    assert_eq!(up, ["1", "1"]);
+
+    // Undo shows the item again and selects it.
+    harness.key(KeyboardEvent::Undo);
+    assert_eq!(harness.app().selected_item().unwrap().id, "3");
+    assert!(harness.screen().contains("Item number 3"));
+    assert_eq!(loader.get_items()[2].hidden, None);
+}
+
+#[tokio::test]
+async fn hidden_items_can_be_shown_and_unhidden() {
+    let mut items: Vec<_> = (1..=3).map(item).collect();
+    items[1].hidden = Some(Hidden::Always);
+    let loader = MemoryLoader::new(items);
+    let mut harness = Harness::new(loader.clone(), AppConfig::default(), 120, 30);
+    harness.wait_for_refresh().await;
+    assert!(harness.screen().contains("2 unread / 2 items (1 hidden)"));
+
+    harness.key(KeyboardEvent::MarkAllRead);
+    assert!(harness.screen().contains("Mark 2 items as read?"));
+    // Key press is a keyboard event followed by an input event.
+    harness.key(KeyboardEvent::SearchNext);
+    harness.send(Event::Input(InputEvent::Char('n')));
+    assert!(!harness.screen().contains("Mark 2 items as read?"));
+
+    harness.key(KeyboardEvent::ShowHidden);
+    let screen = harness.screen();
+    assert!(screen.contains("Items (with hidden)"));
+    assert!(screen.contains("Item number 2"));
+
+    // Hiding a hidden item unhides it.
+    let down = selections(&mut harness, KeyboardEvent::Down, 2);
+    assert_eq!(down, ["1", "2"]);
+    harness.key(KeyboardEvent::Hide);
+    assert_eq!(loader.get_items()[1].hidden, None);
+
+    harness.key(KeyboardEvent::ShowHidden);
+    assert!(harness.screen().contains("3 unread / 3 items"));
+    assert!(harness.screen().contains("Item number 2"));
+}
+
+#[tokio::test]
+async fn star_key_toggles_starred_item() {
+    let loader = MemoryLoader::new((1..=2).map(item).collect());
+    let mut harness = Harness::new(loader.clone(), AppConfig::default(), 120, 30);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::Down);
+    harness.key(KeyboardEvent::ToggleStar);
+    assert!(loader.get_items()[0].starred);
+    assert!(!loader.get_items()[1].starred);
+    assert!(harness.screen().contains("★ Item number 1"));
+
+    harness.key(KeyboardEvent::ToggleStar);
+    assert!(!loader.get_items()[0].starred);
+    assert!(!harness.screen().contains('★'));
+}
+
+#[tokio::test]
+async fn endless_snooze_hides_item() {
+    let loader = MemoryLoader::new((1..=2).map(item).collect());
+    let config = AppConfig {
+        snooze: Some(TimeDelta::MAX),
+        ..Default::default()
+    };
+    let mut harness = Harness::new(loader.clone(), config, 120, 30);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::Down);
+    harness.key(KeyboardEvent::Snooze);
+    assert_eq!(loader.get_items()[0].hidden, Some(Hidden::Always));
+}
+
+#[tokio::test]
+async fn refresh_keeps_hidden_items_hidden() {
+    let loader = MemoryLoader::new((1..=2).map(item).collect());
+    loader.push_refresh((1..=3).map(item).collect());
+    let mut harness = Harness::new(loader.clone(), AppConfig::default(), 120, 30);
+
+    harness.key(KeyboardEvent::Down);
+    harness.key(KeyboardEvent::Hide);
+    harness.wait_for_refresh().await;
+
+    assert!(harness.screen().contains("Item number 3"));
+    assert!(!harness.screen().contains("Item number 1"));
+}
+
+/// Config which opens links with [`Event::OpenInTerminal`], so that no browser is started.
+fn terminal_browser() -> AppConfig {
+    AppConfig {
+        open_command: Some("true".to_string()),
+        terminal_browser: true,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn open_all_opens_shown_items() {
+    let mut items: Vec<_> = (1..=3).map(item).collect();
+    items[1].hidden = Some(Hidden::Always);
+    let loader = MemoryLoader::new(items);
+    let mut harness = Harness::new(loader.clone(), terminal_browser(), 120, 30);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::OpenAll);
+    let mut opened = vec![];
+    for _ in 0..2 {
+        if let Event::OpenInTerminal(url) = harness
+            .wait_for(|event| matches!(event, Event::OpenInTerminal(_)))
+            .await
+        {
+            opened.push(url);
+        }
+    }
+    assert_eq!(
+        opened,
+        ["https://memory.example/1", "https://memory.example/3"]
+    );
+
+    let read: Vec<_> = loader.get_items().iter().map(|it| it.read).collect();
+    assert_eq!(read, [true, false, true]);
+}
+
+#[tokio::test]
+async fn open_all_asks_before_opening_many_items() {
+    let loader = MemoryLoader::new((1..=11).map(item).collect());
+    let mut harness = Harness::new(loader.clone(), terminal_browser(), 120, 30);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::OpenAll);
+    assert!(harness.screen().contains("Open 11 items in browser?"));
+    // Key press is a keyboard event followed by an input event.
+    harness.key(KeyboardEvent::SearchNext);
+    harness.send(Event::Input(InputEvent::Char('n')));
+    assert!(!harness.screen().contains("Open 11 items in browser?"));
+    assert!(loader.get_items().iter().all(|it| !it.read));
+
+    harness.key(KeyboardEvent::OpenAll);
+    harness.key(KeyboardEvent::CopyLink);
+    harness.send(Event::Input(InputEvent::Char('y')));
+    assert!(loader.get_items().iter().all(|it| it.read));
+}
+
+#[tokio::test]
+async fn open_all_opens_at_most_first_items() {
+    let loader = MemoryLoader::new((1..=35).map(item).collect());
+    let mut harness = Harness::new(loader.clone(), terminal_browser(), 120, 30);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::OpenAll);
+    assert!(harness.screen().contains("Open 30 items in browser?"));
+    harness.key(KeyboardEvent::CopyLink);
+    harness.send(Event::Input(InputEvent::Char('y')));
+
+    let read = loader.get_items().iter().filter(|it| it.read).count();
+    assert_eq!(read, 30);
+    let toast = harness
+        .wait_for(|event| matches!(event, Event::Toast(_)))
+        .await;
+    assert_eq!(
+        toast,
+        Event::Toast(ToastEvent::Info(
+            "Opened the first 30 of 35 items".to_string()
+        ))
+    );
+}
+
+/// Content loader separate from the store, greeting with the item's link.
+#[derive(Clone)]
+struct Greeting;
+
+impl ContentLoader for Greeting {
+    async fn load_item(&self, item: &Item) -> simple_rss_lib::Result<String> {
+        Ok(format!("<p>Hello from {}</p>", item.link))
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        _dir: &Path,
+        _progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> simple_rss_lib::Result<PathBuf> {
+        Err(Error::Io(format!("Greeting can't download {}", item.title)))
+    }
+}
+
+#[tokio::test]
+async fn app_loads_content_with_separate_loader() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+
+    let mut bus = EventBus::new();
+    let mut app = App::with_parts(
+        AppConfig::default(),
+        bus.get_sender(),
+        loader.clone(),
+        loader,
+        Greeting,
+        30,
+    );
+    while let Some(event) = bus.next().await {
+        app.handle_event(&event);
+        if matches!(event, Event::RefreshFinished { .. }) {
+            break;
+        }
+    }
+
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Down));
+    app.handle_event(&Event::Keyboard(KeyboardEvent::Enter));
+    let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+    while !screen(&terminal).contains("Hello from") {
+        let event = tokio::time::timeout(Duration::from_secs(5), bus.next())
+            .await
+            .expect("content wasn't loaded")
+            .unwrap();
+        app.handle_event(&event);
+        terminal.draw(|f| app.draw(f)).unwrap();
+    }
+    assert!(screen(&terminal).contains("Hello from https://memory.example/1"));
+}
@@ -0,0 +1,95 @@
+use ratatui::{Terminal, backend::TestBackend};
+use simple_rss_lib::{
+    components::Confirm,
+    event::{Event, EventState, InputEvent},
+};
+
+fn send(confirm: &mut Confirm<u32>, event: InputEvent) -> EventState {
+    confirm.handle_event(&Event::Input(event))
+}
+
+#[test]
+fn answers_confirm_or_cancel() {
+    let mut confirm = Confirm::new();
+    assert_eq!(
+        send(&mut confirm, InputEvent::Char('y')),
+        EventState::Ignored
+    );
+    assert_eq!(confirm.take_confirmed(), None);
+
+    for answer in [
+        InputEvent::Char('y'),
+        InputEvent::Char('Y'),
+        InputEvent::Enter,
+    ] {
+        confirm.open("Delete?", 1);
+        assert_eq!(send(&mut confirm, answer), EventState::Handled);
+        assert!(!confirm.is_open());
+        assert_eq!(confirm.take_confirmed(), Some(1));
+        assert_eq!(confirm.take_confirmed(), None);
+    }
+
+    for answer in [
+        InputEvent::Char('n'),
+        InputEvent::Char('N'),
+        InputEvent::Esc,
+    ] {
+        confirm.open("Delete?", 2);
+        assert_eq!(send(&mut confirm, answer), EventState::Handled);
+        assert!(!confirm.is_open());
+        assert_eq!(confirm.take_confirmed(), None);
+    }
+}
+
+#[test]
+fn other_keys_are_swallowed_while_open() {
+    let mut confirm = Confirm::new();
+    confirm.open("Open 12 items?", 1);
+    // Question asked again replaces the pending action.
+    confirm.open("Open 15 items?", 2);
+
+    assert_eq!(
+        send(&mut confirm, InputEvent::Char('j')),
+        EventState::Handled
+    );
+    assert_eq!(send(&mut confirm, InputEvent::Down), EventState::Handled);
+    assert_eq!(confirm.handle_event(&Event::Tick), EventState::Ignored);
+    assert!(confirm.is_open());
+
+    send(&mut confirm, InputEvent::Enter);
+    assert_eq!(confirm.take_confirmed(), Some(2));
+}
+
+/// Text of the screen, without the styles.
+fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+        .collect()
+}
+
+#[test]
+fn question_is_drawn_while_open() {
+    let mut confirm = Confirm::new();
+    let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+
+    terminal.draw(|f| confirm.draw(f)).unwrap();
+    let empty = " ".repeat(40);
+    assert_eq!(screen(&terminal), vec![empty.clone(); 6]);
+
+    confirm.open("Open 12 items?", 1);
+    terminal.draw(|f| confirm.draw(f)).unwrap();
+    assert_eq!(
+        screen(&terminal),
+        [
+            empty.as_str(),
+            "     ╭Confirm─────────────────────╮     ",
+            "     │       Open 12 items?       │     ",
+            "     │      <y> Yes   <n> No      │     ",
+            "     ╰────────────────────────────╯     ",
+            empty.as_str(),
+        ]
+    );
+}
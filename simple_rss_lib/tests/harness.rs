@@ -0,0 +1,71 @@
+use std::{env, fs, panic};
+
+use simple_rss_lib::{
+    app::AppConfig,
+    event::{Event, KeyboardEvent},
+    testing::{Harness, MemoryLoader, item},
+};
+
+#[tokio::test]
+async fn opened_article_is_drawn_once_settled() {
+    let loader = MemoryLoader::new(vec![item(1), item(2)]);
+    loader.set_content(
+        "https://memory.example/1",
+        Ok("<p>Article from the harness</p>".to_string()),
+    );
+    let mut harness = Harness::new(loader, AppConfig::default(), 100, 20);
+    assert!(harness.wait_for_refresh().await.is_empty());
+
+    harness.key(KeyboardEvent::Down);
+    harness.key(KeyboardEvent::Enter);
+    harness
+        .wait_for(|event| matches!(event, Event::LoadedItem(..)))
+        .await;
+    harness.settle().await;
+    assert!(harness.screen().contains("Article from the harness"));
+
+    harness.key(KeyboardEvent::Search);
+    harness.type_text("number 2");
+    assert!(harness.screen().contains("number 2"));
+}
+
+#[tokio::test]
+async fn help_matches_snapshot() {
+    let loader = MemoryLoader::new(vec![item(1), item(2)]);
+    let mut harness = Harness::new(loader, AppConfig::default(), 80, 16);
+    harness.wait_for_refresh().await;
+
+    harness.key(KeyboardEvent::Help);
+    harness.assert_snapshot("tests/snapshots/help.txt");
+
+    harness.resize(40, 12);
+    harness.assert_snapshot("tests/snapshots/help_narrow.txt");
+}
+
+#[tokio::test]
+async fn changed_screen_fails_snapshot() {
+    let path = env::temp_dir().join(format!("simple-rss-snapshot-{}.txt", std::process::id()));
+    fs::write(&path, "Something else\n").unwrap();
+
+    let mut harness = Harness::new(
+        MemoryLoader::new(vec![item(1)]),
+        AppConfig::default(),
+        40,
+        8,
+    );
+    harness.wait_for_refresh().await;
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| harness.assert_snapshot(&path)));
+    let _ = fs::remove_file(&path);
+
+    let message = res.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("Something else"));
+    assert!(message.contains("Item number 1"));
+
+    // Missing snapshot is not written without UPDATE_SNAPSHOTS.
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| harness.assert_snapshot(&path)));
+    let message = res.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("UPDATE_SNAPSHOTS=1"));
+    assert!(!path.exists());
+}
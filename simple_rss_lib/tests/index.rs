@@ -0,0 +1,56 @@
+use simple_rss_lib::{data::Item, index::SearchIndex, testing};
+
+fn item(title: &str, description: &str) -> Item {
+    Item {
+        id: title.to_string(),
+        title: title.to_string(),
+        description: Some(description.to_string()),
+        ..testing::item(0)
+    }
+}
+
+fn search(index: &SearchIndex, query: &str) -> Vec<usize> {
+    index.search(query).iter().map(|hit| hit.item).collect()
+}
+
+#[test]
+fn title_matches_rank_first() {
+    let index = SearchIndex::new(&[
+        item("Weekly news", "<p>New <b>Rust</b> release is out</p>"),
+        item("Rust 2024 edition", "<p>What changed</p>"),
+        item("Gardening", "<p>Tomatoes</p>"),
+    ]);
+
+    assert_eq!(search(&index, "rust"), vec![1, 0]);
+    assert_eq!(search(&index, "RUST"), vec![1, 0]);
+    assert!(search(&index, "python").is_empty());
+}
+
+#[test]
+fn all_words_must_match() {
+    let index = SearchIndex::new(&[
+        item("Rust release", ""),
+        item("Rust edition", ""),
+        item("Python release", ""),
+    ]);
+
+    assert_eq!(search(&index, "rust release"), vec![0]);
+    assert_eq!(search(&index, "release"), vec![0, 2]);
+}
+
+#[test]
+fn prefixes_match_after_exact_words() {
+    let index = SearchIndex::new(&[item("Rustacean station", ""), item("Rust news", "")]);
+
+    assert_eq!(search(&index, "rust"), vec![1, 0]);
+    assert_eq!(search(&index, "rusta"), vec![0]);
+}
+
+#[test]
+fn added_text_is_searched() {
+    let mut index = SearchIndex::new(&[item("First", ""), item("Second", "")]);
+    assert!(search(&index, "borrow").is_empty());
+
+    index.add_html(1, "<article><p>The borrow checker</p></article>");
+    assert_eq!(search(&index, "borrow checker"), vec![1]);
+}
@@ -0,0 +1,144 @@
+use ratatui::{Terminal, backend::TestBackend};
+use simple_rss_lib::{
+    components::input::{Input, InputState},
+    event::{Event, InputEvent, Modifiers},
+};
+
+fn send(input: &mut Input, event: InputEvent) -> InputState {
+    input.handle_event(&Event::Input(event))
+}
+
+fn ctrl(ch: char) -> InputEvent {
+    InputEvent::Shortcut(
+        ch,
+        Modifiers {
+            ctrl: true,
+            alt: false,
+        },
+    )
+}
+
+#[test]
+fn shortcuts_delete_before_cursor() {
+    let mut input = Input::new();
+    input.set_value("rust  async io");
+    send(&mut input, InputEvent::Left);
+    send(&mut input, InputEvent::Left);
+    send(&mut input, InputEvent::Left);
+
+    assert_eq!(send(&mut input, ctrl('w')), InputState::Changed);
+    assert_eq!(input.value(), "rust   io");
+    assert_eq!(send(&mut input, ctrl('w')), InputState::Changed);
+    assert_eq!(input.value(), " io");
+    assert_eq!(send(&mut input, ctrl('w')), InputState::Moved);
+
+    input.set_value("čaj s mlekom");
+    send(&mut input, InputEvent::Left);
+    assert_eq!(send(&mut input, ctrl('u')), InputState::Changed);
+    assert_eq!(input.value(), "m");
+
+    // Other shortcuts don't type anything.
+    assert_eq!(send(&mut input, ctrl('d')), InputState::Ignored);
+    assert_eq!(input.value(), "m");
+}
+
+fn type_text(input: &mut Input, text: &str) {
+    for ch in text.chars() {
+        send(input, InputEvent::Char(ch));
+    }
+}
+
+#[test]
+fn characters_are_edited_at_cursor() {
+    let mut input = Input::new();
+    type_text(&mut input, "žaba");
+    assert_eq!(input.line("/").to_string(), "/žaba▏");
+
+    send(&mut input, InputEvent::Home);
+    assert_eq!(send(&mut input, InputEvent::Delete), InputState::Changed);
+    assert_eq!(send(&mut input, InputEvent::Backspace), InputState::Moved);
+    type_text(&mut input, "ž");
+    assert_eq!(input.value(), "žaba");
+    assert_eq!(input.line("").to_string(), "ž▏aba");
+
+    send(&mut input, InputEvent::Right);
+    send(&mut input, InputEvent::Right);
+    type_text(&mut input, "m");
+    assert_eq!(input.value(), "žabma");
+    assert_eq!(send(&mut input, InputEvent::Backspace), InputState::Changed);
+
+    // The cursor stays within the value.
+    send(&mut input, InputEvent::End);
+    assert_eq!(send(&mut input, InputEvent::Right), InputState::Moved);
+    assert_eq!(send(&mut input, InputEvent::Delete), InputState::Moved);
+    assert_eq!(input.line("").to_string(), "žaba▏");
+
+    assert_eq!(
+        input.handle_event(&Event::Tick),
+        InputState::Ignored,
+        "only input events are handled"
+    );
+}
+
+#[test]
+fn submitted_values_are_kept_in_history() {
+    let mut input = Input::new();
+    assert_eq!(send(&mut input, InputEvent::Up), InputState::Moved);
+
+    for value in ["first", "second", "second", ""] {
+        input.set_value(value);
+        assert_eq!(send(&mut input, InputEvent::Enter), InputState::Submitted);
+    }
+    // Empty values and repeats of the last value are not remembered.
+    assert_eq!(input.history(), ["first", "second"]);
+
+    input.set_value("draft");
+    send(&mut input, InputEvent::Up);
+    assert_eq!(input.value(), "second");
+    send(&mut input, InputEvent::Up);
+    send(&mut input, InputEvent::Up);
+    assert_eq!(input.value(), "first");
+    send(&mut input, InputEvent::Down);
+    assert_eq!(input.value(), "second");
+    send(&mut input, InputEvent::Down);
+    assert_eq!(input.value(), "draft");
+
+    // Editing a value from history starts a new draft.
+    send(&mut input, InputEvent::Up);
+    type_text(&mut input, "!");
+    assert_eq!(send(&mut input, InputEvent::Down), InputState::Moved);
+    assert_eq!(input.value(), "second!");
+
+    assert_eq!(send(&mut input, InputEvent::Esc), InputState::Cancelled);
+    input.clear();
+    assert_eq!(input.value(), "");
+    assert_eq!(input.history(), ["first", "second"]);
+}
+
+#[test]
+fn history_is_limited() {
+    let mut input = Input::new();
+    for idx in 0..60 {
+        input.set_value(idx.to_string());
+        send(&mut input, InputEvent::Enter);
+    }
+
+    assert_eq!(input.history().len(), 50);
+    assert_eq!(input.history()[0], "10");
+    assert_eq!(input.history()[49], "59");
+}
+
+#[test]
+fn draw_scrolls_to_cursor() {
+    let mut input = Input::new();
+    input.set_value("0123456789");
+    let backend = TestBackend::new(6, 1);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    terminal.draw(|f| input.draw(f, f.area())).unwrap();
+    terminal.backend().assert_buffer_lines(["56789▏"]);
+
+    send(&mut input, InputEvent::Home);
+    terminal.draw(|f| input.draw(f, f.area())).unwrap();
+    terminal.backend().assert_buffer_lines(["▏01234"]);
+}
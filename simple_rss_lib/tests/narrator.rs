@@ -0,0 +1,114 @@
+#![cfg(unix)]
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use simple_rss_lib::{
+    components::narrator::Narrator,
+    event::{Event, EventBus, EventState, KeyboardEvent, ToastEvent},
+};
+use tokio::time::timeout;
+
+/// File in the temporary directory, unique to the test.
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("simple-rss-{}-{name}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+async fn next(bus: &mut EventBus) -> Event {
+    timeout(Duration::from_secs(5), bus.next())
+        .await
+        .expect("no event in 5 seconds")
+        .unwrap()
+}
+
+/// Wait for the narration to finish and pass it to the narrator, like the app does.
+async fn finish(bus: &mut EventBus, narrator: &mut Narrator) -> u64 {
+    loop {
+        if let Event::NarrationFinished(id) = next(bus).await {
+            narrator.handle_event(&Event::NarrationFinished(id));
+            return id;
+        }
+    }
+}
+
+#[tokio::test]
+async fn articles_are_narrated_in_order() {
+    let out = temp_file("narrated");
+    let mut bus = EventBus::new();
+    let command = format!("cat >> {}", out.display());
+    let mut narrator = Narrator::new(Some(command), bus.get_sender());
+    assert!(narrator.enabled());
+
+    narrator.enqueue("first\n".to_string());
+    narrator.enqueue("second\n".to_string());
+    assert_eq!(
+        next(&mut bus).await,
+        Event::Toast(ToastEvent::Loading("Narrating".to_string()))
+    );
+    assert_eq!(
+        next(&mut bus).await,
+        Event::Toast(ToastEvent::Loading("Narrating (1 queued)".to_string()))
+    );
+
+    assert_eq!(finish(&mut bus, &mut narrator).await, 0);
+    assert_eq!(
+        next(&mut bus).await,
+        Event::Toast(ToastEvent::Loading("Narrating".to_string()))
+    );
+    assert_eq!(finish(&mut bus, &mut narrator).await, 1);
+    assert_eq!(next(&mut bus).await, Event::Toast(ToastEvent::Hide));
+
+    assert_eq!(fs::read_to_string(&out).unwrap(), "first\nsecond\n");
+    let _ = fs::remove_file(&out);
+}
+
+#[tokio::test]
+async fn stop_clears_the_queue() {
+    let out = temp_file("stopped");
+    let mut bus = EventBus::new();
+    let command = format!("sleep 5; cat >> {}", out.display());
+    let mut narrator = Narrator::new(Some(command), bus.get_sender());
+
+    narrator.enqueue("first".to_string());
+    narrator.enqueue("second".to_string());
+    let stop = Event::Keyboard(KeyboardEvent::NarrationStop);
+    assert_eq!(narrator.handle_event(&stop), EventState::Handled);
+
+    // The stopped narration finishes right away and nothing else is played.
+    let mut events = vec![];
+    loop {
+        match next(&mut bus).await {
+            Event::NarrationFinished(id) => {
+                narrator.handle_event(&Event::NarrationFinished(id));
+                break;
+            }
+            event => events.push(event),
+        }
+    }
+    assert_eq!(events.last(), Some(&Event::Toast(ToastEvent::Hide)));
+    assert!(
+        timeout(Duration::from_millis(200), bus.next())
+            .await
+            .is_err()
+    );
+    assert!(!out.exists());
+}
+
+#[tokio::test]
+async fn keys_are_ignored_without_command() {
+    let bus = EventBus::new();
+    let mut narrator = Narrator::new(None, bus.get_sender());
+    assert!(!narrator.enabled());
+
+    for key in [
+        KeyboardEvent::NarrationNext,
+        KeyboardEvent::NarrationPause,
+        KeyboardEvent::NarrationStop,
+    ] {
+        assert_eq!(
+            narrator.handle_event(&Event::Keyboard(key)),
+            EventState::Ignored
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer, layout::Size};
+use simple_rss_lib::{
+    app::{App, AppConfig, UiState},
+    data::{Auth, Channel},
+    event::{Event, EventBus, KeyboardEvent},
+    record::{Recorder, Replay},
+    testing::{MemoryLoader, item, screen},
+};
+
+/// Run the app like an embedder does, recording the events, until the opened item is
+/// rendered. Returns the recording and the screen at the end.
+async fn record_session() -> (Vec<u8>, Buffer) {
+    let loader = MemoryLoader::new(vec![item(1), item(2), item(3)]);
+    loader.set_content(
+        "https://memory.example/2",
+        Ok("<p>Recorded article</p>".to_string()),
+    );
+    loader.push_refresh(vec![item(1), item(2), item(3), item(4)]);
+
+    let mut bus = EventBus::new();
+    let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let mut recorder =
+        Recorder::new(vec![], Size::new(100, 20), 30, &loader, &UiState::default()).unwrap();
+    let mut app = App::new(AppConfig::default(), bus.get_sender(), loader.clone(), 30);
+    terminal.draw(|f| app.draw(f)).unwrap();
+
+    let sender = bus.get_sender();
+    while let Some(event) = bus.next().await {
+        recorder.record(&event, &loader).unwrap();
+        app.handle_event(&event);
+        if app.is_dirty() {
+            terminal.draw(|f| app.draw(f)).unwrap();
+        }
+
+        match event {
+            Event::RefreshFinished { .. } => {
+                sender.send(Event::Keyboard(KeyboardEvent::Down));
+                sender.send(Event::Keyboard(KeyboardEvent::Down));
+                sender.send(Event::Keyboard(KeyboardEvent::Enter));
+            }
+            Event::RenderedItem { .. } => break,
+            _ => (),
+        }
+    }
+
+    let buffer = terminal.backend().buffer().clone();
+    (recorder.into_inner(), buffer)
+}
+
+#[tokio::test]
+async fn replay_reproduces_the_session() {
+    let (recording, recorded) = record_session().await;
+    let screen_after = screen(&recorded);
+    assert!(screen_after.contains("Item number 4"));
+    assert!(screen_after.contains("Recorded article"));
+
+    let replay = Replay::read(&recording[..]).unwrap();
+    let replayed = replay.run(AppConfig::default()).await;
+    assert_eq!(screen(&replayed), screen_after);
+}
+
+#[tokio::test]
+async fn truncated_replay_stops_early() {
+    let (recording, _) = record_session().await;
+
+    let mut replay = Replay::read(&recording[..]).unwrap();
+    let events = replay.len();
+    // Without the loaded content and its rendering.
+    replay.truncate(events - 2);
+    let screen = screen(&replay.run(AppConfig::default()).await);
+    assert!(screen.contains("Item number 4"));
+    assert!(!screen.contains("Recorded article"));
+}
+
+#[test]
+fn invalid_recording_is_rejected() {
+    let err = Replay::read(&b"{\"event\":{\"at\":0,\"event\":\"Tick\"}}\n"[..]).unwrap_err();
+    assert!(err.to_string().contains("doesn't start"));
+
+    let err = Replay::read(&b""[..]).unwrap_err();
+    assert!(err.to_string().contains("empty"));
+}
+
+#[test]
+fn secrets_and_ticks_are_not_recorded() {
+    let loader = MemoryLoader::new(vec![item(1)]);
+    loader.set_channels(vec![Channel {
+        url: "https://record.example/feed.xml".to_string(),
+        auth: Some(Auth::Bearer {
+            token: "secret-token".to_string(),
+        }),
+        headers: [("X-Api-Key".to_string(), "secret-key".to_string())].into(),
+        ..Default::default()
+    }]);
+
+    let mut recorder =
+        Recorder::new(vec![], Size::new(80, 20), 30, &loader, &UiState::default()).unwrap();
+    for _ in 0..3 {
+        recorder.record(&Event::Tick, &loader).unwrap();
+    }
+    let lines = String::from_utf8(recorder.into_inner()).unwrap();
+    // Ticks are written with the next event.
+    assert_eq!(lines.lines().count(), 1);
+    assert!(lines.contains("https://record.example/feed.xml"));
+    assert!(!lines.contains("secret"));
+
+    let mut recorder =
+        Recorder::new(vec![], Size::new(80, 20), 30, &loader, &UiState::default()).unwrap();
+    for _ in 0..3 {
+        recorder.record(&Event::Tick, &loader).unwrap();
+    }
+    recorder
+        .record(&Event::Keyboard(KeyboardEvent::Down), &loader)
+        .unwrap();
+    let lines = String::from_utf8(recorder.into_inner()).unwrap();
+    assert_eq!(lines.lines().count(), 3);
+    assert!(lines.contains("\"count\":3"));
+    assert_eq!(Replay::read(lines.as_bytes()).unwrap().len(), 2);
+}
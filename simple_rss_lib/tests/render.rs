@@ -0,0 +1,271 @@
+use ratatui::style::Modifier;
+use simple_rss_lib::render::{
+    LinkFormat, MarkdownBackend, RenderOptions, render_document, render_to_lines, render_to_string,
+    render_with,
+};
+use unicode_width::UnicodeWidthStr;
+
+fn plain() -> RenderOptions {
+    RenderOptions {
+        colorize: false,
+        ..Default::default()
+    }
+}
+
+fn render_text(html: &str, width: usize) -> Vec<String> {
+    render_to_lines(html, width, &plain())
+        .iter()
+        .map(|line| line.to_string().trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn nested_lists_have_hanging_indent() {
+    let html = "<ol>\
+        <li>First item, long enough to wrap\
+            <ul><li>Nested bullet, long enough to wrap\
+                <ol><li>Deep item, long enough to wrap</li></ol>\
+            </li></ul>\
+        </li>\
+        <li>Second</li>\
+    </ol>";
+
+    assert_eq!(
+        render_text(html, 24),
+        [
+            "  1. First item, long",
+            "     enough to wrap",
+            "     - Nested bullet,",
+            "       long enough to",
+            "       wrap",
+            "       1. Deep item,",
+            "          long enough to",
+            "          wrap",
+            "  2. Second",
+        ]
+    );
+}
+
+#[test]
+fn wide_characters_wrap_by_display_width() {
+    let html = "<p>日本語の記事はスペースなしで書かれています</p><p>Party 🎉🎉🎉 time</p>";
+    let lines = render_text(html, 13);
+    assert_eq!(
+        lines,
+        [
+            "日本語の記事",
+            "はスペースな",
+            "しで書かれて",
+            "います",
+            "",
+            "Party 🎉🎉🎉",
+            "time",
+        ]
+    );
+    assert!(lines.iter().all(|line| line.width() < 13));
+}
+
+#[test]
+fn hyphenation_breaks_long_words() {
+    let html = "<p>See Donau\u{ad}dampf\u{ad}schiff\u{ad}fahrt at https://example.com/a/very/long/path</p>";
+
+    assert_eq!(
+        render_text(html, 16),
+        [
+            "See Donaudampfs",
+            "chifffahrt at h",
+            "ttps://example.",
+            "com/a/very/long",
+            "/path",
+        ]
+    );
+
+    let hyphenated: Vec<_> = render_document(
+        html,
+        16,
+        &RenderOptions {
+            hyphenate: true,
+            ..plain()
+        },
+    )
+    .lines
+    .iter()
+    .map(|line| line.to_string().trim_end().to_string())
+    .collect();
+    // Hyphen would read as part of the URL, so it's broken without one.
+    assert_eq!(
+        hyphenated,
+        [
+            "See Donaudampf-",
+            "schifffahrt at",
+            "https://example",
+            ".com/a/very/lon",
+            "g/path",
+        ]
+    );
+}
+
+#[test]
+fn markdown_backend_escapes_text() {
+    let html = "<h2>Notes</h2>\
+        <p>Use <code>a_b*c</code> or <em>snake_case</em> [sic]<br>then <a href=\"/x\">*this*</a>.<br></p>\
+        <p># not a heading</p>\
+        <p>1. not a list</p>";
+
+    let markdown = render_with(html, usize::MAX, MarkdownBackend::new(), &plain()).into_string();
+    assert_eq!(
+        markdown,
+        "## Notes\n\n\
+        Use `a_b*c` or _snake\\_case_ \\[sic\\]\\\nthen [\\*this\\*](/x).\n\n\n\
+        \\# not a heading\n\n\
+        1\\. not a list\n"
+    );
+}
+
+#[test]
+fn options_change_links_and_layout() {
+    let html = "<p>Read <a href=\"/a\">this</a> and <a href=\"/b\">that</a>, \
+        then <strong>this</strong> <a href=\"/a\">again</a>.</p>\
+        <ul><li>Item</li></ul>";
+    let options = RenderOptions {
+        markup: false,
+        links: LinkFormat::Footnote,
+        indent: 4,
+        paragraph_spacing: 0,
+        ..plain()
+    };
+
+    assert_eq!(
+        render_to_string(html, 80, &options),
+        "Read this [1] and that [2], then this again [1].\n    - Item\n[1] /a\n[2] /b\n"
+    );
+
+    let markdown = render_with(html, usize::MAX, MarkdownBackend::new(), &options).into_string();
+    assert_eq!(
+        markdown,
+        "Read [this][1] and [that][2], then **this** [again][1].\n   - Item\n\n[1]: /a\n[2]: /b\n"
+    );
+}
+
+#[test]
+fn document_links_are_split_by_line() {
+    let html = "<p>See <a href=\"https://example.com/a\">the long link text</a> and \
+        <a href=\"/relative\">this</a>.</p>";
+    let options = RenderOptions {
+        links: LinkFormat::Hidden,
+        ..plain()
+    };
+    let document = render_document(html, 16, &options);
+
+    let lines: Vec<_> = document.lines.iter().map(|l| l.to_string()).collect();
+    assert_eq!(lines, ["See the long", "link text and ", "this."]);
+
+    // Relative links are left out, since they can't be opened.
+    let links: Vec<_> = document
+        .links
+        .iter()
+        .map(|link| {
+            let line = &document.lines[link.line];
+            let text: String = line.spans[link.spans.clone()]
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            (link.line, text, link.url.as_str())
+        })
+        .collect();
+    assert_eq!(
+        links,
+        [
+            (0, "the long".to_string(), "https://example.com/a"),
+            (1, "link text".to_string(), "https://example.com/a"),
+        ]
+    );
+}
+
+#[test]
+fn embeds_are_rendered_as_links() {
+    let html = "<p>Watch this:</p>\
+        <iframe src=\"//www.youtube.com/embed/abc123?rel=0\" width=\"560\"></iframe>\
+        <iframe src=\"https://player.vimeo.com/video/42\"></iframe>\
+        <iframe src=\"https://platform.twitter.com/embed/Tweet.html?id=7\"></iframe>\
+        <embed src=\"https://example.com/chart.svg\">\
+        <iframe></iframe>";
+
+    assert_eq!(
+        render_text(html, 80),
+        [
+            "Watch this:",
+            "",
+            "[embedded video](https://www.youtube.com/watch?v=abc123)",
+            "",
+            "[embedded video](https://vimeo.com/42)",
+            "",
+            "[embedded tweet](https://platform.twitter.com/embed/Tweet.html?id=7)",
+            "",
+            "[embedded content](https://example.com/chart.svg)",
+        ]
+    );
+}
+
+#[test]
+fn special_spaces_are_normalized() {
+    let html = "<p>It’s 10&nbsp;&nbsp;km&#x202F;away — see\u{200b}more…</p>";
+
+    assert_eq!(render_text(html, 14), ["It’s", "10 km away —", "seemore…"]);
+}
+
+#[test]
+fn escaped_entities_are_rendered_as_written() {
+    let html = "<p>Write &amp;lt;p&amp;gt; or &amp;amp;lt;</p>";
+
+    assert_eq!(render_text(html, 40), ["Write &lt;p&gt; or &amp;lt;"]);
+}
+
+#[test]
+fn inline_elements_are_styled() {
+    let html = "<p><del>old</del> <ins>added</ins> <u>under</u> <mark>note</mark> \
+        E = mc<sup>2</sup> and H<sub>2</sub>O <strong>bold</strong></p>";
+    let lines = render_to_lines(html, 80, &RenderOptions::default());
+    assert_eq!(
+        lines[0].to_string().trim_end(),
+        "~~old~~ added under note E = mc² and H₂O **bold**"
+    );
+
+    let style = |text: &str| {
+        lines[0]
+            .spans
+            .iter()
+            .find(|span| span.content == text)
+            .unwrap_or_else(|| panic!("no span {text}"))
+            .style
+            .add_modifier
+    };
+    assert_eq!(style("old"), Modifier::CROSSED_OUT);
+    assert_eq!(style("added"), Modifier::UNDERLINED);
+    assert_eq!(style("under"), Modifier::UNDERLINED);
+    assert_eq!(style("note"), Modifier::REVERSED);
+    assert_eq!(style("bold"), Modifier::BOLD);
+    assert_eq!(style("²"), Modifier::empty());
+    assert_eq!(style("E"), Modifier::empty());
+
+    // Styles of nested elements are combined.
+    let options = RenderOptions {
+        markup: false,
+        ..Default::default()
+    };
+    let lines = render_to_lines("<p><del><strong>both</strong></del></p>", 80, &options);
+    let both = lines[0].spans.iter().find(|span| span.content == "both");
+    assert_eq!(
+        both.unwrap().style.add_modifier,
+        Modifier::BOLD | Modifier::CROSSED_OUT
+    );
+
+    // Without colors the text is written plainly.
+    let lines = render_to_lines(html, 80, &plain());
+    assert!(
+        lines[0]
+            .spans
+            .iter()
+            .all(|span| span.style.add_modifier.is_empty())
+    );
+}
@@ -0,0 +1,441 @@
+use std::{collections::HashMap, env, fs, io, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::ValueEnum;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use simple_rss_lib::{
+    components::{item_list::Grouping, toast},
+    render::{LinkFormat, RenderOptions},
+};
+
+use crate::{
+    data::{download_dir, settings_path},
+    keymap::{Keymap, Profile},
+};
+
+/// User settings, read from `simple-rss.toml` in the config directory.
+/// Every setting is optional, missing file is the same as an empty one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Command that reads the article aloud. Article text is passed on stdin.
+    pub tts_command: Option<String>,
+
+    /// Directory where item attachments are downloaded.
+    pub download_dir: Option<PathBuf>,
+
+    /// Timeout of HTTP requests in seconds.
+    pub timeout: Option<u64>,
+
+    /// User-Agent header sent with all HTTP requests.
+    pub user_agent: Option<String>,
+
+    /// Minimum number of seconds between two requests to the same host.
+    pub host_interval: Option<u64>,
+
+    /// Proxy used for all HTTP requests, e.g. `socks5://localhost:1080`. Hosts listed in
+    /// `NO_PROXY` are still requested directly. If not set, `HTTP_PROXY` and `HTTPS_PROXY`
+    /// are used.
+    pub proxy: Option<String>,
+
+    /// Url of the Hacker News API, e.g. of a mirror. Lists of stories under it are read
+    /// like the official ones.
+    pub hacker_news_api: Option<String>,
+
+    /// Interval of automatic refresh in minutes. Disabled if not set.
+    pub refresh_interval: Option<u64>,
+
+    /// Maximum random delay of each channel's automatic refresh in seconds.
+    pub refresh_jitter: Option<u64>,
+
+    /// Check for a new release on startup.
+    pub check_updates: bool,
+
+    /// Items published in this many hours are marked as new. By default items published
+    /// since the previous session are marked.
+    pub new_badge_hours: Option<u64>,
+
+    /// Width of the item list in percent of the screen.
+    pub list_width: Option<u16>,
+
+    /// Number of description lines shown under item titles.
+    pub description_lines: usize,
+
+    /// Group the items by day or by channel.
+    pub group_by: Grouping,
+
+    /// Number of hours snoozed items are hidden for. One day if not set.
+    pub snooze_hours: Option<u64>,
+
+    /// Break words that don't fit on a line with a hyphen.
+    pub hyphenate: bool,
+
+    /// Command used to open links. `{url}` is replaced with the link.
+    pub open_command: Option<String>,
+
+    /// Open command runs in the terminal, like `w3m {url}`. The reader is suspended until
+    /// it exits.
+    pub open_in_terminal: bool,
+
+    /// Don't make links in articles clickable with OSC 8 hyperlinks.
+    pub disable_hyperlinks: bool,
+
+    /// Command run when an item is opened. See [`crate::hooks`] for the placeholders.
+    pub on_open: Option<String>,
+
+    /// Command run when an item is marked as read.
+    pub on_read: Option<String>,
+
+    /// Built-in key bindings.
+    pub keymap: Profile,
+
+    /// Custom key bindings, from key to action. They take precedence over the keymap.
+    pub keys: HashMap<String, String>,
+
+    /// Command used to view articles outside of the reader. Article is passed on stdin.
+    pub pager: Option<String>,
+
+    /// How long toast messages are shown.
+    pub toast: ToastTimeouts,
+
+    /// How articles are rendered in the reader.
+    pub reader: ReaderConfig,
+
+    /// Sync with a server instead of fetching the feeds directly.
+    pub sync: Option<SyncConfig>,
+
+    /// Services the selected item is saved to for reading later.
+    pub read_later: ReadLaterConfig,
+}
+
+/// Account on a sync server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub backend: SyncBackend,
+    /// Url of the API, e.g. `https://example.com/api/greader.php` for FreshRSS or
+    /// `https://example.com/tt-rss/api/` for Tiny Tiny RSS.
+    pub url: String,
+    pub username: String,
+    /// Password of the account, or the API password if the server has one. If not set,
+    /// the password stored with `simple-rss auth login` is used.
+    pub password: Option<String>,
+}
+
+/// API of the sync server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackend {
+    /// Google Reader API, implemented by FreshRSS, The Old Reader and others
+    #[default]
+    #[value(name = "greader")]
+    GReader,
+    /// Tiny Tiny RSS
+    #[value(name = "ttrss")]
+    TtRss,
+}
+
+impl SyncBackend {
+    /// Name used in the config and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            SyncBackend::GReader => "greader",
+            SyncBackend::TtRss => "ttrss",
+        }
+    }
+}
+
+/// Accounts of the read-later services. Items are saved to all the enabled ones.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadLaterConfig {
+    pub instapaper: Option<InstapaperConfig>,
+    pub wallabag: Option<WallabagConfig>,
+}
+
+impl ReadLaterConfig {
+    /// Enabled services, in the order they are saved to.
+    pub fn enabled(&self) -> Vec<ReadLaterService> {
+        let mut services = vec![];
+        if self.instapaper.as_ref().is_some_and(|c| c.enabled) {
+            services.push(ReadLaterService::Instapaper);
+        }
+        if self.wallabag.as_ref().is_some_and(|c| c.enabled) {
+            services.push(ReadLaterService::Wallabag);
+        }
+        services
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstapaperConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Email or username of the account.
+    pub username: String,
+    /// If not set, the password stored with `simple-rss auth login instapaper` is used.
+    pub password: Option<String>,
+    /// Url of the API, without the `/api/add` path.
+    #[serde(default = "default_instapaper_url")]
+    pub url: String,
+}
+
+fn default_instapaper_url() -> String {
+    "https://www.instapaper.com".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WallabagConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Url of the instance, e.g. `https://app.wallabag.it`.
+    pub url: String,
+    /// Id and secret of the API client created in wallabag's settings.
+    pub client_id: String,
+    pub client_secret: String,
+    pub username: String,
+    /// If not set, the password stored with `simple-rss auth login wallabag` is used.
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReadLaterService {
+    Instapaper,
+    Wallabag,
+}
+
+impl ReadLaterService {
+    /// Name used in the config and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadLaterService::Instapaper => "instapaper",
+            ReadLaterService::Wallabag => "wallabag",
+        }
+    }
+
+    /// Name shown to the user.
+    pub fn title(self) -> &'static str {
+        match self {
+            ReadLaterService::Instapaper => "Instapaper",
+            ReadLaterService::Wallabag => "wallabag",
+        }
+    }
+}
+
+/// Timeouts of toast messages in seconds, by severity.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToastTimeouts {
+    pub info: Option<u64>,
+    pub warning: Option<u64>,
+    pub error: Option<u64>,
+}
+
+/// Rendering of the articles. Settings that are not set keep the defaults of
+/// [`RenderOptions`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReaderConfig {
+    /// Where link targets are written: `inline`, `footnote` or `hidden`.
+    pub links: LinkFormat,
+    pub heading_style: Option<TextStyle>,
+    pub link_style: Option<TextStyle>,
+    pub code_style: Option<TextStyle>,
+    /// Columns by which lists and collapsible sections are indented.
+    pub indent: Option<u16>,
+    /// Blank lines between paragraphs.
+    pub paragraph_spacing: Option<u16>,
+}
+
+/// Style written as space separated words, like `bold light-blue on black`. Words are
+/// modifiers and colors, the color after `on` is the background.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TextStyle(Style);
+
+impl TryFrom<String> for TextStyle {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut style = Style::default();
+        let mut words = value.split_whitespace();
+        while let Some(word) = words.next() {
+            let modifier = match word {
+                "bold" => Modifier::BOLD,
+                "dim" => Modifier::DIM,
+                "italic" => Modifier::ITALIC,
+                "underlined" => Modifier::UNDERLINED,
+                "reversed" => Modifier::REVERSED,
+                "crossed-out" => Modifier::CROSSED_OUT,
+                "on" => {
+                    let color = words.next().ok_or("missing background color after `on`")?;
+                    style = style.bg(parse_color(color)?);
+                    continue;
+                }
+                color => {
+                    style = style.fg(parse_color(color)?);
+                    continue;
+                }
+            };
+            style = style.add_modifier(modifier);
+        }
+        Ok(TextStyle(style))
+    }
+}
+
+/// Color by name, like `light-blue`, by index, like `244`, or in hex, like `#ff8800`.
+fn parse_color(color: &str) -> Result<Color, String> {
+    color
+        .parse()
+        .map_err(|_| format!("unknown color or modifier `{color}`"))
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = settings_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        toml::from_str(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// Config for the safe mode: invalid config file is ignored and only settings that
+    /// can't break the reader are kept. Custom keys, external commands and auto refresh are disabled.
+    pub fn load_safe() -> Self {
+        let config = Self::load().unwrap_or_default();
+        Self {
+            download_dir: config.download_dir,
+            timeout: config.timeout,
+            // Feeds may not be reachable without it, but an invalid one would fail startup.
+            proxy: config.proxy.filter(|p| reqwest::Proxy::all(p).is_ok()),
+            host_interval: config.host_interval,
+            new_badge_hours: config.new_badge_hours,
+            sync: config.sync,
+            // Terminals that don't support them may show the escape sequences.
+            disable_hyperlinks: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn download_dir(&self) -> PathBuf {
+        self.download_dir.clone().unwrap_or_else(download_dir)
+    }
+
+    /// Items published after this time are marked as new.
+    pub fn new_since(&self) -> Option<DateTime<Utc>> {
+        let hours = self.new_badge_hours?;
+        // A window longer than dates can go back marks every item as new.
+        let since = i64::try_from(hours)
+            .ok()
+            .and_then(TimeDelta::try_hours)
+            .and_then(|window| Utc::now().checked_sub_signed(window))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        Some(since)
+    }
+
+    /// How long snoozed items are hidden for.
+    pub fn snooze(&self) -> Option<TimeDelta> {
+        // Snooze longer than dates can go is capped and hides the item for good.
+        let hours = i64::try_from(self.snooze_hours?).unwrap_or(i64::MAX);
+        Some(TimeDelta::try_hours(hours).unwrap_or(TimeDelta::MAX))
+    }
+
+    pub fn keymap(&self) -> anyhow::Result<Keymap> {
+        Keymap::new(self.keymap, &self.keys)
+            .with_context(|| format!("Invalid keys in {}", settings_path().display()))
+    }
+
+    pub fn toast(&self) -> toast::Config {
+        let default = toast::Config::default();
+        let timeout = |secs: Option<u64>, default| secs.map_or(default, Duration::from_secs);
+
+        toast::Config {
+            info_timeout: timeout(self.toast.info, default.info_timeout),
+            warning_timeout: timeout(self.toast.warning, default.warning_timeout),
+            error_timeout: timeout(self.toast.error, default.error_timeout),
+        }
+    }
+
+    /// How articles are rendered in the reader.
+    pub fn render_options(&self) -> RenderOptions {
+        let default = RenderOptions::default();
+        let reader = &self.reader;
+        let style = |style: Option<TextStyle>, default| style.map_or(default, |s| s.0);
+
+        RenderOptions {
+            hyphenate: self.hyphenate,
+            links: reader.links,
+            heading_style: style(reader.heading_style, default.heading_style),
+            link_style: style(reader.link_style, default.link_style),
+            code_style: style(reader.code_style, default.code_style),
+            indent: reader.indent.unwrap_or(default.indent),
+            paragraph_spacing: reader
+                .paragraph_spacing
+                .unwrap_or(default.paragraph_spacing),
+            ..default
+        }
+    }
+
+    /// Configured pager, falling back to `$PAGER` and `less`.
+    pub fn pager(&self) -> String {
+        self.pager
+            .clone()
+            .or_else(|| env::var("PAGER").ok())
+            .unwrap_or_else(|| "less".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_settings() {
+        let config: Config = toml::from_str(
+            r##"
+            [reader]
+            links = "footnote"
+            heading_style = "bold light-blue on black"
+            code_style = "#ff8800 italic"
+            indent = 4
+            "##,
+        )
+        .unwrap();
+
+        let options = config.render_options();
+        let default = RenderOptions::default();
+        assert_eq!(options.links, LinkFormat::Footnote);
+        assert_eq!(
+            options.heading_style,
+            Style::new()
+                .fg(Color::LightBlue)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            options.code_style,
+            Style::new()
+                .fg(Color::Rgb(0xff, 0x88, 0))
+                .add_modifier(Modifier::ITALIC)
+        );
+        assert_eq!(options.link_style, default.link_style);
+        assert_eq!(options.indent, 4);
+        assert_eq!(options.paragraph_spacing, default.paragraph_spacing);
+    }
+
+    #[test]
+    fn invalid_style() {
+        for style in ["bold blurple", "red on"] {
+            let config = format!("[reader]\nlink_style = \"{style}\"");
+            assert!(toml::from_str::<Config>(&config).is_err(), "{style}");
+        }
+    }
+}
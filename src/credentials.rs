@@ -0,0 +1,107 @@
+//! Passwords stored in the system keyring (Secret Service, Keychain or Credential Manager),
+//! so that they don't have to be written to the config file.
+
+use std::{env, thread};
+
+use anyhow::{Context, bail};
+use keyring::Entry;
+
+use crate::config::{ReadLaterService, SyncBackend, SyncConfig};
+
+/// Service under which all the entries are stored.
+const SERVICE: &str = "simple-rss";
+
+/// Stored secret with the given name, or `None` if there is none.
+pub fn get(name: &str) -> anyhow::Result<Option<String>> {
+    match with_entry(name, |entry| entry.get_password()) {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err).context("Failed to read from the keyring"),
+    }
+}
+
+pub fn set(name: &str, secret: &str) -> anyhow::Result<()> {
+    with_entry(name, |entry| entry.set_password(secret)).context("Failed to write to the keyring")
+}
+
+/// Remove the secret. Returns false if there was none.
+pub fn delete(name: &str) -> anyhow::Result<bool> {
+    match with_entry(name, |entry| entry.delete_credential()) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(err).context("Failed to remove from the keyring"),
+    }
+}
+
+/// Value of a secret reference: `env:NAME` is read from the environment variable and
+/// `keyring:NAME` from the keyring. Other values are returned as they are.
+pub fn resolve(value: &str) -> anyhow::Result<String> {
+    if let Some(var) = value.strip_prefix("env:") {
+        return env::var(var).with_context(|| format!("Environment variable {var} is not set"));
+    }
+    if let Some(name) = value.strip_prefix("keyring:") {
+        return get(name)?.with_context(|| format!("No secret {name} in the keyring"));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Name under which the password of the sync account is stored.
+pub fn sync_name(backend: SyncBackend, username: &str) -> String {
+    format!("{}:{username}", backend.name())
+}
+
+/// Password of the sync account, from the config or the keyring.
+pub fn sync_password(config: &SyncConfig) -> anyhow::Result<String> {
+    if let Some(password) = &config.password {
+        return Ok(password.clone());
+    }
+
+    match get(&sync_name(config.backend, &config.username))? {
+        Some(password) => Ok(password),
+        None => bail!(
+            "No password for {} sync. Run `simple-rss auth login {}` to store it.",
+            config.username,
+            config.backend.name()
+        ),
+    }
+}
+
+/// Name under which the password of the read-later account is stored.
+pub fn read_later_name(service: ReadLaterService, username: &str) -> String {
+    format!("{}:{username}", service.name())
+}
+
+/// Password of the read-later account, from the config or the keyring.
+pub fn read_later_secret(
+    service: ReadLaterService,
+    username: &str,
+    configured: Option<&str>,
+) -> anyhow::Result<String> {
+    if let Some(secret) = configured {
+        return Ok(secret.to_string());
+    }
+
+    match get(&read_later_name(service, username))? {
+        Some(secret) => Ok(secret),
+        None => bail!(
+            "No credentials for {}. Run `simple-rss auth login {}` to store them.",
+            service.title(),
+            service.name()
+        ),
+    }
+}
+
+/// Run the operation on the entry. Keyring blocks on its own async runtime, which can't be
+/// started from within tokio, so the operation runs on a separate thread.
+fn with_entry<T: Send>(
+    name: &str,
+    op: impl FnOnce(&Entry) -> keyring::Result<T> + Send,
+) -> keyring::Result<T> {
+    thread::scope(|scope| {
+        scope
+            .spawn(|| op(&Entry::new(SERVICE, name)?))
+            .join()
+            .expect("Keyring thread panicked")
+    })
+}
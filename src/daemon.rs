@@ -0,0 +1,561 @@
+//! Daemon owning the stored data and the refresh schedule.
+//!
+//! `simple-rss daemon` loads the data, refreshes the channels on the configured schedule,
+//! or syncs with the configured sync server instead, and serves clients on a Unix socket in
+//! the state directory. While it runs, the TUI and the CLI send refreshes and changes to
+//! the daemon instead of writing the data files themselves, so that multiple instances
+//! don't refresh the same channels or overwrite each other's changes. Requests and responses are JSON objects, one per line. Other
+//! platforms don't support the daemon yet.
+
+use serde::{Deserialize, Serialize};
+use simple_rss_lib::data::{Channel, Hidden, Item, RefreshError};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Request {
+    /// Get all the channels and items.
+    Data,
+    /// Refresh all the channels. If a refresh is already running, its result is returned
+    /// instead of starting another one.
+    Refresh,
+    /// Set read status of all the items with the given ids.
+    SetRead { ids: Vec<String>, read: bool },
+    /// Star or unstar all the items with the given ids.
+    SetStarred { ids: Vec<String>, starred: bool },
+    /// Hide the item, or show it again with `None`.
+    SetHidden { id: String, hidden: Option<Hidden> },
+    /// Move the channel at index `from` to index `to`.
+    MoveChannel { from: usize, to: usize },
+    /// Channels were changed on disk and should be loaded again.
+    Reload,
+    /// Keep the connection open and send notifications about changes.
+    Subscribe,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Response {
+    Ok,
+    Error {
+        message: String,
+    },
+    Data {
+        channels: Vec<Channel>,
+        items: Vec<Item>,
+    },
+    Refreshed {
+        errors: Vec<RefreshError>,
+    },
+
+    // Notifications sent to subscribers.
+    RefreshStarted,
+    RefreshFinished {
+        errors: Vec<RefreshError>,
+    },
+    /// Data was changed, e.g. by another client.
+    Changed,
+}
+
+#[cfg(unix)]
+pub use unix::{Client, run};
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs, io,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use anyhow::{Context, bail};
+    use serde::Serialize;
+    use simple_rss_lib::{
+        data::{Data, Fetcher, Hidden, ItemStore, RefreshError},
+        event::{Event, EventBus, EventSender, ToastEvent},
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+        net::{UnixListener, UnixStream},
+        signal::unix::{SignalKind, signal},
+        sync::{Notify, broadcast},
+    };
+
+    use super::{Request, Response};
+    use crate::{
+        config::{Config, SyncBackend, SyncConfig},
+        credentials,
+        data::{DataLoader, GReaderLoader, TtRssLoader, daemon_socket_path, load_data, save_data},
+        hooks, http,
+        scheduler::{Scheduled, Scheduler},
+        source,
+    };
+
+    /// Time for which changes are collected before they are saved and subscribers are
+    /// notified, so that many quick changes, e.g. marking items as read while scrolling,
+    /// don't save the data and refetch it in every client each time.
+    const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+    /// Socket file, removed when dropped.
+    struct Socket {
+        path: PathBuf,
+    }
+
+    impl Drop for Socket {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    struct Daemon {
+        loader: DataLoader,
+        // Refreshes and read status changes go through the sync backend, if configured.
+        sync: Option<SyncedLoader>,
+        scheduler: Option<Scheduler>,
+        scheduled: Mutex<Option<Scheduled>>,
+        event_tx: EventSender,
+        notifications: broadcast::Sender<Response>,
+        // Notified on every change that should be saved after the delay.
+        changed: Notify,
+        // Errors of the last refresh requested by a client. Locked while the refresh runs.
+        refresh: tokio::sync::Mutex<Vec<RefreshError>>,
+    }
+
+    /// Run the daemon until it is interrupted or terminated.
+    pub async fn run() -> anyhow::Result<()> {
+        let config = Config::load()?;
+        http::init(&config)?;
+        source::init(&config);
+        hooks::init(&config);
+
+        let path = daemon_socket_path();
+        if UnixStream::connect(&path).await.is_ok() {
+            bail!("simple-rss daemon is already running");
+        }
+        // Socket of a daemon that didn't exit cleanly.
+        let _ = fs::remove_file(&path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        let _socket = Socket { path };
+
+        let loader = DataLoader::new()?;
+        let sync = match config.sync.clone() {
+            Some(sync) => {
+                let password = credentials::sync_password(&sync)?;
+                Some(SyncedLoader::new(sync, password, loader.clone()))
+            }
+            None => None,
+        };
+
+        let mut event_bus = EventBus::new();
+        let (notifications, _) = broadcast::channel(16);
+        let daemon = Arc::new(Daemon {
+            loader,
+            sync,
+            scheduler: Scheduler::from_config(&config),
+            scheduled: Mutex::new(None),
+            event_tx: event_bus.get_sender(),
+            notifications,
+            changed: Notify::new(),
+            refresh: tokio::sync::Mutex::new(vec![]),
+        });
+        daemon.schedule();
+
+        let saving = daemon.clone();
+        tokio::spawn(async move {
+            loop {
+                saving.changed.notified().await;
+                tokio::time::sleep(SAVE_DELAY).await;
+                saving.save();
+                saving.notify(Response::Changed);
+            }
+        });
+
+        let accepting = daemon.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, accepting.clone()));
+            }
+        });
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        loop {
+            tokio::select! {
+                Some(event) = event_bus.next() => daemon.handle_event(event),
+                _ = tokio::signal::ctrl_c() => break,
+                _ = terminate.recv() => break,
+            }
+        }
+
+        // Changes that are waiting for the delay.
+        daemon.save();
+
+        Ok(())
+    }
+
+    impl Daemon {
+        /// (Re)start the scheduled refreshes of the current channels.
+        fn schedule(&self) {
+            let scheduled = self.scheduler.as_ref().map(|scheduler| match &self.sync {
+                Some(sync) => scheduler.spawn_sync(sync.clone(), self.event_tx.clone()),
+                None => scheduler.spawn(self.loader.clone(), self.event_tx.clone()),
+            });
+            *self.scheduled.lock().unwrap() = scheduled;
+        }
+
+        /// Handle events of the scheduled refreshes.
+        fn handle_event(&self, event: Event) {
+            match event {
+                Event::RefreshStarted => self.notify(Response::RefreshStarted),
+                Event::RefreshFinished { errors } => {
+                    self.save();
+                    self.notify(Response::RefreshFinished { errors });
+                }
+                _ => (),
+            }
+        }
+
+        async fn handle(&self, request: Request) -> Response {
+            match request {
+                Request::Data => {
+                    let data = self.loader.get_data();
+                    Response::Data {
+                        channels: data.channels.clone(),
+                        items: data.items.clone(),
+                    }
+                }
+                Request::Refresh => Response::Refreshed {
+                    errors: self.refresh().await,
+                },
+                Request::SetRead { ids, read } => {
+                    match &self.sync {
+                        Some(sync) => sync.clone().set_read_by_ids(&ids, read),
+                        None => self.loader.clone().set_read_by_ids(&ids, read),
+                    }
+                    self.changed.notify_one();
+                    Response::Ok
+                }
+                Request::SetStarred { ids, starred } => {
+                    match &self.sync {
+                        Some(sync) => sync.clone().set_starred_by_ids(&ids, starred),
+                        None => self.loader.clone().set_starred_by_ids(&ids, starred),
+                    }
+                    self.changed.notify_one();
+                    Response::Ok
+                }
+                Request::SetHidden { id, hidden } => {
+                    self.loader.clone().set_hidden_by_id(&id, hidden);
+                    self.changed.notify_one();
+                    Response::Ok
+                }
+                Request::MoveChannel { from, to } => {
+                    self.loader.clone().move_channel(from, to);
+                    self.changed.notify_one();
+                    Response::Ok
+                }
+                Request::Reload => match load_data() {
+                    Ok(data) => {
+                        self.loader.set_channels(data.channels);
+                        self.schedule();
+                        self.notify(Response::Changed);
+                        Response::Ok
+                    }
+                    Err(err) => Response::Error {
+                        message: format!("Failed to load channels: {err}"),
+                    },
+                },
+                // Handled by the connection.
+                Request::Subscribe => Response::Ok,
+            }
+        }
+
+        async fn refresh(&self) -> Vec<RefreshError> {
+            // Clients that ask while a refresh is running wait for it instead of starting
+            // another one.
+            let Ok(mut errors) = self.refresh.try_lock() else {
+                return self.refresh.lock().await.clone();
+            };
+
+            let result = match &self.sync {
+                Some(sync) => sync.clone().refresh().await,
+                None => self.loader.clone().refresh().await,
+            };
+            *errors = result.err().unwrap_or_default();
+            self.save();
+            self.notify(Response::Changed);
+            errors.clone()
+        }
+
+        fn save(&self) {
+            if let Err(err) = save_data(&self.loader.get_data()) {
+                eprintln!("Failed to save data: {err}");
+            }
+        }
+
+        fn notify(&self, notification: Response) {
+            // Error only means that nobody is subscribed.
+            let _ = self.notifications.send(notification);
+        }
+    }
+
+    /// Loader of the configured sync backend, sharing the daemon's data loader.
+    #[derive(Clone)]
+    enum SyncedLoader {
+        GReader(GReaderLoader),
+        TtRss(TtRssLoader),
+    }
+
+    impl SyncedLoader {
+        fn new(config: SyncConfig, password: String, data: DataLoader) -> Self {
+            match config.backend {
+                SyncBackend::GReader => Self::GReader(GReaderLoader::new(config, password, data)),
+                SyncBackend::TtRss => Self::TtRss(TtRssLoader::new(config, password, data)),
+            }
+        }
+
+        fn set_read_by_ids(&mut self, ids: &[String], read: bool) {
+            match self {
+                Self::GReader(loader) => loader.set_read_by_ids(ids, read),
+                Self::TtRss(loader) => loader.set_read_by_ids(ids, read),
+            }
+        }
+
+        fn set_starred_by_ids(&mut self, ids: &[String], starred: bool) {
+            match self {
+                Self::GReader(loader) => loader.set_starred_by_ids(ids, starred),
+                Self::TtRss(loader) => loader.set_starred_by_ids(ids, starred),
+            }
+        }
+    }
+
+    impl Fetcher for SyncedLoader {
+        async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+            match self {
+                Self::GReader(loader) => loader.refresh().await,
+                Self::TtRss(loader) => loader.refresh().await,
+            }
+        }
+    }
+
+    async fn handle(stream: UnixStream, daemon: Arc<Daemon>) -> io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            let response = match serde_json::from_str(&line) {
+                Ok(Request::Subscribe) => {
+                    return subscribe(writer, daemon.notifications.subscribe()).await;
+                }
+                Ok(request) => daemon.handle(request).await,
+                Err(err) => Response::Error {
+                    message: format!("Invalid request: {err}"),
+                },
+            };
+            write_message(&mut writer, &response).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn subscribe(
+        mut writer: impl AsyncWrite + Unpin,
+        mut notifications: broadcast::Receiver<Response>,
+    ) -> io::Result<()> {
+        loop {
+            let notification = match notifications.recv().await {
+                Ok(notification) => notification,
+                // Missed notifications are replaced with a full update.
+                Err(broadcast::error::RecvError::Lagged(_)) => Response::Changed,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            };
+            write_message(&mut writer, &notification).await?;
+        }
+    }
+
+    async fn write_message(
+        writer: &mut (impl AsyncWrite + Unpin),
+        message: &impl Serialize,
+    ) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await
+    }
+
+    /// Connection to the running daemon. Each request is sent over a new connection.
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        path: PathBuf,
+    }
+
+    impl Client {
+        /// Returns `None` if the daemon is not running.
+        pub async fn connect() -> Option<Self> {
+            let path = daemon_socket_path();
+            UnixStream::connect(&path).await.ok()?;
+            Some(Self { path })
+        }
+
+        async fn request(&self, request: &Request) -> anyhow::Result<Response> {
+            let stream = UnixStream::connect(&self.path)
+                .await
+                .context("simple-rss daemon is not running")?;
+            let (reader, mut writer) = stream.into_split();
+            write_message(&mut writer, request).await?;
+
+            let mut response = String::new();
+            BufReader::new(reader).read_line(&mut response).await?;
+            if response.is_empty() {
+                bail!("simple-rss daemon closed the connection");
+            }
+            match serde_json::from_str(&response)? {
+                Response::Error { message } => bail!("{message}"),
+                response => Ok(response),
+            }
+        }
+
+        pub async fn data(&self) -> anyhow::Result<Data> {
+            match self.request(&Request::Data).await? {
+                Response::Data { channels, items } => Ok(Data { channels, items }),
+                response => bail!("Unexpected response: {response:?}"),
+            }
+        }
+
+        /// Refresh all the channels and return errors of the ones that failed.
+        pub async fn refresh(&self) -> anyhow::Result<Vec<RefreshError>> {
+            match self.request(&Request::Refresh).await? {
+                Response::Refreshed { errors } => Ok(errors),
+                response => bail!("Unexpected response: {response:?}"),
+            }
+        }
+
+        pub async fn set_read(&self, ids: Vec<String>, read: bool) -> anyhow::Result<()> {
+            self.request(&Request::SetRead { ids, read }).await?;
+            Ok(())
+        }
+
+        pub async fn set_starred(&self, ids: Vec<String>, starred: bool) -> anyhow::Result<()> {
+            self.request(&Request::SetStarred { ids, starred }).await?;
+            Ok(())
+        }
+
+        pub async fn set_hidden(&self, id: String, hidden: Option<Hidden>) -> anyhow::Result<()> {
+            self.request(&Request::SetHidden { id, hidden }).await?;
+            Ok(())
+        }
+
+        pub async fn move_channel(&self, from: usize, to: usize) -> anyhow::Result<()> {
+            self.request(&Request::MoveChannel { from, to }).await?;
+            Ok(())
+        }
+
+        /// Tell the daemon that the channels were changed on disk.
+        pub async fn reload(&self) -> anyhow::Result<()> {
+            self.request(&Request::Reload).await?;
+            Ok(())
+        }
+
+        /// Keep the loader's data in sync with the daemon and forward the daemon's refresh
+        /// status as events.
+        pub fn watch(&self, loader: DataLoader, event_tx: EventSender) {
+            let client = self.clone();
+            tokio::spawn(async move {
+                let Ok(stream) = UnixStream::connect(&client.path).await else {
+                    return;
+                };
+                let (reader, mut writer) = stream.into_split();
+                if write_message(&mut writer, &Request::Subscribe)
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+
+                let mut lines = BufReader::new(reader).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let Ok(notification) = serde_json::from_str(&line) else {
+                        continue;
+                    };
+                    match notification {
+                        Response::RefreshStarted => event_tx.send(Event::RefreshStarted),
+                        Response::RefreshFinished { errors } => {
+                            if let Ok(data) = client.data().await {
+                                loader.set_data(data);
+                            }
+                            event_tx.send(Event::RefreshFinished { errors });
+                        }
+                        Response::Changed => {
+                            if let Ok(data) = client.data().await {
+                                loader.set_data(data);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+
+                event_tx.send(Event::Toast(ToastEvent::Warning(
+                    "Lost connection to the daemon!".to_string(),
+                )));
+            });
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub use other::{Client, run};
+
+#[cfg(not(unix))]
+mod other {
+    use simple_rss_lib::{
+        data::{Data, Hidden, RefreshError},
+        event::EventSender,
+    };
+
+    use crate::data::DataLoader;
+
+    pub async fn run() -> anyhow::Result<()> {
+        anyhow::bail!("Daemon is supported only on Unix")
+    }
+
+    /// Connection to the daemon, which can't be made on this platform.
+    #[derive(Debug, Clone)]
+    pub enum Client {}
+
+    impl Client {
+        pub async fn connect() -> Option<Self> {
+            None
+        }
+
+        pub async fn data(&self) -> anyhow::Result<Data> {
+            match *self {}
+        }
+
+        pub async fn refresh(&self) -> anyhow::Result<Vec<RefreshError>> {
+            match *self {}
+        }
+
+        pub async fn set_read(&self, _ids: Vec<String>, _read: bool) -> anyhow::Result<()> {
+            match *self {}
+        }
+
+        pub async fn set_starred(&self, _ids: Vec<String>, _starred: bool) -> anyhow::Result<()> {
+            match *self {}
+        }
+
+        pub async fn set_hidden(&self, _id: String, _hidden: Option<Hidden>) -> anyhow::Result<()> {
+            match *self {}
+        }
+
+        pub async fn move_channel(&self, _from: usize, _to: usize) -> anyhow::Result<()> {
+            match *self {}
+        }
+
+        pub async fn reload(&self) -> anyhow::Result<()> {
+            match *self {}
+        }
+
+        pub fn watch(&self, _loader: DataLoader, _event_tx: EventSender) {
+            match *self {}
+        }
+    }
+}
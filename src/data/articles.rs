@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of articles kept in the cache. Older ones are dropped first.
+const MAX_ARTICLES: usize = 500;
+
+/// Articles longer than this many bytes are cut, so a few huge pages don't fill the cache.
+const MAX_ARTICLE_LEN: usize = 100_000;
+
+/// Text of the recently loaded articles, so that `simple-rss search` finds items by their
+/// article too.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Articles {
+    /// Oldest first.
+    articles: VecDeque<Article>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Article {
+    /// Id of the item.
+    id: String,
+    text: String,
+}
+
+impl Articles {
+    /// Remember the plain text of the item's article, replacing the previous one.
+    pub fn insert(&mut self, id: &str, text: &str) {
+        self.articles.retain(|article| article.id != id);
+
+        let mut end = text.len().min(MAX_ARTICLE_LEN);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.articles.push_back(Article {
+            id: id.to_string(),
+            text: text[..end].to_string(),
+        });
+
+        while self.articles.len() > MAX_ARTICLES {
+            self.articles.pop_front();
+        }
+    }
+
+    /// Item ids with the text of their articles.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.articles
+            .iter()
+            .map(|article| (article.id.as_str(), article.text.as_str()))
+    }
+}
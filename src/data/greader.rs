@@ -0,0 +1,400 @@
+//! Sync with servers implementing the Google Reader API, like FreshRSS and The Old Reader.
+//!
+//! Subscriptions are managed on the server. Each refresh replaces the channels with the
+//! server's subscriptions, fetches items added since the previous sync and updates read
+//! and starred status of all the items. Changes of the status are pushed to the server right
+//! away, several items in a single request.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, de::DeserializeOwned};
+use simple_rss_lib::Error;
+use tokio::sync::Mutex;
+
+use super::{
+    Channel, Data, DataLoader, Item,
+    sync::{Flag, SyncLoader, SyncServer},
+};
+use crate::{
+    config::SyncConfig,
+    http::{self, client, network_error},
+};
+
+const READING_LIST: &str = "user/-/state/com.google/reading-list";
+const READ_TAG: &str = "user/-/state/com.google/read";
+const STARRED_TAG: &str = "user/-/state/com.google/starred";
+const ITEM_ID_PREFIX: &str = "tag:google.com,2005:reader/item/";
+
+/// Number of items requested at once.
+const PAGE_SIZE: usize = 250;
+/// Maximum number of items fetched in a single sync.
+const MAX_ITEMS: usize = 1000;
+/// Maximum number of unread items whose status is synced. Older ones are shown as read.
+const MAX_UNREAD: usize = 10_000;
+/// Maximum number of starred items whose status is synced.
+const MAX_STARRED: usize = 10_000;
+
+/// [`SyncLoader`] for a Google Reader API server.
+pub type GReaderLoader = SyncLoader<Server>;
+
+pub struct Server {
+    /// Url of the API, e.g. `https://example.com/api/greader.php`.
+    url: String,
+    username: String,
+    password: String,
+    /// Token obtained by logging in.
+    auth: Mutex<Option<String>>,
+}
+
+impl SyncServer for Server {
+    fn new(config: SyncConfig, password: String) -> Self {
+        Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            username: config.username,
+            password,
+            auth: Mutex::new(None),
+        }
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn is_synced(id: &str) -> bool {
+        let short = short_id(id);
+        short.len() == 16 && short.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    async fn sync(&self, data: &DataLoader) -> Result<Data, Error> {
+        let subscriptions: SubscriptionList = self
+            .get("subscription/list", &[("output", "json".to_string())])
+            .await?;
+
+        // Items fetched directly from the feeds, before sync was configured, are replaced.
+        // Once there are synced items, only items added since the previous sync are fetched.
+        let (mut items, since) = {
+            let data = data.get_data();
+            let items: Vec<_> = data
+                .items
+                .iter()
+                .filter(|it| Self::is_synced(&it.id))
+                .cloned()
+                .collect();
+            let since = data
+                .channels
+                .iter()
+                .map(|ch| ch.last_fetched)
+                .min()
+                .flatten();
+            let since = since.filter(|_| !items.is_empty());
+            (items, since)
+        };
+        let fetched = self.fetch_items(since).await?;
+        let unread = self
+            .item_ids(&[("s", READING_LIST), ("xt", READ_TAG)], MAX_UNREAD)
+            .await?;
+        let starred = self.item_ids(&[("s", STARRED_TAG)], MAX_STARRED).await?;
+
+        let now = Utc::now();
+        let channels: Vec<_> = subscriptions
+            .subscriptions
+            .iter()
+            .map(|sub| Channel {
+                name: Some(sub.title.clone()),
+                url: sub.url.clone(),
+                tags: sub.categories.iter().map(|c| c.label.clone()).collect(),
+                last_fetched: Some(now),
+                ..Default::default()
+            })
+            .collect();
+        let streams: HashMap<_, _> = subscriptions
+            .subscriptions
+            .iter()
+            .map(|sub| (sub.id.as_str(), sub))
+            .collect();
+
+        // Items of removed subscriptions are dropped, fetched items replace stored ones.
+        items.retain(|it| {
+            channels
+                .iter()
+                .any(|ch| it.id.starts_with(&format!("{}:", ch.url)))
+        });
+        let mut ids: HashMap<_, _> = items
+            .iter()
+            .enumerate()
+            .map(|(idx, it)| (it.id.clone(), idx))
+            .collect();
+        for item in fetched {
+            let Some(item) = item.into_item(&streams) else {
+                continue;
+            };
+            match ids.get(&item.id) {
+                // Hiding is local, so it's kept.
+                Some(&idx) => {
+                    items[idx] = Item {
+                        hidden: items[idx].hidden,
+                        ..item
+                    }
+                }
+                None => {
+                    ids.insert(item.id.clone(), items.len());
+                    items.push(item);
+                }
+            }
+        }
+
+        for item in items.iter_mut() {
+            item.read = !unread.contains(short_id(&item.id));
+            item.starred = starred.contains(short_id(&item.id));
+        }
+        items.sort_by_key(|it| Reverse(it.pub_date));
+
+        Ok(Data { channels, items })
+    }
+
+    /// All the items are changed with a single request, listing each of them.
+    async fn set_flag(&self, ids: &[&str], flag: Flag, value: bool) -> Result<(), Error> {
+        let token = self.request("token", &[]).await?;
+        let ids: Vec<_> = ids.iter().map(|id| remote_id(id)).collect();
+        let tag = match flag {
+            Flag::Read => READ_TAG,
+            Flag::Starred => STARRED_TAG,
+        };
+        // Tag is added or removed.
+        let action = if value { "a" } else { "r" };
+        let mut form: Vec<_> = ids.iter().map(|id| ("i", id.as_str())).collect();
+        form.extend([(action, tag), ("T", token.trim())]);
+
+        let url = format!("{}/reader/api/0/edit-tag", self.url);
+        self.send(|| client().post(&url).form(&form)).await?;
+        Ok(())
+    }
+}
+
+impl Server {
+    async fn fetch_items(&self, since: Option<DateTime<Utc>>) -> Result<Vec<StreamItem>, Error> {
+        let mut items = vec![];
+        let mut continuation = None;
+        loop {
+            let mut query = vec![("output", "json".to_string()), ("n", PAGE_SIZE.to_string())];
+            if let Some(since) = since {
+                query.push(("ot", since.timestamp().to_string()));
+            }
+            if let Some(continuation) = continuation {
+                query.push(("c", continuation));
+            }
+
+            let page: StreamContents = self
+                .get(&format!("stream/contents/{READING_LIST}"), &query)
+                .await?;
+            items.extend(page.items);
+
+            continuation = page.continuation;
+            if continuation.is_none() || items.len() >= MAX_ITEMS {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Short ids of the items in the stream, e.g. all the unread or starred items.
+    async fn item_ids(
+        &self,
+        stream: &[(&str, &str)],
+        limit: usize,
+    ) -> Result<HashSet<String>, Error> {
+        let mut query = vec![("output", "json".to_string())];
+        query.extend(stream.iter().map(|(key, value)| (*key, value.to_string())));
+        query.push(("n", limit.to_string()));
+        let ids: ItemIds = self.get("stream/items/ids", &query).await?;
+
+        // Ids are listed as decimal numbers, while items use their hex representation.
+        let ids = ids
+            .item_refs
+            .iter()
+            .filter_map(|r| r.id.parse::<i64>().ok())
+            .map(|id| format!("{:016x}", id as u64))
+            .collect();
+        Ok(ids)
+    }
+
+    /// Auth token, logging in if there is none yet.
+    async fn auth(&self) -> Result<String, Error> {
+        let mut auth = self.auth.lock().await;
+        if let Some(auth) = auth.as_ref() {
+            return Ok(auth.clone());
+        }
+
+        let form = [
+            ("Email", self.username.as_str()),
+            ("Passwd", self.password.as_str()),
+        ];
+        let response = http::send(
+            client()
+                .post(format!("{}/accounts/ClientLogin", self.url))
+                .form(&form),
+        )
+        .await?
+        .text()
+        .await
+        .map_err(network_error)?;
+
+        let token = response
+            .lines()
+            .find_map(|line| line.strip_prefix("Auth="))
+            .ok_or_else(|| Error::Parse("Login response has no auth token".to_string()))?;
+        *auth = Some(token.to_string());
+        Ok(token.to_string())
+    }
+
+    /// Body of a GET request to the reader API.
+    async fn request(&self, path: &str, query: &[(&str, String)]) -> Result<String, Error> {
+        let url = format!("{}/reader/api/0/{path}", self.url);
+        self.send(|| client().get(&url).query(query))
+            .await?
+            .text()
+            .await
+            .map_err(network_error)
+    }
+
+    /// Send the request with the auth token. If the token expired, log in again and
+    /// resend the request once.
+    async fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Response, Error> {
+        let auth = self.auth().await?;
+        let response =
+            http::execute(request().header("Authorization", format!("GoogleLogin auth={auth}")))
+                .await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return response.error_for_status().map_err(network_error);
+        }
+
+        {
+            let mut current = self.auth.lock().await;
+            // Another request might have logged in again already.
+            if current.as_ref() == Some(&auth) {
+                *current = None;
+            }
+        }
+        let auth = self.auth().await?;
+        http::send(request().header("Authorization", format!("GoogleLogin auth={auth}"))).await
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        let body = self.request(path, query).await?;
+        serde_json::from_str(&body).map_err(|err| Error::Parse(err.to_string()))
+    }
+}
+
+/// Id of the item on the server, from the id of the stored item.
+fn remote_id(id: &str) -> String {
+    format!("{ITEM_ID_PREFIX}{}", short_id(id))
+}
+
+/// Hex part of the item id, shared by the stored and the remote id.
+fn short_id(id: &str) -> &str {
+    id.rsplit(['/', ':']).next().unwrap_or(id)
+}
+
+#[derive(Deserialize)]
+struct SubscriptionList {
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Deserialize)]
+struct Subscription {
+    id: String,
+    title: String,
+    url: String,
+    #[serde(default)]
+    categories: Vec<Category>,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    label: String,
+}
+
+#[derive(Deserialize)]
+struct StreamContents {
+    items: Vec<StreamItem>,
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamItem {
+    id: String,
+    title: Option<String>,
+    published: Option<i64>,
+    author: Option<String>,
+    #[serde(default)]
+    canonical: Vec<Link>,
+    #[serde(default)]
+    alternate: Vec<Link>,
+    summary: Option<Content>,
+    #[serde(default)]
+    enclosure: Vec<Link>,
+    origin: Origin,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct Origin {
+    #[serde(rename = "streamId")]
+    stream_id: String,
+}
+
+#[derive(Deserialize)]
+struct ItemIds {
+    #[serde(rename = "itemRefs", default)]
+    item_refs: Vec<ItemRef>,
+}
+
+#[derive(Deserialize)]
+struct ItemRef {
+    id: String,
+}
+
+impl StreamItem {
+    /// Stored item, or `None` if the item doesn't belong to any subscription or has no link.
+    /// Read status is set from the unread ids afterwards.
+    fn into_item(self, streams: &HashMap<&str, &Subscription>) -> Option<Item> {
+        let sub = streams.get(self.origin.stream_id.as_str())?;
+        let link = self.canonical.into_iter().chain(self.alternate).next()?;
+
+        Some(Item {
+            id: format!("{}:{}", sub.url, short_id(&self.id)),
+            channel_name: sub.title.clone(),
+            title: self.title?,
+            author: self.author,
+            description: self.summary.map(|s| s.content),
+            pub_date: self
+                .published
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|p| p.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+            link: link.href,
+            enclosure: self.enclosure.into_iter().next().map(|e| e.href),
+            alternate_links: vec![],
+            read: false,
+            starred: false,
+            tags: vec![],
+            hidden: None,
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use simple_rss_lib::data::{Channel, Item};
+
+/// Interaction history, used for engagement statistics of channels.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    /// Every item that was ever stored, by item id.
+    items: HashMap<String, ItemHistory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ItemHistory {
+    /// Url of the item's channel.
+    channel: String,
+    opens: u32,
+}
+
+/// Engagement with items of a single channel.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChannelStats {
+    pub items: usize,
+    pub opened: usize,
+    pub opens: u32,
+}
+
+impl History {
+    /// Remember the items, so that items that are never opened count as skipped.
+    pub fn record_items(&mut self, channels: &[Channel], items: &[Item]) {
+        for item in items {
+            if let Some(channel) = channel_url(channels, item) {
+                self.items
+                    .entry(item.id.clone())
+                    .or_insert_with(|| ItemHistory {
+                        channel: channel.to_string(),
+                        opens: 0,
+                    });
+            }
+        }
+    }
+
+    pub fn record_open(&mut self, channels: &[Channel], item: &Item) {
+        self.record_items(channels, std::slice::from_ref(item));
+        if let Some(history) = self.items.get_mut(&item.id) {
+            history.opens += 1;
+        }
+    }
+
+    /// How many times the item was opened.
+    pub fn opens(&self, id: &str) -> u32 {
+        self.items.get(id).map_or(0, |item| item.opens)
+    }
+
+    /// Statistics of each channel, by channel url.
+    pub fn channel_stats(&self) -> HashMap<&str, ChannelStats> {
+        let mut stats: HashMap<&str, ChannelStats> = HashMap::new();
+        for item in self.items.values() {
+            let st = stats.entry(&item.channel).or_default();
+            st.items += 1;
+            st.opens += item.opens;
+            if item.opens > 0 {
+                st.opened += 1;
+            }
+        }
+
+        stats
+    }
+}
+
+/// Url of the channel the item belongs to.
+fn channel_url<'a>(channels: &'a [Channel], item: &Item) -> Option<&'a str> {
+    channels.iter().map(|ch| ch.url.as_str()).find(|url| {
+        item.id
+            .strip_prefix(url)
+            .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
@@ -1,16 +1,51 @@
 use std::{
-    collections::HashSet,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    io,
     ops::Deref,
-    sync::{self, Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, RwLock, RwLockReadGuard,
+        atomic::{AtomicU16, Ordering},
+    },
+    time::Instant,
 };
 
-use chrono::FixedOffset;
-use futures::future::join_all;
-use simple_rss_lib::data::{Loader, RefreshStatus};
+use chrono::Utc;
+use futures::future::{BoxFuture, join_all};
+use percent_encoding::percent_decode_str;
+use reqwest::Response;
+use simple_rss_lib::{
+    Error,
+    data::{ContentLoader, Fetcher, Hidden, ItemStore, RefreshError},
+    event::EventSender,
+    render::{Format, export},
+};
+use tokio::{fs, io::AsyncWriteExt, sync::mpsc};
+use tracing::{info, warn};
+
+use super::{
+    Channel, Data, Fetch, Item, daemon_socket_path, load_articles, load_data, load_fetch_metrics,
+    save_articles, save_fetch_metrics,
+};
+use crate::{
+    daemon::Client,
+    hooks,
+    http::{self, network_error},
+    source,
+};
 
-use super::{Channel, Data, Item, load_data};
+/// Number of downloaded bytes between two progress reports.
+const PROGRESS_STEP: u64 = 256 * 1024;
 
-pub struct LockGuard<'a>(sync::MutexGuard<'a, Data>);
+/// Held while the article cache is updated, so that articles loaded at the same time are
+/// all kept.
+static ARTICLES: Mutex<()> = Mutex::new(());
+
+/// Change sent to the daemon.
+type Change = BoxFuture<'static, anyhow::Result<()>>;
+
+pub struct LockGuard<'a>(RwLockReadGuard<'a, Data>);
 
 impl<'a> Deref for LockGuard<'a> {
     type Target = Vec<Item>;
@@ -20,137 +55,501 @@ impl<'a> Deref for LockGuard<'a> {
     }
 }
 
+/// Data shared between the UI and the refresh tasks.
+///
+/// Readers only block while a refresh or a read status change is being written, which
+/// is done after everything is fetched and prepared.
+///
+/// When connected to the daemon, the data is a copy of the daemon's. Refreshes and read
+/// status changes are sent to the daemon, which owns the stored data. Changes are sent one
+/// at a time, so that the daemon applies them in the order they were made.
 #[derive(Clone)]
 pub struct DataLoader {
-    version: Arc<Mutex<u16>>,
-    data: Arc<Mutex<Data>>,
+    version: Arc<AtomicU16>,
+    data: Arc<RwLock<Data>>,
+    daemon: Option<Client>,
+    changes: Option<mpsc::UnboundedSender<Change>>,
 }
 
 impl DataLoader {
-    pub fn get_data(&self) -> sync::MutexGuard<Data> {
-        self.data.lock().unwrap()
+    pub fn get_data(&self) -> RwLockReadGuard<'_, Data> {
+        self.data.read().unwrap()
+    }
+
+    /// Returns true if the data is owned by the daemon and shouldn't be saved locally.
+    pub fn uses_daemon(&self) -> bool {
+        self.daemon.is_some()
+    }
+
+    /// Replace all the data, e.g. with the daemon's updated copy.
+    pub fn set_data(&self, data: Data) {
+        http::set_channels(&data.channels);
+        *self.data.write().unwrap() = data;
+        self.bump_version();
+    }
+
+    /// Replace the channels, keeping the items.
+    pub fn set_channels(&self, channels: Vec<Channel>) {
+        http::set_channels(&channels);
+        self.data.write().unwrap().channels = channels;
+        self.bump_version();
+    }
+
+    fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Send the change to the daemon after the previous ones. Does nothing if not connected
+    /// to the daemon.
+    fn send_change<F>(&self, change: impl FnOnce(Client) -> F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        if let (Some(daemon), Some(changes)) = (&self.daemon, &self.changes) {
+            let _ = changes.send(Box::pin(change(daemon.clone())));
+        }
     }
 }
 
-impl Loader for DataLoader {
+impl ItemStore for DataLoader {
     type Guard<'a> = LockGuard<'a>;
 
     fn get_items(&self) -> Self::Guard<'_> {
-        LockGuard(self.data.lock().unwrap())
+        LockGuard(self.data.read().unwrap())
     }
 
     fn get_version(&self) -> u16 {
-        *self.version.lock().unwrap()
+        self.version.load(Ordering::Acquire)
+    }
+
+    fn set_read_by_id(&mut self, id: &str, read: bool) {
+        self.set_read_by_ids(&[id.to_string()], read);
+    }
+
+    fn set_read_by_ids(&mut self, ids: &[String], read: bool) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let ids_set: HashSet<_> = ids.iter().map(String::as_str).collect();
+        let mut newly_read = vec![];
+        let mut lock = self.data.write().unwrap();
+        for item in lock.items.iter_mut() {
+            if ids_set.contains(item.id.as_str()) {
+                if read && !item.read {
+                    newly_read.push(item.clone());
+                }
+                item.read = read;
+            }
+        }
+        drop(lock);
+        self.bump_version();
+
+        // Daemon runs the hooks when the change reaches it.
+        if self.daemon.is_none() {
+            for item in &newly_read {
+                hooks::read(item);
+            }
+        }
+
+        let ids = ids.to_vec();
+        self.send_change(|daemon| async move { daemon.set_read(ids, read).await });
     }
 
-    /// Set item at given index to read.
-    fn set_read(&mut self, index: usize, read: bool) {
-        let mut lock = self.data.lock().unwrap();
-        lock.items[index].read = read;
+    fn set_hidden_by_id(&mut self, id: &str, hidden: Option<Hidden>) {
+        let mut lock = self.data.write().unwrap();
+        if let Some(item) = lock.items.iter_mut().find(|it| it.id == id) {
+            item.hidden = hidden;
+            drop(lock);
+            self.bump_version();
+        }
+
+        let id = id.to_string();
+        self.send_change(|daemon| async move { daemon.set_hidden(id, hidden).await });
+    }
 
-        let mut version = self.version.lock().unwrap();
-        *version += 1;
+    fn set_starred_by_id(&mut self, id: &str, starred: bool) {
+        self.set_starred_by_ids(&[id.to_string()], starred);
     }
 
-    async fn load_item(url: &str) -> String {
-        let resp = reqwest::get(url).await;
-        match resp {
-            Err(err) => {
-                format!("Failed loading item: {err}")
+    fn set_starred_by_ids(&mut self, ids: &[String], starred: bool) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let ids_set: HashSet<_> = ids.iter().map(String::as_str).collect();
+        let mut lock = self.data.write().unwrap();
+        for item in lock.items.iter_mut() {
+            if ids_set.contains(item.id.as_str()) {
+                item.starred = starred;
             }
-            Ok(resp) => match resp.text().await {
-                Ok(data) => data,
-                Err(err) => format!("Failed loading item: {err}"),
-            },
         }
+        drop(lock);
+        self.bump_version();
+
+        let ids = ids.to_vec();
+        self.send_change(|daemon| async move { daemon.set_starred(ids, starred).await });
     }
 
-    async fn refresh(&mut self) -> RefreshStatus {
+    fn get_channels(&self) -> Vec<Channel> {
+        self.get_data().channels.clone()
+    }
+
+    /// Moves are indices into the current order, so they only make sense applied in the
+    /// order they were made. They go through the same queue as the other changes, so the
+    /// daemon gets each one after the previous has been applied.
+    fn move_channel(&mut self, from: usize, to: usize) {
+        if self.data.write().unwrap().move_channel(from, to) {
+            self.bump_version();
+        }
+
+        self.send_change(|daemon| async move { daemon.move_channel(from, to).await });
+    }
+}
+
+impl ContentLoader for DataLoader {
+    async fn load_item(&self, item: &Item) -> Result<String, Error> {
+        HttpContent.load_item(item).await
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        dir: &Path,
+        progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> Result<PathBuf, Error> {
+        HttpContent.download(item, dir, progress).await
+    }
+}
+
+/// Loads the items' pages and attachments over HTTP, with the auth of their channels.
+/// Used by the loaders, and on its own by commands that don't need the stored data.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpContent;
+
+impl ContentLoader for HttpContent {
+    async fn load_item(&self, item: &Item) -> Result<String, Error> {
+        let html = http::send(http::get_item(item, &item.link).await?)
+            .await?
+            .text()
+            .await
+            .map_err(network_error)?;
+        cache_article(item, &html);
+        Ok(html)
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        dir: &Path,
+        progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> Result<PathBuf, Error> {
+        download_file(item, dir, progress).await
+    }
+}
+
+/// Store the text of the article in the background, so that `simple-rss search` finds it.
+fn cache_article(item: &Item, html: &str) {
+    let id = item.id.clone();
+    let html = html.to_string();
+    tokio::task::spawn_blocking(move || {
+        let text = export(&html, Format::Text);
+        let _lock = ARTICLES.lock().unwrap();
+        let result = load_articles().and_then(|mut articles| {
+            articles.insert(&id, &text);
+            save_articles(&articles)
+        });
+        if let Err(err) = result {
+            warn!(error = %err, "failed to cache article");
+        }
+    });
+}
+
+impl Fetcher for DataLoader {
+    async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+        if let Some(daemon) = &self.daemon {
+            return refresh_daemon(self, daemon).await;
+        }
+
         // This syntax is used as workaround for clippy - making sure that lock is dropped before
         // await
         let channels = {
-            let lock = self.data.lock().unwrap();
+            let lock = self.data.read().unwrap();
             lock.channels.clone()
         };
 
-        let res = join_all(channels.iter().map(get_channel)).await;
+        let started = Instant::now();
+        let res = join_all(channels.iter().map(source::fetch_measured)).await;
 
         let mut items = vec![];
         let mut errors = vec![];
-        for result in res {
+        let mut fetches = vec![];
+        let mut fetched = HashSet::new();
+        for (channel, (result, fetch)) in channels.iter().zip(res) {
+            fetches.push((channel.url.as_str(), fetch));
             match result {
-                Ok(mut itms) => items.append(&mut itms),
-                Err(err) => errors.push(err),
+                Ok(mut itms) => {
+                    items.append(&mut itms);
+                    fetched.insert(channel.url.as_str());
+                }
+                Err(error) => errors.push(RefreshError {
+                    channel: channel.name.clone().unwrap_or_else(|| channel.url.clone()),
+                    url: channel.url.clone(),
+                    error,
+                }),
             }
         }
+        info!(
+            channels = channels.len(),
+            items = items.len(),
+            errors = errors.len(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "refresh finished"
+        );
+        record_fetches(fetches, Some(&channels));
 
-        if errors.is_empty() {
-            items.sort_by(|a, b| b.pub_date.cmp(&a.pub_date));
-
-            let mut lock = self.data.lock().unwrap();
-            let mut read_items = HashSet::new();
-            for it in &lock.items {
-                if it.read {
-                    read_items.insert(it.id.clone());
-                }
+        // Channels are fetched even if the others failed.
+        let mut lock = self.data.write().unwrap();
+        let now = Utc::now();
+        for channel in lock.channels.iter_mut() {
+            if fetched.contains(channel.url.as_str()) {
+                channel.last_fetched = Some(now);
             }
+        }
+
+        let result = if errors.is_empty() {
+            items.sort_by_key(|it| Reverse(it.pub_date));
 
+            // Read, starred and hidden status is copied under the write lock, so that no
+            // change is lost.
+            let status: HashMap<_, _> = lock
+                .items
+                .iter()
+                .map(|it| (it.id.clone(), (it.read, it.starred, it.hidden)))
+                .collect();
             for it in items.iter_mut() {
-                it.read = read_items.contains(&it.id);
+                (it.read, it.starred, it.hidden) = status.get(&it.id).copied().unwrap_or_default();
             }
 
             lock.items = items;
-
-            let mut version = self.version.lock().unwrap();
-            *version += 1;
-
-            RefreshStatus::Ok
+            Ok(())
         } else {
-            RefreshStatus::Error
-        }
+            Err(errors)
+        };
+        drop(lock);
+
+        self.bump_version();
+        result
     }
 }
 
 impl DataLoader {
     pub fn new() -> anyhow::Result<Self> {
         let data = load_data()?;
+        http::set_channels(&data.channels);
 
         Ok(Self {
-            data: Arc::new(Mutex::new(data)),
-            version: Arc::new(Mutex::new(0)),
+            data: Arc::new(RwLock::new(data)),
+            version: Arc::new(AtomicU16::new(0)),
+            daemon: None,
+            changes: None,
         })
     }
+
+    /// Loader with the data of the running daemon, or `None` if the daemon is not running.
+    pub async fn connect_daemon() -> anyhow::Result<Option<Self>> {
+        let Some(daemon) = Client::connect().await else {
+            return Ok(None);
+        };
+        let data = daemon.data().await?;
+        http::set_channels(&data.channels);
+
+        let (changes, mut rx) = mpsc::unbounded_channel::<Change>();
+        tokio::spawn(async move {
+            while let Some(change) = rx.recv().await {
+                // Failed change is fixed by the next update from the daemon.
+                let _ = change.await;
+            }
+        });
+
+        Ok(Some(Self {
+            data: Arc::new(RwLock::new(data)),
+            version: Arc::new(AtomicU16::new(0)),
+            daemon: Some(daemon),
+            changes: Some(changes),
+        }))
+    }
+
+    /// Keep the data in sync with the daemon and forward its refresh status as events.
+    /// Does nothing if not connected to the daemon.
+    pub fn watch_daemon(&self, event_tx: EventSender) {
+        if let Some(daemon) = &self.daemon {
+            daemon.watch(self.clone(), event_tx);
+        }
+    }
+
+    /// Fetch a single channel and replace its items. Items of other channels are kept.
+    pub async fn refresh_channel(&self, channel: &Channel) -> Result<(), Error> {
+        let (result, fetch) = source::fetch_measured(channel).await;
+        record_fetches([(channel.url.as_str(), fetch)], None);
+        let mut items = result?;
+
+        let mut lock = self.data.write().unwrap();
+        let prefix = format!("{}:", channel.url);
+
+        let status: HashMap<_, _> = lock
+            .items
+            .iter()
+            .filter(|it| it.id.starts_with(&prefix))
+            .map(|it| (it.id.clone(), (it.read, it.starred, it.hidden)))
+            .collect();
+        for it in items.iter_mut() {
+            (it.read, it.starred, it.hidden) = status.get(&it.id).copied().unwrap_or_default();
+        }
+
+        lock.items.retain(|it| !it.id.starts_with(&prefix));
+        lock.items.append(&mut items);
+        lock.items.sort_by_key(|it| Reverse(it.pub_date));
+
+        if let Some(ch) = lock.channels.iter_mut().find(|ch| ch.url == channel.url) {
+            ch.last_fetched = Some(Utc::now());
+        }
+        drop(lock);
+
+        self.bump_version();
+
+        Ok(())
+    }
+}
+
+/// Add the fetches to the stored metrics. Metrics of channels that are not in `channels`
+/// are removed, if given. Metrics are only for diagnostics, so failing to store them
+/// doesn't fail the refresh.
+fn record_fetches<'a>(
+    fetches: impl IntoIterator<Item = (&'a str, Fetch)>,
+    channels: Option<&[Channel]>,
+) {
+    let mut metrics = load_fetch_metrics().unwrap_or_default();
+    for (url, fetch) in fetches {
+        metrics.record(url, fetch);
+    }
+    if let Some(channels) = channels {
+        metrics.retain_channels(channels);
+    }
+
+    if let Err(err) = save_fetch_metrics(&metrics) {
+        warn!(error = %err, "failed to save fetch metrics");
+    }
 }
 
-async fn get_channel(channel: &Channel) -> anyhow::Result<Vec<Item>> {
-    let content = reqwest::get(&channel.url).await?.bytes().await?;
-    let feed = feed_rs::parser::parse(&content[..])?;
-
-    let items: Vec<_> = feed
-        .entries
-        .into_iter()
-        .filter_map(|it| {
-            Some(Item {
-                id: format!("{}:{}", channel.url, it.id),
-                channel_name: channel.name.as_ref().map_or_else(
-                    || {
-                        feed.title
-                            .as_ref()
-                            .map_or("Unnamed Channel".to_string(), |t| t.content.clone())
-                    },
-                    |v| v.clone(),
-                ),
-                title: it.title?.content,
-                description: it.summary.map(|d| d.content),
-                pub_date: it
-                    .updated
-                    .or(it.published)
-                    .map(|p| p.with_timezone(&FixedOffset::east_opt(0).unwrap())),
-                link: it.links.first()?.href.clone(),
-                read: false,
-            })
+async fn refresh_daemon(loader: &DataLoader, daemon: &Client) -> Result<(), Vec<RefreshError>> {
+    let result = async {
+        let errors = daemon.refresh().await?;
+        loader.set_data(daemon.data().await?);
+        anyhow::Ok(errors)
+    }
+    .await;
+
+    match result {
+        Ok(errors) if errors.is_empty() => Ok(()),
+        Ok(errors) => Err(errors),
+        Err(err) => Err(vec![RefreshError {
+            channel: "simple-rss daemon".to_string(),
+            url: daemon_socket_path().display().to_string(),
+            error: Error::Io(format!("{err:#}")),
+        }]),
+    }
+}
+
+/// Decoded last segment of the url path, safe to use as a file name.
+fn file_name(segment: &str) -> String {
+    let name = percent_decode_str(segment).decode_utf8_lossy();
+    let name: String = name
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
         })
         .collect();
 
-    Ok(items)
+    // Leading dots would hide the file, and trailing dots and spaces are dropped by Windows.
+    name.trim_start_matches(['.', ' '])
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+/// Creates a new file with the name in the directory. If the file exists, a number is added
+/// to the name, like `name (1).ext`, so that earlier downloads are not overwritten.
+async fn create_new(dir: &Path, name: &str) -> Result<(PathBuf, fs::File), Error> {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (name, None),
+    };
+
+    for nr in 0.. {
+        let name = match (nr, ext) {
+            (0, _) => name.to_string(),
+            (nr, Some(ext)) => format!("{stem} ({nr}).{ext}"),
+            (nr, None) => format!("{stem} ({nr})"),
+        };
+        let path = dir.join(name);
+        match fs::File::create_new(&path).await {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    unreachable!("a free file name is found")
+}
+
+async fn download_file(
+    item: &Item,
+    dir: &Path,
+    progress: impl Fn(u64, Option<u64>),
+) -> Result<PathBuf, Error> {
+    let Some(url) = &item.enclosure else {
+        return Err(Error::Io("Item has no attachment".to_string()));
+    };
+    let resp = http::send(http::get_item(item, url).await?).await?;
+
+    let name = resp
+        .url()
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .map(file_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "download".to_string());
+
+    fs::create_dir_all(dir).await?;
+    let (path, file) = create_new(dir, &name).await?;
+
+    // Truncated file would take the name of the retried download.
+    if let Err(err) = write_body(resp, file, progress).await {
+        let _ = fs::remove_file(&path).await;
+        return Err(err);
+    }
+    Ok(path)
+}
+
+async fn write_body(
+    mut resp: Response,
+    mut file: fs::File,
+    progress: impl Fn(u64, Option<u64>),
+) -> Result<(), Error> {
+    let total = resp.content_length();
+    let mut downloaded = 0;
+    let mut reported = 0;
+    while let Some(chunk) = resp.chunk().await.map_err(network_error)? {
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+
+        // Don't report progress for every chunk, it would flood the receiver.
+        if downloaded - reported >= PROGRESS_STEP || Some(downloaded) == total {
+            progress(downloaded, total);
+            reported = downloaded;
+        }
+    }
+    file.flush().await?;
+    Ok(())
 }
@@ -0,0 +1,136 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use simple_rss_lib::data::Channel;
+
+/// Number of the latest fetches of each channel that are kept.
+const MAX_FETCHES: usize = 20;
+
+/// Channels that take longer on average are reported as slow.
+const SLOW_FETCH_MS: u64 = 3000;
+
+/// How long fetching each channel took and how much was downloaded, used to find channels
+/// that slow the refreshes down.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchMetrics {
+    /// Metrics of each channel, by channel url.
+    channels: HashMap<String, ChannelMetrics>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ChannelMetrics {
+    /// Number of all the fetches and of the failed ones, since the channel was added.
+    total: u32,
+    failed: u32,
+    /// The latest fetches, oldest first.
+    recent: VecDeque<Fetch>,
+}
+
+/// Single fetch of a channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fetch {
+    pub at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Size of the downloaded response bodies.
+    pub bytes: u64,
+    pub ok: bool,
+}
+
+/// Summary of the latest fetches of a channel.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FetchSummary {
+    pub total: u32,
+    pub failed: u32,
+    /// Number of the latest fetches the averages are computed from.
+    pub recent: usize,
+    pub recent_failed: usize,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+    /// Average size of the successful fetches.
+    pub avg_bytes: u64,
+}
+
+impl FetchMetrics {
+    pub fn record(&mut self, url: &str, fetch: Fetch) {
+        let metrics = self.channels.entry(url.to_string()).or_default();
+        metrics.total += 1;
+        if !fetch.ok {
+            metrics.failed += 1;
+        }
+        if metrics.recent.len() == MAX_FETCHES {
+            metrics.recent.pop_front();
+        }
+        metrics.recent.push_back(fetch);
+    }
+
+    /// Forget the channels that were removed.
+    pub fn retain_channels(&mut self, channels: &[Channel]) {
+        self.channels
+            .retain(|url, _| channels.iter().any(|ch| ch.url == *url));
+    }
+
+    /// Summary of the channel's fetches, `None` if it was never fetched.
+    pub fn summary(&self, url: &str) -> Option<FetchSummary> {
+        let metrics = self.channels.get(url)?;
+        let count = metrics.recent.len() as u64;
+        let ok: Vec<_> = metrics.recent.iter().filter(|f| f.ok).collect();
+
+        Some(FetchSummary {
+            total: metrics.total,
+            failed: metrics.failed,
+            recent: metrics.recent.len(),
+            recent_failed: metrics.recent.len() - ok.len(),
+            avg_ms: metrics
+                .recent
+                .iter()
+                .map(|f| f.duration_ms)
+                .sum::<u64>()
+                .checked_div(count)
+                .unwrap_or(0),
+            max_ms: metrics
+                .recent
+                .iter()
+                .map(|f| f.duration_ms)
+                .max()
+                .unwrap_or(0),
+            avg_bytes: ok
+                .iter()
+                .map(|f| f.bytes)
+                .sum::<u64>()
+                .checked_div(ok.len() as u64)
+                .unwrap_or(0),
+        })
+    }
+}
+
+impl FetchSummary {
+    pub fn is_slow(&self) -> bool {
+        self.avg_ms >= SLOW_FETCH_MS
+    }
+
+    /// Whether at least half of the latest fetches failed.
+    pub fn is_unreliable(&self) -> bool {
+        self.recent > 1 && self.recent_failed * 2 >= self.recent
+    }
+}
+
+/// Duration in milliseconds, e.g. `1.2 s`.
+pub fn duration_label(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms} ms")
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+/// Size in bytes, e.g. `340 kB`.
+pub fn size_label(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{bytes} B"),
+        1_000..1_000_000 => format!("{} kB", bytes / 1_000),
+        _ => format!("{:.1} MB", bytes as f64 / 1_000_000.0),
+    }
+}
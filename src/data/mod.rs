@@ -1,12 +1,32 @@
-use std::{fs, io, path::Path};
-
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+mod articles;
+mod greader;
+mod history;
 mod loader;
+mod metrics;
 mod path;
-
-pub use loader::DataLoader;
-
-use path::{config_path, data_dir};
-use simple_rss_lib::data::{Channel, Data, Item};
+mod sync;
+mod ttrss;
+
+pub use articles::Articles;
+pub use greader::GReaderLoader;
+pub use history::History;
+pub use loader::{DataLoader, HttpContent};
+pub use metrics::{Fetch, FetchMetrics, duration_label, size_label};
+pub use path::{daemon_socket_path, download_dir, log_dir, remote_socket_path, settings_path};
+pub use sync::Flag;
+pub use ttrss::TtRssLoader;
+
+use path::{config_path, data_dir, state_dir};
+use simple_rss_lib::{
+    app::UiState,
+    data::{Channel, Data, Item},
+};
 
 pub fn load_data() -> io::Result<Data> {
     let items = load_items()?;
@@ -21,14 +41,27 @@ pub fn save_data(data: &Data) -> io::Result<()> {
     Ok(())
 }
 
+/// Files with the stored data, with their descriptions. Invalid files are ignored when loaded.
+pub fn data_files() -> [(&'static str, PathBuf); 6] {
+    [
+        ("Channels", config_path()),
+        ("Items", data_dir().join("data.json")),
+        ("History", data_dir().join("history.json")),
+        ("UI state", state_dir().join("state.json")),
+        ("Fetch metrics", state_dir().join("fetches.json")),
+        ("Article cache", state_dir().join("articles.json")),
+    ]
+}
+
 /// Creates all the directories that are needed to have a file at path.
 ///
 /// Example:
 /// `/foo/bar/baz.txt`: makes sure that path `/foo/bar` exists
 fn create_root(path: impl AsRef<Path>) -> io::Result<()> {
-    let exists = path.as_ref().parent().map(|p| p.exists());
-    if let Some(false) = exists {
-        fs::create_dir_all(&path)?;
+    if let Some(parent) = path.as_ref().parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
     }
 
     Ok(())
@@ -58,10 +91,14 @@ fn save_items(items: &[Item]) -> io::Result<()> {
     let path = data_dir().join("data.json");
     create_root(&path)?;
 
-    let file = fs::File::create(&path)?;
-    let writer = io::BufWriter::new(file);
-    serde_json::to_writer(writer, items)?;
-    Ok(())
+    // Written next to the file and renamed, so that other instances reading the items
+    // while the daemon saves them never see a partial file.
+    let tmp = path.with_extension("json.tmp");
+    let file = fs::File::create(&tmp)?;
+    let mut writer = io::BufWriter::new(file);
+    serde_json::to_writer(&mut writer, items)?;
+    writer.flush()?;
+    fs::rename(&tmp, &path)
 }
 
 fn load_channels() -> io::Result<Vec<Channel>> {
@@ -83,3 +120,87 @@ fn save_channels(channels: &[Channel]) -> io::Result<()> {
     serde_json::to_writer(writer, channels)?;
     Ok(())
 }
+
+/// Loads UI state from the previous session. Missing or invalid state is ignored.
+pub fn load_ui_state() -> io::Result<UiState> {
+    let path = state_dir().join("state.json");
+    create_root(&path)?;
+
+    let file = open_file_read(&path)?;
+    let reader = io::BufReader::new(file);
+    let state = serde_json::from_reader(reader).unwrap_or_default();
+    Ok(state)
+}
+
+pub fn save_ui_state(state: &UiState) -> io::Result<()> {
+    let path = state_dir().join("state.json");
+    create_root(&path)?;
+
+    let file = fs::File::create(&path)?;
+    let writer = io::BufWriter::new(file);
+    serde_json::to_writer(writer, state)?;
+    Ok(())
+}
+
+/// Loads interaction history. Missing or invalid history is ignored.
+pub fn load_history() -> io::Result<History> {
+    let path = data_dir().join("history.json");
+    create_root(&path)?;
+
+    let file = open_file_read(&path)?;
+    let reader = io::BufReader::new(file);
+    let history = serde_json::from_reader(reader).unwrap_or_default();
+    Ok(history)
+}
+
+pub fn save_history(history: &History) -> io::Result<()> {
+    let path = data_dir().join("history.json");
+    create_root(&path)?;
+
+    let file = fs::File::create(&path)?;
+    let writer = io::BufWriter::new(file);
+    serde_json::to_writer(writer, history)?;
+    Ok(())
+}
+
+/// Loads the cached articles. Missing or invalid cache is ignored.
+pub fn load_articles() -> io::Result<Articles> {
+    let path = state_dir().join("articles.json");
+    create_root(&path)?;
+
+    let file = open_file_read(&path)?;
+    let reader = io::BufReader::new(file);
+    let articles = serde_json::from_reader(reader).unwrap_or_default();
+    Ok(articles)
+}
+
+pub fn save_articles(articles: &Articles) -> io::Result<()> {
+    let path = state_dir().join("articles.json");
+    create_root(&path)?;
+
+    let file = fs::File::create(&path)?;
+    let writer = io::BufWriter::new(file);
+    serde_json::to_writer(writer, articles)?;
+    Ok(())
+}
+
+/// Loads metrics of the previous fetches. Missing or invalid metrics are ignored.
+pub fn load_fetch_metrics() -> io::Result<FetchMetrics> {
+    let path = state_dir().join("fetches.json");
+    create_root(&path)?;
+
+    let file = open_file_read(&path)?;
+    let reader = io::BufReader::new(file);
+    let metrics = serde_json::from_reader(reader).unwrap_or_default();
+    Ok(metrics)
+}
+
+pub fn save_fetch_metrics(metrics: &FetchMetrics) -> io::Result<()> {
+    let path = state_dir().join("fetches.json");
+    create_root(&path)?;
+
+    let file = fs::File::create(&path)?;
+    let writer = io::BufWriter::new(file);
+    serde_json::to_writer(writer, metrics)?;
+    Ok(())
+}
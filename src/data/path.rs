@@ -12,9 +12,40 @@ pub fn data_dir() -> PathBuf {
     data_dir.join("simple-rss")
 }
 
+pub fn state_dir() -> PathBuf {
+    let state_dir = std::env::var("XDG_STATE_HOME")
+        .map_or_else(|_| home_dir().join(".local").join("state"), PathBuf::from);
+
+    state_dir.join("simple-rss")
+}
+
+fn config_dir() -> PathBuf {
+    std::env::var("XDG_CONFIG_HOME").map_or_else(|_| home_dir().join(".config"), PathBuf::from)
+}
+
 pub fn config_path() -> PathBuf {
-    let config_dir =
-        std::env::var("XDG_CONFIG_HOME").map_or_else(|_| home_dir().join(".config"), PathBuf::from);
+    config_dir().join("simple-rss")
+}
+
+pub fn settings_path() -> PathBuf {
+    config_dir().join("simple-rss.toml")
+}
+
+/// Socket on which the running TUI listens for remote commands.
+pub fn remote_socket_path() -> PathBuf {
+    state_dir().join("remote.sock")
+}
+
+/// Socket on which the daemon serves the TUI and the CLI.
+pub fn daemon_socket_path() -> PathBuf {
+    state_dir().join("daemon.sock")
+}
+
+/// Directory of the daily log files.
+pub fn log_dir() -> PathBuf {
+    state_dir().join("logs")
+}
 
-    config_dir.join("simple-rss")
+pub fn download_dir() -> PathBuf {
+    std::env::var("XDG_DOWNLOAD_DIR").map_or_else(|_| home_dir().join("Downloads"), PathBuf::from)
 }
@@ -0,0 +1,228 @@
+//! Loader shared by the sync backends.
+//!
+//! The backends only talk to their servers, through [`SyncServer`]. [`SyncLoader`] keeps the
+//! synced items in a [`DataLoader`] and pushes read and starred status changes in batches.
+//! Changes that couldn't be pushed stay queued and are retried with the next change or
+//! refresh, so a sync doesn't revert them in the meantime.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use simple_rss_lib::{
+    Error,
+    data::{ContentLoader, Data, Fetcher, Hidden, ItemStore, RefreshError},
+};
+
+use super::{DataLoader, Item};
+use crate::config::SyncConfig;
+
+/// Maximum number of items whose status is pushed in a single request.
+const BATCH_SIZE: usize = 250;
+
+/// Server of a sync backend.
+pub trait SyncServer: Send + Sync + 'static {
+    fn new(config: SyncConfig, password: String) -> Self;
+
+    /// Url of the server, shown in refresh errors.
+    fn url(&self) -> &str;
+
+    /// Returns true if the stored item was synced from the server.
+    fn is_synced(id: &str) -> bool;
+
+    /// Channels and items on the server, merged with the stored ones.
+    fn sync(&self, data: &DataLoader) -> impl Future<Output = Result<Data, Error>> + Send;
+
+    /// Set the flag of the synced items with the given ids.
+    fn set_flag(
+        &self,
+        ids: &[&str],
+        flag: Flag,
+        value: bool,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// Status of an item that is synced both ways.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flag {
+    Read,
+    Starred,
+}
+
+/// [`Loader`](simple_rss_lib::data::Loader) that syncs with the server of a sync backend.
+///
+/// Items are kept in the [`DataLoader`], which is stored locally as usual, so the last
+/// synced items are available offline.
+pub struct SyncLoader<S> {
+    data: DataLoader,
+    server: Arc<S>,
+    pending: Arc<Pending>,
+}
+
+/// Status changes that weren't pushed to the server yet.
+#[derive(Default)]
+struct Pending {
+    changes: Mutex<HashMap<(String, Flag), bool>>,
+    /// Held while pushing, so changes of the same item reach the server in order.
+    push: tokio::sync::Mutex<()>,
+}
+
+impl<S> Clone for SyncLoader<S> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            server: self.server.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<S: SyncServer> SyncLoader<S> {
+    pub fn new(config: SyncConfig, password: String, data: DataLoader) -> Self {
+        Self {
+            data,
+            server: Arc::new(S::new(config, password)),
+            pending: Arc::default(),
+        }
+    }
+
+    /// Send the flag of the items to the server, waiting until it's done. Items that
+    /// weren't synced from the server are skipped. Changes that fail are retried later.
+    pub async fn push(&self, ids: &[String], flag: Flag, value: bool) -> Result<(), Error> {
+        self.queue(ids, flag, value);
+        self.flush().await
+    }
+
+    fn queue(&self, ids: &[String], flag: Flag, value: bool) {
+        let mut changes = self.pending.changes.lock().unwrap();
+        for id in ids.iter().filter(|id| S::is_synced(id)) {
+            changes.insert((id.clone(), flag), value);
+        }
+    }
+
+    /// Queue the change and push it in the background.
+    fn push_later(&self, ids: &[String], flag: Flag, value: bool) {
+        self.queue(ids, flag, value);
+
+        let loader = self.clone();
+        // Failed changes stay queued and are retried with the next push.
+        tokio::spawn(async move { loader.flush().await });
+    }
+
+    /// Push all the queued changes. Changes that weren't pushed are queued again, unless
+    /// the item was changed in the meantime.
+    async fn flush(&self) -> Result<(), Error> {
+        let _push = self.pending.push.lock().await;
+        let changes = std::mem::take(&mut *self.pending.changes.lock().unwrap());
+
+        let mut batches = vec![];
+        for flag in [Flag::Read, Flag::Starred] {
+            for value in [true, false] {
+                let ids: Vec<_> = changes
+                    .iter()
+                    .filter(|((_, f), v)| *f == flag && **v == value)
+                    .map(|((id, _), _)| id.as_str())
+                    .collect();
+                batches.extend(
+                    ids.chunks(BATCH_SIZE)
+                        .map(|ids| (ids.to_vec(), flag, value)),
+                );
+            }
+        }
+
+        for (idx, (ids, flag, value)) in batches.iter().enumerate() {
+            if let Err(err) = self.server.set_flag(ids, *flag, *value).await {
+                let mut pending = self.pending.changes.lock().unwrap();
+                for (ids, flag, value) in &batches[idx..] {
+                    for id in ids {
+                        pending.entry((id.to_string(), *flag)).or_insert(*value);
+                    }
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: SyncServer> ItemStore for SyncLoader<S> {
+    type Guard<'a> = <DataLoader as ItemStore>::Guard<'a>;
+
+    fn get_items(&self) -> Self::Guard<'_> {
+        self.data.get_items()
+    }
+
+    fn get_version(&self) -> u16 {
+        self.data.get_version()
+    }
+
+    fn set_read_by_id(&mut self, id: &str, read: bool) {
+        self.set_read_by_ids(&[id.to_string()], read);
+    }
+
+    fn set_read_by_ids(&mut self, ids: &[String], read: bool) {
+        self.data.set_read_by_ids(ids, read);
+        self.push_later(ids, Flag::Read, read);
+    }
+
+    fn set_starred_by_id(&mut self, id: &str, starred: bool) {
+        self.set_starred_by_ids(&[id.to_string()], starred);
+    }
+
+    fn set_starred_by_ids(&mut self, ids: &[String], starred: bool) {
+        self.data.set_starred_by_ids(ids, starred);
+        self.push_later(ids, Flag::Starred, starred);
+    }
+
+    /// Hidden items are local, the server doesn't know about them.
+    fn set_hidden_by_id(&mut self, id: &str, hidden: Option<Hidden>) {
+        self.data.set_hidden_by_id(id, hidden);
+    }
+}
+
+impl<S: SyncServer> Fetcher for SyncLoader<S> {
+    async fn refresh(&mut self) -> Result<(), Vec<RefreshError>> {
+        let error = |error| {
+            vec![RefreshError {
+                channel: "Sync server".to_string(),
+                url: self.server.url().to_string(),
+                error,
+            }]
+        };
+
+        // Queued changes are pushed first, so the server's status includes them.
+        self.flush().await.map_err(error)?;
+        let mut data = self.server.sync(&self.data).await.map_err(error)?;
+
+        // Changes made while syncing aren't included yet.
+        let changes = self.pending.changes.lock().unwrap().clone();
+        for ((id, flag), value) in changes {
+            if let Some(item) = data.items.iter_mut().find(|it| it.id == id) {
+                match flag {
+                    Flag::Read => item.read = value,
+                    Flag::Starred => item.starred = value,
+                }
+            }
+        }
+
+        self.data.set_data(data);
+        Ok(())
+    }
+}
+
+impl<S: SyncServer> ContentLoader for SyncLoader<S> {
+    async fn load_item(&self, item: &Item) -> Result<String, Error> {
+        self.data.load_item(item).await
+    }
+
+    async fn download(
+        &self,
+        item: &Item,
+        dir: &Path,
+        progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> Result<PathBuf, Error> {
+        self.data.download(item, dir, progress).await
+    }
+}
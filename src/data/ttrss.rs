@@ -0,0 +1,385 @@
+//! Sync with a Tiny Tiny RSS server through its JSON API.
+//!
+//! Feeds are managed on the server. Each refresh replaces the channels with the server's
+//! feeds, fetches articles newer than the newest stored one and updates read status of all
+//! the items, and which of them are starred. Changes of the status are pushed to the server
+//! right away, several articles in a single call.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use simple_rss_lib::Error;
+use tokio::sync::Mutex;
+
+use super::{
+    Channel, Data, DataLoader, Item,
+    sync::{Flag, SyncLoader, SyncServer},
+};
+use crate::{
+    config::SyncConfig,
+    http::{self, client, network_error},
+};
+
+/// Virtual feed with all the articles.
+const ALL_ARTICLES: i64 = -4;
+/// Category with all the feeds, excluding virtual ones.
+const ALL_FEEDS: i64 = -3;
+/// Separates the feed url and the article id in ids of synced items, telling them apart
+/// from items fetched from the feeds directly.
+const ID_MARKER: &str = ":ttrss:";
+/// Field of `updateArticle` that sets the starred flag.
+const STARRED_FIELD: u8 = 0;
+/// Field of `updateArticle` that sets the unread flag.
+const UNREAD_FIELD: u8 = 2;
+
+/// Maximum number of headlines the server returns at once.
+const PAGE_SIZE: usize = 200;
+/// Maximum number of articles fetched in a single sync.
+const MAX_ITEMS: usize = 1000;
+/// Maximum number of unread articles whose status is synced. Older ones are shown as read.
+const MAX_UNREAD: usize = 10_000;
+/// Maximum number of starred articles whose status is synced.
+const MAX_STARRED: usize = 10_000;
+
+/// [`SyncLoader`] for a Tiny Tiny RSS server.
+pub type TtRssLoader = SyncLoader<Server>;
+
+pub struct Server {
+    /// Url of the API, e.g. `https://example.com/tt-rss/api/`.
+    url: String,
+    username: String,
+    password: String,
+    /// Id of the session obtained by logging in.
+    session: Mutex<Option<String>>,
+}
+
+impl SyncServer for Server {
+    fn new(config: SyncConfig, password: String) -> Self {
+        let mut url = config.url;
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+
+        Self {
+            url,
+            username: config.username,
+            password,
+            session: Mutex::new(None),
+        }
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn is_synced(id: &str) -> bool {
+        article_id(id).is_some()
+    }
+
+    async fn sync(&self, data: &DataLoader) -> Result<Data, Error> {
+        let feeds: Vec<Feed> = self
+            .call("getFeeds", json!({ "cat_id": ALL_FEEDS }))
+            .await?;
+
+        // Items fetched directly from the feeds, before sync was configured, are replaced.
+        let mut items: Vec<_> = data
+            .get_data()
+            .items
+            .iter()
+            .filter(|it| article_id(&it.id).is_some())
+            .cloned()
+            .collect();
+        let since_id = items.iter().filter_map(|it| article_id(&it.id)).max();
+
+        let fetched = self
+            .headlines("all_articles", since_id, MAX_ITEMS, true)
+            .await?;
+        let unread: HashSet<_> = self
+            .headlines("unread", None, MAX_UNREAD, false)
+            .await?
+            .iter()
+            .map(|h| h.id)
+            .collect();
+        let starred: HashSet<_> = self
+            .headlines("marked", None, MAX_STARRED, false)
+            .await?
+            .iter()
+            .map(|h| h.id)
+            .collect();
+
+        let now = Utc::now();
+        let channels: Vec<_> = feeds
+            .iter()
+            .map(|feed| Channel {
+                name: Some(feed.title.clone()),
+                url: feed.feed_url.clone(),
+                last_fetched: Some(now),
+                ..Default::default()
+            })
+            .collect();
+        let feeds: HashMap<_, _> = feeds.iter().map(|feed| (feed.id, feed)).collect();
+
+        // Items of removed feeds are dropped, fetched items replace stored ones.
+        items.retain(|it| {
+            channels
+                .iter()
+                .any(|ch| it.id.starts_with(&format!("{}:", ch.url)))
+        });
+        let mut ids: HashMap<_, _> = items
+            .iter()
+            .enumerate()
+            .map(|(idx, it)| (it.id.clone(), idx))
+            .collect();
+        for headline in fetched {
+            let Some(item) = headline.into_item(&feeds) else {
+                continue;
+            };
+            match ids.get(&item.id) {
+                // Hiding is local, so it's kept.
+                Some(&idx) => {
+                    items[idx] = Item {
+                        hidden: items[idx].hidden,
+                        ..item
+                    }
+                }
+                None => {
+                    ids.insert(item.id.clone(), items.len());
+                    items.push(item);
+                }
+            }
+        }
+
+        for item in items.iter_mut() {
+            let id = article_id(&item.id);
+            item.read = id.is_none_or(|id| !unread.contains(&id));
+            item.starred = id.is_some_and(|id| starred.contains(&id));
+        }
+        items.sort_by_key(|it| Reverse(it.pub_date));
+
+        Ok(Data { channels, items })
+    }
+
+    /// All the articles are updated with a single call, listing their ids.
+    async fn set_flag(&self, ids: &[&str], flag: Flag, value: bool) -> Result<(), Error> {
+        let article_ids: Vec<_> = ids
+            .iter()
+            .filter_map(|id| article_id(id))
+            .map(|id| id.to_string())
+            .collect();
+        if article_ids.is_empty() {
+            return Ok(());
+        }
+
+        let (field, value) = match flag {
+            Flag::Read => (UNREAD_FIELD, !value),
+            Flag::Starred => (STARRED_FIELD, value),
+        };
+        let params = json!({
+            "article_ids": article_ids.join(","),
+            "mode": u8::from(value),
+            "field": field,
+        });
+        self.call::<Value>("updateArticle", params).await?;
+        Ok(())
+    }
+}
+
+impl Server {
+    /// Headlines of all the feeds, in pages until `limit` is reached.
+    async fn headlines(
+        &self,
+        view_mode: &str,
+        since_id: Option<u64>,
+        limit: usize,
+        excerpt: bool,
+    ) -> Result<Vec<Headline>, Error> {
+        let mut headlines = vec![];
+        loop {
+            let mut params = json!({
+                "feed_id": ALL_ARTICLES,
+                "view_mode": view_mode,
+                "limit": PAGE_SIZE,
+                "skip": headlines.len(),
+                "show_excerpt": excerpt,
+                "include_attachments": excerpt,
+            });
+            if let Some(since_id) = since_id {
+                params["since_id"] = json!(since_id);
+            }
+
+            let page: Vec<Headline> = self.call("getHeadlines", params).await?;
+            let done = page.len() < PAGE_SIZE;
+            headlines.extend(page);
+            if done || headlines.len() >= limit {
+                return Ok(headlines);
+            }
+        }
+    }
+
+    /// Call the API operation, logging in first if there is no session yet. Expired
+    /// session is renewed once.
+    async fn call<T: DeserializeOwned>(&self, op: &str, params: Value) -> Result<T, Error> {
+        let content = match self.call_in_session(op, params.clone()).await {
+            Err(ApiError::NotLoggedIn) => {
+                *self.session.lock().await = None;
+                self.call_in_session(op, params).await
+            }
+            res => res,
+        }
+        .map_err(ApiError::into_error)?;
+
+        serde_json::from_value(content).map_err(|err| Error::Parse(err.to_string()))
+    }
+
+    async fn call_in_session(&self, op: &str, mut params: Value) -> Result<Value, ApiError> {
+        params["sid"] = json!(self.session().await?);
+        params["op"] = json!(op);
+        self.post(params).await
+    }
+
+    /// Id of the session, logging in if there is none yet.
+    async fn session(&self) -> Result<String, ApiError> {
+        let mut session = self.session.lock().await;
+        if let Some(session) = session.as_ref() {
+            return Ok(session.clone());
+        }
+
+        let content = self
+            .post(json!({
+                "op": "login",
+                "user": self.username,
+                "password": self.password,
+            }))
+            .await?;
+        let id = content["session_id"]
+            .as_str()
+            .ok_or_else(|| ApiError::Other(Error::Parse("Login response has no session".into())))?;
+        *session = Some(id.to_string());
+        Ok(id.to_string())
+    }
+
+    async fn post(&self, body: Value) -> Result<Value, ApiError> {
+        let response: Response = http::send(
+            client()
+                .post(&self.url)
+                .body(body.to_string())
+                .header("Content-Type", "application/json"),
+        )
+        .await?
+        .bytes()
+        .await
+        .map_err(network_error)
+        .and_then(|body| {
+            serde_json::from_slice(&body).map_err(|err| Error::Parse(err.to_string()))
+        })?;
+
+        if response.status == 0 {
+            return Ok(response.content);
+        }
+        match response.content["error"].as_str() {
+            Some("NOT_LOGGED_IN") => Err(ApiError::NotLoggedIn),
+            Some(error) => Err(ApiError::Other(Error::Network(format!(
+                "Server returned {error}"
+            )))),
+            None => Err(ApiError::Other(Error::Network(
+                "Server returned an error".to_string(),
+            ))),
+        }
+    }
+}
+
+enum ApiError {
+    /// Session expired or was never valid.
+    NotLoggedIn,
+    Other(Error),
+}
+
+impl ApiError {
+    fn into_error(self) -> Error {
+        match self {
+            ApiError::NotLoggedIn => Error::Network("Not logged in".to_string()),
+            ApiError::Other(err) => err,
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(err: Error) -> Self {
+        ApiError::Other(err)
+    }
+}
+
+/// Id of the article on the server, from the id of the stored item. `None` if the item
+/// wasn't synced from the server, even if its own id is a number.
+fn article_id(id: &str) -> Option<u64> {
+    id.rsplit_once(ID_MARKER)?.1.parse().ok()
+}
+
+#[derive(Deserialize)]
+struct Response {
+    status: i32,
+    content: Value,
+}
+
+#[derive(Deserialize)]
+struct Feed {
+    id: i64,
+    title: String,
+    feed_url: String,
+}
+
+#[derive(Deserialize)]
+struct Headline {
+    id: u64,
+    title: Option<String>,
+    link: Option<String>,
+    /// Number in newer versions, string in older ones.
+    feed_id: Value,
+    author: Option<String>,
+    updated: Option<i64>,
+    excerpt: Option<String>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    content_url: String,
+}
+
+impl Headline {
+    /// Stored item, or `None` if the article doesn't belong to any feed or has no link.
+    /// Read status is set from the unread ids afterwards.
+    fn into_item(self, feeds: &HashMap<i64, &Feed>) -> Option<Item> {
+        let feed_id = match &self.feed_id {
+            Value::Number(id) => id.as_i64()?,
+            Value::String(id) => id.parse().ok()?,
+            _ => return None,
+        };
+        let feed = feeds.get(&feed_id)?;
+
+        Some(Item {
+            id: format!("{}{ID_MARKER}{}", feed.feed_url, self.id),
+            channel_name: feed.title.clone(),
+            title: self.title?,
+            author: self.author.filter(|a| !a.is_empty()),
+            description: self.excerpt.filter(|e| !e.is_empty()),
+            pub_date: self
+                .updated
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|p| p.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+            link: self.link?,
+            enclosure: self.attachments.into_iter().next().map(|a| a.content_url),
+            alternate_links: vec![],
+            read: false,
+            starred: false,
+            tags: vec![],
+            hidden: None,
+        })
+    }
+}
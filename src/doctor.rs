@@ -0,0 +1,260 @@
+//! Diagnostics of the config, the stored data and the channels.
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use chrono::{TimeDelta, Utc};
+use colored::Colorize;
+use futures::future::join_all;
+use reqwest::Url;
+use serde_json::Value;
+use simple_rss_lib::{Error, data::Channel};
+
+use crate::{
+    config::Config,
+    data::{data_files, duration_label, load_data, load_fetch_metrics, settings_path},
+    http::{self, network_error},
+    source::{self, Source},
+};
+
+/// Feeds without new posts for this many days are reported as dead.
+const DEAD_FEED_DAYS: i64 = 365;
+
+/// Counts of the found problems.
+#[derive(Default)]
+struct Report {
+    errors: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&mut self, what: &str, message: &str) {
+        println!("✅ {} {message}", what.bold());
+    }
+
+    fn warning(&mut self, what: &str, message: &str) {
+        self.warnings += 1;
+        println!("⚠️  {} {}", what.bold(), message.yellow());
+    }
+
+    fn error(&mut self, what: &str, message: &str) {
+        self.errors += 1;
+        println!("❌ {} {}", what.bold(), message.red());
+    }
+}
+
+/// Result of fetching a channel's feed.
+struct FeedCheck {
+    latency: Duration,
+    result: Result<Feed, Error>,
+}
+
+struct Feed {
+    /// Status code of the feed's response, or name of the source for other channels.
+    status: String,
+    entries: usize,
+    newest: Option<TimeDelta>,
+}
+
+/// Check the config, the data files and all the channels, and print what is wrong.
+pub async fn run() -> anyhow::Result<()> {
+    let mut report = Report::default();
+
+    let config = match Config::load() {
+        Ok(config) => {
+            let path = settings_path();
+            let missing = if path.exists() {
+                ""
+            } else {
+                " (not created, defaults are used)"
+            };
+            report.ok("Config", &format!("{}{missing}", path.display()));
+            config
+        }
+        Err(err) => {
+            report.error("Config", &format!("{err:#}"));
+            Config::default()
+        }
+    };
+    http::init(&config)?;
+    source::init(&config);
+
+    for (name, path) in data_files() {
+        check_file(&mut report, name, &path);
+    }
+
+    let data = load_data()?;
+    http::set_channels(&data.channels);
+    if data.channels.is_empty() {
+        report.warning("Channels", "no channels added");
+    }
+
+    let metrics = load_fetch_metrics()?;
+    let checks = join_all(data.channels.iter().map(check_channel)).await;
+    let mut seen = HashMap::new();
+    for (idx, (channel, check)) in data.channels.iter().zip(checks).enumerate() {
+        let what = format!("{idx} {}", channel.name.as_deref().unwrap_or(&channel.url));
+        match seen.entry(feed_key(&channel.url)) {
+            Entry::Occupied(first) => {
+                report.warning(&what, &format!("duplicate of channel {}", first.get()))
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(idx);
+            }
+        }
+
+        let latency = format!("{} ms", check.latency.as_millis());
+        match check.result {
+            Err(err) => report.error(&what, &format!("{err} ({latency})")),
+            Ok(feed) if feed.entries == 0 => report.warning(
+                &what,
+                &format!("{}, {latency}, feed has no items", feed.status),
+            ),
+            Ok(feed) => {
+                let age = feed.newest.map(|age| age.num_days());
+                let message = format!(
+                    "{}, {latency}, {} items, newest {}",
+                    feed.status,
+                    feed.entries,
+                    age.map_or("undated".to_string(), |days| format!("{days} days ago"))
+                );
+                if age.is_some_and(|days| days > DEAD_FEED_DAYS) {
+                    report.warning(&what, &format!("{message}, feed looks dead"));
+                } else {
+                    report.ok(&what, &message);
+                }
+            }
+        }
+
+        // Single check can be lucky, the previous refreshes show how the channel usually is.
+        if let Some(summary) = metrics.summary(&channel.url) {
+            if summary.is_slow() {
+                report.warning(
+                    &what,
+                    &format!(
+                        "slow, refreshes took {} on average, up to {}",
+                        duration_label(summary.avg_ms),
+                        duration_label(summary.max_ms)
+                    ),
+                );
+            }
+            if summary.is_unreliable() {
+                report.warning(
+                    &what,
+                    &format!(
+                        "failed in {} of the last {} refreshes",
+                        summary.recent_failed, summary.recent
+                    ),
+                );
+            }
+        }
+    }
+
+    println!();
+    match (report.errors, report.warnings) {
+        (0, 0) => println!("✅ {}", "Everything looks fine!".green().bold()),
+        (0, warnings) => println!(
+            "⚠️  {}",
+            format!("Found {warnings} warnings").yellow().bold()
+        ),
+        (errors, warnings) => bail!("Found {errors} errors and {warnings} warnings"),
+    }
+    Ok(())
+}
+
+/// Check that the file can be read and is valid JSON. Missing files are created when needed.
+fn check_file(report: &mut Report, name: &str, path: &Path) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            report.ok(name, &format!("{} (not created yet)", path.display()));
+            return;
+        }
+        Err(err) => {
+            report.error(name, &format!("{}: {err}", path.display()));
+            return;
+        }
+    };
+
+    if content.trim().is_empty() {
+        report.ok(name, &format!("{} (empty)", path.display()));
+        return;
+    }
+    match serde_json::from_str::<Value>(&content) {
+        Ok(_) => report.ok(name, &path.display().to_string()),
+        Err(err) => report.error(
+            name,
+            &format!(
+                "{}: invalid JSON ({err}), it is ignored and will be overwritten",
+                path.display()
+            ),
+        ),
+    }
+}
+
+async fn check_channel(channel: &Channel) -> FeedCheck {
+    let start = Instant::now();
+    let result = fetch_feed(channel).await;
+    FeedCheck {
+        latency: start.elapsed(),
+        result,
+    }
+}
+
+async fn fetch_feed(channel: &Channel) -> Result<Feed, Error> {
+    // Channels read from other sources have no feed to check, only their items.
+    let channel_source = source::for_channel(channel);
+    if channel_source.name() != source::Feed.name() {
+        let items = channel_source.fetch(channel).await?;
+        let newest = items
+            .iter()
+            .filter_map(|item| item.pub_date)
+            .max()
+            .map(|date| Utc::now() - date.with_timezone(&Utc));
+        return Ok(Feed {
+            status: channel_source.name().to_string(),
+            entries: items.len(),
+            newest,
+        });
+    }
+
+    let response = http::send(http::get_channel(channel).await?).await?;
+    let status = response.status().as_u16();
+    let content = response.bytes().await.map_err(network_error)?;
+    let feed = feed_rs::parser::parse(&content[..])
+        .map_err(|err| Error::Parse(format!("Not a feed: {err}")))?;
+
+    let newest = feed
+        .entries
+        .iter()
+        .filter_map(|entry| entry.updated.or(entry.published))
+        .max()
+        .map(|date| Utc::now() - date);
+    Ok(Feed {
+        status: status.to_string(),
+        entries: feed.entries.len(),
+        newest,
+    })
+}
+
+/// Url of the feed without the parts that don't change the feed, to find duplicates.
+fn feed_key(url: &str) -> String {
+    let Ok(url) = Url::parse(url) else {
+        return url.to_string();
+    };
+
+    let host = url
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches("www.");
+    let path = url.path().trim_end_matches('/');
+    match url.query() {
+        Some(query) => format!("{host}{path}?{query}"),
+        None => format!("{host}{path}"),
+    }
+}
@@ -1,25 +1,70 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use crossterm::event::{Event as CrosstermEvent, KeyCode};
 use futures::{FutureExt, StreamExt};
-use simple_rss_lib::event::{Event, EventSender, KeyboardEvent};
+use simple_rss_lib::event::{Event, EventSender, InputEvent};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
+};
+
+use crate::keymap::{Key, Keymap, Matcher};
 
 pub const TICK_FPS: f64 = 30.0;
 
+/// Tick rate while nothing is animated.
+const IDLE_TICK_FPS: f64 = 1.0;
+
+/// How long to wait for the next key of a sequence.
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+enum Control {
+    Suspend(oneshot::Sender<()>),
+    Resume,
+    Animating(bool),
+}
+
 /// A thread that handles reading crossterm events and emitting tick events on a regular schedule.
 pub struct EventTask {
     sender: EventSender,
+    matcher: Matcher,
+    control: mpsc::UnboundedReceiver<Control>,
+}
+
+/// Handle for pausing the [`EventTask`] while another program uses the terminal and
+/// for changing its tick rate.
+#[derive(Clone)]
+pub struct EventTaskHandle {
+    control: mpsc::UnboundedSender<Control>,
+    animating: Arc<AtomicBool>,
 }
 
 impl EventTask {
-    pub fn new(sender: EventSender) -> Self {
-        Self { sender }
+    pub fn new(sender: EventSender, keymap: Keymap) -> (Self, EventTaskHandle) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = Self {
+            sender,
+            matcher: Matcher::new(keymap),
+            control: rx,
+        };
+
+        let handle = EventTaskHandle {
+            control: tx,
+            animating: Arc::new(AtomicBool::new(true)),
+        };
+        (task, handle)
     }
 
-    pub async fn run(self) -> anyhow::Result<()> {
-        let tick_rate = Duration::from_secs_f64(1.0 / TICK_FPS);
-        let mut tick = tokio::time::interval(tick_rate);
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut tick = tick_interval(true);
         let mut reader = crossterm::event::EventStream::new();
+        let mut sequence_deadline = None;
         loop {
             let tick_delay = tick.tick();
             let crossterm_event = reader.next().fuse();
@@ -30,30 +75,106 @@ impl EventTask {
               _ = tick_delay => {
                 self.sender.send(Event::Tick);
               }
+              _ = sleep_until(sequence_deadline), if sequence_deadline.is_some() => {
+                sequence_deadline = None;
+                if let Some(event) = self.matcher.flush() {
+                    self.sender.send(Event::Keyboard(event));
+                }
+              }
               Some(Ok(evt)) = crossterm_event => {
-                if let CrosstermEvent::Key(key_evt) = evt {
-                    send_keycode(key_evt.code, &self.sender);
+                match evt {
+                    CrosstermEvent::Key(key_evt) => {
+                        send_key(key_evt.into(), &mut self.matcher, &self.sender);
+                        sequence_deadline = self
+                            .matcher
+                            .is_pending()
+                            .then(|| Instant::now() + SEQUENCE_TIMEOUT);
+                    }
+                    CrosstermEvent::Resize(width, height) => {
+                        self.sender.send(Event::Resize(width, height));
+                    }
+                    _ => (),
                 }
               }
+              Some(control) = self.control.recv() => match control {
+                Control::Suspend(ack) => {
+                    // Reader has to be dropped, otherwise it keeps consuming the input.
+                    drop(reader);
+                    let _ = ack.send(());
+
+                    while let Some(control) = self.control.recv().await {
+                        match control {
+                            Control::Resume => break,
+                            Control::Animating(animating) => tick = tick_interval(animating),
+                            Control::Suspend(ack) => {
+                                let _ = ack.send(());
+                            }
+                        }
+                    }
+                    reader = crossterm::event::EventStream::new();
+                }
+                Control::Animating(animating) => tick = tick_interval(animating),
+                Control::Resume => (),
+              }
             };
         }
         Ok(())
     }
 }
 
-fn send_keycode(code: KeyCode, sender: &EventSender) {
-    let event = match code {
-        KeyCode::Left | KeyCode::Char('h') => KeyboardEvent::Left,
-        KeyCode::Right | KeyCode::Char('l') => KeyboardEvent::Right,
-        KeyCode::Up | KeyCode::Char('k') => KeyboardEvent::Up,
-        KeyCode::Down | KeyCode::Char('j') => KeyboardEvent::Down,
-        KeyCode::Esc | KeyCode::Char('q') => KeyboardEvent::Back,
-        KeyCode::Enter => KeyboardEvent::Enter,
-        KeyCode::Char(' ') => KeyboardEvent::Space,
-        KeyCode::Char('o') => KeyboardEvent::Open,
-        KeyCode::Char('?') => KeyboardEvent::Help,
+impl EventTaskHandle {
+    /// Stops reading terminal events. Returns once the reading has stopped.
+    pub async fn suspend(&self) {
+        let (tx, rx) = oneshot::channel();
+        if self.control.send(Control::Suspend(tx)).is_ok() {
+            let _ = rx.await;
+        }
+    }
+
+    pub fn resume(&self) {
+        let _ = self.control.send(Control::Resume);
+    }
+
+    /// Tick at the full rate while something is animated, otherwise rarely.
+    pub fn set_animating(&self, animating: bool) {
+        if self.animating.swap(animating, Ordering::Relaxed) != animating {
+            let _ = self.control.send(Control::Animating(animating));
+        }
+    }
+}
+
+fn tick_interval(animating: bool) -> tokio::time::Interval {
+    let fps = if animating { TICK_FPS } else { IDLE_TICK_FPS };
+    tokio::time::interval(Duration::from_secs_f64(1.0 / fps))
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    if let Some(deadline) = deadline {
+        tokio::time::sleep_until(deadline).await;
+    }
+}
+
+fn send_key(key: Key, matcher: &mut Matcher, sender: &EventSender) {
+    for event in matcher.push(key) {
+        sender.send(Event::Keyboard(event));
+    }
+
+    let input = match key.code() {
+        // Shortcuts like Ctrl-W don't type anything.
+        KeyCode::Char(ch) if key.is_shortcut() => InputEvent::Shortcut(ch, key.modifiers()),
+        _ if key.is_shortcut() => return,
+        KeyCode::Char(ch) => InputEvent::Char(ch),
+        KeyCode::Backspace => InputEvent::Backspace,
+        KeyCode::Delete => InputEvent::Delete,
+        KeyCode::Enter => InputEvent::Enter,
+        KeyCode::Esc => InputEvent::Esc,
+        KeyCode::Left => InputEvent::Left,
+        KeyCode::Right => InputEvent::Right,
+        KeyCode::Up => InputEvent::Up,
+        KeyCode::Down => InputEvent::Down,
+        KeyCode::Home => InputEvent::Home,
+        KeyCode::End => InputEvent::End,
         _ => return,
     };
-
-    sender.send(Event::Keyboard(event));
+    sender.send(Event::Input(input));
 }
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::LazyLock,
+};
+
+use chrono::{Local, Utc};
+use regex::Regex;
+use simple_rss_lib::data::Item;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use super::{escape, paragraphs};
+
+const TITLE: &str = "simple-rss articles";
+/// Language of books whose articles don't declare one, "undetermined".
+const UNKNOWN_LANGUAGE: &str = "und";
+
+/// `lang` attribute of the `html` element.
+static HTML_LANG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<html\b[^>]*?\slang\s*=\s*["']?([a-z]{1,8}(?:-[a-z0-9]{1,8})*)"#).unwrap()
+});
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLE: &str = "\
+h1 { margin-bottom: 0.2em; }
+.meta { margin-top: 0; color: #666; font-size: 0.9em; }
+";
+
+/// Write the articles, given as items with their HTML, to an EPUB 3 book with a chapter
+/// for each article. Articles are rendered as plain text paragraphs, which every reader
+/// supports.
+pub fn write(path: &Path, articles: &[(&Item, String)]) -> anyhow::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut zip = ZipWriter::new(file);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // Mimetype has to be the first file and can't be compressed.
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER.as_bytes())?;
+
+    let languages: Vec<_> = articles.iter().map(|(_, html)| language(html)).collect();
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(package(articles.len(), book_language(&languages)).as_bytes())?;
+
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(nav(articles, book_language(&languages)).as_bytes())?;
+
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(STYLE.as_bytes())?;
+
+    for (idx, ((item, html), lang)) in articles.iter().zip(&languages).enumerate() {
+        let lang = lang.as_deref().unwrap_or(UNKNOWN_LANGUAGE);
+        zip.start_file(format!("OEBPS/{}", chapter_file(idx)), deflated)?;
+        zip.write_all(chapter(item, html, lang).as_bytes())?;
+    }
+
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+fn chapter_file(idx: usize) -> String {
+    format!("article-{}.xhtml", idx + 1)
+}
+
+/// Language the article's page declares, like `en` or `pt-BR`.
+fn language(html: &str) -> Option<String> {
+    HTML_LANG.captures(html).map(|caps| caps[1].to_string())
+}
+
+/// Language of most of the articles, by their primary language, e.g. `en` for `en-US`.
+fn book_language(languages: &[Option<String>]) -> &str {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for lang in languages.iter().flatten() {
+        let primary = lang.split('-').next().unwrap_or(lang);
+        *counts.entry(primary).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(lang, count)| (*count, std::cmp::Reverse(*lang)))
+        .map_or(UNKNOWN_LANGUAGE, |(lang, _)| lang)
+}
+
+/// Package document with the metadata, the files and their reading order.
+fn package(nr_chapters: usize, language: &str) -> String {
+    let now = Utc::now();
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for idx in 0..nr_chapters {
+        let _ = writeln!(
+            manifest,
+            r#"    <item id="article-{}" href="{}" media-type="application/xhtml+xml"/>"#,
+            idx + 1,
+            chapter_file(idx)
+        );
+        let _ = writeln!(spine, r#"    <itemref idref="article-{}"/>"#, idx + 1);
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">simple-rss-{}</dc:identifier>
+    <dc:title>{TITLE} {}</dc:title>
+    <dc:language>{}</dc:language>
+    <dc:creator>simple-rss</dc:creator>
+    <meta property="dcterms:modified">{}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="style" href="style.css" media-type="text/css"/>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        now.timestamp(),
+        now.with_timezone(&Local).format("%Y-%m-%d"),
+        escape(language),
+        now.format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
+
+/// Table of contents, listing the articles.
+fn nav(articles: &[(&Item, String)], language: &str) -> String {
+    let mut list = String::new();
+    for (idx, (item, _)) in articles.iter().enumerate() {
+        let _ = writeln!(
+            list,
+            r#"      <li><a href="{}">{}</a></li>"#,
+            chapter_file(idx),
+            escape(&item.title)
+        );
+    }
+
+    xhtml(
+        "Contents",
+        language,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n    <h1>Contents</h1>\n    <ol>\n{list}    </ol>\n  </nav>\n"
+        ),
+    )
+}
+
+fn chapter(item: &Item, html: &str, language: &str) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape(&item.title));
+
+    let mut meta = vec![escape(&item.channel_name)];
+    if let Some(date) = item.pub_date {
+        let date = date.with_timezone(&Local);
+        meta.push(date.format("%Y-%m-%d").to_string());
+    }
+    if let Some(author) = &item.author {
+        meta.push(escape(author));
+    }
+    let _ = writeln!(body, "  <p class=\"meta\">{}</p>", meta.join(" · "));
+    let _ = writeln!(
+        body,
+        "  <p class=\"meta\"><a href=\"{0}\">{0}</a></p>",
+        escape(&item.link)
+    );
+
+    for paragraph in paragraphs(html) {
+        let _ = writeln!(body, "  <p>{}</p>", escape(&paragraph));
+    }
+
+    xhtml(&item.title, language, &body)
+}
+
+fn xhtml(title: &str, language: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+  <title>{}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+  {body}</body>
+</html>
+"#,
+        escape(title),
+        lang = escape(language),
+    )
+}
@@ -0,0 +1,117 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+use simple_rss_lib::data::Item;
+
+use super::{escape, paragraphs};
+
+const STYLE: &str = "\
+body { max-width: 42rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; \
+line-height: 1.5; color: #222; background: #fff; }
+h1 { font-size: 1.6rem; margin-bottom: 0; }
+h2 { margin-top: 2.5rem; border-bottom: 1px solid #ddd; }
+h3 { margin-bottom: 0.2rem; }
+a { color: #1a5fb4; }
+.meta { margin-top: 0; color: #666; font-size: 0.9rem; }
+@media (prefers-color-scheme: dark) {
+  body { color: #ddd; background: #1e1e1e; }
+  h2 { border-color: #444; }
+  a { color: #78aeed; }
+  .meta { color: #999; }
+}
+";
+
+/// Self-contained page with the items grouped by channel. Channels are ordered by their
+/// newest item and summaries are rendered as plain text, so the page has no scripts or
+/// external resources.
+pub fn digest(items: &[&Item], since: Option<DateTime<Utc>>) -> String {
+    let mut channels: Vec<(&str, Vec<&Item>)> = vec![];
+    for item in items {
+        match channels
+            .iter_mut()
+            .find(|(name, _)| *name == item.channel_name)
+        {
+            Some((_, items)) => items.push(item),
+            None => channels.push((&item.channel_name, vec![item])),
+        }
+    }
+
+    let now = Local::now().format("%Y-%m-%d");
+    let subtitle = match since {
+        Some(since) => format!(
+            "{} items from {} to {now}",
+            items.len(),
+            since.with_timezone(&Local).format("%Y-%m-%d")
+        ),
+        None => format!("{} items, exported {now}", items.len()),
+    };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str("<title>simple-rss digest</title>\n");
+    let _ = writeln!(html, "<style>\n{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(
+        html,
+        "<h1>simple-rss digest</h1>\n<p class=\"meta\">{}</p>",
+        escape(&subtitle)
+    );
+
+    for (name, items) in channels {
+        let _ = writeln!(html, "<section>\n<h2>{}</h2>", escape(name));
+        for item in items {
+            write_item(&mut html, item);
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Returns true if the url can be used as a link. Feeds can contain any url, and the
+/// others, e.g. `javascript:`, would run when the link is clicked.
+fn is_web_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+fn write_item(html: &mut String, item: &Item) {
+    if is_web_url(&item.link) {
+        let _ = writeln!(
+            html,
+            "<article>\n<h3><a href=\"{}\">{}</a></h3>",
+            escape(&item.link),
+            escape(&item.title)
+        );
+    } else {
+        let _ = writeln!(html, "<article>\n<h3>{}</h3>", escape(&item.title));
+    }
+
+    let mut meta = vec![];
+    if let Some(date) = item.pub_date {
+        let date = date.with_timezone(&Local);
+        meta.push(date.format("%Y-%m-%d %H:%M").to_string());
+    }
+    if let Some(author) = &item.author {
+        meta.push(escape(author));
+    }
+    for link in &item.alternate_links {
+        let label = link.kind.label();
+        if is_web_url(&link.url) {
+            meta.push(format!("<a href=\"{}\">{label}</a>", escape(&link.url)));
+        } else {
+            meta.push(format!("{label}: {}", escape(&link.url)));
+        }
+    }
+    if !meta.is_empty() {
+        let _ = writeln!(html, "<p class=\"meta\">{}</p>", meta.join(" · "));
+    }
+
+    if let Some(description) = &item.description {
+        for paragraph in paragraphs(description) {
+            let _ = writeln!(html, "<p>{}</p>", escape(&paragraph));
+        }
+    }
+    html.push_str("</article>\n");
+}
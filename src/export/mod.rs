@@ -0,0 +1,186 @@
+//! Export of stored items for reading outside the terminal.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use clap::{ArgGroup, Subcommand};
+use colored::Colorize;
+use futures::future::join_all;
+use simple_rss_lib::{
+    data::Item,
+    render::{Format, export},
+};
+
+use crate::{
+    config::Config,
+    data::load_data,
+    http,
+    item::{find, load_article, parse_time_ago},
+};
+
+mod epub;
+mod html;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write items to a self-contained HTML page, grouped by channel
+    Html {
+        /// Only items published in this period, e.g. `12h`, `7d` or `2w`
+        #[arg(long, value_parser = parse_time_ago)]
+        since: Option<DateTime<Utc>>,
+
+        /// File to write the page to
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+
+    /// Bundle articles into an EPUB book, for reading on an e-reader
+    ///
+    /// Articles are fetched like `simple-rss read` does. The given items are exported
+    /// together with the ones matching `--starred` and `--since`.
+    #[command(group(ArgGroup::new("selection").required(true).multiple(true)))]
+    Epub {
+        /// Indexes or ids of the items
+        #[arg(group = "selection")]
+        items: Vec<String>,
+
+        /// Starred items
+        #[arg(long, group = "selection")]
+        starred: bool,
+
+        /// Items published in this period, e.g. `12h`, `7d` or `2w`
+        #[arg(long, group = "selection", value_parser = parse_time_ago)]
+        since: Option<DateTime<Utc>>,
+
+        /// File to write the book to
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+}
+
+pub async fn run(cmd: Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Html { since, output } => {
+            let data = load_data()?;
+            let items: Vec<_> = data
+                .items
+                .iter()
+                .filter(|it| published_since(it, since))
+                .collect();
+            if items.is_empty() {
+                println!("{}", "No items to export!".yellow().bold());
+                return Ok(());
+            }
+
+            fs::write(&output, html::digest(&items, since))?;
+            print_exported(items.len(), &output);
+            Ok(())
+        }
+        Command::Epub {
+            items,
+            starred,
+            since,
+            output,
+        } => export_epub(&items, starred, since, output).await,
+    }
+}
+
+async fn export_epub(
+    keys: &[String],
+    starred: bool,
+    since: Option<DateTime<Utc>>,
+    output: PathBuf,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    http::init(&config)?;
+
+    let data = load_data()?;
+    http::set_channels(&data.channels);
+
+    let mut ids = HashSet::new();
+    for key in keys {
+        match find(&data.items, key) {
+            Some(item) => {
+                ids.insert(&item.id);
+            }
+            None => {
+                println!("{} {key}", "Item not found:".yellow().bold());
+                return Ok(());
+            }
+        }
+    }
+
+    let is_filtered = starred || since.is_some();
+    let items: Vec<_> = data
+        .items
+        .iter()
+        .filter(|it| {
+            ids.contains(&it.id)
+                || (is_filtered && (!starred || it.starred) && published_since(it, since))
+        })
+        .collect();
+    if items.is_empty() {
+        println!("{}", "No items to export!".yellow().bold());
+        return Ok(());
+    }
+
+    // Articles that fail to load are replaced by their descriptions.
+    let articles = join_all(items.iter().map(|it| load_article(it, &data.channels))).await;
+    let mut chapters = vec![];
+    for (item, article) in items.into_iter().zip(articles) {
+        match article {
+            Ok(html) => chapters.push((item, html)),
+            Err(err) => {
+                println!(
+                    "⚠️  {} {}: {err}",
+                    "Failed to load".yellow().bold(),
+                    item.title
+                );
+                if let Some(description) = &item.description {
+                    chapters.push((item, description.clone()));
+                }
+            }
+        }
+    }
+    if chapters.is_empty() {
+        println!("{}", "No items to export!".yellow().bold());
+        return Ok(());
+    }
+
+    epub::write(&output, &chapters)?;
+    print_exported(chapters.len(), &output);
+    Ok(())
+}
+
+fn published_since(item: &Item, since: Option<DateTime<Utc>>) -> bool {
+    since.is_none_or(|since| item.pub_date.is_some_and(|date| date >= since))
+}
+
+fn print_exported(count: usize, path: &Path) {
+    println!(
+        "✅ {} {}",
+        format!("Exported {count} items to").green().bold(),
+        path.display()
+    );
+}
+
+/// Paragraphs of the HTML as plain text, each on a single line.
+fn paragraphs(html: &str) -> Vec<String> {
+    export(html, Format::Text)
+        .split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// Text escaped for HTML and XHTML, in elements and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
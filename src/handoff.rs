@@ -0,0 +1,81 @@
+//! Handing the terminal over to other programs and to the shell.
+//!
+//! While the terminal is released, terminal events are not read and the screen is left
+//! as the other program draws it. Reclaiming the terminal enters raw mode again and clears
+//! the screen, so the reader has to be drawn from scratch.
+
+use std::{future::Future, io};
+
+use ratatui::DefaultTerminal;
+
+use crate::{event::EventTaskHandle, tty};
+
+/// Stop reading terminal events and restore the terminal, so that another program can
+/// use it.
+pub async fn release(events: &EventTaskHandle) {
+    events.suspend().await;
+    tty::restore();
+}
+
+/// Take the terminal back after [`release`]. Everything has to be redrawn.
+pub fn reclaim(events: &EventTaskHandle) -> io::Result<DefaultTerminal> {
+    let mut terminal = tty::enable()?;
+    terminal.clear()?;
+    events.resume();
+    Ok(terminal)
+}
+
+/// `SIGTSTP` sent to the reader, by `kill -TSTP` or by Ctrl-Z while another program uses
+/// the terminal. In raw mode Ctrl-Z is read as a key instead. Once this is created, the
+/// signal doesn't stop the process anymore, so it has to be stopped with [`stop`].
+pub struct StopSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl StopSignal {
+    pub fn new() -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            let signal = signal(SignalKind::from_raw(libc::SIGTSTP))?;
+            Ok(Self { signal })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.signal.recv().await;
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Wait for another program using the terminal. If it's stopped with Ctrl-Z, the reader
+/// is stopped with it, so that the shell takes over until both are continued.
+pub async fn wait<T>(program: impl Future<Output = T>, stop_signal: &mut StopSignal) -> T {
+    tokio::pin!(program);
+    loop {
+        tokio::select! {
+            res = &mut program => return res,
+            _ = stop_signal.recv() => stop(),
+        }
+    }
+}
+
+/// Stop the process, like Ctrl-Z does in the shell, and return once it's continued with
+/// `fg`. Terminal has to be released before calling this.
+#[cfg(unix)]
+pub fn stop() {
+    // SIGTSTP is handled by the reader itself, SIGSTOP can't be handled.
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+/// Job control is only supported on unix.
+#[cfg(not(unix))]
+pub fn stop() {}
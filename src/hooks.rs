@@ -0,0 +1,111 @@
+//! User commands run when items are opened or marked as read, for custom integrations.
+//!
+//! Commands are run with `sh -c` (PowerShell on Windows) in the background and their
+//! output is discarded, so a slow or failing hook never blocks the reader. Values of the
+//! item are passed in environment variables, never pasted into the command, so that feeds
+//! can't inject commands. Placeholders `{url}`, `{title}`, `{channel}` and `{id}` are
+//! replaced with references to the variables.
+
+use std::{process::Stdio, sync::OnceLock};
+
+use simple_rss_lib::data::Item;
+use tokio::process::Command;
+
+use crate::config::Config;
+
+static HOOKS: OnceLock<Hooks> = OnceLock::new();
+
+struct Hooks {
+    on_open: Option<String>,
+    on_read: Option<String>,
+}
+
+/// Use the hooks of the config. Until this is called, no hooks are run.
+pub fn init(config: &Config) {
+    let _ = HOOKS.set(Hooks {
+        on_open: config.on_open.clone(),
+        on_read: config.on_read.clone(),
+    });
+}
+
+/// Run the `on_open` hook for the item. Has to be called within a tokio runtime.
+pub fn opened(item: &Item) {
+    if let Some(command) = HOOKS.get().and_then(|h| h.on_open.as_deref()) {
+        run(command, item);
+    }
+}
+
+/// Run the `on_read` hook for the item. Has to be called within a tokio runtime.
+pub fn read(item: &Item) {
+    if let Some(command) = HOOKS.get().and_then(|h| h.on_read.as_deref()) {
+        run(command, item);
+    }
+}
+
+/// Placeholders with the environment variables holding their values.
+const PLACEHOLDERS: [(&str, &str); 4] = [
+    ("url", "SIMPLE_RSS_URL"),
+    ("title", "SIMPLE_RSS_TITLE"),
+    ("channel", "SIMPLE_RSS_CHANNEL"),
+    ("id", "SIMPLE_RSS_ID"),
+];
+
+fn run(command: &str, item: &Item) {
+    let values = [&item.link, &item.title, &item.channel_name, &item.id];
+
+    let mut child = shell(&expand(command));
+    for ((_, var), value) in PLACEHOLDERS.iter().zip(values) {
+        child.env(var, value);
+    }
+    let child = child
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    // Failing hooks are ignored, they are the user's integrations.
+    if let Ok(mut child) = child {
+        tokio::spawn(async move {
+            let _ = child.wait().await;
+        });
+    }
+}
+
+/// Replace the placeholders with references to their variables. Placeholders the user
+/// already quoted, like `'{title}'`, are replaced together with the quotes.
+fn expand(command: &str) -> String {
+    let mut command = command.to_string();
+    for (name, var) in PLACEHOLDERS {
+        let reference = variable(var);
+        for quoted in [format!("'{{{name}}}'"), format!("\"{{{name}}}\"")] {
+            command = command.replace(&quoted, &reference);
+        }
+        command = command.replace(&format!("{{{name}}}"), &reference);
+    }
+    command
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn variable(name: &str) -> String {
+    format!("\"${name}\"")
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", command]);
+    cmd
+}
+
+// PowerShell doesn't parse the values of variables again, unlike `cmd`.
+#[cfg(windows)]
+fn variable(name: &str) -> String {
+    format!("$env:{name}")
+}
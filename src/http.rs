@@ -0,0 +1,463 @@
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap},
+    error::Error as _,
+    sync::{Arc, LazyLock, Mutex, OnceLock, RwLock},
+    time::Duration,
+};
+
+use anyhow::Context;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::{
+    Client, Method, NoProxy, Proxy, Request, RequestBuilder, Response, StatusCode, Url, header,
+    redirect,
+};
+use simple_rss_lib::{
+    Error,
+    data::{Auth, Channel, Item},
+};
+
+use crate::{config::Config, credentials};
+
+use tokio::{
+    sync::OnceCell,
+    task,
+    time::{Instant, sleep_until},
+};
+use tracing::{debug, warn};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_USER_AGENT: &str = concat!(
+    "simple-rss/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/viddrobnic/simple-rss)"
+);
+
+/// Longest `Retry-After` that is waited for before retrying. Requests to hosts that ask
+/// for a longer wait fail until it passes.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(10);
+
+/// Longest time a host is not requested after asking for it with `Retry-After`.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most redirects followed by [`send`] for one request.
+const MAX_REDIRECTS: usize = 10;
+
+/// Headers kept when a request is redirected to another origin. The others, like
+/// authentication and the channel's headers, are meant only for the original one.
+const CROSS_ORIGIN_HEADERS: &[header::HeaderName] = &[
+    header::ACCEPT,
+    header::ACCEPT_LANGUAGE,
+    header::CONTENT_TYPE,
+    header::USER_AGENT,
+];
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Client of the requests made with [`get`], [`get_item`] and [`get_channel`], which doesn't
+/// follow redirects, so that [`send`] can drop the credentials when leaving the origin.
+static MANUAL_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Minimum time between two requests to the same host.
+static HOST_INTERVAL: OnceLock<Duration> = OnceLock::new();
+
+/// When the hosts can be requested again. Hosts that can be requested right away are
+/// removed on the next request.
+static HOSTS: LazyLock<Mutex<HashMap<String, Host>>> = LazyLock::new(Mutex::default);
+
+#[derive(Default)]
+struct Host {
+    /// Earliest time of the next request, spaced by the host interval.
+    next: Option<Instant>,
+    /// Time until which the host asked not to be requested.
+    retry_after: Option<Instant>,
+}
+
+/// Authentication and headers of the channels, with the channel they are for.
+static CHANNEL_AUTH: RwLock<Vec<(Channel, Arc<ChannelAuth>)>> = RwLock::new(Vec::new());
+
+struct ChannelAuth {
+    configured: Credentials,
+    /// Configured credentials with the secrets resolved, on the first request of the channel.
+    resolved: OnceCell<Result<Credentials, Error>>,
+}
+
+#[derive(Clone)]
+struct Credentials {
+    auth: Option<Auth>,
+    headers: BTreeMap<String, String>,
+}
+
+impl ChannelAuth {
+    fn new(channel: &Channel) -> Self {
+        Self {
+            configured: Credentials {
+                auth: channel.auth.clone(),
+                headers: channel.headers.clone(),
+            },
+            resolved: OnceCell::new(),
+        }
+    }
+
+    fn is_for(&self, channel: &Channel) -> bool {
+        self.configured.auth == channel.auth && self.configured.headers == channel.headers
+    }
+
+    /// Resolve the secrets once. Keyring blocks, so it's read on a blocking thread.
+    async fn resolved(&self) -> Result<&Credentials, Error> {
+        let resolved = self
+            .resolved
+            .get_or_init(|| async {
+                let configured = self.configured.clone();
+                task::spawn_blocking(move || resolve_secrets(configured))
+                    .await
+                    .unwrap_or_else(|err| {
+                        Err(Error::Network(format!("Missing credentials: {err}")))
+                    })
+            })
+            .await;
+        resolved.as_ref().map_err(Clone::clone)
+    }
+}
+
+/// Configure the shared HTTP client. Has no effect once the client has been used.
+pub fn init(config: &Config) -> anyhow::Result<()> {
+    let client = build_client(config, redirect::Policy::default())?;
+    let _ = CLIENT.set(client);
+    let _ = MANUAL_CLIENT.set(build_client(config, redirect::Policy::none())?);
+    let _ = HOST_INTERVAL.set(Duration::from_secs(
+        config.host_interval.unwrap_or_default(),
+    ));
+    Ok(())
+}
+
+/// HTTP client used for all the requests.
+pub fn client() -> &'static Client {
+    CLIENT.get_or_init(|| {
+        build_client(&Config::default(), redirect::Policy::default())
+            .expect("Default client is valid")
+    })
+}
+
+fn manual_client() -> &'static Client {
+    MANUAL_CLIENT.get_or_init(|| {
+        build_client(&Config::default(), redirect::Policy::none()).expect("Default client is valid")
+    })
+}
+
+/// Remember authentication of the channels. Requests made with [`get_item`] for pages of
+/// the channel's items are authenticated like the channel, since items of private feeds are
+/// usually hosted next to the feed. Secrets of the channels that didn't change stay resolved.
+pub fn set_channels(channels: &[Channel]) {
+    let mut lock = CHANNEL_AUTH.write().unwrap();
+    let auth = channels
+        .iter()
+        .filter(|ch| ch.auth.is_some() || !ch.headers.is_empty())
+        .map(|ch| {
+            let known = lock
+                .iter()
+                .find(|(known, auth)| known.url == ch.url && auth.is_for(ch))
+                .map(|(_, auth)| auth.clone());
+            let auth = known.unwrap_or_else(|| Arc::new(ChannelAuth::new(ch)));
+            (ch.clone(), auth)
+        })
+        .collect();
+    *lock = auth;
+}
+
+/// GET request for the channel's feed.
+pub async fn get_channel(channel: &Channel) -> Result<RequestBuilder, Error> {
+    let request = manual_client().get(&channel.url);
+    if channel.auth.is_none() && channel.headers.is_empty() {
+        return Ok(request);
+    }
+
+    let known = CHANNEL_AUTH
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(known, auth)| known.url == channel.url && auth.is_for(channel))
+        .map(|(_, auth)| auth.clone());
+    let auth = known.unwrap_or_else(|| Arc::new(ChannelAuth::new(channel)));
+    Ok(authenticate(request, auth.resolved().await?))
+}
+
+/// GET request for the url, without authentication.
+pub fn get(url: &str) -> RequestBuilder {
+    manual_client().get(url)
+}
+
+/// GET request for a page of the item, like its article or attachment. It's authenticated
+/// like the item's channel if it's on the channel's origin.
+pub async fn get_item(item: &Item, url: &str) -> Result<RequestBuilder, Error> {
+    let request = get(url);
+    let auth = CHANNEL_AUTH
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(channel, _)| channel.contains(item))
+        .filter(|(channel, _)| origin(url).is_some_and(|o| origin(&channel.url) == Some(o)))
+        .map(|(_, auth)| auth.clone());
+    match auth {
+        Some(auth) => Ok(authenticate(request, auth.resolved().await?)),
+        None => Ok(request),
+    }
+}
+
+/// Send the request and return an error for unsuccessful responses.
+///
+/// Requests to the same host are spaced by the configured interval. When the host responds
+/// with `Retry-After`, it is not requested again until then. The request is retried once if
+/// the wait is short. Redirects to other origins don't get the authentication and the
+/// headers of the request.
+pub async fn send(request: RequestBuilder) -> Result<Response, Error> {
+    execute(request)
+        .await?
+        .error_for_status()
+        .map_err(network_error)
+}
+
+/// Send the request like [`send`], but return unsuccessful responses too, for callers
+/// that handle some of them, like an expired login.
+pub async fn execute(request: RequestBuilder) -> Result<Response, Error> {
+    let (client, request) = request.build_split();
+    let request = request.map_err(network_error)?;
+    let url = request.url().clone();
+    let started = Instant::now();
+
+    let result = send_with_redirects(client, request).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(response) => debug!(%url, status = %response.status(), elapsed_ms, "request"),
+        Err(err) => warn!(%url, error = %err, elapsed_ms, "request failed"),
+    }
+    result
+}
+
+/// Follow the redirects that the client didn't, keeping only the harmless headers when the
+/// redirect leaves the origin.
+async fn send_with_redirects(client: Client, mut request: Request) -> Result<Response, Error> {
+    let origin = request.url().origin();
+    for _ in 0..=MAX_REDIRECTS {
+        let next = request.try_clone();
+        let response = send_with_retry(client.clone(), request).await?;
+        let (Some(location), Some(mut next)) = (redirect_location(&response), next) else {
+            return Ok(response);
+        };
+
+        let status = response.status();
+        if status == StatusCode::SEE_OTHER
+            || (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                && next.method() == Method::POST)
+        {
+            *next.method_mut() = Method::GET;
+            *next.body_mut() = None;
+            next.headers_mut().remove(header::CONTENT_TYPE);
+            next.headers_mut().remove(header::CONTENT_LENGTH);
+        }
+        if location.origin() != origin {
+            let kept = next
+                .headers()
+                .iter()
+                .filter(|(name, _)| CROSS_ORIGIN_HEADERS.contains(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            *next.headers_mut() = kept;
+        }
+        *next.url_mut() = location;
+        request = next;
+    }
+
+    Err(Error::Network(format!(
+        "More than {MAX_REDIRECTS} redirects"
+    )))
+}
+
+/// Target of a redirect response.
+fn redirect_location(response: &Response) -> Option<Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+    response.url().join(location).ok()
+}
+
+async fn send_with_retry(client: Client, mut request: Request) -> Result<Response, Error> {
+    let host = request.url().host_str().unwrap_or_default().to_string();
+
+    let mut retried = false;
+    loop {
+        wait_for_host(&host).await?;
+        let retry = request.try_clone().filter(|_| !retried);
+        let response = client.execute(request).await.map_err(network_error)?;
+
+        let Some(delay) = retry_after(&response) else {
+            return Ok(response);
+        };
+        let until = Instant::now() + delay.min(MAX_RETRY_AFTER);
+        HOSTS
+            .lock()
+            .unwrap()
+            .entry(host.clone())
+            .or_default()
+            .retry_after = Some(until);
+
+        match retry {
+            Some(retry) if delay <= MAX_RETRY_WAIT => {
+                request = retry;
+                retried = true;
+            }
+            _ => return Ok(response),
+        }
+    }
+}
+
+/// Wait until the host can be requested, or fail if it asked for a long wait.
+async fn wait_for_host(host: &str) -> Result<(), Error> {
+    let now = Instant::now();
+    let interval = HOST_INTERVAL.get().copied().unwrap_or_default();
+
+    let slot = {
+        let mut hosts = HOSTS.lock().unwrap();
+        hosts.retain(|_, state| state.next.max(state.retry_after).is_some_and(|t| t > now));
+        let state = hosts.entry(host.to_string()).or_default();
+        if let Some(until) = state
+            .retry_after
+            .filter(|until| *until > now + MAX_RETRY_WAIT)
+        {
+            return Err(Error::Network(format!(
+                "{host} asked to wait {} more seconds before the next request",
+                (until - now).as_secs()
+            )));
+        }
+
+        let slot = [state.next, state.retry_after]
+            .into_iter()
+            .flatten()
+            .fold(now, Instant::max);
+        state.next = Some(slot + interval);
+        slot
+    };
+
+    sleep_until(slot).await;
+    Ok(())
+}
+
+/// Wait requested by a rate limited or unavailable host. It can be arbitrarily long.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(
+        response.status(),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        return None;
+    }
+
+    // Either a number of seconds or an HTTP date.
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(secs) = value.trim().parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+tokio::task_local! {
+    /// Size of the response bodies read within [`count_bytes`].
+    static DOWNLOADED: Cell<u64>;
+}
+
+/// Run the future and count the size of the response bodies it reads with [`body`].
+pub async fn count_bytes<T>(future: impl Future<Output = T>) -> (T, u64) {
+    DOWNLOADED
+        .scope(Cell::new(0), async {
+            let res = future.await;
+            (res, DOWNLOADED.with(Cell::get))
+        })
+        .await
+}
+
+/// Body of the response.
+pub async fn body(response: Response) -> Result<Bytes, Error> {
+    let body = response.bytes().await.map_err(network_error)?;
+    let _ = DOWNLOADED.try_with(|size| size.set(size.get() + body.len() as u64));
+    Ok(body)
+}
+
+/// Network error with its causes, which reqwest doesn't include in the message.
+pub fn network_error(err: reqwest::Error) -> Error {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+
+    Error::Network(message)
+}
+
+fn authenticate(mut request: RequestBuilder, auth: &Credentials) -> RequestBuilder {
+    request = match &auth.auth {
+        Some(Auth::Basic { username, password }) => request.basic_auth(username, Some(password)),
+        Some(Auth::Bearer { token }) => request.bearer_auth(token),
+        None => request,
+    };
+    for (name, value) in &auth.headers {
+        request = request.header(name, value);
+    }
+
+    request
+}
+
+/// Credentials with the secret references replaced by their values.
+fn resolve_secrets(configured: Credentials) -> Result<Credentials, Error> {
+    let auth = match configured.auth {
+        Some(Auth::Basic { username, password }) => Some(Auth::Basic {
+            username,
+            password: secret(&password)?,
+        }),
+        Some(Auth::Bearer { token }) => Some(Auth::Bearer {
+            token: secret(&token)?,
+        }),
+        None => None,
+    };
+    let headers = configured
+        .headers
+        .into_iter()
+        .map(|(name, value)| Ok((name, secret(&value)?)))
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Credentials { auth, headers })
+}
+
+fn secret(value: &str) -> Result<String, Error> {
+    credentials::resolve(value)
+        .map_err(|err| Error::Network(format!("Missing credentials: {err:#}")))
+}
+
+fn origin(url: &str) -> Option<String> {
+    Some(Url::parse(url).ok()?.origin().ascii_serialization())
+}
+
+fn build_client(config: &Config, redirect: redirect::Policy) -> anyhow::Result<Client> {
+    let timeout = config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let user_agent = config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .user_agent(user_agent)
+        .redirect(redirect);
+    if let Some(proxy) = &config.proxy {
+        let proxy = Proxy::all(proxy)
+            .with_context(|| format!("Invalid proxy {proxy}"))?
+            .no_proxy(NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build()?;
+
+    Ok(client)
+}
@@ -0,0 +1,182 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use anyhow::{Context, bail};
+use colored::Colorize;
+use futures::future::join_all;
+use reqwest::Url;
+use simple_rss_lib::{Error, data::Channel};
+
+use crate::{
+    config::Config,
+    data::{load_data, save_data},
+    http, source,
+};
+
+mod newsboat;
+mod opml;
+mod review;
+
+/// Imports channels from OPML file. Discovered feeds are reviewed by the user before
+/// they are added, unless `review` is false.
+pub fn import_opml(path: impl AsRef<Path>, review: bool) -> anyhow::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let outlines = opml::parse(&content)?;
+    import_outlines(outlines, None, review)
+}
+
+/// Imports channels from Newsboat's `urls` file, and optionally the items with their read
+/// status from Newsboat's cache. Feeds are reviewed like the ones from OPML files.
+pub fn import_newsboat(
+    path: impl AsRef<Path>,
+    cache: Option<&Path>,
+    review: bool,
+) -> anyhow::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let urls = newsboat::parse(&content);
+    for (url, reason) in &urls.skipped {
+        println!("⚠️  {} {url}: {reason}", "Skipped".yellow().bold());
+    }
+    import_outlines(urls.outlines, cache, review)
+}
+
+fn import_outlines(
+    outlines: Vec<opml::Outline>,
+    cache: Option<&Path>,
+    review: bool,
+) -> anyhow::Result<()> {
+    if outlines.is_empty() {
+        println!("{}", "No feeds found!".yellow().bold());
+        return Ok(());
+    }
+
+    let mut data = load_data()?;
+    let mut channels = if review {
+        let Some(channels) = review::review(outlines, &data.channels)? else {
+            println!("{}", "Import cancelled!".bold());
+            return Ok(());
+        };
+        channels
+    } else {
+        review::accept_all(outlines, &data.channels)
+    };
+
+    let mut items = match cache {
+        Some(cache) => newsboat::read_cache(cache, &channels)
+            .with_context(|| format!("Failed to read {}", cache.display()))?,
+        None => vec![],
+    };
+    items.retain(|it| !data.items.iter().any(|existing| existing.id == it.id));
+    let nr_items = items.len();
+
+    let count = channels.len();
+    data.channels.append(&mut channels);
+    data.items.append(&mut items);
+    data.items.sort_by_key(|it| Reverse(it.pub_date));
+    save_data(&data)?;
+
+    let message = if cache.is_some() {
+        format!("Imported {count} channels and {nr_items} items!")
+    } else {
+        format!("Imported {count} channels!")
+    };
+    println!("✅ {}", message.green().bold());
+
+    Ok(())
+}
+
+/// Adds the feeds listed in the file, one url per line, or in stdin if the path is `-`.
+/// Each feed is fetched first and only valid feeds are added. Channels are created from
+/// the template, which holds the authentication shared by all of them.
+pub async fn add_urls(path: &Path, template: Channel) -> anyhow::Result<()> {
+    let content = if path == Path::new("-") {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        fs::read_to_string(path)?
+    };
+    let urls = parse_urls(&content);
+    if urls.is_empty() {
+        println!("{}", "No feeds found!".yellow().bold());
+        return Ok(());
+    }
+
+    let config = Config::load()?;
+    http::init(&config)?;
+    source::init(&config);
+
+    let mut data = load_data()?;
+    let mut seen: HashSet<_> = data.channels.iter().map(|ch| ch.url.clone()).collect();
+    let mut pending = vec![];
+    let mut failed = 0;
+    for url in urls {
+        let error = if Url::parse(&url).is_err() {
+            Some("invalid url")
+        } else if !seen.insert(url.clone()) {
+            Some("already added")
+        } else {
+            None
+        };
+        match error {
+            Some(error) => {
+                println!("❌ {url}: {}", error.red());
+                failed += 1;
+            }
+            None => pending.push(Channel {
+                url,
+                ..template.clone()
+            }),
+        }
+    }
+
+    let checks = join_all(pending.iter().map(check_feed)).await;
+    let mut added = 0;
+    for (channel, check) in pending.into_iter().zip(checks) {
+        match check {
+            Ok(entries) => {
+                println!("✅ {} ({entries} items)", channel.url);
+                data.channels.push(channel);
+                added += 1;
+            }
+            Err(err) => {
+                println!("❌ {}: {}", channel.url, err.to_string().red());
+                failed += 1;
+            }
+        }
+    }
+
+    if added > 0 {
+        save_data(&data)?;
+    }
+
+    println!();
+    if failed > 0 {
+        bail!("Added {added} channels, {failed} failed");
+    }
+    println!("✅ {}", format!("Added {added} channels!").green().bold());
+    Ok(())
+}
+
+/// Urls in a plain text list. Blank lines and `#` comments are skipped, and only the first
+/// word of each line is used, so lists with extra columns, like Newsboat's, work as well.
+fn parse_urls(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Fetch the channel and return the number of its items.
+async fn check_feed(channel: &Channel) -> Result<usize, Error> {
+    let items = source::fetch(channel).await?;
+    Ok(items.len())
+}
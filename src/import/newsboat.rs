@@ -0,0 +1,210 @@
+use std::{collections::HashMap, path::Path};
+
+use chrono::{DateTime, FixedOffset};
+use rusqlite::{Connection, OpenFlags};
+use simple_rss_lib::data::{Channel, Item};
+
+use super::opml::Outline;
+
+/// Feeds of a Newsboat `urls` file.
+pub struct Urls {
+    pub outlines: Vec<Outline>,
+
+    /// Lines that can't be imported, like query and command feeds, with the reason.
+    pub skipped: Vec<(String, &'static str)>,
+}
+
+/// Parses Newsboat's `urls` file. Each line is a feed url followed by its tags, where a tag
+/// starting with `~` is the feed's title and a `!` tag hides the feed. Tags with spaces are
+/// quoted.
+pub fn parse(content: &str) -> Urls {
+    let mut urls = Urls {
+        outlines: vec![],
+        skipped: vec![],
+    };
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut words = split(line).into_iter();
+        let Some(url) = words.next() else {
+            continue;
+        };
+        if url.starts_with("query:") {
+            urls.skipped.push((url, "query feeds are not supported"));
+            continue;
+        }
+        if url.starts_with("exec:") || url.starts_with("filter:") {
+            urls.skipped.push((url, "command feeds are not supported"));
+            continue;
+        }
+
+        let mut outline = Outline {
+            title: None,
+            url,
+            categories: vec![],
+        };
+        for word in words {
+            if let Some(title) = word.strip_prefix('~') {
+                outline.title = Some(title.to_string()).filter(|t| !t.is_empty());
+            } else if !word.starts_with('!') {
+                outline.categories.push(word);
+            }
+        }
+        urls.outlines.push(outline);
+    }
+
+    urls
+}
+
+/// Words of the line, separated by whitespace. Quoted words can contain spaces and
+/// escaped quotes. Anything after an unquoted `#` is a comment.
+fn split(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            '\\' if quoted => word.extend(chars.next()),
+            '#' if !quoted && !in_word => break,
+            ch if ch.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            ch => {
+                word.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Reads the items of the channels from Newsboat's cache, with their read status. Items get
+/// the same ids as when they are fetched, so the read status is kept on refresh.
+pub fn read_cache(path: &Path, channels: &[Channel]) -> anyhow::Result<Vec<Item>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let mut titles = HashMap::new();
+    let mut stmt = conn.prepare("SELECT rssurl, title FROM rss_feed")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let url: String = row.get(0)?;
+        let title: Option<String> = row.get(1)?;
+        titles.insert(url, title.unwrap_or_default());
+    }
+
+    let channels: HashMap<_, _> = channels.iter().map(|ch| (ch.url.as_str(), ch)).collect();
+    let mut items = vec![];
+    let mut stmt = conn.prepare(
+        "SELECT guid, title, author, url, feedurl, pubDate, content, unread, enclosure_url \
+         FROM rss_item WHERE deleted = 0",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let feed_url: String = row.get(4)?;
+        let Some(channel) = channels.get(feed_url.as_str()) else {
+            continue;
+        };
+
+        let guid: String = row.get(0)?;
+        let channel_name = channel
+            .name
+            .clone()
+            .or_else(|| titles.get(&feed_url).cloned().filter(|t| !t.is_empty()))
+            .unwrap_or_else(|| "Unnamed Channel".to_string());
+        let pub_date: Option<i64> = row.get(5)?;
+        let unread: bool = row.get(7)?;
+
+        items.push(Item {
+            id: format!("{feed_url}:{guid}"),
+            channel_name,
+            title: row.get(1)?,
+            author: row.get::<_, Option<String>>(2)?.filter(|a| !a.is_empty()),
+            description: row.get::<_, Option<String>>(6)?.filter(|c| !c.is_empty()),
+            pub_date: pub_date
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|p| p.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+            link: row.get(3)?,
+            enclosure: row.get::<_, Option<String>>(8)?.filter(|e| !e.is_empty()),
+            alternate_links: vec![],
+            read: !unread,
+            starred: false,
+            tags: vec![],
+            hidden: None,
+        });
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_urls_file() {
+        let urls = parse(include_str!("../../tests/fixtures/newsboat/urls"));
+
+        let outlines: Vec<_> = urls
+            .outlines
+            .iter()
+            .map(|o| (o.url.as_str(), o.title.as_deref(), o.categories.clone()))
+            .collect();
+        assert_eq!(
+            outlines,
+            [
+                (
+                    "https://one.example/feed.xml",
+                    Some("One Blog"),
+                    vec!["tech".to_string(), "news".to_string()]
+                ),
+                (
+                    "https://two.example/feed.xml",
+                    Some("Quoted \"Title\""),
+                    vec!["two words".to_string()]
+                ),
+                (
+                    "https://hidden.example/feed.xml",
+                    Some("Hidden feed"),
+                    vec![]
+                ),
+            ]
+        );
+
+        let skipped: Vec<_> = urls.skipped.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            skipped,
+            [
+                "query:Unread Articles:unread = \"yes\"",
+                "exec:~/bin/feed.sh",
+                "filter:~/bin/filter.sh:https://three.example/feed.xml",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_words() {
+        assert_eq!(split(r#"a  "b c" d"#), ["a", "b c", "d"]);
+        assert_eq!(split(r#"a "" b"#), ["a", "", "b"]);
+        assert_eq!(split(r#""a \" \\ b""#), [r#"a " \ b"#]);
+        assert_eq!(split("a #comment"), ["a"]);
+        assert_eq!(split("a#b \"#c\""), ["a#b", "#c"]);
+        assert_eq!(split(r#""unterminated"#), ["unterminated"]);
+    }
+}
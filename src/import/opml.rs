@@ -0,0 +1,85 @@
+use anyhow::bail;
+use quick_xml::{
+    Reader,
+    escape::unescape,
+    events::{BytesStart, Event},
+};
+
+/// Feed discovered in an OPML file.
+#[derive(Debug, Clone)]
+pub struct Outline {
+    pub title: Option<String>,
+    pub url: String,
+
+    /// Titles of the folders the feed is nested in.
+    pub categories: Vec<String>,
+}
+
+/// Parses OPML document and returns all feeds (outlines with `xmlUrl`) in it.
+/// Outlines without url are treated as folders.
+pub fn parse(content: &str) -> anyhow::Result<Vec<Outline>> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut outlines = vec![];
+
+    // For each open outline element, the folder name it introduced (if any).
+    let mut stack: Vec<Option<String>> = vec![];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(elt) if elt.local_name().as_ref() == b"outline" => {
+                let folder = match read_outline(&elt, &stack)? {
+                    Some(outline) => {
+                        outlines.push(outline);
+                        None
+                    }
+                    None => attr(&elt, b"text")?.or(attr(&elt, b"title")?),
+                };
+                stack.push(folder);
+            }
+            Event::Empty(elt) if elt.local_name().as_ref() == b"outline" => {
+                if let Some(outline) = read_outline(&elt, &stack)? {
+                    outlines.push(outline);
+                }
+            }
+            Event::End(elt) if elt.local_name().as_ref() == b"outline" => {
+                stack.pop();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    if !stack.is_empty() {
+        bail!("Unexpected end of OPML document");
+    }
+
+    Ok(outlines)
+}
+
+fn read_outline(elt: &BytesStart, stack: &[Option<String>]) -> anyhow::Result<Option<Outline>> {
+    let Some(url) = attr(elt, b"xmlUrl")? else {
+        return Ok(None);
+    };
+
+    Ok(Some(Outline {
+        title: attr(elt, b"title")?.or(attr(elt, b"text")?),
+        url,
+        categories: stack.iter().flatten().cloned().collect(),
+    }))
+}
+
+fn attr(elt: &BytesStart, name: &[u8]) -> anyhow::Result<Option<String>> {
+    let Some(attr) = elt.try_get_attribute(name)? else {
+        return Ok(None);
+    };
+
+    let value = String::from_utf8_lossy(&attr.value);
+    let value = unescape(&value)?.trim().to_string();
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(value))
+    }
+}
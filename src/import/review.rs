@@ -0,0 +1,280 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, List, ListItem, ListState, Paragraph},
+};
+use simple_rss_lib::data::Channel;
+
+use super::opml::Outline;
+use crate::tty;
+
+/// Feed discovered in the imported file, with user's choices.
+struct Entry {
+    selected: bool,
+    already_added: bool,
+
+    name: String,
+    url: String,
+    tags: Vec<String>,
+}
+
+enum Mode {
+    Browse,
+    EditName(String),
+    EditTags(String),
+}
+
+struct Review {
+    entries: Vec<Entry>,
+    list_state: ListState,
+    mode: Mode,
+}
+
+enum Action {
+    Continue,
+    Commit,
+    Cancel,
+}
+
+/// Opens a checklist of discovered feeds where the user can select which to import,
+/// edit their names and assign tags.
+///
+/// Returns `None` if the import was cancelled.
+pub fn review(
+    outlines: Vec<Outline>,
+    existing: &[Channel],
+) -> anyhow::Result<Option<Vec<Channel>>> {
+    let mut review = Review {
+        entries: entries(outlines, existing),
+        list_state: ListState::default().with_selected(Some(0)),
+        mode: Mode::Browse,
+    };
+
+    let (mut terminal, guard) = tty::enter()?;
+    let res = review.run(&mut terminal);
+    drop(guard);
+
+    if !res? {
+        return Ok(None);
+    }
+
+    Ok(Some(channels(review.entries)))
+}
+
+/// Channels of all the discovered feeds that are not added yet, as they would be selected
+/// in the review.
+pub fn accept_all(outlines: Vec<Outline>, existing: &[Channel]) -> Vec<Channel> {
+    channels(entries(outlines, existing))
+}
+
+/// Entry for each feed. Feeds listed more than once, like in several folders, get a single
+/// entry with the tags of all of them.
+fn entries(outlines: Vec<Outline>, existing: &[Channel]) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = vec![];
+    for outline in outlines {
+        if let Some(entry) = entries.iter_mut().find(|e| e.url == outline.url) {
+            for tag in outline.categories {
+                if !entry.tags.contains(&tag) {
+                    entry.tags.push(tag);
+                }
+            }
+            continue;
+        }
+
+        let already_added = existing.iter().any(|ch| ch.url == outline.url);
+        entries.push(Entry {
+            selected: !already_added,
+            already_added,
+            name: outline.title.unwrap_or_default(),
+            url: outline.url,
+            tags: outline.categories,
+        });
+    }
+    entries
+}
+
+/// Channels of the selected entries.
+fn channels(entries: Vec<Entry>) -> Vec<Channel> {
+    entries
+        .into_iter()
+        .filter(|entry| entry.selected)
+        .map(|entry| Channel {
+            name: if entry.name.is_empty() {
+                None
+            } else {
+                Some(entry.name)
+            },
+            url: entry.url,
+            tags: entry.tags,
+            ..Default::default()
+        })
+        .collect()
+}
+
+impl Review {
+    /// Runs the review until user commits (`true`) or cancels (`false`) the import.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<bool> {
+        loop {
+            terminal.draw(|f| self.draw(f))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match self.handle_key(key.code) {
+                Action::Continue => (),
+                Action::Commit => return Ok(true),
+                Action::Cancel => return Ok(false),
+            }
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        match &mut self.mode {
+            Mode::Browse => self.handle_browse_key(code),
+            Mode::EditName(input) | Mode::EditTags(input) => {
+                match code {
+                    KeyCode::Char(ch) => input.push(ch),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Esc => self.mode = Mode::Browse,
+                    KeyCode::Enter => self.finish_edit(),
+                    _ => (),
+                }
+
+                Action::Continue
+            }
+        }
+    }
+
+    fn handle_browse_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.list_state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.list_state.select_next(),
+            KeyCode::Char(' ') => {
+                if let Some(entry) = self.selected_entry() {
+                    entry.selected = !entry.selected;
+                }
+            }
+            KeyCode::Char('a') => {
+                let select = !self.entries.iter().all(|entry| entry.selected);
+                for entry in &mut self.entries {
+                    entry.selected = select;
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = Mode::EditName(entry.name.clone());
+                }
+            }
+            KeyCode::Char('t') => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = Mode::EditTags(entry.tags.join(", "));
+                }
+            }
+            KeyCode::Enter => return Action::Commit,
+            KeyCode::Esc | KeyCode::Char('q') => return Action::Cancel,
+            _ => (),
+        }
+
+        Action::Continue
+    }
+
+    fn finish_edit(&mut self) {
+        let mode = std::mem::replace(&mut self.mode, Mode::Browse);
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+
+        match mode {
+            Mode::Browse => (),
+            Mode::EditName(name) => entry.name = name.trim().to_string(),
+            Mode::EditTags(tags) => {
+                entry.tags = tags
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+            }
+        }
+    }
+
+    fn selected_entry(&mut self) -> Option<&mut Entry> {
+        let selected = self.list_state.selected()?;
+        self.entries.get_mut(selected)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let layout =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).split(frame.area());
+
+        let nr_selected = self.entries.iter().filter(|entry| entry.selected).count();
+        let instructions = Line::from(vec![
+            "Toggle ".into(),
+            "<Space>  ".blue().bold(),
+            "All ".into(),
+            "<a>  ".blue().bold(),
+            "Name ".into(),
+            "<e>  ".blue().bold(),
+            "Tags ".into(),
+            "<t>  ".blue().bold(),
+            "Import ".into(),
+            "<Enter>  ".blue().bold(),
+            "Cancel ".into(),
+            "<Esc> / <q>".blue().bold(),
+        ]);
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(
+                "Import feeds ({nr_selected}/{})",
+                self.entries.len()
+            ))
+            .title_bottom(instructions.centered());
+
+        let list = List::new(self.entries.iter().map(entry_to_list_item))
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .block(block);
+        frame.render_stateful_widget(list, layout[0], &mut self.list_state);
+
+        let (title, input) = match &self.mode {
+            Mode::Browse => return,
+            Mode::EditName(input) => ("Name", input),
+            Mode::EditTags(input) => ("Tags (comma separated)", input),
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(title);
+        frame.render_widget(Paragraph::new(format!("{input}▏")).block(block), layout[1]);
+    }
+}
+
+fn entry_to_list_item(entry: &Entry) -> ListItem<'static> {
+    let checkbox = if entry.selected { "[X] " } else { "[ ] " };
+    let name = if entry.name.is_empty() {
+        "Unnamed Channel".to_string()
+    } else {
+        entry.name.clone()
+    };
+
+    let mut title = Line::from(vec![
+        checkbox.into(),
+        Span::from(name).bold().fg(Color::LightGreen),
+    ]);
+    if entry.already_added {
+        title.push_span(" (already added)".fg(Color::Yellow));
+    }
+
+    let mut details = Line::from(vec!["    ".into(), entry.url.clone().fg(Color::Blue)]);
+    if !entry.tags.is_empty() {
+        details.push_span(format!("  [{}]", entry.tags.join(", ")).fg(Color::Gray));
+    }
+
+    ListItem::new(vec![title, details])
+}
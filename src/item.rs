@@ -0,0 +1,807 @@
+//! Item commands, for scripts and integrations that don't use the TUI.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, IsTerminal},
+    ops::Range,
+    path::PathBuf,
+};
+
+use anyhow::{Context, bail};
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use clap::{ArgGroup, Args, Subcommand, ValueEnum};
+use colored::{Color as TermColor, ColoredString, Colorize};
+use ratatui::{
+    style::{Color, Modifier},
+    text::Span,
+};
+use regex::RegexBuilder;
+use serde::Serialize;
+use simple_rss_lib::{
+    Error,
+    data::{Channel, ContentLoader, Item, ItemStore},
+    index::SearchIndex,
+    render::{Format, RenderOptions, export, export_item, main_content, render_to_lines},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    config::{Config, SyncBackend},
+    credentials,
+    daemon::Client,
+    data::{
+        DataLoader, Flag, GReaderLoader, HttpContent, TtRssLoader, load_articles, load_data,
+        load_history, save_data, save_history,
+    },
+    hooks, http, pager, read_later,
+};
+
+/// Width of the article when the terminal's width is unknown.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Number of characters shown on each side of a search match.
+const SEARCH_CONTEXT: usize = 40;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List stored items, newest first
+    #[clap(visible_alias = "ls")]
+    List {
+        /// Show only unread items
+        #[arg(long)]
+        unread: bool,
+
+        /// Show only starred items
+        #[arg(long)]
+        starred: bool,
+
+        /// Show only items with this tag, set by the channel's script
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Show only items hidden or snoozed in the TUI
+        #[arg(long)]
+        hidden: bool,
+
+        /// Show only items of the channel with this index
+        #[arg(long)]
+        channel: Option<usize>,
+
+        /// Output format. TSV columns are index, id, title, channel, date, url and read.
+        #[arg(long, value_enum, default_value_t)]
+        format: ListFormat,
+    },
+
+    /// Mark items as read
+    MarkRead(Selection),
+
+    /// Mark items as unread
+    MarkUnread(Selection),
+
+    /// Star items, e.g. to export them later with `simple-rss export epub --starred`
+    Star {
+        /// Indexes or ids of the items
+        #[arg(required = true)]
+        items: Vec<String>,
+    },
+
+    /// Remove the star from items
+    Unstar {
+        /// Indexes or ids of the items
+        #[arg(required = true)]
+        items: Vec<String>,
+    },
+
+    /// Show items hidden or snoozed in the TUI again
+    Unhide {
+        /// Indexes or ids of the items
+        #[arg(required = true)]
+        items: Vec<String>,
+    },
+
+    /// Save items to the read-later services enabled in the config
+    Save {
+        /// Indexes or ids of the items
+        #[arg(required = true)]
+        items: Vec<String>,
+    },
+}
+
+/// Items changed by `mark-read` and `mark-unread`.
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("selection").required(true)))]
+pub struct Selection {
+    /// Indexes or ids of the items
+    #[arg(group = "selection")]
+    items: Vec<String>,
+
+    /// All the items
+    #[arg(long, group = "selection")]
+    all: bool,
+
+    /// Items published more than this long ago, e.g. `12h`, `7d` or `2w`
+    #[arg(long, group = "selection", value_parser = parse_time_ago)]
+    older_than: Option<DateTime<Utc>>,
+
+    /// Only items of the channel with this index
+    #[arg(long)]
+    channel: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+pub enum ListFormat {
+    #[default]
+    Table,
+    Json,
+    Tsv,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+}
+
+impl From<ExportFormat> for Format {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Markdown => Format::Markdown,
+            ExportFormat::Text => Format::Text,
+        }
+    }
+}
+
+/// Item as printed by `item list --format json`.
+#[derive(Serialize)]
+struct ListedItem<'a> {
+    /// Index used by other commands, like `download`.
+    idx: usize,
+    id: &'a str,
+    title: &'a str,
+    channel: &'a str,
+    date: Option<String>,
+    url: &'a str,
+    read: bool,
+    starred: bool,
+    hidden: bool,
+    tags: &'a [String],
+}
+
+impl<'a> ListedItem<'a> {
+    fn new(idx: usize, item: &'a Item, now: DateTime<Utc>) -> Self {
+        Self {
+            idx,
+            id: &item.id,
+            title: &item.title,
+            channel: &item.channel_name,
+            date: item.pub_date.map(|date| date.to_rfc3339()),
+            url: &item.link,
+            read: item.read,
+            starred: item.starred,
+            hidden: item.is_hidden(now),
+            tags: &item.tags,
+        }
+    }
+}
+
+pub async fn run(cmd: Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::List {
+            unread,
+            starred,
+            tag,
+            hidden,
+            channel,
+            format,
+        } => list(unread, starred, tag.as_deref(), hidden, channel, format),
+        Command::MarkRead(selection) => mark(selection, true).await,
+        Command::MarkUnread(selection) => mark(selection, false).await,
+        Command::Star { items } => star(&items, true).await,
+        Command::Unstar { items } => star(&items, false).await,
+        Command::Unhide { items } => unhide(&items).await,
+        Command::Save { items } => save(&items).await,
+    }
+}
+
+fn list(
+    unread: bool,
+    starred: bool,
+    tag: Option<&str>,
+    hidden: bool,
+    channel: Option<usize>,
+    format: ListFormat,
+) -> anyhow::Result<()> {
+    let data = load_data()?;
+    let Some(in_channel) = channel_filter(&data.channels, channel) else {
+        println!("{}", "Invalid index!".yellow().bold());
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let items: Vec<_> = data
+        .items
+        .iter()
+        .enumerate()
+        .filter(|(_, it)| !unread || !it.read)
+        .filter(|(_, it)| !starred || it.starred)
+        .filter(|(_, it)| tag.is_none_or(|tag| it.tags.iter().any(|t| t == tag)))
+        .filter(|(_, it)| !hidden || it.is_hidden(now))
+        .filter(|(_, it)| in_channel(it))
+        .map(|(idx, it)| ListedItem::new(idx, it, now))
+        .collect();
+
+    match format {
+        ListFormat::Table => print_table(&data.items, &items),
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&items)?),
+        ListFormat::Tsv => {
+            for it in &items {
+                let fields = [
+                    it.idx.to_string(),
+                    tsv_field(it.id),
+                    tsv_field(it.title),
+                    tsv_field(it.channel),
+                    it.date.clone().unwrap_or_default(),
+                    tsv_field(it.url),
+                    it.read.to_string(),
+                ];
+                println!("{}", fields.join("\t"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(all_items: &[Item], items: &[ListedItem]) {
+    if items.is_empty() {
+        println!("{}", "No items!".yellow().bold());
+        return;
+    }
+
+    let idx_len = items
+        .iter()
+        .map(|it| it.idx.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let channel_len = items.iter().map(|it| it.channel.width()).max().unwrap_or(0);
+    for it in items {
+        let date = all_items[it.idx]
+            .pub_date
+            .map(|date| date.with_timezone(&Local).format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| " ".repeat(10));
+        let marker = if it.read { " " } else { "●" };
+        let tags = if it.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", it.tags.join(", "))
+        };
+
+        println!(
+            "{:>idx_len$} {} {}  {}{}  {}{}{}",
+            it.idx.to_string().white(),
+            marker.blue(),
+            date.dimmed(),
+            it.channel,
+            " ".repeat(channel_len - it.channel.width()),
+            if it.read {
+                it.title.normal()
+            } else {
+                it.title.bold()
+            },
+            if it.starred { " ★" } else { "" }.yellow(),
+            tags.cyan()
+        );
+    }
+}
+
+/// Field without the tabs and newlines that would break the columns.
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+async fn mark(selection: Selection, read: bool) -> anyhow::Result<()> {
+    let data = load_data()?;
+    let Some(in_channel) = channel_filter(&data.channels, selection.channel) else {
+        println!("{}", "Invalid index!".yellow().bold());
+        return Ok(());
+    };
+
+    let mut ids = HashSet::new();
+    for key in &selection.items {
+        match find(&data.items, key) {
+            Some(item) => {
+                ids.insert(item.id.clone());
+            }
+            None => {
+                println!("{} {key}", "Item not found:".yellow().bold());
+                return Ok(());
+            }
+        }
+    }
+    if selection.all || selection.older_than.is_some() {
+        let before = selection.older_than;
+        ids.extend(
+            data.items
+                .iter()
+                .filter(|it| before.is_none_or(|before| it.pub_date.is_some_and(|p| p < before)))
+                .map(|it| it.id.clone()),
+        );
+    }
+
+    let ids: Vec<_> = data
+        .items
+        .iter()
+        .filter(|it| it.read != read && in_channel(it) && ids.contains(&it.id))
+        .map(|it| it.id.clone())
+        .collect();
+    set_read(&ids, read).await?;
+
+    let status = if read { "read" } else { "unread" };
+    println!(
+        "✅ {}",
+        format!("Marked {} items as {status}", ids.len())
+            .green()
+            .bold()
+    );
+    Ok(())
+}
+
+/// Star or unstar the items. Like read status, the change is sent to the daemon when it's
+/// running.
+async fn star(keys: &[String], starred: bool) -> anyhow::Result<()> {
+    let data = load_data()?;
+    let mut ids = vec![];
+    for key in keys {
+        match find(&data.items, key) {
+            Some(item) => ids.push(item.id.clone()),
+            None => {
+                println!("{} {key}", "Item not found:".yellow().bold());
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(daemon) = Client::connect().await {
+        daemon.set_starred(ids, starred).await?;
+    } else {
+        let config = Config::load()?;
+        let mut loader = DataLoader::new()?;
+        loader.set_starred_by_ids(&ids, starred);
+        save_data(&loader.get_data())?;
+
+        push_to_sync(&config, loader, &ids, Flag::Starred, starred)
+            .await
+            .context("Failed to send starred status to the sync server")?;
+    }
+
+    let status = if starred { "Starred" } else { "Unstarred" };
+    println!(
+        "✅ {}",
+        format!("{status} {} items", keys.len()).green().bold()
+    );
+    Ok(())
+}
+
+/// Show the hidden or snoozed items in the TUI again. The change is sent to the daemon when
+/// it's running.
+async fn unhide(keys: &[String]) -> anyhow::Result<()> {
+    let data = load_data()?;
+    let mut ids = vec![];
+    for key in keys {
+        match find(&data.items, key) {
+            Some(item) => ids.push(item.id.clone()),
+            None => {
+                println!("{} {key}", "Item not found:".yellow().bold());
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(daemon) = Client::connect().await {
+        for id in ids {
+            daemon.set_hidden(id, None).await?;
+        }
+    } else {
+        let mut loader = DataLoader::new()?;
+        for id in &ids {
+            loader.set_hidden_by_id(id, None);
+        }
+        save_data(&loader.get_data())?;
+    }
+
+    println!(
+        "✅ {}",
+        format!("Unhid {} items", keys.len()).green().bold()
+    );
+    Ok(())
+}
+
+/// Save the items to all the enabled read-later services.
+async fn save(keys: &[String]) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    if config.read_later.enabled().is_empty() {
+        println!(
+            "{}",
+            "No read-later service is enabled in the config!"
+                .yellow()
+                .bold()
+        );
+        return Ok(());
+    }
+    http::init(&config)?;
+
+    let data = load_data()?;
+    let mut items = vec![];
+    for key in keys {
+        match find(&data.items, key) {
+            Some(item) => items.push(item),
+            None => {
+                println!("{} {key}", "Item not found:".yellow().bold());
+                return Ok(());
+            }
+        }
+    }
+
+    let mut failed = false;
+    for item in items {
+        for (service, result) in read_later::save_all(&config.read_later, item).await {
+            match result {
+                Ok(()) => println!(
+                    "✅ {} {}",
+                    format!("Saved to {}:", service.title()).green().bold(),
+                    item.title
+                ),
+                Err(err) => {
+                    failed = true;
+                    eprintln!(
+                        "❌ {} {}: {err:#}",
+                        format!("Failed to save to {}:", service.title()).bold(),
+                        item.title
+                    );
+                }
+            }
+        }
+    }
+
+    if failed {
+        bail!("Failed to save items");
+    }
+    Ok(())
+}
+
+/// Print items whose title, description or cached article matches the query, case
+/// insensitively. Items containing all the words of the query, or words starting with them,
+/// are ranked by relevance. Regex matches are printed newest first.
+pub fn search(query: &str, is_regex: bool, channel: Option<usize>) -> anyhow::Result<()> {
+    let pattern = if is_regex {
+        query.to_string()
+    } else {
+        word_pattern(query)
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .context("Invalid regex")?;
+
+    let data = load_data()?;
+    let Some(in_channel) = channel_filter(&data.channels, channel) else {
+        println!("{}", "Invalid index!".yellow().bold());
+        return Ok(());
+    };
+
+    let cached = load_articles()?;
+    let articles: HashMap<_, _> = cached.iter().collect();
+    let article = |item: &Item| articles.get(item.id.as_str()).map(|text| one_line(text));
+
+    let matches: Vec<_> = if is_regex {
+        (0..data.items.len())
+            .filter(|&idx| {
+                let item = &data.items[idx];
+                regex.is_match(&item.title)
+                    || regex.is_match(&plain_description(item))
+                    || article(item).is_some_and(|text| regex.is_match(&text))
+            })
+            .collect()
+    } else {
+        let mut index = SearchIndex::new(&data.items);
+        for (idx, item) in data.items.iter().enumerate() {
+            if let Some(text) = articles.get(item.id.as_str()) {
+                index.add_text(idx, text);
+            }
+        }
+        index
+            .search(query)
+            .into_iter()
+            .map(|hit| hit.item)
+            .collect()
+    };
+
+    let mut count = 0;
+    for idx in matches {
+        let item = &data.items[idx];
+        if !in_channel(item) {
+            continue;
+        }
+        count += 1;
+
+        let title = match regex.find(&item.title) {
+            Some(m) => highlight(&item.title, m.range()),
+            None => item.title.bold().to_string(),
+        };
+        println!(
+            "{} {}  {}",
+            idx.to_string().white(),
+            title,
+            item.channel_name.dimmed()
+        );
+
+        let description = plain_description(item);
+        if let Some(m) = regex.find(&description) {
+            println!("    {}", context(&description, m.range()));
+        } else if let Some(text) = article(item)
+            && let Some(m) = regex.find(&text)
+        {
+            println!("    {}", context(&text, m.range()));
+        }
+    }
+
+    if count == 0 {
+        println!("{}", "No matches!".yellow().bold());
+    }
+    Ok(())
+}
+
+/// Regex matching the words of the query and the words that start with them, like the
+/// search index does.
+fn word_pattern(query: &str) -> String {
+    let words: Vec<_> = query
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(regex::escape)
+        .collect();
+    format!(r"\b(?:{})\w*", words.join("|"))
+}
+
+/// Description of the item as a single line of text.
+fn plain_description(item: &Item) -> String {
+    item.description
+        .as_deref()
+        .map(|desc| one_line(&export(desc, Format::Text)))
+        .unwrap_or_default()
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Text around the match, with the match highlighted.
+fn context(text: &str, range: Range<usize>) -> String {
+    let start = text[..range.start]
+        .char_indices()
+        .rev()
+        .nth(SEARCH_CONTEXT)
+        .map_or(0, |(idx, _)| idx);
+    let end = text[range.end..]
+        .char_indices()
+        .nth(SEARCH_CONTEXT)
+        .map_or(text.len(), |(idx, _)| range.end + idx);
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    let range = range.start - start..range.end - start;
+    format!("{prefix}{}{suffix}", highlight(&text[start..end], range))
+}
+
+fn highlight(text: &str, range: Range<usize>) -> String {
+    format!(
+        "{}{}{}",
+        &text[..range.start],
+        text[range.clone()].yellow().bold(),
+        &text[range.end..]
+    )
+}
+
+/// Print the article of the item, or export it to a file. Printed article is marked as read.
+pub async fn read(
+    key: &str,
+    output: Option<PathBuf>,
+    format: Option<ExportFormat>,
+    use_pager: bool,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    http::init(&config)?;
+    hooks::init(&config);
+
+    let data = load_data()?;
+    http::set_channels(&data.channels);
+    let Some(item) = find(&data.items, key) else {
+        println!("{}", "Item not found!".yellow().bold());
+        return Ok(());
+    };
+
+    let html = load_article(item, &data.channels).await?;
+    hooks::opened(item);
+    if let Some(path) = output {
+        let is_txt = path.extension().is_some_and(|ext| ext == "txt");
+        let format = match format {
+            Some(format) => format.into(),
+            None if is_txt => Format::Text,
+            None => Format::Markdown,
+        };
+        fs::write(&path, export_item(item, &html, format))?;
+        println!("✅ {} {}", "Exported to".green().bold(), path.display());
+        return Ok(());
+    }
+
+    let text = match format {
+        Some(format) => export_item(item, &html, format.into()),
+        None if use_pager || io::stdout().is_terminal() => styled_article(item, &html),
+        None => export_item(item, &html, Format::Markdown),
+    };
+    if use_pager {
+        pager::show(&config.pager(), &text).await?;
+    } else {
+        print!("{text}");
+    }
+
+    let mut history = load_history()?;
+    history.record_open(&data.channels, item);
+    save_history(&history)?;
+    set_read(std::slice::from_ref(&item.id), true).await
+}
+
+/// Article of the item, as the reader shows it: the description for channels that use it,
+/// and only the main content for channels with readability. The http client has to be
+/// initialized.
+pub async fn load_article(item: &Item, channels: &[Channel]) -> Result<String, Error> {
+    let settings = channels
+        .iter()
+        .find(|ch| ch.contains(item))
+        .map(|ch| ch.settings.clone())
+        .unwrap_or_default();
+    let html = match &item.description {
+        Some(description) if settings.use_description == Some(true) => description.clone(),
+        _ => HttpContent.load_item(item).await?,
+    };
+    let content = main_content(&html).filter(|_| settings.readability == Some(true));
+    Ok(content.unwrap_or(html))
+}
+
+/// Item with the given index or id.
+pub fn find<'a>(items: &'a [Item], key: &str) -> Option<&'a Item> {
+    match key.parse::<usize>() {
+        Ok(idx) => items.get(idx),
+        Err(_) => items.iter().find(|it| it.id == key),
+    }
+}
+
+/// Filter of the items of the channel with the given index, or of all the items if there is
+/// no index. Returns `None` for an invalid index.
+fn channel_filter(channels: &[Channel], channel: Option<usize>) -> Option<impl Fn(&Item) -> bool> {
+    let prefix = match channel {
+        Some(idx) => Some(format!("{}:", channels.get(idx)?.url)),
+        None => None,
+    };
+    Some(move |item: &Item| prefix.as_ref().is_none_or(|p| item.id.starts_with(p)))
+}
+
+/// Change read status of the items, like the TUI does. When the daemon is running, the change
+/// is sent to it, since it owns the data.
+async fn set_read(ids: &[String], read: bool) -> anyhow::Result<()> {
+    if let Some(daemon) = Client::connect().await {
+        return daemon.set_read(ids.to_vec(), read).await;
+    }
+
+    let config = Config::load()?;
+    hooks::init(&config);
+    let mut loader = DataLoader::new()?;
+    loader.set_read_by_ids(ids, read);
+    save_data(&loader.get_data())?;
+
+    push_to_sync(&config, loader, ids, Flag::Read, read)
+        .await
+        .context("Failed to send read status to the sync server")
+}
+
+/// Send the changed flag of the items to the sync server, if one is configured. Next sync
+/// takes the status from the server, so the change has to reach it first.
+async fn push_to_sync(
+    config: &Config,
+    loader: DataLoader,
+    ids: &[String],
+    flag: Flag,
+    value: bool,
+) -> anyhow::Result<()> {
+    let Some(sync) = config.sync.clone() else {
+        return Ok(());
+    };
+
+    http::init(config)?;
+    let password = credentials::sync_password(&sync)?;
+    match sync.backend {
+        SyncBackend::GReader => {
+            GReaderLoader::new(sync, password, loader)
+                .push(ids, flag, value)
+                .await?
+        }
+        SyncBackend::TtRss => {
+            TtRssLoader::new(sync, password, loader)
+                .push(ids, flag, value)
+                .await?
+        }
+    }
+    Ok(())
+}
+
+/// Time the given age ago, like `12h` or `7d`.
+pub(crate) fn parse_time_ago(age: &str) -> Result<DateTime<Utc>, String> {
+    let (number, unit) = age.split_at(age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len()));
+    let number: i64 = number
+        .parse()
+        .map_err(|_| "expected a number followed by m, h, d or w".to_string())?;
+    let age = match unit {
+        "m" => TimeDelta::try_minutes(number),
+        "h" => TimeDelta::try_hours(number),
+        "d" => TimeDelta::try_days(number),
+        "w" => TimeDelta::try_weeks(number),
+        _ => return Err(format!("unknown unit `{unit}`, expected m, h, d or w")),
+    };
+    age.and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| "age is too large".to_string())
+}
+
+/// Article with the title and link, styled and wrapped to the terminal's width.
+fn styled_article(item: &Item, html: &str) -> String {
+    let width = crossterm::terminal::size()
+        .map(|(width, _)| width as usize)
+        .unwrap_or(DEFAULT_WIDTH);
+
+    let mut text = format!("{}\n{}\n\n", item.title.bold(), item.link.blue());
+    for line in render_to_lines(html, width, &RenderOptions::default()) {
+        for span in &line.spans {
+            text.push_str(&styled(span).to_string());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn styled(span: &Span) -> ColoredString {
+    let mut text = span.content.normal();
+    if let Some(color) = span.style.fg.and_then(term_color) {
+        text = text.color(color);
+    }
+    if span.style.add_modifier.contains(Modifier::BOLD) {
+        text = text.bold();
+    }
+    if span.style.add_modifier.contains(Modifier::ITALIC) {
+        text = text.italic();
+    }
+    text
+}
+
+fn term_color(color: Color) -> Option<TermColor> {
+    let color = match color {
+        Color::Black => TermColor::Black,
+        Color::Red => TermColor::Red,
+        Color::Green => TermColor::Green,
+        Color::Yellow => TermColor::Yellow,
+        Color::Blue => TermColor::Blue,
+        Color::Magenta => TermColor::Magenta,
+        Color::Cyan => TermColor::Cyan,
+        Color::Gray => TermColor::White,
+        Color::DarkGray => TermColor::BrightBlack,
+        Color::LightRed => TermColor::BrightRed,
+        Color::LightGreen => TermColor::BrightGreen,
+        Color::LightYellow => TermColor::BrightYellow,
+        Color::LightBlue => TermColor::BrightBlue,
+        Color::LightMagenta => TermColor::BrightMagenta,
+        Color::LightCyan => TermColor::BrightCyan,
+        Color::White => TermColor::BrightWhite,
+        Color::Rgb(r, g, b) => TermColor::TrueColor { r, g, b },
+        Color::Reset | Color::Indexed(_) => return None,
+    };
+    Some(color)
+}
@@ -0,0 +1,607 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use simple_rss_lib::event::{KeyboardEvent, Modifiers};
+
+/// Built-in set of key bindings.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// Vim-like letters and mnemonics.
+    #[default]
+    Default,
+    /// Arrows, Enter, Escape and function keys only. Useful with non-Latin keyboard layouts.
+    Arrows,
+}
+
+/// Key with the modifiers it was pressed with. Shift is not a modifier of characters,
+/// since it's already part of the character, e.g. `G` instead of `shift-g`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(mut code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(ch) = code
+            && modifiers.contains(KeyModifiers::SHIFT)
+        {
+            code = KeyCode::Char(ch.to_ascii_uppercase());
+        }
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    pub fn code(&self) -> KeyCode {
+        self.code
+    }
+
+    /// Whether the key is pressed with Ctrl or Alt, so it's not typed as text.
+    pub fn is_shortcut(&self) -> bool {
+        self.modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            ctrl: self.modifiers.contains(KeyModifiers::CONTROL),
+            alt: self.modifiers.contains(KeyModifiers::ALT),
+        }
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+/// Maps pressed keys and sequences of keys to actions.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    // Vec instead of a map, to keep the order of keys in help.
+    bindings: Vec<(Vec<Key>, KeyboardEvent)>,
+}
+
+impl Keymap {
+    /// Keymap of the profile, with keys in `overrides` bound to the given actions.
+    /// Action `none` unbinds the key.
+    pub fn new(profile: Profile, overrides: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let mut bindings: Vec<_> = match profile {
+            Profile::Default => default_bindings(),
+            Profile::Arrows => arrows_bindings(),
+        }
+        .into_iter()
+        .map(|(code, event)| (vec![Key::from(code)], event))
+        .collect();
+        // Suspending is only supported on unix, where Ctrl-Z does it in every program.
+        if cfg!(unix) {
+            bindings.push((
+                vec![Key::new(KeyCode::Char('z'), KeyModifiers::CONTROL)],
+                KeyboardEvent::Suspend,
+            ));
+        }
+
+        for (key, action) in overrides {
+            let keys = parse_keys(key).with_context(|| format!("Invalid key `{key}`"))?;
+            bindings.retain(|(k, _)| *k != keys);
+
+            if action == "none" {
+                continue;
+            }
+            let Some(event) = parse_action(action) else {
+                bail!("Invalid action `{action}` for key `{key}`");
+            };
+            bindings.push((keys, event));
+        }
+
+        Ok(Self { bindings })
+    }
+
+    /// Bound keys of each action, e.g. `<Up> / <k>`.
+    pub fn labels(&self) -> HashMap<KeyboardEvent, String> {
+        let mut labels: HashMap<KeyboardEvent, String> = HashMap::new();
+        for (keys, event) in &self.bindings {
+            let label: String = keys.iter().map(|key| key_label(*key)).collect();
+            labels
+                .entry(*event)
+                .and_modify(|l| {
+                    l.push_str(" / ");
+                    l.push_str(&label);
+                })
+                .or_insert(label);
+        }
+
+        labels
+    }
+
+    /// Action bound to exactly these keys.
+    fn get(&self, keys: &[Key]) -> Option<KeyboardEvent> {
+        self.bindings
+            .iter()
+            .find(|(k, _)| k == keys)
+            .map(|(_, event)| *event)
+    }
+
+    /// Whether a longer sequence starts with these keys.
+    fn has_longer(&self, keys: &[Key]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(k, _)| k.len() > keys.len() && k.starts_with(keys))
+    }
+}
+
+/// Collects pressed keys until they form a bound sequence.
+///
+/// When a key is bound on its own and also starts a longer sequence, like `g` and `gg`, the
+/// matcher waits for the next key. If none comes, [`Matcher::flush`] returns the shorter one,
+/// and if the next key doesn't continue the sequence, both are returned.
+#[derive(Debug)]
+pub struct Matcher {
+    keymap: Keymap,
+    pending: Vec<Key>,
+}
+
+impl Matcher {
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            pending: vec![],
+        }
+    }
+
+    /// Actions of the bindings completed by the pressed key, in order.
+    pub fn push(&mut self, key: Key) -> Vec<KeyboardEvent> {
+        self.pending.push(key);
+        let mut events = vec![];
+        loop {
+            if self.keymap.has_longer(&self.pending) {
+                return events;
+            }
+            if let Some(event) = self.keymap.get(&self.pending) {
+                self.pending.clear();
+                events.push(event);
+                return events;
+            }
+
+            // Pending keys don't form a binding. The longest bound keys at the start are
+            // their own action, the rest can still start a sequence.
+            let bound = (1..self.pending.len())
+                .rev()
+                .find_map(|len| Some((len, self.keymap.get(&self.pending[..len])?)));
+            match bound {
+                Some((len, event)) => {
+                    events.push(event);
+                    self.pending.drain(..len);
+                }
+                None => {
+                    self.pending.remove(0);
+                }
+            }
+            if self.pending.is_empty() {
+                return events;
+            }
+        }
+    }
+
+    /// Whether keys of an unfinished sequence are waiting for the next key.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Stop waiting for the rest of the sequence. Returns the action bound to the keys
+    /// pressed so far.
+    pub fn flush(&mut self) -> Option<KeyboardEvent> {
+        let event = self.keymap.get(&self.pending);
+        self.pending.clear();
+        event
+    }
+}
+
+fn default_bindings() -> Vec<(KeyCode, KeyboardEvent)> {
+    vec![
+        (KeyCode::Left, KeyboardEvent::Left),
+        (KeyCode::Char('h'), KeyboardEvent::Left),
+        (KeyCode::Right, KeyboardEvent::Right),
+        (KeyCode::Char('l'), KeyboardEvent::Right),
+        (KeyCode::Up, KeyboardEvent::Up),
+        (KeyCode::Char('k'), KeyboardEvent::Up),
+        (KeyCode::Down, KeyboardEvent::Down),
+        (KeyCode::Char('j'), KeyboardEvent::Down),
+        (KeyCode::Esc, KeyboardEvent::Back),
+        (KeyCode::Char('q'), KeyboardEvent::Back),
+        (KeyCode::Enter, KeyboardEvent::Enter),
+        (KeyCode::Char(' '), KeyboardEvent::Space),
+        (KeyCode::Char('o'), KeyboardEvent::Open),
+        (KeyCode::Char('c'), KeyboardEvent::OpenComments),
+        (KeyCode::Char('A'), KeyboardEvent::OpenAll),
+        (KeyCode::Char('D'), KeyboardEvent::Download),
+        (KeyCode::Char('?'), KeyboardEvent::Help),
+        (KeyCode::Char('s'), KeyboardEvent::Listen),
+        (KeyCode::Char('p'), KeyboardEvent::NarrationPause),
+        (KeyCode::Char('.'), KeyboardEvent::NarrationNext),
+        (KeyCode::Char('x'), KeyboardEvent::NarrationStop),
+        (KeyCode::Char('/'), KeyboardEvent::Search),
+        (KeyCode::Char('n'), KeyboardEvent::SearchNext),
+        (KeyCode::Char('N'), KeyboardEvent::SearchPrevious),
+        (KeyCode::Char('m'), KeyboardEvent::SetMark),
+        (KeyCode::Char('\''), KeyboardEvent::JumpToMark),
+        (KeyCode::Char('t'), KeyboardEvent::Toc),
+        (KeyCode::Char('{'), KeyboardEvent::PreviousHeading),
+        (KeyCode::Char('}'), KeyboardEvent::NextHeading),
+        (KeyCode::Char('y'), KeyboardEvent::CopyLink),
+        (KeyCode::Char('Y'), KeyboardEvent::CopyArticle),
+        (KeyCode::Char('E'), KeyboardEvent::Export),
+        (KeyCode::Char('C'), KeyboardEvent::Changelog),
+        (KeyCode::Char('v'), KeyboardEvent::Pager),
+        (KeyCode::Char('b'), KeyboardEvent::ReadLater),
+        (KeyCode::Char('<'), KeyboardEvent::ShrinkList),
+        (KeyCode::Char('>'), KeyboardEvent::GrowList),
+        (KeyCode::Char('f'), KeyboardEvent::Fullscreen),
+        (KeyCode::Char('R'), KeyboardEvent::MarkAllRead),
+        (KeyCode::Char('u'), KeyboardEvent::Undo),
+        (KeyCode::Char('d'), KeyboardEvent::Hide),
+        (KeyCode::Char('z'), KeyboardEvent::Snooze),
+        (KeyCode::Char('H'), KeyboardEvent::ShowHidden),
+        (KeyCode::Char('*'), KeyboardEvent::ToggleStar),
+        (KeyCode::Char('e'), KeyboardEvent::RefreshErrors),
+        (KeyCode::Char('S'), KeyboardEvent::Stats),
+        (KeyCode::Char('M'), KeyboardEvent::Channels),
+        (KeyCode::Char('['), KeyboardEvent::HistoryBack),
+        (KeyCode::Char(']'), KeyboardEvent::HistoryForward),
+    ]
+}
+
+fn arrows_bindings() -> Vec<(KeyCode, KeyboardEvent)> {
+    vec![
+        (KeyCode::Left, KeyboardEvent::Left),
+        (KeyCode::Right, KeyboardEvent::Right),
+        (KeyCode::Up, KeyboardEvent::Up),
+        (KeyCode::Down, KeyboardEvent::Down),
+        (KeyCode::Esc, KeyboardEvent::Back),
+        (KeyCode::Enter, KeyboardEvent::Enter),
+        (KeyCode::Char(' '), KeyboardEvent::Space),
+        (KeyCode::F(1), KeyboardEvent::Help),
+        (KeyCode::F(2), KeyboardEvent::Open),
+        (KeyCode::F(3), KeyboardEvent::Search),
+        (KeyCode::Tab, KeyboardEvent::SearchNext),
+        (KeyCode::BackTab, KeyboardEvent::SearchPrevious),
+        (KeyCode::F(4), KeyboardEvent::Download),
+        (KeyCode::F(5), KeyboardEvent::CopyLink),
+        (KeyCode::F(6), KeyboardEvent::Export),
+        (KeyCode::F(7), KeyboardEvent::Pager),
+        (KeyCode::F(8), KeyboardEvent::Listen),
+        (KeyCode::F(9), KeyboardEvent::NarrationPause),
+        (KeyCode::F(10), KeyboardEvent::NarrationNext),
+        (KeyCode::F(11), KeyboardEvent::NarrationStop),
+        (KeyCode::F(12), KeyboardEvent::Changelog),
+        (KeyCode::Insert, KeyboardEvent::SetMark),
+        (KeyCode::Home, KeyboardEvent::JumpToMark),
+        (KeyCode::Delete, KeyboardEvent::Hide),
+        (KeyCode::End, KeyboardEvent::ShowHidden),
+        (KeyCode::PageUp, KeyboardEvent::ToggleStar),
+        (KeyCode::PageDown, KeyboardEvent::OpenAll),
+    ]
+}
+
+fn parse_action(action: &str) -> Option<KeyboardEvent> {
+    let event = match action {
+        "left" => KeyboardEvent::Left,
+        "right" => KeyboardEvent::Right,
+        "up" => KeyboardEvent::Up,
+        "down" => KeyboardEvent::Down,
+        "back" => KeyboardEvent::Back,
+        "select" => KeyboardEvent::Enter,
+        "toggle_read" => KeyboardEvent::Space,
+        "open" => KeyboardEvent::Open,
+        "open_comments" => KeyboardEvent::OpenComments,
+        "open_all" => KeyboardEvent::OpenAll,
+        "download" => KeyboardEvent::Download,
+        "help" => KeyboardEvent::Help,
+        "listen" => KeyboardEvent::Listen,
+        "narration_pause" => KeyboardEvent::NarrationPause,
+        "narration_next" => KeyboardEvent::NarrationNext,
+        "narration_stop" => KeyboardEvent::NarrationStop,
+        "search" => KeyboardEvent::Search,
+        "search_next" => KeyboardEvent::SearchNext,
+        "search_previous" => KeyboardEvent::SearchPrevious,
+        "set_mark" => KeyboardEvent::SetMark,
+        "jump_to_mark" => KeyboardEvent::JumpToMark,
+        "toc" => KeyboardEvent::Toc,
+        "previous_heading" => KeyboardEvent::PreviousHeading,
+        "next_heading" => KeyboardEvent::NextHeading,
+        "copy_link" => KeyboardEvent::CopyLink,
+        "copy_article" => KeyboardEvent::CopyArticle,
+        "export" => KeyboardEvent::Export,
+        "changelog" => KeyboardEvent::Changelog,
+        "pager" => KeyboardEvent::Pager,
+        "read_later" => KeyboardEvent::ReadLater,
+        "shrink_list" => KeyboardEvent::ShrinkList,
+        "grow_list" => KeyboardEvent::GrowList,
+        "fullscreen" => KeyboardEvent::Fullscreen,
+        "mark_all_read" => KeyboardEvent::MarkAllRead,
+        "undo" => KeyboardEvent::Undo,
+        "hide" => KeyboardEvent::Hide,
+        "snooze" => KeyboardEvent::Snooze,
+        "show_hidden" => KeyboardEvent::ShowHidden,
+        "toggle_star" => KeyboardEvent::ToggleStar,
+        "refresh_errors" => KeyboardEvent::RefreshErrors,
+        "stats" => KeyboardEvent::Stats,
+        "channels" => KeyboardEvent::Channels,
+        "history_back" => KeyboardEvent::HistoryBack,
+        "history_forward" => KeyboardEvent::HistoryForward,
+        "suspend" => KeyboardEvent::Suspend,
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+/// Parses a key or a sequence of keys separated by spaces, like `ctrl-d` or `g h`.
+/// Sequence of characters can also be written without spaces, like `gh`.
+fn parse_keys(keys: &str) -> Option<Vec<Key>> {
+    let parts: Vec<_> = keys.split_whitespace().collect();
+    if let [key] = parts[..]
+        && parse_key(key).is_none()
+        && !key.contains('-')
+    {
+        return Some(key.chars().map(|ch| Key::from(KeyCode::Char(ch))).collect());
+    }
+
+    if parts.is_empty() {
+        return None;
+    }
+    parts.into_iter().map(parse_key).collect()
+}
+
+/// Parses a single key with modifiers, like `ctrl-d`, `alt-enter` or `shift-left`.
+fn parse_key(key: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = key;
+    // Character `-` has no modifier before it.
+    while let Some((modifier, tail)) = rest.split_once('-')
+        && !tail.is_empty()
+    {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "c" => KeyModifiers::CONTROL,
+            "alt" | "a" => KeyModifiers::ALT,
+            "shift" | "s" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        rest = tail;
+    }
+
+    Some(Key::new(parse_code(rest)?, modifiers))
+}
+
+/// Parses a single character or a key name, like `enter` or `f1`.
+fn parse_code(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(ch));
+    }
+
+    let code = match key.to_lowercase().as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => {
+            let nr = name.strip_prefix('f')?.parse().ok()?;
+            KeyCode::F(nr)
+        }
+    };
+
+    Some(code)
+}
+
+fn key_label(key: Key) -> String {
+    let mut modifiers = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        modifiers.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        modifiers.push_str("A-");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        modifiers.push_str("S-");
+    }
+
+    let label = code_label(key.code);
+    let (open, name) = label.split_at(1);
+    format!("{open}{modifiers}{name}")
+}
+
+fn code_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "<Space>".to_string(),
+        KeyCode::Char(ch) => format!("<{ch}>"),
+        KeyCode::F(nr) => format!("<F{nr}>"),
+        KeyCode::BackTab => "<S-Tab>".to_string(),
+        KeyCode::PageUp => "<PgUp>".to_string(),
+        KeyCode::PageDown => "<PgDn>".to_string(),
+        code => format!("<{code:?}>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn char_key(ch: char) -> Key {
+        Key::from(KeyCode::Char(ch))
+    }
+
+    fn matcher(overrides: &[(&str, &str)]) -> Matcher {
+        let overrides = overrides
+            .iter()
+            .map(|(key, action)| (key.to_string(), action.to_string()))
+            .collect();
+        Matcher::new(Keymap::new(Profile::Default, &overrides).unwrap())
+    }
+
+    #[test]
+    fn shift_is_part_of_characters() {
+        let key = Key::new(KeyCode::Char('g'), KeyModifiers::SHIFT);
+        assert_eq!(key, char_key('G'));
+        assert!(!key.is_shortcut());
+
+        let left = Key::new(KeyCode::Left, KeyModifiers::SHIFT);
+        assert_eq!(left.modifiers, KeyModifiers::SHIFT);
+        assert_eq!(
+            Key::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+            Key::from(KeyCode::BackTab)
+        );
+    }
+
+    #[test]
+    fn keys_are_parsed() {
+        let ctrl_d = Key::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(parse_key("ctrl-d"), Some(ctrl_d));
+        assert_eq!(parse_key("C-d"), Some(ctrl_d));
+        assert_eq!(parse_key("shift-g"), Some(char_key('G')));
+        assert_eq!(
+            parse_key("alt-enter"),
+            Some(Key::new(KeyCode::Enter, KeyModifiers::ALT))
+        );
+        assert_eq!(parse_key("-"), Some(char_key('-')));
+        assert_eq!(
+            parse_key("ctrl--"),
+            Some(Key::new(KeyCode::Char('-'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse_key("F12"), Some(Key::from(KeyCode::F(12))));
+        assert_eq!(parse_key("meta-x"), None);
+        assert_eq!(parse_key("enterr"), None);
+    }
+
+    #[test]
+    fn sequences_are_parsed() {
+        assert_eq!(parse_keys("gg"), Some(vec![char_key('g'), char_key('g')]));
+        assert_eq!(
+            parse_keys("g ctrl-h"),
+            Some(vec![
+                char_key('g'),
+                Key::new(KeyCode::Char('h'), KeyModifiers::CONTROL)
+            ])
+        );
+        assert_eq!(parse_keys("space"), Some(vec![char_key(' ')]));
+        assert_eq!(parse_keys("  "), None);
+        assert_eq!(parse_keys("g bogus-x"), None);
+    }
+
+    #[test]
+    fn overrides_replace_and_unbind_keys() {
+        let overrides = HashMap::from([
+            ("j".to_string(), "up".to_string()),
+            ("k".to_string(), "none".to_string()),
+        ]);
+        let keymap = Keymap::new(Profile::Default, &overrides).unwrap();
+        assert_eq!(keymap.get(&[char_key('j')]), Some(KeyboardEvent::Up));
+        assert_eq!(keymap.get(&[char_key('k')]), None);
+
+        let invalid = HashMap::from([("j".to_string(), "jump".to_string())]);
+        assert!(Keymap::new(Profile::Default, &invalid).is_err());
+    }
+
+    #[test]
+    fn matcher_completes_sequences() {
+        let mut matcher = matcher(&[("gh", "help")]);
+        assert_eq!(matcher.push(char_key('g')), vec![]);
+        assert!(matcher.is_pending());
+        assert_eq!(matcher.push(char_key('h')), vec![KeyboardEvent::Help]);
+        assert!(!matcher.is_pending());
+
+        // Key that doesn't continue the sequence is matched on its own.
+        matcher.push(char_key('g'));
+        assert_eq!(matcher.push(char_key('j')), vec![KeyboardEvent::Down]);
+    }
+
+    #[test]
+    fn matcher_keeps_actions_of_bound_prefixes() {
+        let mut matcher = matcher(&[("g", "toc"), ("gg", "up")]);
+        assert_eq!(matcher.push(char_key('g')), vec![]);
+        assert_eq!(matcher.push(char_key('g')), vec![KeyboardEvent::Up]);
+
+        matcher.push(char_key('g'));
+        assert_eq!(
+            matcher.push(char_key('j')),
+            vec![KeyboardEvent::Toc, KeyboardEvent::Down]
+        );
+
+        // Next key can start a sequence again.
+        matcher.push(char_key('g'));
+        assert_eq!(matcher.push(char_key('w')), vec![KeyboardEvent::Toc]);
+        assert_eq!(matcher.push(char_key('g')), vec![]);
+        assert_eq!(matcher.flush(), Some(KeyboardEvent::Toc));
+        assert!(!matcher.is_pending());
+    }
+
+    #[test]
+    fn profiles_bind_each_key_once() {
+        for profile in [Profile::Default, Profile::Arrows] {
+            let keymap = Keymap::new(profile, &HashMap::new()).unwrap();
+            for (idx, (keys, _)) in keymap.bindings.iter().enumerate() {
+                let duplicate = keymap.bindings[idx + 1..].iter().any(|(k, _)| k == keys);
+                assert!(!duplicate, "{profile:?} binds {keys:?} twice");
+            }
+        }
+
+        let arrows = Keymap::new(Profile::Arrows, &HashMap::new()).unwrap();
+        assert_eq!(
+            arrows.get(&[Key::from(KeyCode::F(2))]),
+            Some(KeyboardEvent::Open)
+        );
+        assert_eq!(arrows.get(&[char_key('j')]), None);
+    }
+
+    #[test]
+    fn documented_actions_are_parsed() {
+        let readme = include_str!("../README.md");
+        let start = readme.find("Actions are: ").unwrap();
+        let end = start + readme[start..].find(". Action `none`").unwrap();
+        let actions: Vec<_> = readme[start + "Actions are: ".len()..end]
+            .split([',', ' ', '\n', '#'])
+            .filter(|word| !word.is_empty() && *word != "and")
+            .collect();
+
+        assert!(actions.len() > 40);
+        for action in actions {
+            assert!(parse_action(action).is_some(), "{action}");
+        }
+    }
+
+    #[test]
+    fn labels_list_all_keys_of_action() {
+        let overrides = HashMap::from([("g ctrl-d".to_string(), "down".to_string())]);
+        let labels = Keymap::new(Profile::Default, &overrides).unwrap().labels();
+
+        assert_eq!(labels[&KeyboardEvent::Down], "<Down> / <j> / <g><C-d>");
+        assert_eq!(labels[&KeyboardEvent::Space], "<Space>");
+        assert_eq!(labels[&KeyboardEvent::SearchPrevious], "<N>");
+    }
+}
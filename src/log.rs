@@ -0,0 +1,47 @@
+//! Log file in the state directory, for diagnosing refreshes, network errors and the
+//! reader's behaviour.
+//!
+//! Warnings and errors are always logged. `-v` adds informational messages, like refresh
+//! timings, and `-vv` debug messages, like every HTTP request and handled event. A new file
+//! is started every day and only the last week of files is kept.
+
+use tracing::level_filters::LevelFilter;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{filter::Targets, fmt, prelude::*};
+
+use crate::data::log_dir;
+
+/// Number of daily log files that are kept.
+const MAX_FILES: usize = 7;
+
+/// Start logging with the verbosity given by the number of `-v` flags. If the log
+/// directory can't be created, nothing is logged.
+pub fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+
+    let Ok(appender) = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("simple-rss")
+        .filename_suffix("log")
+        .max_log_files(MAX_FILES)
+        .build(log_dir())
+    else {
+        return;
+    };
+
+    // Libraries log too much below warnings, only our own messages are included.
+    let targets = Targets::new()
+        .with_target("simple_rss", level)
+        .with_target("simple_rss_lib", level)
+        .with_default(LevelFilter::WARN);
+    let layer = fmt::layer()
+        .with_ansi(false)
+        .with_writer(appender)
+        .with_filter(targets);
+    let _ = tracing_subscriber::registry().with(layer).try_init();
+}
@@ -1,19 +1,62 @@
-use clap::{Parser, Subcommand};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, bail};
+use chrono::Local;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum, builder::PossibleValue};
 use colored::{ColoredString, Colorize};
-use data::{DataLoader, load_data, save_data};
+use config::{Config, ReadLaterConfig, ReadLaterService, SyncBackend};
+use data::{
+    DataLoader, GReaderLoader, HttpContent, TtRssLoader, load_data, load_history, load_ui_state,
+    save_data, save_history, save_ui_state,
+};
 use event::{EventTask, TICK_FPS};
+use handoff::StopSignal;
+use keymap::Keymap;
+use scheduler::Scheduler;
 use simple_rss_lib::{
     app::{App, AppConfig},
-    data::Channel,
-    event::{Event, EventBus, KeyboardEvent},
+    browser,
+    data::{Auth, Channel, ChannelSettings, ContentLoader, Fetcher, Item, Loader},
+    event::{Event, EventBus, EventSender, KeyboardEvent, ToastEvent},
+    record::{Recorder, Replay},
+    render::Format,
 };
+use tokio::{process::Command, sync::mpsc};
+use tracing::{debug, warn};
 use unicode_width::UnicodeWidthStr;
 
+mod config;
+mod credentials;
+mod daemon;
 mod data;
+mod doctor;
 mod event;
+mod export;
+mod handoff;
+mod hooks;
+mod http;
+mod import;
+mod item;
+mod keymap;
+mod log;
+mod pager;
+mod read_later;
+mod remote;
+mod scheduler;
+mod script;
+mod source;
+mod stats;
+mod tty;
+mod update;
 
 const NAME_TITLE: &str = "Name";
 const URL_TITLE: &str = "URL";
+const ITEMS_TITLE: &str = "Unread/Total";
+const FETCHED_TITLE: &str = "Last fetch";
+const FETCHED_LEN: usize = 18; // Date and time with space around
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about)]
@@ -23,6 +66,20 @@ const URL_TITLE: &str = "URL";
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Start the TUI with default keys and without auto refresh or external commands.
+    /// Invalid config file is ignored.
+    #[arg(long)]
+    safe_mode: bool,
+
+    /// Record the events of the TUI session to the file, to reproduce a bug with `replay`
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Log more to the log file in the state directory: `-v` for refresh timings, `-vv`
+    /// for requests and events
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
 }
 
 #[derive(Debug, Subcommand)]
@@ -33,6 +90,144 @@ enum Commands {
         #[command(subcommand)]
         command: ChannelCommands,
     },
+
+    /// Fetch all channels and store new items
+    Refresh,
+
+    /// List stored items and change their read status
+    Item {
+        #[command(subcommand)]
+        command: item::Command,
+    },
+
+    /// Search titles, descriptions and cached articles of stored items, best matches first
+    Search {
+        /// Words to search for, case insensitively. Words also match longer words starting
+        /// with them, like `feed` matches `feeds`
+        query: String,
+
+        /// Treat the query as a regular expression
+        #[arg(long)]
+        regex: bool,
+
+        /// Search only items of the channel with this index
+        #[arg(long)]
+        channel: Option<usize>,
+    },
+
+    /// Export stored items for reading outside the terminal
+    Export {
+        #[command(subcommand)]
+        command: export::Command,
+    },
+
+    /// Download attachment (podcast episode, video, ...) of an item
+    Download {
+        /// Index of the item.
+        idx: usize,
+    },
+
+    /// Show item and unread counts, posting frequency, average age and opened share of each
+    /// channel, and how long its fetches take
+    Stats,
+
+    /// Check the config, stored data and channels for problems
+    Doctor,
+
+    /// Run in the background, refresh channels on schedule and serve the TUI and the CLI
+    Daemon,
+
+    /// Manage passwords stored in the system keyring
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommands,
+    },
+
+    /// Control the running TUI, e.g. from window manager key bindings
+    #[cfg(unix)]
+    Remote {
+        #[arg(value_enum)]
+        command: remote::Command,
+    },
+
+    /// Print article of an item and mark the item as read, or export it to a file
+    ///
+    /// In a terminal, the article is styled and wrapped to the terminal's width. Otherwise
+    /// it is printed as Markdown.
+    Read {
+        /// Index or id of the item
+        item: String,
+
+        /// File to write the article to
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Format of the article. Defaults to text for `.txt` files and Markdown otherwise.
+        #[arg(long, short)]
+        format: Option<item::ExportFormat>,
+
+        /// Show the article in the pager
+        #[arg(long, short, conflicts_with = "output")]
+        pager: bool,
+    },
+
+    /// Replay a TUI session recorded with `--record` and print the screen after it
+    Replay {
+        /// Recording file
+        file: PathBuf,
+
+        /// Stop after this many events
+        #[arg(long, short)]
+        events: Option<usize>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum AuthCommands {
+    /// Store the password or token of an account, so it doesn't have to be in the config
+    Login {
+        #[arg(value_enum)]
+        account: Account,
+
+        /// Username of the account. Defaults to the username in the config of the account.
+        #[arg(long)]
+        username: Option<String>,
+    },
+
+    /// Remove the stored password or token of an account
+    Logout {
+        #[arg(value_enum)]
+        account: Account,
+
+        /// Username of the account. Defaults to the username in the config of the account.
+        #[arg(long)]
+        username: Option<String>,
+    },
+}
+
+/// Account whose password is stored in the keyring: a sync server or a read-later service.
+#[derive(Debug, Clone, Copy)]
+enum Account {
+    Sync(SyncBackend),
+    ReadLater(ReadLaterService),
+}
+
+impl ValueEnum for Account {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Account::Sync(SyncBackend::GReader),
+            Account::Sync(SyncBackend::TtRss),
+            Account::ReadLater(ReadLaterService::Instapaper),
+            Account::ReadLater(ReadLaterService::Wallabag),
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Account::Sync(backend) => backend.to_possible_value(),
+            Account::ReadLater(service) => service.to_possible_value(),
+        }
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -44,11 +239,45 @@ enum ChannelCommands {
     /// Add a new channel
     Add {
         /// URL of the feed
-        url: String,
+        #[arg(
+            required_unless_present_any = ["from_file", "github", "reddit", "lobsters"],
+            conflicts_with = "shorthand"
+        )]
+        url: Option<String>,
 
         /// Custom name for the feed
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from_file")]
         name: Option<String>,
+
+        /// Add the releases feed of a GitHub repository, given as `owner/repo`
+        #[arg(long, value_name = "OWNER/REPO", group = "shorthand")]
+        github: Option<String>,
+
+        /// With `--github`, add the commits feed instead of the releases
+        #[arg(long, conflicts_with_all = ["url", "from_file", "reddit", "lobsters"])]
+        commits: bool,
+
+        /// Add the posts of a subreddit, like `r/rust`, or of a user, like `u/name`
+        #[arg(long, value_name = "SUBREDDIT", group = "shorthand")]
+        reddit: Option<String>,
+
+        /// Add the stories of Lobsters, all of them or those with the tag
+        #[arg(
+            long,
+            value_name = "TAG",
+            num_args = 0..=1,
+            default_missing_value = "",
+            group = "shorthand"
+        )]
+        lobsters: Option<String>,
+
+        /// Add all the feeds listed in the file, one url per line. Use `-` to read stdin.
+        /// Feeds are checked before they are added.
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["url", "shorthand"])]
+        from_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        auth: ChannelAuthArgs,
     },
 
     /// Remove a channel
@@ -59,6 +288,17 @@ enum ChannelCommands {
         idx: usize,
     },
 
+    /// Move a channel to another position
+    #[clap(visible_alias = "mv")]
+    Move {
+        /// Index of the channel to move.
+        /// Run `simple-rss channel list` to see indices.
+        from: usize,
+
+        /// New index of the channel
+        to: usize,
+    },
+
     /// Edit a channel
     Edit {
         /// Index of the channel to remove.
@@ -72,64 +312,734 @@ enum ChannelCommands {
         /// URL of the feed
         #[arg(long)]
         url: Option<String>,
+
+        #[command(flatten)]
+        auth: ChannelAuthArgs,
+
+        /// Remove authentication and headers
+        #[arg(long, conflicts_with_all = ["username", "token", "headers"])]
+        clear_auth: bool,
+
+        /// Override a default for this channel, as `key=value`. Keys are refresh_interval
+        /// (minutes), use_description, readability, retention_days, open_in_browser and
+        /// script (path of a Rhai script that filters and transforms the items).
+        /// Value `default` removes the override. Can be repeated.
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_setting)]
+        settings: Vec<(String, String)>,
+    },
+
+    /// Import channels from OPML file or Newsboat's urls file
+    Import {
+        /// Path to the OPML file, or to Newsboat's urls file with `--newsboat`
+        file: PathBuf,
+
+        /// The file is Newsboat's urls file
+        #[arg(long)]
+        newsboat: bool,
+
+        /// Newsboat's cache, e.g. `~/.newsboat/cache.db`, to import the items with their
+        /// read status
+        #[arg(long, value_name = "PATH", requires = "newsboat")]
+        cache: Option<PathBuf>,
+
+        /// Import all feeds that are not added yet, without reviewing them
+        #[arg(short, long)]
+        yes: bool,
     },
 }
 
+/// Authentication of the requests for a private feed. Secrets can be given as `env:NAME` or
+/// `keyring:NAME`, to read them from an environment variable or the system keyring when needed.
+#[derive(Debug, Args)]
+struct ChannelAuthArgs {
+    /// Username for Basic authentication
+    #[arg(long, requires = "password")]
+    username: Option<String>,
+
+    /// Password for Basic authentication
+    #[arg(long, requires = "username")]
+    password: Option<String>,
+
+    /// Token for Bearer authentication
+    #[arg(long, conflicts_with = "username")]
+    token: Option<String>,
+
+    /// Extra header sent with the requests, as `Name: value`. Can be repeated.
+    #[arg(long = "header", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+}
+
+impl ChannelAuthArgs {
+    fn auth(&self) -> Option<Auth> {
+        match (&self.username, &self.password, &self.token) {
+            (Some(username), Some(password), _) => Some(Auth::Basic {
+                username: username.clone(),
+                password: password.clone(),
+            }),
+            (_, _, Some(token)) => Some(Auth::Bearer {
+                token: token.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    match header.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err("expected `Name: value`".to_string()),
+    }
+}
+
+fn parse_setting(setting: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = setting.split_once('=') else {
+        return Err("expected `key=value`".to_string());
+    };
+    let (key, value) = (key.trim().replace('-', "_"), value.trim().to_string());
+
+    // Check that the setting can be applied, so that mistakes are reported by clap.
+    apply_setting(&mut ChannelSettings::default(), &key, &value)?;
+    Ok((key, value))
+}
+
+fn apply_setting(settings: &mut ChannelSettings, key: &str, value: &str) -> Result<(), String> {
+    fn parse<T: std::str::FromStr>(value: &str) -> Result<Option<T>, String> {
+        if value == "default" {
+            return Ok(None);
+        }
+        value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("invalid value `{value}`"))
+    }
+
+    match key {
+        "refresh_interval" => settings.refresh_interval = parse(value)?,
+        "use_description" => settings.use_description = parse(value)?,
+        "readability" => settings.readability = parse(value)?,
+        "retention_days" => settings.retention_days = parse(value)?,
+        "open_in_browser" => settings.open_in_browser = parse(value)?,
+        "script" => settings.script = parse(value)?,
+        _ => return Err(format!("unknown setting `{key}`")),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    log::init(cli.verbose);
     match cli.command {
-        None => run().await,
-        Some(Commands::Channel { command }) => manage_channel(command),
+        None => run(cli.safe_mode, cli.record).await,
+        Some(Commands::Channel { command }) => {
+            // Batch add can fail after some of the channels were added, so the daemon is
+            // reloaded either way. The error of the command is the one worth reporting.
+            let res = manage_channel(command).await;
+            let reload = reload_daemon().await;
+            if let (Err(_), Err(err)) = (&res, &reload) {
+                warn!(error = %err, "failed to reload the daemon");
+            }
+            res.and(reload)
+        }
+        Some(Commands::Refresh) => refresh().await,
+        Some(Commands::Item { command }) => item::run(command).await,
+        Some(Commands::Search {
+            query,
+            regex,
+            channel,
+        }) => item::search(&query, regex, channel),
+        Some(Commands::Export { command }) => export::run(command).await,
+        Some(Commands::Download { idx }) => download(idx).await,
+        Some(Commands::Stats) => stats::show_stats(),
+        Some(Commands::Doctor) => doctor::run().await,
+        Some(Commands::Daemon) => daemon::run().await,
+        Some(Commands::Auth { command }) => manage_auth(command),
+        #[cfg(unix)]
+        Some(Commands::Remote { command }) => remote::send(command).await,
+        Some(Commands::Read {
+            item,
+            output,
+            format,
+            pager,
+        }) => item::read(&item, output, format, pager).await,
+        Some(Commands::Replay { file, events }) => replay(file, events).await,
     }
 }
 
-async fn run() -> anyhow::Result<()> {
-    let mut terminal = ratatui::init();
+async fn run(safe_mode: bool, record: Option<PathBuf>) -> anyhow::Result<()> {
+    let config = if safe_mode {
+        Config::load_safe()
+    } else {
+        Config::load()?
+    };
+    http::init(&config)?;
+    source::init(&config);
+    hooks::init(&config);
+
+    let data_loader = match DataLoader::connect_daemon().await? {
+        Some(loader) => loader,
+        None => DataLoader::new()?,
+    };
+    match config.sync.clone() {
+        // The daemon syncs itself, its data is used instead.
+        Some(sync) if !data_loader.uses_daemon() => {
+            let password = credentials::sync_password(&sync)?;
+            match sync.backend {
+                SyncBackend::GReader => {
+                    let loader = GReaderLoader::new(sync, password, data_loader.clone());
+                    run_tui(config, loader, data_loader, record).await
+                }
+                SyncBackend::TtRss => {
+                    let loader = TtRssLoader::new(sync, password, data_loader.clone());
+                    run_tui(config, loader, data_loader, record).await
+                }
+            }
+        }
+        _ => run_tui(config, data_loader.clone(), data_loader, record).await,
+    }
+}
+
+/// Run the TUI with the loader. Data loader holds the items shown by the loader.
+async fn run_tui<L: Loader + Clone + Send + Sync + 'static>(
+    config: Config,
+    loader: L,
+    data_loader: DataLoader,
+    record: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let keymap = config.keymap()?;
+    let ui_state = load_ui_state()?;
+
+    let (mut terminal, _guard) = tty::enter()?;
 
     let mut event_bus = EventBus::new();
-    let event_task = EventTask::new(event_bus.get_sender());
+    let (event_task, event_handle) = EventTask::new(event_bus.get_sender(), keymap.clone());
     tokio::spawn(async move { event_task.run().await });
 
-    let data_loader = DataLoader::new()?;
+    let mut recorder = match record {
+        Some(path) => Some(Recorder::create(
+            &path,
+            terminal.size()?,
+            TICK_FPS as u32,
+            &loader,
+            &ui_state,
+        )?),
+        None => None,
+    };
+
     let mut app = App::new(
-        AppConfig::default(),
+        app_config(&config, &keymap),
         event_bus.get_sender(),
-        data_loader.clone(),
+        loader.clone(),
         TICK_FPS as u32,
     );
+    app.restore_ui_state(ui_state);
+    let mut history = load_history()?;
+
+    // The daemon refreshes on its own schedule. Synced items are only refreshed manually.
+    let _scheduled = if data_loader.uses_daemon() {
+        data_loader.watch_daemon(event_bus.get_sender());
+        None
+    } else if config.sync.is_some() {
+        None
+    } else {
+        Scheduler::from_config(&config)
+            .map(|scheduler| scheduler.spawn(data_loader.clone(), event_bus.get_sender()))
+    };
+    if config.check_updates {
+        update::spawn_check(event_bus.get_sender());
+    }
+
+    let mut stop_signal = StopSignal::new()?;
+
+    let (remote_tx, mut remote_rx) = mpsc::unbounded_channel();
+    let _remote_server = match remote::serve(remote_tx) {
+        Ok(server) => server,
+        Err(_) => {
+            event_bus
+                .get_sender()
+                .send(Event::Toast(ToastEvent::Warning(
+                    "Remote control is not available!".to_string(),
+                )));
+            None
+        }
+    };
 
     loop {
-        let event = event_bus.next().await;
+        let event = tokio::select! {
+            event = event_bus.next() => event,
+            Some(command) = remote_rx.recv() => {
+                match command {
+                    remote::Command::Refresh => app.refresh(),
+                    remote::Command::NextUnread => app.select_next_unread(),
+                    remote::Command::OpenSelected => app.open_selected(),
+                }
+                if app.is_dirty() {
+                    terminal.draw(|f| app.draw(f))?;
+                }
+                continue;
+            }
+            _ = stop_signal.recv() => Some(Event::Keyboard(KeyboardEvent::Suspend)),
+            _ = tty::panicked() => bail!("Stopped after a panic in a background task"),
+        };
         let Some(event) = event else {
             break;
         };
 
+        log_event(&event);
+        if let Some(rec) = &mut recorder
+            && let Err(err) = rec.record(&event, &loader)
+        {
+            warn!("recording stopped: {err}");
+            recorder = None;
+        }
+
+        if let Event::StartLoadingItem(_, item) = &event {
+            history.record_open(&data_loader.get_data().channels, item);
+            hooks::opened(item);
+        }
+
         let state = app.handle_event(&event);
+        if app.is_dirty() {
+            terminal.draw(|f| app.draw(f))?;
+        }
+        event_handle.set_animating(app.is_animating());
 
         if state.is_handled() {
-            terminal.draw(|f| app.draw(f))?;
             continue;
         }
 
+        if event == Event::Keyboard(KeyboardEvent::Changelog) {
+            update::show_changelog(event_bus.get_sender());
+        }
+
+        if event == Event::Keyboard(KeyboardEvent::ReadLater)
+            && let Some(item) = app.selected_item()
+        {
+            save_to_read_later(config.read_later.clone(), item, event_bus.get_sender());
+        }
+
+        if event == Event::Keyboard(KeyboardEvent::Suspend) {
+            handoff::release(&event_handle).await;
+            handoff::stop();
+
+            terminal = handoff::reclaim(&event_handle)?;
+            app.invalidate();
+            terminal.draw(|f| app.draw(f))?;
+        }
+
+        if let Event::OpenInTerminal(url) = &event
+            && let Some(command) = &config.open_command
+        {
+            handoff::release(&event_handle).await;
+
+            let browser = Command::new("sh")
+                .arg("-c")
+                .arg(browser::command_for(url, command))
+                .status();
+            let res = handoff::wait(browser, &mut stop_signal).await;
+
+            terminal = handoff::reclaim(&event_handle)?;
+            app.invalidate();
+            terminal.draw(|f| app.draw(f))?;
+
+            if !res.is_ok_and(|status| status.success()) {
+                event_bus.get_sender().send(Event::Toast(ToastEvent::Error(
+                    "Failed to open browser!".to_string(),
+                )));
+            }
+        }
+
+        if event == Event::Keyboard(KeyboardEvent::Pager)
+            && let Some(text) = app.opened_article(Format::Markdown)
+        {
+            handoff::release(&event_handle).await;
+
+            let res = handoff::wait(pager::show(&config.pager(), &text), &mut stop_signal).await;
+
+            terminal = handoff::reclaim(&event_handle)?;
+            app.invalidate();
+            terminal.draw(|f| app.draw(f))?;
+
+            if res.is_err() {
+                event_bus.get_sender().send(Event::Toast(ToastEvent::Error(
+                    "Failed to open pager!".to_string(),
+                )));
+            }
+        }
+
         if event == Event::Keyboard(KeyboardEvent::Back) {
+            save_ui_state(&app.ui_state())?;
+
             let data = data_loader.get_data();
-            save_data(&data)?;
+            history.record_items(&data.channels, &data.items);
+            save_history(&history)?;
+            if !data_loader.uses_daemon() {
+                save_data(&data)?;
+            }
             break;
         }
     }
 
-    ratatui::restore();
     Ok(())
 }
 
-fn manage_channel(cmd: ChannelCommands) -> anyhow::Result<()> {
+/// Log the event, without the loaded articles, which would make the log unreadable.
+fn log_event(event: &Event) {
+    match event {
+        Event::Tick | Event::Input(_) => (),
+        Event::Keyboard(key) => debug!(?key, "key"),
+        Event::StartLoadingItem(id, item) => debug!(id, item = %item.id, "loading item"),
+        Event::LoadedItem(id, Ok(_)) => debug!(id, "loaded item"),
+        Event::LoadedItem(id, Err(err)) => warn!(id, error = %err, "failed to load item"),
+        Event::RenderedItem { id, .. } => debug!(id, "rendered item"),
+        Event::Toast(ToastEvent::Error(msg)) => warn!(msg, "error shown"),
+        Event::RefreshFinished { errors } => debug!(errors = errors.len(), "refresh finished"),
+        event => debug!(?event, "event"),
+    }
+}
+
+/// Save the item to the enabled read-later services in the background, reporting the
+/// result with a toast.
+fn save_to_read_later(config: ReadLaterConfig, item: Item, event_tx: EventSender) {
+    if config.enabled().is_empty() {
+        event_tx.send(Event::Toast(ToastEvent::Warning(
+            "No read-later service is enabled!".to_string(),
+        )));
+        return;
+    }
+
+    tokio::spawn(async move {
+        event_tx.send(Event::Toast(ToastEvent::Loading("Saving item".to_string())));
+
+        let mut saved = vec![];
+        let mut failed = vec![];
+        for (service, result) in read_later::save_all(&config, &item).await {
+            match result {
+                Ok(()) => saved.push(service.title()),
+                Err(err) => failed.push(format!("{}: {err:#}", service.title())),
+            }
+        }
+
+        if failed.is_empty() {
+            event_tx.send(Event::Toast(ToastEvent::Hide));
+            event_tx.send(Event::Toast(ToastEvent::Info(format!(
+                "Saved to {}",
+                saved.join(", ")
+            ))));
+        } else {
+            event_tx.send(Event::Toast(ToastEvent::Error(format!(
+                "Failed to save to {}",
+                failed.join("; ")
+            ))));
+        }
+    });
+}
+
+async fn refresh() -> anyhow::Result<()> {
+    let config = Config::load()?;
+    http::init(&config)?;
+    source::init(&config);
+
+    let data_loader = match DataLoader::connect_daemon().await? {
+        Some(loader) => loader,
+        None => DataLoader::new()?,
+    };
+    let result = match config.sync {
+        Some(sync) if !data_loader.uses_daemon() => {
+            let password = credentials::sync_password(&sync)?;
+            match sync.backend {
+                SyncBackend::GReader => {
+                    let mut loader = GReaderLoader::new(sync, password, data_loader.clone());
+                    loader.refresh().await
+                }
+                SyncBackend::TtRss => {
+                    let mut loader = TtRssLoader::new(sync, password, data_loader.clone());
+                    loader.refresh().await
+                }
+            }
+        }
+        _ => data_loader.clone().refresh().await,
+    };
+    // Channels that were fetched are saved even if the others failed.
+    if !data_loader.uses_daemon() {
+        save_data(&data_loader.get_data())?;
+    }
+    if let Err(errors) = result {
+        for err in errors {
+            eprintln!(
+                "❌ {} {}: {}",
+                err.channel.bold(),
+                format!("({})", err.url).blue(),
+                err.error
+            );
+        }
+        bail!("Failed to refresh channels");
+    }
+
+    let data = data_loader.get_data();
+    let mut history = load_history()?;
+    history.record_items(&data.channels, &data.items);
+    save_history(&history)?;
+
+    println!(
+        "✅ {}",
+        format!("Refreshed {} items!", data.items.len())
+            .green()
+            .bold()
+    );
+    Ok(())
+}
+
+fn app_config(config: &Config, keymap: &Keymap) -> AppConfig {
+    AppConfig {
+        download_dir: Some(config.download_dir()),
+        narration_command: config.tts_command.clone(),
+        open_command: config.open_command.clone(),
+        terminal_browser: config.open_in_terminal,
+        disable_hyperlinks: config.disable_hyperlinks,
+        description_lines: config.description_lines,
+        grouping: config.group_by,
+        snooze: config.snooze(),
+        render_options: config.render_options(),
+        list_width: config.list_width,
+        toast: config.toast(),
+        export_dir: Some(config.download_dir()),
+        new_since: config.new_since(),
+        key_labels: Some(keymap.labels()),
+        ..Default::default()
+    }
+}
+
+/// Replay the recorded TUI session and print the screen after the last event.
+async fn replay(file: PathBuf, events: Option<usize>) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let keymap = config.keymap()?;
+
+    let mut replay = Replay::open(&file)
+        .with_context(|| format!("Failed to read recording {}", file.display()))?;
+    if let Some(events) = events {
+        replay.truncate(events);
+    }
+
+    let buffer = replay.run(app_config(&config, &keymap)).await;
+    for row in buffer.content().chunks(buffer.area.width as usize) {
+        let line: String = row.iter().map(|cell| cell.symbol()).collect();
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+async fn download(idx: usize) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    http::init(&config)?;
+
+    let data = load_data()?;
+    http::set_channels(&data.channels);
+    let Some(item) = data.items.get(idx) else {
+        println!("{}", "Invalid index!".yellow().bold());
+        return Ok(());
+    };
+    if item.enclosure.is_none() {
+        println!("{}", "Item has no attachment!".yellow().bold());
+        return Ok(());
+    }
+
+    let progress = |downloaded: u64, total: Option<u64>| {
+        match total {
+            Some(total) if total > 0 => print!("\rDownloading {}%", downloaded * 100 / total),
+            _ => print!("\rDownloading {} MB", downloaded / 1_000_000),
+        }
+        let _ = io::stdout().flush();
+    };
+
+    match HttpContent
+        .download(item, &config.download_dir(), progress)
+        .await
+    {
+        Ok(path) => println!("\r✅ {} {}", "Downloaded to".green().bold(), path.display()),
+        Err(err) => println!("\r{}", format!("Failed to download: {err}").red().bold()),
+    }
+
+    Ok(())
+}
+
+async fn manage_channel(cmd: ChannelCommands) -> anyhow::Result<()> {
     match cmd {
         ChannelCommands::List => list_channels(),
-        ChannelCommands::Add { url, name } => add_channel(Channel { name, url }),
+        ChannelCommands::Add {
+            url,
+            name,
+            from_file,
+            github,
+            commits,
+            reddit,
+            lobsters,
+            auth,
+        } => {
+            let shorthand = match (github, reddit, lobsters) {
+                (Some(repo), _, _) => Some(github_feed(&repo, commits)?),
+                (_, Some(name), _) => Some(reddit_feed(&name)?),
+                (_, _, Some(tag)) => Some(lobsters_feed(&tag)?),
+                _ => None,
+            };
+            let (url, name) = match shorthand {
+                Some((url, default_name)) => (Some(url), name.or(Some(default_name))),
+                None => (url, name),
+            };
+            let channel = Channel {
+                name,
+                url: url.unwrap_or_default(),
+                auth: auth.auth(),
+                headers: auth.headers.into_iter().collect(),
+                ..Default::default()
+            };
+            match from_file {
+                Some(path) => import::add_urls(&path, channel).await,
+                None => add_channel(channel),
+            }
+        }
         ChannelCommands::Remove { idx } => remove_channel(idx),
-        ChannelCommands::Edit { idx, name, url } => edit_channel(idx, name, url),
+        ChannelCommands::Move { from, to } => move_channel(from, to),
+        ChannelCommands::Edit {
+            idx,
+            name,
+            url,
+            auth,
+            clear_auth,
+            settings,
+        } => edit_channel(idx, name, url, auth, clear_auth, settings),
+        ChannelCommands::Import {
+            file,
+            newsboat: true,
+            cache,
+            yes,
+        } => import::import_newsboat(file, cache.as_deref(), !yes),
+        ChannelCommands::Import { file, yes, .. } => import::import_opml(file, !yes),
+    }
+}
+
+fn manage_auth(cmd: AuthCommands) -> anyhow::Result<()> {
+    let (account, username, login) = match cmd {
+        AuthCommands::Login { account, username } => (account, username, true),
+        AuthCommands::Logout { account, username } => (account, username, false),
+    };
+
+    let config = Config::load()?;
+    let name = match account {
+        Account::Sync(backend) => {
+            let username = match username {
+                Some(username) => username,
+                None => match config.sync {
+                    Some(sync) if sync.backend == backend => sync.username,
+                    _ => bail!("Pass --username or configure {} sync", backend.name()),
+                },
+            };
+            credentials::sync_name(backend, &username)
+        }
+        Account::ReadLater(service) => {
+            let configured = match service {
+                ReadLaterService::Instapaper => config.read_later.instapaper.map(|c| c.username),
+                ReadLaterService::Wallabag => config.read_later.wallabag.map(|c| c.username),
+            };
+            let Some(username) = username.or(configured) else {
+                bail!("Pass --username or configure {}", service.name());
+            };
+            credentials::read_later_name(service, &username)
+        }
+    };
+
+    if !login {
+        if credentials::delete(&name)? {
+            println!("✅ {}", "Password removed!".green().bold());
+        } else {
+            println!("{}", "No password stored!".yellow().bold());
+        }
+        return Ok(());
+    }
+
+    let password = rpassword::prompt_password("Password: ")?;
+    if password.is_empty() {
+        println!("{}", "Nothing to do!".bold());
+        return Ok(());
+    }
+    credentials::set(&name, &password)?;
+    println!("✅ {}", "Password stored!".green().bold());
+
+    Ok(())
+}
+
+/// Let the running daemon know that the channels were changed.
+async fn reload_daemon() -> anyhow::Result<()> {
+    match daemon::Client::connect().await {
+        Some(daemon) => daemon.reload().await,
+        None => Ok(()),
+    }
+}
+
+/// Url and default name of the releases or commits feed of a GitHub repository.
+fn github_feed(repo: &str, commits: bool) -> anyhow::Result<(String, String)> {
+    let repo = repo
+        .trim()
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/');
+    let Some((owner, name)) = repo.split_once('/') else {
+        bail!("Expected a repository as owner/repo, got: {repo}");
+    };
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !valid(owner) || !valid(name) {
+        bail!("Expected a repository as owner/repo, got: {repo}");
+    }
+
+    let kind = if commits { "commits" } else { "releases" };
+    Ok((
+        format!("https://github.com/{owner}/{name}/{kind}.atom"),
+        format!("{name} {kind}"),
+    ))
+}
+
+/// Url and default name of the feed of a subreddit or a Reddit user.
+fn reddit_feed(name: &str) -> anyhow::Result<(String, String)> {
+    let name = name.trim().trim_matches('/');
+    let (kind, name) = match name.split_once('/') {
+        Some(("r", name)) => ("r", name),
+        Some(("u" | "user", name)) => ("user", name),
+        Some(_) => bail!("Expected a subreddit as r/name or a user as u/name, got: {name}"),
+        None => ("r", name),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Invalid Reddit name: {name}");
     }
+
+    let prefix = if kind == "r" { "r" } else { "u" };
+    Ok((
+        format!("https://www.reddit.com/{kind}/{name}/.rss"),
+        format!("{prefix}/{name}"),
+    ))
+}
+
+/// Url and default name of the Lobsters feed of the tag, or of all the stories if the tag
+/// is empty.
+fn lobsters_feed(tag: &str) -> anyhow::Result<(String, String)> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Ok(("https://lobste.rs/rss".to_string(), "Lobsters".to_string()));
+    }
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Invalid Lobsters tag: {tag}");
+    }
+
+    Ok((
+        format!("https://lobste.rs/t/{tag}.rss"),
+        format!("Lobsters: {tag}"),
+    ))
 }
 
 fn add_channel(channel: Channel) -> anyhow::Result<()> {
@@ -156,8 +1066,34 @@ fn remove_channel(idx: usize) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn edit_channel(idx: usize, name: Option<String>, url: Option<String>) -> anyhow::Result<()> {
-    if name.is_none() && url.is_none() {
+fn move_channel(from: usize, to: usize) -> anyhow::Result<()> {
+    let mut data = load_data()?;
+    if !data.move_channel(from, to) {
+        println!("{}", "Invalid index!".yellow().bold());
+        return Ok(());
+    }
+    save_data(&data)?;
+
+    println!("✅ {}", "Channel moved!".green().bold());
+    Ok(())
+}
+
+fn edit_channel(
+    idx: usize,
+    name: Option<String>,
+    url: Option<String>,
+    auth: ChannelAuthArgs,
+    clear_auth: bool,
+    settings: Vec<(String, String)>,
+) -> anyhow::Result<()> {
+    let new_auth = auth.auth();
+    if name.is_none()
+        && url.is_none()
+        && new_auth.is_none()
+        && auth.headers.is_empty()
+        && !clear_auth
+        && settings.is_empty()
+    {
         println!("{}", "Nothing to do!".bold());
         return Ok(());
     }
@@ -174,6 +1110,20 @@ fn edit_channel(idx: usize, name: Option<String>, url: Option<String>) -> anyhow
     if let Some(url) = url {
         data.channels[idx].url = url;
     }
+    if clear_auth {
+        data.channels[idx].auth = None;
+        data.channels[idx].headers.clear();
+    }
+    if new_auth.is_some() {
+        data.channels[idx].auth = new_auth;
+    }
+    if !auth.headers.is_empty() {
+        data.channels[idx].headers = auth.headers.into_iter().collect();
+    }
+    for (key, value) in settings {
+        apply_setting(&mut data.channels[idx].settings, &key, &value)
+            .map_err(anyhow::Error::msg)?;
+    }
     save_data(&data)?;
 
     println!("✅ {}", "Channel updated!".green().bold());
@@ -208,31 +1158,48 @@ fn list_channels() -> anyhow::Result<()> {
     }
     url_len += 1; // Space at the left
 
+    let counts: Vec<_> = data
+        .channels
+        .iter()
+        .map(|ch| {
+            let items = data.items.iter().filter(|it| ch.contains(it));
+            let (unread, total) = items.fold((0, 0), |(u, t), it| (u + !it.read as usize, t + 1));
+            format!("{unread}/{total}")
+        })
+        .collect();
+    let items_len = counts
+        .iter()
+        .map(|c| c.len())
+        .max()
+        .unwrap_or(0)
+        .max(ITEMS_TITLE.len())
+        + 2; // Space around
+
     // Print header
     print!("{} │", "idx".bold());
     print_center(name_len, NAME_TITLE.bold());
     print!("│");
+    print_center(items_len, ITEMS_TITLE.bold());
+    print!("│");
+    print_center(FETCHED_LEN, FETCHED_TITLE.bold());
+    print!("│");
     print_center(url_len, URL_TITLE.bold());
     println!();
 
-    print!("────┼");
-    for _ in 0..name_len {
-        print!("─");
-    }
-    print!("┼");
-    for _ in 0..url_len {
-        print!("─");
+    print!("────");
+    for len in [name_len, items_len, FETCHED_LEN, url_len] {
+        print!("┼{}", "─".repeat(len));
     }
     println!();
 
-    for (idx, ch) in data.channels.iter().enumerate() {
-        print_channel(idx, ch, name_len);
+    for (idx, (ch, count)) in data.channels.iter().zip(&counts).enumerate() {
+        print_channel(idx, ch, name_len, count, items_len);
     }
 
     Ok(())
 }
 
-fn print_channel(idx: usize, ch: &Channel, name_len: usize) {
+fn print_channel(idx: usize, ch: &Channel, name_len: usize, count: &str, items_len: usize) {
     let idx = idx.to_string();
     print!("{}", idx.white());
     for _ in 0..(4 - idx.len()) {
@@ -248,6 +1215,20 @@ fn print_channel(idx: usize, ch: &Channel, name_len: usize) {
     for _ in 0..space {
         print!(" ");
     }
+    print!("│");
+
+    print_center(items_len, count.normal());
+    print!("│");
+
+    let fetched = match ch.last_fetched {
+        Some(time) => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+            .normal(),
+        None => "never".dimmed(),
+    };
+    print_center(FETCHED_LEN, fetched);
     print!("│ ");
 
     println!("{}", ch.url.blue());
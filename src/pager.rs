@@ -0,0 +1,22 @@
+use std::{env, process::Stdio};
+
+use tokio::{io::AsyncWriteExt, process::Command};
+
+/// Shows the text in the pager. Terminal has to be restored before calling this.
+pub async fn show(pager: &str, text: &str) -> anyhow::Result<()> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(pager).stdin(Stdio::piped());
+    // Let less show the colors of styled text, unless the user configured it.
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+    let mut child = command.spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // Pager can be closed before reading everything.
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+
+    child.wait().await?;
+    Ok(())
+}
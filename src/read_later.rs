@@ -0,0 +1,123 @@
+//! Saving items to read-later services: Instapaper and wallabag.
+//!
+//! Each service is configured in the `read_later` section of the config and used only when
+//! it's enabled. Passwords are read from the config or from the keyring, where they are
+//! stored with `simple-rss auth login <service>`.
+
+use anyhow::{Context, bail};
+use futures::future::join_all;
+use serde::Deserialize;
+use simple_rss_lib::data::Item;
+use tokio::task;
+
+use crate::{
+    config::{InstapaperConfig, ReadLaterConfig, ReadLaterService, WallabagConfig},
+    credentials,
+    http::{client, network_error},
+};
+
+/// Save the item to all the enabled services, at the same time. Returns the result of
+/// each service.
+pub async fn save_all(
+    config: &ReadLaterConfig,
+    item: &Item,
+) -> Vec<(ReadLaterService, anyhow::Result<()>)> {
+    let services = config.enabled();
+    let results = join_all(services.iter().map(|service| save(config, *service, item))).await;
+    services.into_iter().zip(results).collect()
+}
+
+async fn save(
+    config: &ReadLaterConfig,
+    service: ReadLaterService,
+    item: &Item,
+) -> anyhow::Result<()> {
+    match service {
+        ReadLaterService::Instapaper => {
+            save_instapaper(config.instapaper.as_ref().unwrap(), item).await
+        }
+        ReadLaterService::Wallabag => save_wallabag(config.wallabag.as_ref().unwrap(), item).await,
+    }
+}
+
+/// Password of the account. Keyring blocks, so it's read on a blocking thread.
+async fn secret(
+    service: ReadLaterService,
+    username: &str,
+    configured: Option<&str>,
+) -> anyhow::Result<String> {
+    let username = username.to_string();
+    let configured = configured.map(str::to_string);
+    task::spawn_blocking(move || {
+        credentials::read_later_secret(service, &username, configured.as_deref())
+    })
+    .await?
+}
+
+async fn save_instapaper(config: &InstapaperConfig, item: &Item) -> anyhow::Result<()> {
+    let password = secret(
+        ReadLaterService::Instapaper,
+        &config.username,
+        config.password.as_deref(),
+    )
+    .await?;
+    let form = [("url", item.link.as_str()), ("title", item.title.as_str())];
+
+    client()
+        .post(format!("{}/api/add", config.url.trim_end_matches('/')))
+        .basic_auth(&config.username, Some(password))
+        .form(&form)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(network_error)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct WallabagToken {
+    access_token: String,
+}
+
+async fn save_wallabag(config: &WallabagConfig, item: &Item) -> anyhow::Result<()> {
+    let password = secret(
+        ReadLaterService::Wallabag,
+        &config.username,
+        config.password.as_deref(),
+    )
+    .await?;
+    let url = config.url.trim_end_matches('/');
+
+    // Tokens expire after an hour, so a new one is requested every time.
+    let form = [
+        ("grant_type", "password"),
+        ("client_id", config.client_id.as_str()),
+        ("client_secret", config.client_secret.as_str()),
+        ("username", config.username.as_str()),
+        ("password", password.as_str()),
+    ];
+    let response = client()
+        .post(format!("{url}/oauth/v2/token"))
+        .form(&form)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(network_error)?
+        .text()
+        .await
+        .map_err(network_error)?;
+    let token: WallabagToken = serde_json::from_str(&response).context("Invalid token response")?;
+
+    let form = [("url", item.link.as_str()), ("title", item.title.as_str())];
+    let response = client()
+        .post(format!("{url}/api/entries.json"))
+        .bearer_auth(token.access_token)
+        .form(&form)
+        .send()
+        .await
+        .map_err(network_error)?;
+    if !response.status().is_success() {
+        bail!("wallabag responded with {}", response.status());
+    }
+    Ok(())
+}
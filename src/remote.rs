@@ -0,0 +1,130 @@
+//! Remote control of the running TUI.
+//!
+//! While the TUI runs, it listens on a Unix socket in the state directory. Each line sent
+//! to the socket is a command name, answered with `ok` or `error: <message>`. Other
+//! platforms don't support remote control: the TUI doesn't listen and the `remote` command
+//! isn't available.
+
+use clap::ValueEnum;
+
+/// Command sent with `simple-rss remote`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Command {
+    /// Fetch all channels
+    Refresh,
+    /// Select the next unread item
+    NextUnread,
+    /// Open the selected item
+    OpenSelected,
+}
+
+impl Command {
+    #[cfg(unix)]
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{send, serve};
+
+#[cfg(unix)]
+mod unix {
+    use std::{fs, io, path::PathBuf};
+
+    use anyhow::{Context, bail};
+    use clap::ValueEnum;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{UnixListener, UnixStream},
+        sync::mpsc,
+    };
+
+    use super::Command;
+    use crate::data::remote_socket_path;
+
+    /// Listening socket. The socket file is removed when dropped.
+    pub struct Server {
+        path: PathBuf,
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Start listening for commands, which are sent to the channel. Returns `None` if
+    /// another instance is already listening.
+    pub fn serve(commands: mpsc::UnboundedSender<Command>) -> io::Result<Option<Server>> {
+        let path = remote_socket_path();
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            return Ok(None);
+        }
+
+        // Socket of an instance that didn't exit cleanly.
+        let _ = fs::remove_file(&path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, commands.clone()));
+            }
+        });
+
+        Ok(Some(Server { path }))
+    }
+
+    async fn handle(
+        stream: UnixStream,
+        commands: mpsc::UnboundedSender<Command>,
+    ) -> io::Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            let name = line.trim();
+            let response = match Command::from_str(name, true) {
+                Ok(command) if commands.send(command).is_ok() => "ok".to_string(),
+                Ok(_) => "error: simple-rss is closing".to_string(),
+                Err(_) => format!("error: unknown command `{name}`"),
+            };
+            writer.write_all(format!("{response}\n").as_bytes()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the command to the running TUI.
+    pub async fn send(command: Command) -> anyhow::Result<()> {
+        let stream = UnixStream::connect(remote_socket_path())
+            .await
+            .context("simple-rss is not running")?;
+        let (reader, mut writer) = stream.into_split();
+        writer
+            .write_all(format!("{}\n", command.name()).as_bytes())
+            .await?;
+
+        let mut response = String::new();
+        BufReader::new(reader).read_line(&mut response).await?;
+        match response.trim() {
+            "ok" => Ok(()),
+            "" => bail!("simple-rss closed the connection"),
+            response => bail!("{}", response.strip_prefix("error: ").unwrap_or(response)),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Server;
+
+#[cfg(not(unix))]
+pub fn serve(
+    _commands: tokio::sync::mpsc::UnboundedSender<Command>,
+) -> std::io::Result<Option<Server>> {
+    Ok(None)
+}
@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use simple_rss_lib::{
+    data::{Channel, Fetcher, RefreshError},
+    event::{Event, EventSender, ToastEvent},
+};
+use tokio::task::AbortHandle;
+
+use crate::{config::Config, data::DataLoader};
+
+/// Periodically refreshes the channels in the background.
+///
+/// Channels are not refreshed all at once. Each channel has its own schedule, staggered
+/// evenly across the interval, and every refresh is delayed by a random jitter. This way
+/// a large number of feeds doesn't cause network and CPU spikes. Channels with their own
+/// `refresh_interval` setting use it instead of the configured interval.
+pub struct Scheduler {
+    interval: Duration,
+    jitter: Duration,
+}
+
+impl Scheduler {
+    /// Returns `None` if auto refresh is not configured.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let interval = config.refresh_interval.filter(|i| *i > 0)?;
+        Some(Self {
+            interval: minutes(interval),
+            jitter: Duration::from_secs(config.refresh_jitter.unwrap_or(0)),
+        })
+    }
+
+    /// Start refreshing the loader's current channels. Refreshing stops when the returned
+    /// handle is dropped.
+    pub fn spawn(&self, loader: DataLoader, event_tx: EventSender) -> Scheduled {
+        let channels = loader.get_data().channels.clone();
+        let nr_channels = channels.len();
+
+        let mut tasks = Vec::with_capacity(channels.len());
+        for (idx, channel) in channels.into_iter().enumerate() {
+            let Some((offset, interval)) = self.schedule(&channel, idx, nr_channels) else {
+                continue;
+            };
+            let jitter = self.jitter;
+            let loader = loader.clone();
+            let event_tx = event_tx.clone();
+
+            let task = tokio::spawn(async move {
+                tokio::time::sleep(offset.saturating_add(random_jitter(jitter))).await;
+                loop {
+                    event_tx.send(Event::RefreshStarted);
+                    let res = loader.refresh_channel(&channel).await;
+                    let errors = match res {
+                        Ok(_) => vec![],
+                        Err(err) => {
+                            event_tx.send(Event::Toast(ToastEvent::Error(format!(
+                                "Failed to refresh {}!",
+                                channel_name(&channel)
+                            ))));
+                            vec![RefreshError {
+                                channel: channel_name(&channel).to_string(),
+                                url: channel.url.clone(),
+                                error: err,
+                            }]
+                        }
+                    };
+                    event_tx.send(Event::RefreshFinished { errors });
+
+                    tokio::time::sleep(interval.saturating_add(random_jitter(jitter))).await;
+                }
+            });
+            tasks.push(task.abort_handle());
+        }
+
+        Scheduled { tasks }
+    }
+
+    /// Delay of the channel's first refresh, without the jitter, and the interval of the
+    /// following ones. `None` if the channel is not refreshed automatically.
+    fn schedule(
+        &self,
+        channel: &Channel,
+        idx: usize,
+        nr_channels: usize,
+    ) -> Option<(Duration, Duration)> {
+        let interval = match channel.settings.refresh_interval {
+            Some(0) => return None,
+            Some(nr) => minutes(nr),
+            None => self.interval,
+        };
+        let offset = (interval / nr_channels as u32).saturating_mul(idx as u32 + 1);
+        Some((offset, interval))
+    }
+
+    /// Start syncing the loader with its sync server. All the channels are refreshed at
+    /// once, by the server, so there is a single schedule. Syncing stops when the returned
+    /// handle is dropped.
+    pub fn spawn_sync<F>(&self, mut loader: F, event_tx: EventSender) -> Scheduled
+    where
+        F: Fetcher + Send + 'static,
+    {
+        let interval = self.interval;
+        let jitter = self.jitter;
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval.saturating_add(random_jitter(jitter))).await;
+
+                event_tx.send(Event::RefreshStarted);
+                let errors = loader.refresh().await.err().unwrap_or_default();
+                if !errors.is_empty() {
+                    event_tx.send(Event::Toast(ToastEvent::Error(
+                        "Failed to sync!".to_string(),
+                    )));
+                }
+                event_tx.send(Event::RefreshFinished { errors });
+            }
+        });
+
+        Scheduled {
+            tasks: vec![task.abort_handle()],
+        }
+    }
+}
+
+/// Refresh tasks started by [`Scheduler::spawn`] or [`Scheduler::spawn_sync`], aborted when
+/// dropped.
+pub struct Scheduled {
+    tasks: Vec<AbortHandle>,
+}
+
+impl Drop for Scheduled {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Duration of the configured number of minutes. Absurdly long intervals are capped
+/// instead of overflowing.
+fn minutes(nr: u64) -> Duration {
+    Duration::from_secs(nr.saturating_mul(60))
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    let max = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+    if max == 0 {
+        return Duration::ZERO;
+    }
+
+    Duration::from_millis(fastrand::u64(0..max))
+}
+
+fn channel_name(channel: &Channel) -> &str {
+    channel.name.as_deref().unwrap_or(&channel.url)
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_rss_lib::{Error, event::EventBus, testing::MemoryLoader};
+
+    use super::*;
+
+    fn scheduler(interval: u64, jitter: Option<u64>) -> Scheduler {
+        let config = Config {
+            refresh_interval: Some(interval),
+            refresh_jitter: jitter,
+            ..Default::default()
+        };
+        Scheduler::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn disabled_without_interval() {
+        assert!(Scheduler::from_config(&Config::default()).is_none());
+        let config = Config {
+            refresh_interval: Some(0),
+            ..Default::default()
+        };
+        assert!(Scheduler::from_config(&config).is_none());
+
+        let scheduler = scheduler(30, Some(10));
+        assert_eq!(scheduler.interval, Duration::from_secs(30 * 60));
+        assert_eq!(scheduler.jitter, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn channels_are_staggered() {
+        let scheduler = scheduler(30, None);
+        let mut channel = Channel::default();
+        let schedules: Vec<_> = (0..3)
+            .map(|idx| scheduler.schedule(&channel, idx, 3).unwrap())
+            .collect();
+        assert_eq!(
+            schedules,
+            [10, 20, 30].map(|offset| (minutes(offset), minutes(30)))
+        );
+
+        // Channel's own interval is staggered the same way.
+        channel.settings.refresh_interval = Some(60);
+        assert_eq!(
+            scheduler.schedule(&channel, 0, 3),
+            Some((minutes(20), minutes(60)))
+        );
+        channel.settings.refresh_interval = Some(0);
+        assert_eq!(scheduler.schedule(&channel, 0, 3), None);
+    }
+
+    #[test]
+    fn jitter_is_below_max() {
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(random_jitter(Duration::from_secs(1)) < Duration::from_secs(1));
+        }
+        assert_eq!(minutes(u64::MAX), Duration::from_secs(u64::MAX));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sync_runs_on_schedule_until_dropped() {
+        let loader = MemoryLoader::new(vec![]);
+        loader.fail_refresh(vec![RefreshError {
+            channel: "Server".to_string(),
+            url: "https://rss.example.com".to_string(),
+            error: Error::Network("connection refused".to_string()),
+        }]);
+        let mut bus = EventBus::new();
+        let scheduled = scheduler(30, None).spawn_sync(loader.clone(), bus.get_sender());
+
+        tokio::time::sleep(minutes(29)).await;
+        assert_eq!(loader.refresh_count(), 0);
+
+        assert_eq!(bus.next().await, Some(Event::RefreshStarted));
+        assert_eq!(
+            bus.next().await,
+            Some(Event::Toast(ToastEvent::Error(
+                "Failed to sync!".to_string()
+            )))
+        );
+        let Some(Event::RefreshFinished { errors }) = bus.next().await else {
+            panic!("refresh didn't finish");
+        };
+        assert_eq!(errors.len(), 1);
+
+        assert_eq!(bus.next().await, Some(Event::RefreshStarted));
+        assert_eq!(
+            bus.next().await,
+            Some(Event::RefreshFinished { errors: vec![] })
+        );
+        assert_eq!(loader.refresh_count(), 2);
+
+        drop(scheduled);
+        tokio::time::sleep(minutes(120)).await;
+        assert_eq!(loader.refresh_count(), 2);
+    }
+}
@@ -0,0 +1,195 @@
+//! Rhai scripts that filter and transform the items of a channel on refresh, set with
+//! `simple-rss channel edit <idx> --set script=<path>`.
+//!
+//! The script is run for every fetched item, with the item in the `item` variable. It can
+//! change the item's `title`, `description`, `author`, `link` and `tags`, and drop the item
+//! by evaluating to `false`:
+//!
+//! ```rhai
+//! if item.title.contains("Sponsored") {
+//!     return false;
+//! }
+//! item.title = item.title.replace("[video] ", "");
+//! if item.link.contains("youtube.com") {
+//!     item.tags.push("video");
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
+    time::SystemTime,
+};
+
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use simple_rss_lib::{Error, data::Item};
+
+use crate::data::settings_path;
+
+/// Limit of operations for each item, so that an endless loop doesn't block the refresh.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Limits of nested calls and of the values a script builds, so that it can't take all
+/// the memory.
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 10 * 1024 * 1024;
+const MAX_ARRAY_SIZE: usize = 10_000;
+
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_STRING_SIZE)
+        .set_max_array_size(MAX_ARRAY_SIZE)
+        .set_max_map_size(MAX_ARRAY_SIZE);
+    engine
+});
+
+/// Compiled scripts with the modification time of their file, so a script is only
+/// compiled again after it's edited.
+static SCRIPTS: LazyLock<Mutex<HashMap<PathBuf, Compiled>>> = LazyLock::new(Default::default);
+
+struct Compiled {
+    modified: Option<SystemTime>,
+    ast: Arc<AST>,
+}
+
+/// Run the script on the items, dropping the ones it rejects. The script runs on a
+/// blocking thread, so a slow script doesn't hold up the other channels.
+pub async fn apply(path: &str, items: Vec<Item>) -> Result<Vec<Item>, Error> {
+    let path = resolve(path);
+    tokio::task::spawn_blocking(move || {
+        let ast = compile(&path)?;
+
+        let mut kept = Vec::with_capacity(items.len());
+        for item in items {
+            let title = item.title.clone();
+            if let Some(item) = run(&ast, item).map_err(|err| {
+                Error::Parse(format!("script {} ({title}): {err}", path.display()))
+            })? {
+                kept.push(item);
+            }
+        }
+        Ok(kept)
+    })
+    .await
+    .map_err(|err| Error::Parse(format!("script failed: {err}")))?
+}
+
+/// Compiled script, from the cache if the file didn't change.
+fn compile(path: &Path) -> Result<Arc<AST>, Error> {
+    let modified = fs::metadata(path)
+        .map_err(|err| Error::Io(format!("{}: {err}", path.display())))?
+        .modified()
+        .ok();
+    if let Some(compiled) = SCRIPTS.lock().unwrap().get(path)
+        && modified.is_some()
+        && compiled.modified == modified
+    {
+        return Ok(compiled.ast.clone());
+    }
+
+    let source =
+        fs::read_to_string(path).map_err(|err| Error::Io(format!("{}: {err}", path.display())))?;
+    let ast = ENGINE
+        .compile(source)
+        .map_err(|err| Error::Parse(format!("script {}: {err}", path.display())))?;
+    let ast = Arc::new(ast);
+    let compiled = Compiled {
+        modified,
+        ast: ast.clone(),
+    };
+    SCRIPTS.lock().unwrap().insert(path.to_path_buf(), compiled);
+    Ok(ast)
+}
+
+/// Script's result for the item, `None` if it was dropped.
+fn run(ast: &AST, mut item: Item) -> Result<Option<Item>, String> {
+    let mut scope = Scope::new();
+    scope.push("item", to_map(&item));
+
+    let result: Dynamic = ENGINE
+        .eval_ast_with_scope(&mut scope, ast)
+        .map_err(|err| err.to_string())?;
+    if result.as_bool() == Ok(false) {
+        return Ok(None);
+    }
+
+    let map = scope
+        .get_value::<Map>("item")
+        .ok_or("`item` is not an object anymore")?;
+    item.title = string(&map, "title")?.unwrap_or_default();
+    item.description = string(&map, "description")?;
+    item.author = string(&map, "author")?;
+    item.link = string(&map, "link")?.unwrap_or_default();
+    item.tags = match map.get("tags") {
+        Some(tags) if tags.is_array() => tags
+            .clone()
+            .into_array()?
+            .into_iter()
+            .map(|tag| tag.into_string().map_err(|_| "tags have to be strings"))
+            .collect::<Result<_, _>>()?,
+        Some(tags) if tags.is_unit() => vec![],
+        None => vec![],
+        Some(_) => return Err("`item.tags` has to be an array".to_string()),
+    };
+    Ok(Some(item))
+}
+
+fn to_map(item: &Item) -> Map {
+    let optional = |value: &Option<String>| value.clone().map_or(Dynamic::UNIT, Dynamic::from);
+
+    let mut map = Map::new();
+    map.insert("title".into(), item.title.clone().into());
+    map.insert("description".into(), optional(&item.description));
+    map.insert("author".into(), optional(&item.author));
+    map.insert("link".into(), item.link.clone().into());
+    map.insert("channel".into(), item.channel_name.clone().into());
+    map.insert(
+        "comments".into(),
+        item.comments().map_or(Dynamic::UNIT, Into::into),
+    );
+    map.insert("enclosure".into(), optional(&item.enclosure));
+    map.insert(
+        "date".into(),
+        item.pub_date
+            .map_or(Dynamic::UNIT, |date| date.to_rfc3339().into()),
+    );
+    let tags: Array = item.tags.iter().cloned().map(Dynamic::from).collect();
+    map.insert("tags".into(), tags.into());
+    map
+}
+
+/// String field of the item, `None` if it's unset.
+fn string(map: &Map, key: &str) -> Result<Option<String>, String> {
+    match map.get(key) {
+        None => Ok(None),
+        Some(value) if value.is_unit() => Ok(None),
+        Some(value) => value
+            .clone()
+            .into_string()
+            .map(Some)
+            .map_err(|_| format!("`item.{key}` has to be a string")),
+    }
+}
+
+/// Path of the script. `~` is the home directory and relative paths are in the config
+/// directory, next to `simple-rss.toml`.
+fn resolve(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = env::home_dir()
+    {
+        return home.join(rest);
+    }
+
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+    settings_path()
+        .parent()
+        .map_or_else(|| path.clone(), |dir| dir.join(&path))
+}
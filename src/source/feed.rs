@@ -0,0 +1,211 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use chrono::FixedOffset;
+use feed_rs::model::Entry;
+use futures::future::BoxFuture;
+use quick_xml::{Reader, events::Event};
+use regex::Regex;
+use simple_rss_lib::{
+    Error,
+    data::{AlternateLink, Channel, Item, LinkKind},
+};
+
+use super::Source;
+use crate::http;
+
+/// Typographic entities escaped twice, like `&amp;rsquo;`, by feeds that escape their already
+/// escaped HTML. Entities like `&amp;lt;` are left alone, since they are also written on
+/// purpose to show the entity.
+static DOUBLE_ESCAPED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"&amp;(#[0-9]{2,7}|#[xX][0-9a-fA-F]{2,6}|nbsp|shy|hellip|[mn]dash|[lr][sd]quo|[lr]aquo|bull|middot|copy|reg|trade|deg|times|euro);",
+    )
+    .unwrap()
+});
+
+/// RSS or Atom feed at the channel's url.
+pub struct Feed;
+
+impl Source for Feed {
+    fn name(&self) -> &'static str {
+        "Feed"
+    }
+
+    fn matches(&self, _url: &str) -> bool {
+        true
+    }
+
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>> {
+        Box::pin(get_items(channel, |_, _| ()))
+    }
+}
+
+/// Items of the feed at the channel's url. Each item is passed to `adjust` with the entry
+/// it was read from, for sources whose feeds need cleaning up.
+pub(super) async fn get_items(
+    channel: &Channel,
+    adjust: impl Fn(&Entry, &mut Item),
+) -> Result<Vec<Item>, Error> {
+    let content = http::body(http::send(http::get_channel(channel).await?).await?).await?;
+    let feed = feed_rs::parser::parse(&content[..])
+        .map_err(|err| Error::Parse(format!("Not a feed: {err}")))?;
+
+    let comments = rss_comments(&content);
+
+    let channel_name = channel.name.clone().unwrap_or_else(|| {
+        feed.title
+            .as_ref()
+            .map_or("Unnamed Channel".to_string(), |t| t.content.clone())
+    });
+    let items = feed
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let link = link(entry)?;
+            let mut alternate_links: Vec<_> = comments
+                .get(&entry.id)
+                .or_else(|| comments.get(&link))
+                .cloned()
+                .or_else(|| replies(entry))
+                .map(AlternateLink::comments)
+                .into_iter()
+                .collect();
+            alternate_links.extend(related(entry));
+
+            let mut item = Item {
+                id: format!("{}:{}", channel.url, entry.id),
+                channel_name: channel_name.clone(),
+                title: entry.title.as_ref()?.content.clone(),
+                author: entry.authors.first().map(|author| author.name.clone()),
+                description: entry
+                    .summary
+                    .as_ref()
+                    .map(|d| DOUBLE_ESCAPED.replace_all(&d.content, "&$1;").into_owned()),
+                pub_date: entry
+                    .updated
+                    .or(entry.published)
+                    .map(|p| p.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+                link,
+                enclosure: enclosure(entry),
+                alternate_links,
+                read: false,
+                starred: false,
+                tags: vec![],
+                hidden: None,
+            };
+            adjust(entry, &mut item);
+            Some(item)
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Web page of the entry. Atom entries can also link to their comments, media files and
+/// similar, so the alternate link is preferred.
+fn link(entry: &Entry) -> Option<String> {
+    entry
+        .links
+        .iter()
+        .find(|link| matches!(link.rel.as_deref(), None | Some("alternate")))
+        .or(entry.links.first())
+        .map(|link| link.href.clone())
+}
+
+/// Page of the comments of an Atom entry.
+fn replies(entry: &Entry) -> Option<String> {
+    entry
+        .links
+        .iter()
+        .find(|link| {
+            link.rel.as_deref() == Some("replies")
+                && link.media_type.as_deref().is_none_or(|t| t == "text/html")
+        })
+        .map(|link| link.href.clone())
+}
+
+/// Related pages of an Atom entry.
+fn related(entry: &Entry) -> impl Iterator<Item = AlternateLink> {
+    entry
+        .links
+        .iter()
+        .filter(|link| link.rel.as_deref() == Some("related"))
+        .map(|link| AlternateLink {
+            kind: LinkKind::Related,
+            url: link.href.clone(),
+        })
+}
+
+/// Urls of the comments of RSS items, from their `<comments>` elements, by the guids and
+/// links of the items. The feed parser skips the element.
+fn rss_comments(content: &[u8]) -> HashMap<String, String> {
+    let mut reader = Reader::from_reader(content);
+    reader.config_mut().trim_text(true);
+
+    let mut comments = HashMap::new();
+    // Text of the guid, link and comments elements of the current item.
+    let mut fields: Option<HashMap<Vec<u8>, String>> = None;
+    let mut element = None;
+    let mut buf = vec![];
+    loop {
+        let text = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(elt)) if elt.name().as_ref() == b"item" => {
+                fields = Some(HashMap::new());
+                None
+            }
+            Ok(Event::End(elt)) if elt.name().as_ref() == b"item" => {
+                let mut fields = fields.take().unwrap_or_default();
+                if let Some(url) = fields.remove(&b"comments"[..]) {
+                    for key in [&b"guid"[..], b"link"] {
+                        if let Some(key) = fields.remove(key) {
+                            comments.insert(key, url.clone());
+                        }
+                    }
+                }
+                None
+            }
+            Ok(Event::Start(elt))
+                if matches!(elt.name().as_ref(), b"guid" | b"link" | b"comments") =>
+            {
+                element = Some(elt.name().as_ref().to_vec());
+                None
+            }
+            Ok(Event::End(_)) => {
+                element = None;
+                None
+            }
+            Ok(Event::Text(text)) if element.is_some() => {
+                text.unescape().ok().map(|t| t.into_owned())
+            }
+            Ok(Event::CData(data)) if element.is_some() => {
+                Some(String::from_utf8_lossy(&data).into_owned())
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => None,
+        };
+
+        let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+        if let (Some(text), Some(fields), Some(element)) = (text, &mut fields, &element) {
+            fields.insert(element.clone(), text);
+        }
+        buf.clear();
+    }
+
+    comments
+}
+
+fn enclosure(entry: &Entry) -> Option<String> {
+    let link = entry
+        .links
+        .iter()
+        .find(|link| link.rel.as_deref() == Some("enclosure"))
+        .map(|link| link.href.clone());
+
+    link.or_else(|| {
+        entry
+            .media
+            .iter()
+            .flat_map(|media| &media.content)
+            .find_map(|content| content.url.as_ref().map(|url| url.to_string()))
+    })
+}
@@ -0,0 +1,154 @@
+use std::sync::OnceLock;
+
+use chrono::DateTime;
+use futures::future::{BoxFuture, join_all};
+use serde::Deserialize;
+use simple_rss_lib::{
+    Error,
+    data::{AlternateLink, Channel, Item},
+};
+
+use tracing::warn;
+
+use super::{Source, get_json};
+
+/// Url of the API, used unless another one is configured.
+const API: &str = "https://hacker-news.firebaseio.com/v0";
+
+/// Configured url of the API.
+static API_URL: OnceLock<String> = OnceLock::new();
+
+/// Number of stories on a page of the site.
+const PAGE_SIZE: usize = 30;
+
+/// Stories of Hacker News, read from its API. The channel's url is the front page,
+/// `https://news.ycombinator.com`, or a list of stories in the API, like
+/// `https://hacker-news.firebaseio.com/v0/beststories.json`.
+pub struct HackerNews;
+
+#[derive(Deserialize)]
+struct Story {
+    id: u64,
+    by: Option<String>,
+    /// Unix time of submission.
+    time: Option<i64>,
+    title: Option<String>,
+    url: Option<String>,
+    /// Text of Ask HN and similar posts, in HTML.
+    text: Option<String>,
+    score: Option<u64>,
+    descendants: Option<u64>,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(default)]
+    dead: bool,
+}
+
+impl Source for HackerNews {
+    fn name(&self) -> &'static str {
+        "Hacker News"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        let url = url.trim_end_matches('/');
+        let front_page = [
+            "https://news.ycombinator.com",
+            "https://news.ycombinator.com/news",
+        ];
+        let is_list = url
+            .strip_prefix(api())
+            .is_some_and(|list| list.starts_with('/') && list.ends_with("stories.json"));
+        front_page.contains(&url) || is_list
+    }
+
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>> {
+        Box::pin(get_stories(channel))
+    }
+}
+
+/// Use the API at the url instead of the official one, e.g. a mirror.
+pub fn set_api(url: Option<&str>) {
+    if let Some(url) = url {
+        let _ = API_URL.set(url.trim_end_matches('/').to_string());
+    }
+}
+
+fn api() -> &'static str {
+    API_URL.get().map_or(API, String::as_str)
+}
+
+async fn get_stories(channel: &Channel) -> Result<Vec<Item>, Error> {
+    let api = api();
+    let list = if channel.url.ends_with(".json") {
+        channel.url.clone()
+    } else {
+        format!("{api}/topstories.json")
+    };
+
+    let ids: Vec<u64> = get_json(list).await?;
+    let results = join_all(
+        ids.iter()
+            .take(PAGE_SIZE)
+            .map(|id| get_json::<Option<Story>>(format!("{api}/item/{id}.json"))),
+    )
+    .await;
+
+    // Stories that failed are left out, the channel only fails if all of them did.
+    let mut stories = vec![];
+    let mut error = None;
+    for result in results {
+        match result {
+            Ok(story) => stories.extend(story),
+            Err(err) => {
+                warn!(channel = %channel.url, error = %err, "failed to fetch story");
+                error.get_or_insert(err);
+            }
+        }
+    }
+    if let Some(err) = error.filter(|_| stories.is_empty()) {
+        return Err(err);
+    }
+
+    let channel_name = channel.name.as_deref().unwrap_or("Hacker News");
+    let items = stories
+        .into_iter()
+        .filter(|story| !story.deleted && !story.dead)
+        .filter_map(|story| {
+            let comments = format!("https://news.ycombinator.com/item?id={}", story.id);
+            Some(Item {
+                id: format!("{}:{}", channel.url, story.id),
+                channel_name: channel_name.to_string(),
+                title: story.title.clone()?,
+                author: story.by.clone(),
+                description: Some(description(&story, &comments)),
+                pub_date: story
+                    .time
+                    .and_then(|time| DateTime::from_timestamp(time, 0))
+                    .map(|date| date.fixed_offset()),
+                // Posts without a link are discussed on the site.
+                link: story.url.unwrap_or(comments.clone()),
+                enclosure: None,
+                alternate_links: vec![AlternateLink::comments(comments)],
+                read: false,
+                starred: false,
+                tags: vec![],
+                hidden: None,
+            })
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Text of the story, if it has one, followed by its score and a link to the comments.
+fn description(story: &Story, comments: &str) -> String {
+    let text = story
+        .text
+        .as_ref()
+        .map_or(String::new(), |text| format!("<p>{text}</p>"));
+    format!(
+        "{text}<p>{} points, <a href=\"{comments}\">{} comments</a></p>",
+        story.score.unwrap_or_default(),
+        story.descendants.unwrap_or_default(),
+    )
+}
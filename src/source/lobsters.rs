@@ -0,0 +1,50 @@
+use feed_rs::model::Entry;
+use futures::future::BoxFuture;
+use regex::Regex;
+use reqwest::Url;
+use simple_rss_lib::{
+    Error,
+    data::{Channel, Item},
+};
+
+use super::{Source, feed};
+
+/// Stories of Lobsters, read from its feeds, like `https://lobste.rs/rss` or
+/// `https://lobste.rs/t/rust.rss`. The story's page on the site is kept as the item's
+/// discussion and the link to it is removed from the text.
+pub struct Lobsters;
+
+impl Source for Lobsters {
+    fn name(&self) -> &'static str {
+        "Lobsters"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| url.host_str() == Some("lobste.rs"))
+    }
+
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>> {
+        let footer = Regex::new(r#"<p><a href="([^"]+)">Comments</a></p>"#).unwrap();
+        Box::pin(feed::get_items(channel, move |entry, item| {
+            clean_up(&footer, entry, item)
+        }))
+    }
+}
+
+fn clean_up(footer: &Regex, entry: &Entry, item: &mut Item) {
+    // Guids are the short links of the stories' pages.
+    item.set_comments(entry.id.contains("/s/").then(|| entry.id.clone()));
+
+    let Some(description) = &item.description else {
+        return;
+    };
+    let Some(captures) = footer.captures(description) else {
+        return;
+    };
+    let comments = captures[1].to_string();
+
+    let text = footer.replace(description, "");
+    let text = text.trim();
+    item.description = (!text.is_empty()).then(|| text.to_string());
+    item.set_comments(Some(comments));
+}
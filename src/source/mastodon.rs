@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+};
+
+use chrono::DateTime;
+use futures::future::BoxFuture;
+use reqwest::Url;
+use serde::Deserialize;
+use simple_rss_lib::{
+    Error,
+    data::{AlternateLink, Channel, Item},
+    render::{LinkFormat, RenderOptions, render_to_string},
+};
+
+use tracing::debug;
+
+use super::{Feed, Source, get_json};
+
+/// Longest title taken from the text of a status, in characters.
+const TITLE_LENGTH: usize = 80;
+
+/// Statuses of a Mastodon account, like `https://mastodon.social/@user`, or of a hashtag,
+/// like `https://mastodon.social/tags/rust`, read from the public API of the instance.
+/// Replies and boosts of the account are left out.
+///
+/// Other sites use the same paths, so the site is first asked for its instance info.
+/// Channels of sites that aren't Mastodon instances are read as feeds.
+pub struct Mastodon;
+
+/// Origins that were asked for their instance info, with whether they are instances.
+static INSTANCES: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(Default::default);
+
+/// Timeline followed by the channel.
+enum Timeline {
+    Account(String),
+    Tag(String),
+}
+
+#[derive(Deserialize)]
+struct Instance {
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    id: String,
+    acct: String,
+    #[serde(default)]
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    id: String,
+    created_at: String,
+    /// Page of the status, or `None` for some statuses from other instances.
+    url: Option<String>,
+    uri: String,
+    /// Text of the status, in HTML.
+    #[serde(default)]
+    content: String,
+    /// Content warning, shown instead of the text.
+    #[serde(default)]
+    spoiler_text: String,
+    account: Account,
+    /// Preview of the first link in the status.
+    card: Option<Card>,
+    #[serde(default)]
+    media_attachments: Vec<Attachment>,
+}
+
+#[derive(Deserialize)]
+struct Card {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+impl Source for Mastodon {
+    fn name(&self) -> &'static str {
+        "Mastodon"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        timeline(url).is_some()
+    }
+
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>> {
+        Box::pin(async move {
+            match timeline(&channel.url) {
+                Some((instance, timeline)) if is_instance(&instance).await => {
+                    get_statuses(channel, &instance, timeline).await
+                }
+                _ => Feed.fetch(channel).await,
+            }
+        })
+    }
+}
+
+impl Account {
+    fn name(&self) -> String {
+        if self.display_name.is_empty() {
+            format!("@{}", self.acct)
+        } else {
+            self.display_name.clone()
+        }
+    }
+}
+
+/// Origin of the instance and the timeline at the url, or `None` if the url isn't a profile
+/// or a hashtag. Their RSS feeds, like `/@user.rss`, are read as feeds.
+fn timeline(url: &str) -> Option<(String, Timeline)> {
+    let url = Url::parse(url).ok()?;
+    let segments: Vec<_> = url.path().trim_matches('/').split('/').collect();
+    let timeline = match segments[..] {
+        [account]
+            if account.len() > 1 && account.starts_with('@') && !account.ends_with(".rss") =>
+        {
+            Timeline::Account(account[1..].to_string())
+        }
+        ["tags", tag] if !tag.is_empty() && !tag.contains('.') => Timeline::Tag(tag.to_string()),
+        _ => return None,
+    };
+
+    Some((url.origin().ascii_serialization(), timeline))
+}
+
+/// Returns true if the site at the origin is a Mastodon instance. The answer is remembered,
+/// unless the site couldn't be reached.
+async fn is_instance(origin: &str) -> bool {
+    if let Some(known) = INSTANCES.lock().unwrap().get(origin) {
+        return *known;
+    }
+
+    let known = match get_json::<Instance>(format!("{origin}/api/v1/instance")).await {
+        Ok(instance) => {
+            debug!(
+                origin,
+                version = instance.version,
+                "found Mastodon instance"
+            );
+            true
+        }
+        Err(Error::Parse(_)) => false,
+        Err(_) => return false,
+    };
+    INSTANCES.lock().unwrap().insert(origin.to_string(), known);
+    known
+}
+
+async fn get_statuses(
+    channel: &Channel,
+    instance: &str,
+    timeline: Timeline,
+) -> Result<Vec<Item>, Error> {
+    let (statuses, name): (Vec<Status>, _) = match timeline {
+        Timeline::Account(acct) => {
+            let account: Account =
+                get_json(format!("{instance}/api/v1/accounts/lookup?acct={acct}")).await?;
+            let statuses = get_json(format!(
+                "{instance}/api/v1/accounts/{}/statuses?exclude_replies=true&exclude_reblogs=true",
+                account.id
+            ))
+            .await?;
+            (statuses, account.name())
+        }
+        Timeline::Tag(tag) => {
+            let statuses = get_json(format!("{instance}/api/v1/timelines/tag/{tag}")).await?;
+            (statuses, format!("#{tag}"))
+        }
+    };
+
+    let channel_name = channel.name.clone().unwrap_or(name);
+    let items = statuses
+        .into_iter()
+        .map(|status| {
+            let url = status.url.clone().unwrap_or_else(|| status.uri.clone());
+            // Statuses announcing a post link to it, so the post is opened in the reader
+            // and the status is kept as its discussion.
+            let (link, alternate_links) = match &status.card {
+                Some(card) => (card.url.clone(), vec![AlternateLink::comments(url)]),
+                None => (url, vec![]),
+            };
+
+            Item {
+                id: format!("{}:{}", channel.url, status.id),
+                channel_name: channel_name.clone(),
+                title: title(&status),
+                author: Some(status.account.name()),
+                pub_date: DateTime::parse_from_rfc3339(&status.created_at).ok(),
+                link,
+                enclosure: status
+                    .media_attachments
+                    .into_iter()
+                    .find(|media| media.kind == "video" || media.kind == "audio")
+                    .map(|media| media.url),
+                alternate_links,
+                description: Some(status.content),
+                read: false,
+                starred: false,
+                tags: vec![],
+                hidden: None,
+            }
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Content warning of the status, or else the first line of its text, shortened.
+fn title(status: &Status) -> String {
+    if !status.spoiler_text.is_empty() {
+        return status.spoiler_text.clone();
+    }
+
+    let options = RenderOptions {
+        colorize: false,
+        markup: false,
+        links: LinkFormat::Hidden,
+        ..Default::default()
+    };
+    let text = render_to_string(&status.content, usize::MAX, &options);
+    let Some(line) = text.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return format!("Post by {}", status.account.name());
+    };
+
+    if line.chars().count() > TITLE_LENGTH {
+        let short: String = line.chars().take(TITLE_LENGTH - 1).collect();
+        format!("{}…", short.trim_end())
+    } else {
+        line.to_string()
+    }
+}
@@ -0,0 +1,113 @@
+//! Sources of the channels' items. Most channels are RSS or Atom feeds, but items can also
+//! be read from the APIs of sites or from feeds that need cleaning up, chosen by the
+//! channel's url.
+
+use std::time::Instant;
+
+use chrono::{TimeDelta, Utc};
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use simple_rss_lib::{
+    Error,
+    data::{Channel, Item},
+};
+use tracing::{debug, warn};
+
+use crate::{config::Config, data::Fetch, http, script};
+
+mod feed;
+mod hacker_news;
+mod lobsters;
+mod mastodon;
+mod reddit;
+
+pub use feed::Feed;
+pub use hacker_news::HackerNews;
+pub use lobsters::Lobsters;
+pub use mastodon::Mastodon;
+pub use reddit::Reddit;
+
+/// Source of the items of a channel.
+pub trait Source: Sync {
+    /// Name of the source, shown when checking the channels.
+    fn name(&self) -> &'static str;
+
+    /// Whether the channel with the url is read from this source.
+    fn matches(&self, url: &str) -> bool;
+
+    /// Fetch the current items of the channel. Items are returned unread.
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>>;
+}
+
+/// Sources in the order they are matched. Channels that match no other source are feeds.
+static SOURCES: &[&dyn Source] = &[&HackerNews, &Mastodon, &Reddit, &Lobsters];
+
+/// Set up the sources with the user's config.
+pub fn init(config: &Config) {
+    hacker_news::set_api(config.hacker_news_api.as_deref());
+}
+
+/// Source of the channel's items.
+pub fn for_channel(channel: &Channel) -> &'static dyn Source {
+    SOURCES
+        .iter()
+        .copied()
+        .find(|source| source.matches(&channel.url))
+        .unwrap_or(&Feed)
+}
+
+/// Fetch the channel's items from its source. Items older than the channel's retention
+/// are dropped, then the rest are passed through the channel's script.
+pub async fn fetch(channel: &Channel) -> Result<Vec<Item>, Error> {
+    let started = Instant::now();
+    let result = fetch_items(channel).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(items) => {
+            debug!(channel = %channel.url, items = items.len(), elapsed_ms, "fetched channel");
+        }
+        Err(err) => {
+            warn!(channel = %channel.url, error = %err, elapsed_ms, "failed to fetch channel")
+        }
+    }
+    result
+}
+
+/// Fetch the channel's items like [`fetch`], measuring how long it took and how much was
+/// downloaded.
+pub async fn fetch_measured(channel: &Channel) -> (Result<Vec<Item>, Error>, Fetch) {
+    let at = Utc::now();
+    let started = Instant::now();
+    let (result, bytes) = http::count_bytes(fetch(channel)).await;
+    let fetch = Fetch {
+        at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        bytes,
+        ok: result.is_ok(),
+    };
+    (result, fetch)
+}
+
+async fn fetch_items(channel: &Channel) -> Result<Vec<Item>, Error> {
+    let mut items = for_channel(channel).fetch(channel).await?;
+
+    // Retention longer than dates can go back keeps all the items.
+    let since = channel.settings.retention_days.and_then(|days| {
+        let days = TimeDelta::try_days(i64::try_from(days).ok()?)?;
+        Utc::now().checked_sub_signed(days)
+    });
+    if let Some(since) = since {
+        items.retain(|it| it.pub_date.is_none_or(|date| date >= since));
+    }
+    if let Some(path) = &channel.settings.script {
+        items = script::apply(path, items).await?;
+    }
+
+    Ok(items)
+}
+
+/// Response of a JSON API.
+async fn get_json<T: DeserializeOwned>(url: impl AsRef<str>) -> Result<T, Error> {
+    let body = http::body(http::send(http::get(url.as_ref())).await?).await?;
+    serde_json::from_slice(&body).map_err(|err| Error::Parse(err.to_string()))
+}
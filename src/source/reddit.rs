@@ -0,0 +1,73 @@
+use feed_rs::model::Entry;
+use futures::future::BoxFuture;
+use regex::Regex;
+use reqwest::Url;
+use simple_rss_lib::{
+    Error,
+    data::{Channel, Item},
+};
+
+use super::{Source, feed};
+
+/// Posts of a subreddit or a user, read from their feed, like
+/// `https://www.reddit.com/r/rust/.rss`. Feeds on the other hosts of the site, like
+/// `old.reddit.com`, work as well.
+///
+/// Reddit replaces the text of the posts with a "submitted by" footer linking to the post
+/// and its comments. Items link to the submitted page instead, the comments are kept as the
+/// item's discussion and only the text of self posts is kept.
+pub struct Reddit;
+
+impl Source for Reddit {
+    fn name(&self) -> &'static str {
+        "Reddit"
+    }
+
+    fn matches(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else {
+            return false;
+        };
+        let is_reddit = url
+            .host_str()
+            .is_some_and(|host| host == "reddit.com" || host.ends_with(".reddit.com"));
+        let path = url.path();
+        is_reddit
+            && (path.starts_with("/r/") || path.starts_with("/user/"))
+            && path.ends_with(".rss")
+    }
+
+    fn fetch<'a>(&'a self, channel: &'a Channel) -> BoxFuture<'a, Result<Vec<Item>, Error>> {
+        let link = Regex::new(r#"<a href="([^"]+)">\[link\]</a>"#).unwrap();
+        Box::pin(feed::get_items(channel, move |entry, item| {
+            clean_up(&link, entry, item)
+        }))
+    }
+}
+
+fn clean_up(link: &Regex, entry: &Entry, item: &mut Item) {
+    let Some(content) = entry.content.as_ref().and_then(|c| c.body.as_ref()) else {
+        return;
+    };
+    if !content.contains("submitted by") {
+        return;
+    }
+
+    // The entry links to the comments.
+    item.set_comments(Some(item.link.clone()));
+    if let Some(href) = link.captures(content).map(|c| c[1].replace("&amp;", "&")) {
+        item.link = href;
+    }
+
+    item.description = self_text(content).map(str::to_string);
+    item.author = item
+        .author
+        .take()
+        .map(|a| a.trim_start_matches('/').to_string());
+}
+
+/// Text of a self post, without the footer.
+fn self_text(content: &str) -> Option<&str> {
+    let (_, text) = content.split_once("<!-- SC_OFF -->")?;
+    let (text, _) = text.split_once("<!-- SC_ON -->")?;
+    Some(text.trim())
+}
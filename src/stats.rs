@@ -0,0 +1,166 @@
+use std::cmp::Reverse;
+
+use chrono::Utc;
+use colored::Colorize;
+use simple_rss_lib::data::{Channel, ItemStats};
+use unicode_width::UnicodeWidthStr;
+
+use crate::data::{
+    duration_label, load_data, load_fetch_metrics, load_history, save_history, size_label,
+};
+
+/// Channels with at least this many items can be reported as low engagement.
+const MIN_ITEMS: usize = 10;
+
+/// Channels with a smaller share of opened items are reported as low engagement.
+const LOW_ENGAGEMENT_PERCENT: usize = 10;
+
+/// Prints item counts, posting frequency and average age of the items of each channel, how
+/// many of them were opened, and suggests channels to remove based on all the items ever
+/// stored. Then prints the metrics of the latest fetches of each channel.
+pub fn show_stats() -> anyhow::Result<()> {
+    let data = load_data()?;
+    let mut history = load_history()?;
+    history.record_items(&data.channels, &data.items);
+    save_history(&history)?;
+
+    if data.channels.is_empty() {
+        println!(
+            "No channels added!\nRun `{}` to add a channel.",
+            "simple-rss ch add".white()
+        );
+        return Ok(());
+    }
+
+    let stats = history.channel_stats();
+    let names: Vec<_> = data
+        .channels
+        .iter()
+        .map(|ch| ch.name.clone().unwrap_or_else(|| ch.url.clone()))
+        .collect();
+    let name_len = names.iter().map(|n| n.width()).max().unwrap_or(0).max(7);
+
+    println!(
+        "{}{}  {:>6}  {:>6}  {:>10}  {:>9}  {:>6}  {:>6}",
+        "Channel".bold(),
+        " ".repeat(name_len - 7),
+        "Items".bold(),
+        "Unread".bold(),
+        "Frequency".bold(),
+        "Avg. age".bold(),
+        "Opened".bold(),
+        "Opens".bold()
+    );
+
+    let now = Utc::now();
+    let mut low_engagement = vec![];
+    for (channel, name) in data.channels.iter().zip(&names) {
+        let channel_items: Vec<_> = data
+            .items
+            .iter()
+            .filter(|it| channel.contains(it))
+            .collect();
+        let items = ItemStats::new(channel_items.iter().copied(), now);
+
+        // Opened share of the items in the table, which don't include the removed ones.
+        let opens: Vec<_> = channel_items
+            .iter()
+            .map(|it| history.opens(&it.id))
+            .collect();
+        let opened = opens.iter().filter(|opens| **opens > 0).count();
+        let percent = (opened * 100).checked_div(items.items).unwrap_or(0);
+
+        println!(
+            "{name}{}  {:>6}  {:>6}  {:>10}  {:>9}  {:>5}%  {:>6}",
+            " ".repeat(name_len - name.width()),
+            items.items,
+            items.unread,
+            items.frequency_label(),
+            items.age_label(),
+            percent,
+            opens.iter().sum::<u32>()
+        );
+
+        let st = stats.get(channel.url.as_str()).copied().unwrap_or_default();
+        let all_time_percent = (st.opened * 100).checked_div(st.items).unwrap_or(0);
+        if st.items >= MIN_ITEMS && all_time_percent < LOW_ENGAGEMENT_PERCENT {
+            low_engagement.push((name, st));
+        }
+    }
+
+    if !low_engagement.is_empty() {
+        println!("\n{}", "Low engagement, consider removing:".yellow().bold());
+        for (name, st) in low_engagement {
+            println!(
+                "  {name} ({} of {} items ever stored were opened)",
+                st.opened, st.items
+            );
+        }
+    }
+
+    show_fetches(&data.channels, &names)?;
+    Ok(())
+}
+
+/// Prints how long the latest fetches of each channel took, how much they downloaded and how
+/// many failed, and which channels slow the refreshes down.
+fn show_fetches(channels: &[Channel], names: &[String]) -> anyhow::Result<()> {
+    let metrics = load_fetch_metrics()?;
+    let summaries: Vec<_> = channels
+        .iter()
+        .zip(names)
+        .filter_map(|(ch, name)| Some((name, metrics.summary(&ch.url)?)))
+        .collect();
+    if summaries.is_empty() {
+        return Ok(());
+    }
+
+    let name_len = names.iter().map(|n| n.width()).max().unwrap_or(0).max(7);
+    println!(
+        "\n{}{}  {:>9}  {:>9}  {:>9}  {:>7}",
+        "Channel".bold(),
+        " ".repeat(name_len - 7),
+        "Avg. time".bold(),
+        "Max. time".bold(),
+        "Avg. size".bold(),
+        "Failed".bold()
+    );
+    for (name, summary) in &summaries {
+        println!(
+            "{name}{}  {:>9}  {:>9}  {:>9}  {:>7}",
+            " ".repeat(name_len - name.width()),
+            duration_label(summary.avg_ms),
+            duration_label(summary.max_ms),
+            size_label(summary.avg_bytes),
+            format!("{}/{}", summary.recent_failed, summary.recent)
+        );
+    }
+
+    let mut slow: Vec<_> = summaries.iter().filter(|(_, s)| s.is_slow()).collect();
+    slow.sort_by_key(|(_, s)| Reverse(s.avg_ms));
+    if !slow.is_empty() {
+        println!(
+            "\n{}",
+            "Slow, every refresh waits for them:".yellow().bold()
+        );
+        for (name, summary) in slow {
+            println!("  {name} ({} on average)", duration_label(summary.avg_ms));
+        }
+    }
+
+    let unreliable: Vec<_> = summaries
+        .iter()
+        .filter(|(_, s)| s.is_unreliable())
+        .collect();
+    if !unreliable.is_empty() {
+        println!("\n{}", "Often failing:".yellow().bold());
+        for (name, summary) in unreliable {
+            println!(
+                "  {name} ({} of the last {} fetches, {} of {} in total)",
+                summary.recent_failed, summary.recent, summary.failed, summary.total
+            );
+        }
+    }
+
+    Ok(())
+}
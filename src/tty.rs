@@ -0,0 +1,86 @@
+//! Raw mode and the alternate screen used by the reader, restored on every exit.
+//!
+//! The terminal is restored when the [`Guard`] is dropped, also when returning early with an
+//! error, and before the message of a panic in any thread is printed. Otherwise the shell is
+//! left in raw mode and unusable. Panics in background tasks are reported by [`panicked`], so
+//! that the reader stops instead of drawing to the restored terminal.
+
+use std::{
+    io::{self, stdout},
+    panic,
+    sync::{
+        Once,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crossterm::{
+    cursor::Show,
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{DefaultTerminal, Terminal, backend::CrosstermBackend};
+use tokio::sync::Notify;
+
+/// Whether the terminal is in raw mode and has to be restored.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Notified when a thread panics while the terminal is active.
+static PANICKED: Notify = Notify::const_new();
+
+/// Restores the terminal when dropped.
+pub struct Guard(());
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Enter raw mode and the alternate screen, until the guard is dropped.
+pub fn enter() -> io::Result<(DefaultTerminal, Guard)> {
+    set_panic_hook();
+    let terminal = enable()?;
+    Ok((terminal, Guard(())))
+}
+
+/// Enter raw mode and the alternate screen again, after [`restore`].
+pub fn enable() -> io::Result<DefaultTerminal> {
+    ACTIVE.store(true, Ordering::SeqCst);
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+/// Leave raw mode and the alternate screen. Does nothing if the terminal is already restored.
+pub fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    // Nothing more can be done if restoring fails.
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), LeaveAlternateScreen, Show);
+}
+
+fn set_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let active = ACTIVE.load(Ordering::SeqCst);
+            restore();
+            hook(info);
+
+            if active {
+                PANICKED.notify_one();
+            }
+        }));
+    });
+}
+
+/// Completes when a thread panicked while the terminal was active. Panic of the calling
+/// thread unwinds on its own, this is for the ones in background tasks.
+pub async fn panicked() {
+    PANICKED.notified().await;
+}
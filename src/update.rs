@@ -0,0 +1,181 @@
+//! Checking for new releases with the project's own release feed.
+
+use feed_rs::model::Entry;
+use simple_rss_lib::{
+    Error,
+    data::Item,
+    event::{Event, EventSender, ToastEvent, next_load_id},
+};
+
+use crate::http::client;
+
+const RELEASES_URL: &str = "https://github.com/viddrobnic/simple-rss/releases.atom";
+
+struct Release {
+    version: String,
+    notes: String,
+}
+
+/// Shows a toast if a newer version than the running one was released.
+pub fn spawn_check(event_tx: EventSender) {
+    tokio::spawn(async move {
+        // Failed check is not worth bothering the user with.
+        let Ok(releases) = fetch_releases().await else {
+            return;
+        };
+
+        if let Some(name) = newer_release(&releases, env!("CARGO_PKG_VERSION")) {
+            event_tx.send(Event::Toast(ToastEvent::Info(format!(
+                "{name} released! Press C"
+            ))));
+        }
+    });
+}
+
+/// Loads the release notes and opens them in the content pane.
+pub fn show_changelog(event_tx: EventSender) {
+    let item = Item {
+        id: RELEASES_URL.to_string(),
+        channel_name: "simple-rss".to_string(),
+        title: "Changelog".to_string(),
+        author: None,
+        description: None,
+        pub_date: None,
+        link: "https://github.com/viddrobnic/simple-rss/releases".to_string(),
+        enclosure: None,
+        alternate_links: vec![],
+        read: true,
+        starred: false,
+        tags: vec![],
+        hidden: None,
+    };
+    let id = next_load_id();
+    event_tx.send(Event::StartLoadingItem(id, Box::new(item)));
+
+    tokio::spawn(async move {
+        let html = fetch_releases()
+            .await
+            .map(|releases| {
+                releases
+                    .iter()
+                    .map(|r| format!("<h2>{}</h2>{}", escape(&r.version), r.notes))
+                    .collect()
+            })
+            .map_err(|err| Error::Network(format!("{err:#}")));
+
+        event_tx.send(Event::LoadedItem(id, html));
+    });
+}
+
+/// Name of the newest release, if it's newer than the current version.
+fn newer_release<'a>(releases: &'a [Release], current: &str) -> Option<&'a str> {
+    let current = parse_version(current);
+    let (version, name) = releases
+        .iter()
+        .filter_map(|r| Some((parse_version(&r.version)?, &r.version)))
+        .max()?;
+
+    (Some(version) > current).then_some(name.as_str())
+}
+
+async fn fetch_releases() -> anyhow::Result<Vec<Release>> {
+    let content = client()
+        .get(RELEASES_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    parse_releases(&content)
+}
+
+fn parse_releases(content: &[u8]) -> anyhow::Result<Vec<Release>> {
+    let feed = feed_rs::parser::parse(content)?;
+    Ok(feed.entries.into_iter().map(release).collect())
+}
+
+fn release(entry: Entry) -> Release {
+    // Release links end with the tag, which is more reliable than the title.
+    let tag = entry
+        .links
+        .first()
+        .and_then(|link| link.href.rsplit('/').next())
+        .map(|tag| tag.to_string());
+    let version = tag
+        .or_else(|| entry.title.map(|t| t.content))
+        .unwrap_or_default();
+
+    let notes = entry
+        .content
+        .and_then(|c| c.body)
+        .or_else(|| entry.summary.map(|s| s.content))
+        .unwrap_or_default();
+
+    Release { version, notes }
+}
+
+/// Parses versions like `v1.2.3` into comparable numbers.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Release notes from simple-rss</title>
+  <entry>
+    <id>tag:github.com,2008:Repository/1/v0.10.0</id>
+    <title>Search and sync</title>
+    <link rel="alternate" type="text/html" href="https://github.com/viddrobnic/simple-rss/releases/tag/v0.10.0"/>
+    <content type="html">&lt;p&gt;New search&lt;/p&gt;</content>
+  </entry>
+  <entry>
+    <id>tag:github.com,2008:Repository/1/v0.9.2</id>
+    <title>v0.9.2</title>
+    <content type="html">&lt;p&gt;Fixes&lt;/p&gt;</content>
+  </entry>
+  <entry>
+    <id>tag:github.com,2008:Repository/1/nightly</id>
+    <title>Nightly</title>
+    <link rel="alternate" type="text/html" href="https://github.com/viddrobnic/simple-rss/releases/tag/nightly"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn releases_are_parsed() {
+        let releases = parse_releases(RELEASES.as_bytes()).unwrap();
+        let parsed: Vec<_> = releases
+            .iter()
+            .map(|r| (r.version.as_str(), r.notes.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("v0.10.0", "<p>New search</p>"),
+                ("v0.9.2", "<p>Fixes</p>"),
+                ("nightly", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_newer_release_is_reported() {
+        let releases = parse_releases(RELEASES.as_bytes()).unwrap();
+        // Versions are compared as numbers, not as text.
+        assert_eq!(newer_release(&releases, "0.9.10"), Some("v0.10.0"));
+        assert_eq!(newer_release(&releases, "0.10.0"), None);
+        assert_eq!(newer_release(&releases, "1.0.0"), None);
+        assert_eq!(newer_release(&[], "0.1.0"), None);
+    }
+}
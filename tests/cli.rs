@@ -0,0 +1,1958 @@
+use std::{
+    fs, io,
+    time::{Duration, Instant},
+};
+
+use chrono::{TimeDelta, Utc};
+use serde_json::Value;
+use simple_rss_lib::testing::{MockResponse, MockServer};
+
+mod common;
+
+use common::Env;
+
+const FEED: &str = include_str!("fixtures/feed.xml");
+const ARTICLE: &str = include_str!("fixtures/article.html");
+
+fn fixture_server() -> MockServer {
+    let server = MockServer::start();
+    server.serve("/feed.xml", MockResponse::Body(FEED.to_string()));
+    server.serve("/articles/first", MockResponse::Body(ARTICLE.to_string()));
+    server
+}
+
+fn sync_server() -> MockServer {
+    let server = MockServer::start();
+    let api = "/api/greader.php";
+    let reader = format!("{api}/reader/api/0");
+    server.serve(
+        &format!("{api}/accounts/ClientLogin"),
+        MockResponse::Body("SID=sid\nLSID=lsid\nAuth=token\n".to_string()),
+    );
+    server.serve(
+        &format!("{reader}/subscription/list"),
+        MockResponse::Body(include_str!("fixtures/greader/subscriptions.json").to_string()),
+    );
+    server.serve(
+        &format!("{reader}/stream/contents/user/-/state/com.google/reading-list"),
+        MockResponse::Body(include_str!("fixtures/greader/items.json").to_string()),
+    );
+    server.serve(
+        &format!("{reader}/stream/items/ids"),
+        MockResponse::Body(include_str!("fixtures/greader/unread.json").to_string()),
+    );
+    server.serve(
+        &format!("{reader}/stream/items/ids?output=json&s=user%2F-%2Fstate%2Fcom.google%2Fstarred&n=10000"),
+        MockResponse::Body(include_str!("fixtures/greader/starred.json").to_string()),
+    );
+    server.serve(
+        &format!("{reader}/token"),
+        MockResponse::Body("token\n".to_string()),
+    );
+    server.serve(
+        &format!("{reader}/edit-tag"),
+        MockResponse::Body("OK".to_string()),
+    );
+    server
+}
+
+fn stored_items(env: &Env) -> Vec<Value> {
+    let content = fs::read_to_string(env.data_file()).unwrap();
+    serde_json::from_str(&content).unwrap()
+}
+
+/// Url of the stored item's discussion, or null.
+fn comments(item: &Value) -> Value {
+    item["alternate_links"]
+        .as_array()
+        .and_then(|links| links.iter().find(|link| link["kind"] == "comments"))
+        .map_or(Value::Null, |link| link["url"].clone())
+}
+
+#[test]
+fn channel_add_list_remove() {
+    let env = Env::new();
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("No channels added!"));
+
+    env.run_ok(&["channel", "add", "https://one.example/feed.xml"]);
+    env.run_ok(&[
+        "channel",
+        "add",
+        "https://two.example/atom.xml",
+        "--name",
+        "Second",
+    ]);
+
+    let out = env.run_ok(&["ch", "ls"]);
+    assert!(out.contains("https://one.example/feed.xml"));
+    assert!(out.contains("https://two.example/atom.xml"));
+    assert!(out.contains("Second"));
+
+    env.run_ok(&["channel", "remove", "0"]);
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(!out.contains("https://one.example/feed.xml"));
+    assert!(out.contains("https://two.example/atom.xml"));
+
+    let out = env.run_ok(&["channel", "remove", "5"]);
+    assert!(out.contains("Invalid index!"));
+}
+
+#[test]
+fn import_opml() {
+    let env = Env::new();
+    let opml = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/subscriptions.opml"
+    );
+
+    env.run_ok(&["channel", "add", "https://three.example/feed.xml"]);
+    let out = env.run_ok(&["channel", "import", opml, "--yes"]);
+    assert!(out.contains("Imported 3 channels!"));
+
+    // Feeds in several folders are added once, with the tags of all of them. The channel
+    // that was already added is kept as it was.
+    let channels = fs::read_to_string(env.path().join("config").join("simple-rss")).unwrap();
+    let channels: Vec<Value> = serde_json::from_str(&channels).unwrap();
+    let urls: Vec<_> = channels
+        .iter()
+        .map(|ch| ch["url"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            "https://three.example/feed.xml",
+            "https://one.example/feed.xml",
+            "https://two.example/feed.xml",
+            "https://untitled.example/feed.xml",
+        ]
+    );
+    assert_eq!(channels[0]["tags"], Value::Null);
+    assert_eq!(channels[1]["name"], "One Blog");
+    assert_eq!(channels[1]["tags"], serde_json::json!(["Tech", "News"]));
+    assert_eq!(channels[2]["name"], "Second & Blog");
+    assert_eq!(channels[3]["name"], Value::Null);
+
+    let out = env.run_ok(&["channel", "import", opml, "--yes"]);
+    assert!(out.contains("Imported 0 channels!"));
+
+    let out = env.run_ok(&["channel", "import", "--yes", "Cargo.toml"]);
+    assert!(out.contains("No feeds found!"));
+    let output = env.run(&["channel", "import", "--yes", "missing.opml"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn import_newsboat() {
+    let env = Env::new();
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/newsboat");
+    let urls = format!("{fixtures}/urls");
+    let cache = format!("{fixtures}/cache.db");
+
+    env.run_ok(&["channel", "add", "https://two.example/feed.xml"]);
+    let out = env.run_ok(&[
+        "channel",
+        "import",
+        "--newsboat",
+        &urls,
+        "--cache",
+        &cache,
+        "--yes",
+    ]);
+    assert!(out.contains("query:Unread Articles:unread = \"yes\": query feeds are not supported"));
+    assert!(out.contains("exec:~/bin/feed.sh: command feeds are not supported"));
+    assert!(out.contains("filter:~/bin/filter.sh:https://three.example/feed.xml: command feeds"));
+    assert!(out.contains("Imported 2 channels and 2 items!"));
+
+    // The channel that was already added keeps its settings.
+    let channels = fs::read_to_string(env.path().join("config").join("simple-rss")).unwrap();
+    let channels: Vec<Value> = serde_json::from_str(&channels).unwrap();
+    assert_eq!(channels.len(), 3);
+    assert_eq!(channels[0]["url"], "https://two.example/feed.xml");
+    assert_eq!(channels[0]["name"], Value::Null);
+    assert_eq!(channels[1]["name"], "One Blog");
+    assert_eq!(channels[1]["tags"], serde_json::json!(["tech", "news"]));
+    assert_eq!(channels[2]["name"], "Hidden feed");
+    assert_eq!(channels[2]["tags"], Value::Null);
+
+    // Deleted items and items of the channels that were already added are not imported.
+    let items = stored_items(&env);
+    let titles: Vec<_> = items
+        .iter()
+        .map(|it| it["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Unread post", "Read post"]);
+    assert_eq!(items[0]["read"], false);
+    assert_eq!(items[0]["enclosure"], "https://one.example/2.mp3");
+    assert_eq!(items[1]["read"], true);
+    assert_eq!(items[1]["author"], "Ana");
+    assert_eq!(items[1]["channel_name"], "One Blog");
+    assert_eq!(items[1]["id"], "https://one.example/feed.xml:one-1");
+
+    // Importing again doesn't duplicate anything.
+    let out = env.run_ok(&[
+        "channel",
+        "import",
+        "--newsboat",
+        &urls,
+        "--cache",
+        &cache,
+        "--yes",
+    ]);
+    assert!(out.contains("Imported 0 channels and 0 items!"));
+    assert_eq!(stored_items(&env).len(), 2);
+}
+
+#[test]
+fn channel_add_shorthands() {
+    let env = Env::new();
+
+    env.run_ok(&["channel", "add", "--github", "ratatui/ratatui"]);
+    env.run_ok(&[
+        "channel",
+        "add",
+        "--github",
+        "https://github.com/tokio-rs/tokio/",
+        "--commits",
+        "--name",
+        "Tokio",
+    ]);
+    env.run_ok(&["channel", "add", "--reddit", "r/rust"]);
+    env.run_ok(&["channel", "add", "--lobsters"]);
+    env.run_ok(&["channel", "add", "--lobsters", "rust"]);
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("https://github.com/ratatui/ratatui/releases.atom"));
+    assert!(out.contains("ratatui releases"));
+    assert!(out.contains("https://github.com/tokio-rs/tokio/commits.atom"));
+    assert!(out.contains("Tokio"));
+    assert!(out.contains("https://www.reddit.com/r/rust/.rss"));
+    assert!(out.contains("https://lobste.rs/rss"));
+    assert!(out.contains("https://lobste.rs/t/rust.rss"));
+    assert!(out.contains("Lobsters: rust"));
+
+    let output = env.run(&["channel", "add", "--github", "ratatui"]);
+    assert!(!output.status.success());
+    let output = env.run(&[
+        "channel",
+        "add",
+        "https://one.example/feed.xml",
+        "--commits",
+    ]);
+    assert!(!output.status.success());
+    let output = env.run(&["channel", "add", "--reddit", "rust", "--lobsters"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn channel_add_from_file() {
+    let server = fixture_server();
+    server.serve("/gone.xml", MockResponse::Status(404));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+
+    let list = env.path().join("urls.txt");
+    fs::write(
+        &list,
+        format!(
+            "# Feeds\n\n{} tech \"~Fixture\"\n{}\n{}\nnot a url\n",
+            server.url("/feed.xml"),
+            server.url("/feed.xml?all"),
+            server.url("/gone.xml"),
+        ),
+    )
+    .unwrap();
+
+    let output = env.run(&["channel", "add", "--from-file", list.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("already added"));
+    assert!(out.contains("(2 items)"));
+    assert!(out.contains("404"));
+    assert!(out.contains("invalid url"));
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains(&server.url("/feed.xml?all")));
+    assert!(!out.contains(&server.url("/gone.xml")));
+}
+
+#[test]
+fn channel_move() {
+    let env = Env::new();
+    for name in ["first", "second", "third"] {
+        env.run_ok(&[
+            "channel",
+            "add",
+            &format!("https://{name}.example/feed.xml"),
+        ]);
+    }
+
+    env.run_ok(&["channel", "move", "0", "2"]);
+    env.run_ok(&["ch", "mv", "1", "0"]);
+    let out = env.run_ok(&["channel", "list"]);
+    let order: Vec<_> = ["third", "second", "first"]
+        .iter()
+        .map(|name| out.find(&format!("https://{name}.example")).unwrap())
+        .collect();
+    assert!(order.is_sorted());
+
+    let out = env.run_ok(&["channel", "move", "0", "3"]);
+    assert!(out.contains("Invalid index!"));
+}
+
+#[test]
+fn channel_edit() {
+    let env = Env::new();
+    env.run_ok(&["channel", "add", "https://one.example/feed.xml"]);
+
+    env.run_ok(&[
+        "channel",
+        "edit",
+        "0",
+        "--name",
+        "Renamed",
+        "--url",
+        "https://new.example/feed.xml",
+    ]);
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("Renamed"));
+    assert!(out.contains("https://new.example/feed.xml"));
+    assert!(!out.contains("https://one.example/feed.xml"));
+}
+
+#[test]
+fn channel_settings() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+
+    let output = env.run(&["channel", "edit", "0", "--set", "colour=blue"]);
+    assert!(!output.status.success());
+    let output = env.run(&["channel", "edit", "0", "--set", "readability=maybe"]);
+    assert!(!output.status.success());
+
+    env.run_ok(&["channel", "edit", "0", "--set", "use_description=true"]);
+    env.run_ok(&["refresh"]);
+    let out = env.run_ok(&["read", "1", "--format", "text"]);
+    assert!(out.contains("Summary of the first post"));
+    assert_eq!(server.hits("/articles/first"), 0);
+
+    // Fixture items are from January 2025.
+    env.run_ok(&[
+        "channel",
+        "edit",
+        "0",
+        "--set",
+        "use_description=default",
+        "--set",
+        "retention_days=30",
+    ]);
+    env.run_ok(&["refresh"]);
+    assert!(stored_items(&env).is_empty());
+
+    // Retention longer than dates can go back keeps everything instead of panicking.
+    env.run_ok(&[
+        "channel",
+        "edit",
+        "0",
+        "--set",
+        "retention_days=18446744073709551615",
+    ]);
+    env.run_ok(&["refresh"]);
+    assert_eq!(stored_items(&env).len(), 2);
+
+    env.run_ok(&["channel", "edit", "0", "--set", "retention_days=30"]);
+    let channels = fs::read_to_string(env.path().join("config").join("simple-rss")).unwrap();
+    assert!(channels.contains(r#""settings":{"retention_days":30}"#));
+}
+
+#[test]
+fn refresh_runs_channel_script() {
+    let server = fixture_server();
+    let env = Env::new().with_config("");
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    fs::write(
+        env.path().join("config").join("filter.rhai"),
+        r#"
+        if item.title.starts_with("Second") {
+            return false;
+        }
+        item.title = "[" + item.channel + "] " + item.title;
+        item.tags.push("script");
+        "#,
+    )
+    .unwrap();
+    env.run_ok(&["channel", "edit", "0", "--set", "script=filter.rhai"]);
+
+    env.run_ok(&["refresh"]);
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["title"], "[Fixture Blog] First post");
+    assert_eq!(items[0]["tags"], serde_json::json!(["script"]));
+    let out = env.run_ok(&["item", "list", "--tag", "script"]);
+    assert!(out.contains("First post [script]"));
+
+    fs::write(
+        env.path().join("config").join("filter.rhai"),
+        "item.title = ",
+    )
+    .unwrap();
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("script ") && stderr.contains("filter.rhai"));
+}
+
+#[test]
+fn verbose_refresh_is_logged() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["channel", "add", &server.url("/missing.xml")]);
+
+    let output = env.run(&["-v", "refresh"]);
+    assert!(!output.status.success());
+
+    let logs = env.path().join("state").join("simple-rss").join("logs");
+    let log: String = fs::read_dir(logs)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(log.contains("refresh finished channels=2 items=2 errors=1"));
+    assert!(log.contains("failed to fetch channel"));
+    assert!(log.contains("/missing.xml"));
+    // Requests are logged only with -vv.
+    assert!(!log.contains("DEBUG"));
+}
+
+#[test]
+fn refresh_needs_channel_credentials() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&[
+        "channel",
+        "add",
+        &server.url("/feed.xml"),
+        "--token",
+        "env:SIMPLE_RSS_TEST_MISSING_TOKEN",
+        "--header",
+        "X-Api-Key: key",
+    ]);
+
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Missing credentials"));
+    assert_eq!(server.hits("/feed.xml"), 0);
+
+    env.run_ok(&["channel", "edit", "0", "--clear-auth"]);
+    env.run_ok(&["refresh"]);
+    assert_eq!(stored_items(&env).len(), 2);
+}
+
+#[test]
+fn channel_credentials_stay_on_channel() {
+    let server = fixture_server();
+    server.serve("/other.xml", MockResponse::Body(FEED.to_string()));
+    let elsewhere = MockServer::start();
+    elsewhere.serve("/articles/first", MockResponse::Body(ARTICLE.to_string()));
+    let env = Env::new();
+    env.run_ok(&[
+        "channel",
+        "add",
+        &server.url("/feed.xml"),
+        "--header",
+        "X-Api-Key: key",
+    ]);
+    env.run_ok(&["channel", "add", &server.url("/other.xml")]);
+    env.run_ok(&["refresh"]);
+    assert_eq!(
+        server.header("/feed.xml", "x-api-key").as_deref(),
+        Some("key")
+    );
+    assert_eq!(server.header("/other.xml", "x-api-key"), None);
+
+    let id = |channel: &str| {
+        let out = env.run_ok(&["item", "list", "--channel", channel, "--format", "json"]);
+        let items: Vec<Value> = serde_json::from_str(&out).unwrap();
+        items[1]["id"].as_str().unwrap().to_string()
+    };
+    let (private, public) = (id("0"), id("1"));
+
+    // Article next to the feed is requested like the feed, but only for its own channel.
+    env.run_ok(&["read", &private]);
+    assert_eq!(
+        server.header("/articles/first", "x-api-key").as_deref(),
+        Some("key")
+    );
+    env.run_ok(&["read", &public]);
+    assert_eq!(server.header("/articles/first", "x-api-key"), None);
+
+    // Redirect to another origin doesn't take the credentials along.
+    server.serve(
+        "/articles/first",
+        MockResponse::Redirect(elsewhere.url("/articles/first")),
+    );
+    let out = env.run_ok(&["read", &private, "--format", "text"]);
+    assert!(out.contains("Content of the first post."));
+    assert_eq!(elsewhere.hits("/articles/first"), 1);
+    assert_eq!(elsewhere.header("/articles/first", "x-api-key"), None);
+}
+
+#[test]
+fn refresh_stores_items() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+
+    // Newest first
+    assert_eq!(items[0]["title"], "Second post");
+    assert_eq!(items[1]["title"], "First post");
+    assert_eq!(items[1]["channel_name"], "Fixture Blog");
+    assert_eq!(items[1]["link"], server.url("/articles/first"));
+    assert_eq!(items[1]["read"], false);
+    assert_eq!(comments(&items[0]), server.url("/articles/second#comments"));
+    assert!(comments(&items[1]).is_null());
+}
+
+#[test]
+fn refresh_reads_atom_comment_links() {
+    let server = MockServer::start();
+    server.serve(
+        "/atom.xml",
+        MockResponse::Body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Blog</title>
+  <id>urn:blog</id>
+  <updated>2025-01-06T10:00:00Z</updated>
+  <entry>
+    <id>urn:post</id>
+    <title>Post</title>
+    <link rel="replies" type="text/html" href="https://blog.example/post#comments"/>
+    <link rel="alternate" href="https://blog.example/post"/>
+    <link rel="related" href="https://other.example/source"/>
+    <updated>2025-01-06T10:00:00Z</updated>
+  </entry>
+</feed>"#
+                .to_string(),
+        ),
+    );
+
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/atom.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items[0]["link"], "https://blog.example/post");
+    assert_eq!(comments(&items[0]), "https://blog.example/post#comments");
+    assert_eq!(
+        items[0]["alternate_links"][1],
+        serde_json::json!({"kind": "related", "url": "https://other.example/source"})
+    );
+}
+
+#[test]
+fn refresh_matches_rss_comments_by_guid_or_link() {
+    let server = MockServer::start();
+    server.serve(
+        "/feed.xml",
+        MockResponse::Body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Blog</title>
+    <item>
+      <guid>with-guid</guid>
+      <title>Guid</title>
+      <link>https://blog.example/guid</link>
+      <comments>https://blog.example/guid#comments</comments>
+    </item>
+    <item>
+      <title>Untitled comments</title>
+      <link>https://blog.example/none</link>
+    </item>
+    <item>
+      <title>Link</title>
+      <link>https://blog.example/link</link>
+      <comments><![CDATA[https://blog.example/link#comments]]></comments>
+    </item>
+  </channel>
+</rss>"#
+                .to_string(),
+        ),
+    );
+
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    let by_title = |title: &str| items.iter().find(|it| it["title"] == title).unwrap();
+    assert_eq!(
+        comments(by_title("Guid")),
+        "https://blog.example/guid#comments"
+    );
+    assert!(comments(by_title("Untitled comments")).is_null());
+    assert_eq!(
+        comments(by_title("Link")),
+        "https://blog.example/link#comments"
+    );
+}
+
+#[test]
+fn refresh_fixes_double_escaped_entities() {
+    let server = fixture_server();
+    let feed = FEED.replace(
+        "Summary of the first post",
+        "It&amp;amp;rsquo;s &amp;amp;lt;b&amp;amp;gt;",
+    );
+    server.serve("/escaped.xml", MockResponse::Body(feed));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/escaped.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items[1]["description"], "It&rsquo;s &amp;lt;b&amp;gt;");
+}
+
+#[test]
+fn refresh_reads_hacker_news_api() {
+    let server = MockServer::start();
+    server.serve(
+        "/v0/topstories.json",
+        MockResponse::Body("[1, 2, 3, 4]".to_string()),
+    );
+    server.serve(
+        "/v0/item/1.json",
+        MockResponse::Body(
+            r#"{"id": 1, "by": "pg", "time": 1700000000, "title": "Show HN: A reader",
+                "url": "https://example.com/reader", "score": 42, "descendants": 7}"#
+                .to_string(),
+        ),
+    );
+    server.serve(
+        "/v0/item/2.json",
+        MockResponse::Body(r#"{"id": 2, "dead": true, "title": "Spam"}"#.to_string()),
+    );
+    server.serve(
+        "/v0/item/3.json",
+        MockResponse::Body(
+            r#"{"id": 3, "by": "dang", "time": 1700000100, "title": "Ask HN: Feeds?",
+                "text": "Which feeds do you read?", "score": 5, "descendants": 0}"#
+                .to_string(),
+        ),
+    );
+
+    // Story 4 fails and is left out.
+    let env = Env::new().with_config(&format!("hacker_news_api = \"{}\"", server.url("/v0")));
+    let url = server.url("/v0/topstories.json");
+    env.run_ok(&["channel", "add", &url]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "Ask HN: Feeds?");
+    assert_eq!(items[0]["channel_name"], "Hacker News");
+    assert_eq!(items[0]["link"], "https://news.ycombinator.com/item?id=3");
+    assert!(
+        items[0]["description"]
+            .as_str()
+            .unwrap()
+            .starts_with("<p>Which feeds do you read?</p>")
+    );
+    assert_eq!(items[1]["id"], format!("{url}:1"));
+    assert_eq!(items[1]["author"], "pg");
+    assert_eq!(items[1]["link"], "https://example.com/reader");
+    assert!(
+        items[1]["description"]
+            .as_str()
+            .unwrap()
+            .contains("42 points")
+    );
+    assert_eq!(
+        comments(&items[1]),
+        "https://news.ycombinator.com/item?id=1"
+    );
+}
+
+#[test]
+fn refresh_cleans_up_reddit_posts() {
+    let server = MockServer::start();
+    let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Rust</title>
+  <id>/r/rust/.rss</id>
+  <updated>2024-01-02T00:00:00+00:00</updated>
+  <entry>
+    <author><name>/u/ferris</name></author>
+    <id>t3_link</id>
+    <title>A crate</title>
+    <link href="https://www.reddit.com/r/rust/comments/link/a_crate/"/>
+    <updated>2024-01-02T00:00:00+00:00</updated>
+    <content type="html">&lt;table&gt; &lt;tr&gt;&lt;td&gt; &amp;#32; submitted by &amp;#32; &lt;a href="https://www.reddit.com/user/ferris"&gt; /u/ferris &lt;/a&gt; &lt;br/&gt; &lt;span&gt;&lt;a href="https://crates.example/a?x=1&amp;amp;y=2"&gt;[link]&lt;/a&gt;&lt;/span&gt; &amp;#32; &lt;span&gt;&lt;a href="https://www.reddit.com/r/rust/comments/link/a_crate/"&gt;[comments]&lt;/a&gt;&lt;/span&gt; &lt;/td&gt;&lt;/tr&gt;&lt;/table&gt;</content>
+  </entry>
+  <entry>
+    <author><name>/u/crab</name></author>
+    <id>t3_self</id>
+    <title>A question</title>
+    <link href="https://www.reddit.com/r/rust/comments/self/a_question/"/>
+    <updated>2024-01-01T00:00:00+00:00</updated>
+    <content type="html">&lt;!-- SC_OFF --&gt;&lt;div class="md"&gt;&lt;p&gt;Why?&lt;/p&gt;&lt;/div&gt;&lt;!-- SC_ON --&gt; &amp;#32; submitted by &amp;#32; &lt;a href="https://www.reddit.com/user/crab"&gt; /u/crab &lt;/a&gt; &lt;span&gt;&lt;a href="https://www.reddit.com/r/rust/comments/self/a_question/"&gt;[link]&lt;/a&gt;&lt;/span&gt;</content>
+  </entry>
+</feed>"#;
+    // Requests go through the server as a proxy, so it can serve the site's urls.
+    let reddit = "http://www.reddit.com/r/rust/.rss";
+    let other = "http://forum.example/r/rust/.rss";
+    server.serve(reddit, MockResponse::Body(feed.to_string()));
+    server.serve(other, MockResponse::Body(feed.to_string()));
+
+    let env = Env::new().with_config(&format!("proxy = \"{}\"", server.url("")));
+    env.run_ok(&["channel", "add", reddit]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "A crate");
+    assert_eq!(items[0]["author"], "u/ferris");
+    assert_eq!(items[0]["link"], "https://crates.example/a?x=1&y=2");
+    assert_eq!(
+        comments(&items[0]),
+        "https://www.reddit.com/r/rust/comments/link/a_crate/"
+    );
+    assert!(items[0]["description"].is_null());
+    assert_eq!(
+        items[1]["link"],
+        "https://www.reddit.com/r/rust/comments/self/a_question/"
+    );
+    assert_eq!(
+        items[1]["description"],
+        "<div class=\"md\"><p>Why?</p></div>"
+    );
+
+    // Same paths on other sites are feeds.
+    env.run_ok(&["channel", "remove", "0"]);
+    env.run_ok(&["channel", "add", other]);
+    env.run_ok(&["refresh"]);
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert!(comments(&items[0]).is_null());
+    assert_eq!(
+        items[0]["link"],
+        "https://www.reddit.com/r/rust/comments/link/a_crate/"
+    );
+}
+
+#[test]
+fn refresh_cleans_up_lobsters_stories() {
+    let server = MockServer::start();
+    server.serve(
+        "http://lobste.rs/t/rust.rss",
+        MockResponse::Body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Lobsters: rust</title>
+    <link>https://lobste.rs/t/rust</link>
+    <item>
+      <title>A crate</title>
+      <link>https://crates.example/a</link>
+      <guid isPermaLink="false">https://lobste.rs/s/abc123</guid>
+      <author>ferris@users.lobste.rs (ferris)</author>
+      <pubDate>Tue, 02 Jan 2024 10:00:00 +0000</pubDate>
+      <description>&lt;p&gt;Short text&lt;/p&gt;
+&lt;p&gt;&lt;a href="https://lobste.rs/s/abc123/a_crate"&gt;Comments&lt;/a&gt;&lt;/p&gt;</description>
+    </item>
+    <item>
+      <title>Link only</title>
+      <link>https://crates.example/b</link>
+      <guid isPermaLink="false">https://lobste.rs/s/def456</guid>
+      <pubDate>Mon, 01 Jan 2024 10:00:00 +0000</pubDate>
+      <description>&lt;p&gt;&lt;a href="https://lobste.rs/s/def456/link_only"&gt;Comments&lt;/a&gt;&lt;/p&gt;</description>
+    </item>
+  </channel>
+</rss>"#
+                .to_string(),
+        ),
+    );
+
+    let env = Env::new().with_config(&format!("proxy = \"{}\"", server.url("")));
+    env.run_ok(&["channel", "add", "http://lobste.rs/t/rust.rss"]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["link"], "https://crates.example/a");
+    assert_eq!(comments(&items[0]), "https://lobste.rs/s/abc123/a_crate");
+    assert_eq!(items[0]["description"], "<p>Short text</p>");
+    assert_eq!(comments(&items[1]), "https://lobste.rs/s/def456/link_only");
+    assert!(items[1]["description"].is_null());
+}
+
+#[test]
+fn refresh_reads_mastodon_timelines() {
+    let server = MockServer::start();
+    server.serve(
+        "/api/v1/instance",
+        MockResponse::Body(r#"{"uri": "social.example", "version": "4.3.0"}"#.to_string()),
+    );
+    let account = r#"{"id": "7", "acct": "blog", "display_name": "The Blog"}"#;
+    server.serve(
+        "/api/v1/accounts/lookup",
+        MockResponse::Body(account.to_string()),
+    );
+    server.serve(
+        "/api/v1/accounts/7/statuses",
+        MockResponse::Body(format!(
+            r#"[{{"id": "2", "created_at": "2024-05-02T10:00:00.000Z",
+                "url": "https://social.example/@blog/2", "uri": "https://social.example/2",
+                "content": "<p>New post: <a href=\"https://blog.example/post\">Writing a reader</a></p>",
+                "spoiler_text": "", "account": {account},
+                "card": {{"url": "https://blog.example/post"}}, "media_attachments": []}}]"#
+        )),
+    );
+    server.serve(
+        "/api/v1/timelines/tag/rust",
+        MockResponse::Body(format!(
+            r#"[{{"id": "5", "created_at": "2024-05-01T10:00:00.000Z", "url": null,
+                "uri": "https://other.example/5", "content": "", "spoiler_text": "Long thread",
+                "account": {account}, "card": null,
+                "media_attachments": [{{"type": "video", "url": "https://cdn.example/v.mp4"}}]}}]"#
+        )),
+    );
+
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/@blog")]);
+    env.run_ok(&["channel", "add", &server.url("/tags/rust")]);
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "New post: Writing a reader");
+    assert_eq!(items[0]["channel_name"], "The Blog");
+    assert_eq!(items[0]["link"], "https://blog.example/post");
+    assert_eq!(items[1]["title"], "Long thread");
+    assert_eq!(items[1]["channel_name"], "#rust");
+    assert_eq!(items[1]["link"], "https://other.example/5");
+    assert_eq!(items[1]["enclosure"], "https://cdn.example/v.mp4");
+
+    // Same paths on other sites are feeds.
+    let site = MockServer::start();
+    site.serve("/@blog", MockResponse::Body(FEED.to_string()));
+    env.run_ok(&["channel", "add", &site.url("/@blog")]);
+    env.run_ok(&["refresh"]);
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 4);
+    assert!(items.iter().any(|it| it["title"] == "First post"));
+}
+
+#[test]
+fn refresh_keeps_read_state() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml"), "--name", "Blog"]);
+    env.run_ok(&["refresh"]);
+
+    // Mark first post as read
+    let mut items = stored_items(&env);
+    items[1]["read"] = Value::Bool(true);
+    fs::write(env.data_file(), serde_json::to_string(&items).unwrap()).unwrap();
+
+    env.run_ok(&["refresh"]);
+
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["read"], false);
+    assert_eq!(items[1]["read"], true);
+    assert_eq!(items[1]["channel_name"], "Blog");
+}
+
+#[test]
+fn refresh_syncs_with_greader_server() {
+    let server = sync_server();
+    let env = Env::new().with_config(&format!(
+        "[sync]\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        server.url("/api/greader.php")
+    ));
+
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+    assert_eq!(server.hits("/api/greader.php/accounts/ClientLogin"), 1);
+
+    // Item of an unknown subscription is skipped, read status comes from the unread ids.
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "Second synced post");
+    assert_eq!(items[0]["link"], "https://blog.example/second");
+    assert_eq!(items[0]["read"], false);
+    assert_eq!(
+        items[1]["id"],
+        "https://blog.example/feed.xml:000000000000001f"
+    );
+    assert_eq!(items[1]["channel_name"], "Synced Blog");
+    assert_eq!(items[1]["author"], "Jane");
+    assert_eq!(items[1]["read"], true);
+    assert_eq!(items[0]["starred"], false);
+    assert_eq!(items[1]["starred"], true);
+
+    // Stars are sent to the server.
+    env.run_ok(&["item", "star", "0"]);
+    let body = server
+        .body("/api/greader.php/reader/api/0/edit-tag")
+        .unwrap();
+    assert!(body.contains("i=tag%3Agoogle.com%2C2005%3Areader%2Fitem%2F0000000000000020"));
+    assert!(body.contains("&a=user%2F-%2Fstate%2Fcom.google%2Fstarred"));
+
+    // Channels are replaced with the subscriptions.
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("Synced Blog"));
+    assert!(out.contains("1/2"));
+}
+
+#[test]
+fn greader_pushes_read_status_in_batches() {
+    let server = sync_server();
+    let env = Env::new().with_config(&format!(
+        "[sync]\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        server.url("/api/greader.php")
+    ));
+    env.run_ok(&["refresh"]);
+
+    // Expired token is renewed and the request sent again.
+    let reader = "/api/greader.php/reader/api/0";
+    server.serve(
+        &format!("{reader}/token"),
+        MockResponse::FailOnce {
+            status: 401,
+            body: "token\n".to_string(),
+        },
+    );
+    env.run_ok(&["item", "mark-read", "--all"]);
+    assert_eq!(server.hits("/api/greader.php/accounts/ClientLogin"), 3);
+    assert_eq!(server.hits(&format!("{reader}/edit-tag")), 1);
+
+    let out = env.run_ok(&["item", "mark-unread", "--all"]);
+    assert!(out.contains("Marked 2 items as unread"));
+
+    // Both items are changed with a single request.
+    assert_eq!(server.hits(&format!("{reader}/edit-tag")), 2);
+    let body = server.body(&format!("{reader}/edit-tag")).unwrap();
+    assert_eq!(body.matches("i=tag%3Agoogle.com").count(), 2);
+    assert!(body.contains("&r=user%2F-%2Fstate%2Fcom.google%2Fread"));
+}
+
+#[test]
+fn refresh_syncs_with_ttrss_server() {
+    let server = MockServer::start();
+    let login = include_str!("fixtures/ttrss/login.json").to_string();
+    server.serve(
+        "/tt-rss/api/",
+        MockResponse::Sequence(vec![
+            login.clone(),
+            include_str!("fixtures/ttrss/feeds.json").to_string(),
+            include_str!("fixtures/ttrss/headlines.json").to_string(),
+            include_str!("fixtures/ttrss/unread.json").to_string(),
+            include_str!("fixtures/ttrss/marked.json").to_string(),
+            login,
+            r#"{ "seq": 0, "status": 0, "content": { "status": "OK", "updated": 2 } }"#.to_string(),
+        ]),
+    );
+    let env = Env::new().with_config(&format!(
+        "[sync]\nbackend = \"ttrss\"\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        server.url("/tt-rss/api")
+    ));
+
+    // Item fetched from the feed directly, whose guid is a number.
+    let fetched = serde_json::json!([{
+        "id": "https://tiny.example/feed.xml:7",
+        "channel_name": "Tiny Blog",
+        "title": "Fetched directly",
+        "description": null,
+        "pub_date": null,
+        "link": "https://tiny.example/7",
+        "read": false,
+    }]);
+    fs::create_dir_all(env.data_file().parent().unwrap()).unwrap();
+    fs::write(env.data_file(), fetched.to_string()).unwrap();
+
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+    assert_eq!(server.hits("/tt-rss/api/"), 5);
+
+    // Directly fetched item is replaced, instead of being taken for a synced article.
+    let items = stored_items(&env);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["id"], "https://tiny.example/feed.xml:ttrss:42");
+    assert_eq!(items[0]["channel_name"], "Tiny Blog");
+    assert_eq!(items[0]["enclosure"], "https://tiny.example/42.mp3");
+    assert_eq!(items[0]["author"], Value::Null);
+    assert_eq!(items[0]["read"], false);
+    assert_eq!(items[1]["title"], "Read article");
+    assert_eq!(items[1]["read"], true);
+    assert_eq!(items[0]["starred"], false);
+    assert_eq!(items[1]["starred"], true);
+
+    // Both articles are updated with a single call.
+    let mut items = stored_items(&env);
+    items[1]["read"] = Value::Bool(false);
+    fs::write(env.data_file(), serde_json::to_string(&items).unwrap()).unwrap();
+    let out = env.run_ok(&["item", "mark-read", "--all"]);
+    assert!(out.contains("Marked 2 items as read"));
+    assert_eq!(server.hits("/tt-rss/api/"), 7);
+
+    let body: Value = serde_json::from_str(&server.body("/tt-rss/api/").unwrap()).unwrap();
+    assert_eq!(body["op"], "updateArticle");
+    let mut ids: Vec<_> = body["article_ids"].as_str().unwrap().split(',').collect();
+    ids.sort();
+    assert_eq!(ids, ["41", "42"]);
+}
+
+#[test]
+fn item_list_formats() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml"), "--name", "Blog"]);
+    env.run_ok(&["refresh"]);
+
+    let mut items = stored_items(&env);
+    items[0]["read"] = Value::Bool(true);
+    fs::write(env.data_file(), serde_json::to_string(&items).unwrap()).unwrap();
+
+    let out = env.run_ok(&["item", "list", "--format", "json"]);
+    let listed: Vec<Value> = serde_json::from_str(&out).unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[1]["idx"], 1);
+    assert_eq!(listed[1]["title"], "First post");
+    assert_eq!(listed[1]["channel"], "Blog");
+    assert_eq!(listed[1]["url"], server.url("/articles/first"));
+    assert_eq!(listed[1]["read"], false);
+
+    let out = env.run_ok(&["item", "ls", "--unread", "--format", "tsv"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let fields: Vec<_> = lines[0].split('\t').collect();
+    assert_eq!(fields[0], "1");
+    assert_eq!(fields[2], "First post");
+    assert_eq!(fields[6], "false");
+
+    let out = env.run_ok(&["item", "list", "--channel", "3"]);
+    assert!(out.contains("Invalid index!"));
+}
+
+#[test]
+fn item_mark_read_and_unread() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+    let read = |env: &Env| -> Vec<bool> {
+        stored_items(env)
+            .iter()
+            .map(|it| it["read"].as_bool().unwrap())
+            .collect()
+    };
+
+    let out = env.run_ok(&["item", "mark-read", "--all", "--channel", "0"]);
+    assert!(out.contains("Marked 2 items as read"));
+    assert_eq!(read(&env), [true, true]);
+
+    let id = stored_items(&env)[0]["id"].as_str().unwrap().to_string();
+    env.run_ok(&["item", "mark-unread", "1", &id]);
+    assert_eq!(read(&env), [false, false]);
+
+    // Both fixture items are from January 2025.
+    env.run_ok(&["item", "mark-read", "--older-than", "1d"]);
+    assert_eq!(read(&env), [true, true]);
+
+    let output = env.run(&["item", "mark-read", "--older-than", "7x"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn hooks_run_on_open_and_read() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let log = env.path().join("hooks.log");
+    let env = env.with_config(&format!(
+        "on_open = \"echo open {{title}} >> '{0}'\"\non_read = \"echo read {{title}} {{url}} >> '{0}'\"\n",
+        log.display()
+    ));
+    env.run_ok(&["read", "1", "--format", "text"]);
+    // Already read items don't run the hook again.
+    env.run_ok(&["item", "mark-read", "--all"]);
+
+    // Hooks run in the background, in any order and possibly after the command exits.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut lines = vec![];
+    while lines.len() < 3 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+        let log = fs::read_to_string(&log).unwrap_or_default();
+        lines = log.lines().map(str::to_string).collect();
+    }
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            "open First post".to_string(),
+            format!("read First post {}", server.url("/articles/first")),
+            format!("read Second post {}", server.url("/articles/second")),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_dont_run_item_values() {
+    let server = MockServer::start();
+    let feed = FEED.replace("First post", "x'; touch injected; echo '");
+    server.serve("/feed.xml", MockResponse::Body(feed));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let dir = env.path().to_path_buf();
+    let env = env.with_config(&format!(
+        "on_read = \"cd '{0}' && echo '{{title}}' \\\"{{channel}}\\\" >> hooks.log\"\n",
+        dir.display()
+    ));
+    env.run_ok(&["item", "mark-read", "1"]);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut content = String::new();
+    while content.is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+        content = fs::read_to_string(dir.join("hooks.log")).unwrap_or_default();
+    }
+    assert_eq!(content, "x'; touch injected; echo ' Fixture Blog\n");
+    assert!(!dir.join("injected").exists());
+}
+
+#[test]
+fn hooks_run_for_synced_items() {
+    let server = sync_server();
+    let env = Env::new();
+    let log = env.path().join("hooks.log");
+    let env = env.with_config(&format!(
+        "on_read = \"echo read {{title}} >> '{}'\"\n[sync]\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        log.display(),
+        server.url("/api/greader.php")
+    ));
+    env.run_ok(&["refresh"]);
+    env.run_ok(&["item", "mark-read", "--all"]);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut content = String::new();
+    while content.is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+        content = fs::read_to_string(&log).unwrap_or_default();
+    }
+    assert_eq!(content, "read Second synced post\n");
+
+    // Change is sent to the server before the command exits.
+    assert_eq!(server.hits("/api/greader.php/reader/api/0/edit-tag"), 1);
+}
+
+#[test]
+fn search_matches_titles_and_descriptions() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["search", "SUMMARY OF THE SECOND"]);
+    assert!(out.contains("0 Second post"));
+    assert!(out.contains("Summary of the second post"));
+    assert!(!out.contains("First post"));
+
+    // Words match in any order and as prefixes.
+    let out = env.run_ok(&["search", "seco summ"]);
+    assert!(out.contains("0 Second post"));
+    assert!(!out.contains("First post"));
+
+    let out = env.run_ok(&["search", "--regex", "^(first|second) post$"]);
+    assert!(out.contains("First post"));
+    assert!(out.contains("Second post"));
+
+    let out = env.run_ok(&["search", "third"]);
+    assert!(out.contains("No matches!"));
+
+    assert!(!env.run(&["search", "--regex", "("]).status.success());
+}
+
+#[test]
+fn search_matches_read_articles() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["search", "violets"]);
+    assert!(out.contains("No matches!"));
+
+    env.run_ok(&["read", "1", "--format", "text"]);
+    let out = env.run_ok(&["search", "violets"]);
+    assert!(out.contains("1 First post"));
+    assert!(out.contains("violets are blue"));
+
+    let out = env.run_ok(&["search", "--regex", "roses.*red"]);
+    assert!(out.contains("1 First post"));
+}
+
+#[test]
+fn download_keeps_existing_files() {
+    let server = MockServer::start();
+    let feed = r#"<rss version="2.0"><channel><title>Podcast</title>
+        <item>
+          <guid>episode</guid>
+          <title>Episode</title>
+          <link>{base}/episode</link>
+          <pubDate>Mon, 06 Jan 2025 10:00:00 GMT</pubDate>
+          <enclosure url="{base}/files/..%2Fmy%20episode%3F.mp3" type="audio/mpeg" length="5"/>
+        </item>
+        </channel></rss>"#;
+    server.serve("/feed.xml", MockResponse::Body(feed.to_string()));
+    server.serve(
+        "/files/..%2Fmy%20episode%3F.mp3",
+        MockResponse::Body("audio".to_string()),
+    );
+
+    let env = Env::new();
+    let downloads = env.path().join("downloads");
+    let env = env.with_config(&format!("download_dir = {:?}", downloads.to_str().unwrap()));
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    env.run_ok(&["download", "0"]);
+    env.run_ok(&["download", "0"]);
+    let out = env.run_ok(&["download", "0"]);
+    assert!(out.contains("my episode_ (2).mp3"));
+
+    let mut names: Vec<_> = fs::read_dir(&downloads)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "_my episode_ (1).mp3",
+            "_my episode_ (2).mp3",
+            "_my episode_.mp3"
+        ]
+    );
+    assert_eq!(
+        fs::read_to_string(downloads.join("_my episode_.mp3")).unwrap(),
+        "audio"
+    );
+}
+
+#[test]
+fn failed_download_leaves_no_file() {
+    let server = MockServer::start();
+    let feed = r#"<rss version="2.0"><channel><title>Podcast</title>
+        <item>
+          <guid>episode</guid>
+          <title>Episode</title>
+          <link>{base}/episode</link>
+          <enclosure url="{base}/episode.mp3" type="audio/mpeg" length="10"/>
+        </item>
+        </channel></rss>"#;
+    server.serve("/feed.xml", MockResponse::Body(feed.to_string()));
+    server.serve("/episode.mp3", MockResponse::Truncated("audio".to_string()));
+
+    let env = Env::new();
+    let downloads = env.path().join("downloads");
+    let env = env.with_config(&format!("download_dir = {:?}", downloads.to_str().unwrap()));
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["download", "0"]);
+    assert!(out.contains("Failed to download"));
+    assert_eq!(fs::read_dir(&downloads).unwrap().count(), 0);
+
+    // Retry gets the name of the broken download.
+    server.serve("/episode.mp3", MockResponse::Body("audio".to_string()));
+    env.run_ok(&["download", "0"]);
+    assert_eq!(
+        fs::read_to_string(downloads.join("episode.mp3")).unwrap(),
+        "audio"
+    );
+}
+
+#[test]
+fn channel_list_shows_counts() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("0/0"));
+    assert!(out.contains("never"));
+
+    env.run_ok(&["refresh"]);
+    let mut items = stored_items(&env);
+    items[1]["read"] = Value::Bool(true);
+    fs::write(env.data_file(), serde_json::to_string(&items).unwrap()).unwrap();
+
+    let out = env.run_ok(&["channel", "list"]);
+    assert!(out.contains("1/2"));
+    assert!(!out.contains("never"));
+}
+
+#[test]
+fn refresh_failure_keeps_data() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    env.run_ok(&["channel", "add", &server.url("/missing.xml")]);
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+
+    assert_eq!(stored_items(&env).len(), 2);
+    assert!(env.path().join("config").join("simple-rss").is_file());
+
+    // Only the failed channel was never fetched.
+    let out = env.run_ok(&["channel", "list"]);
+    assert_eq!(out.matches("never").count(), 1);
+}
+
+#[test]
+fn refresh_fails_on_server_error() {
+    let server = fixture_server();
+    server.serve("/error.xml", MockResponse::Status(500));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/error.xml")]);
+
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+    assert_eq!(server.hits("/error.xml"), 1);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&server.url("/error.xml")));
+    assert!(stderr.contains("500"));
+}
+
+#[test]
+fn refresh_fails_on_malformed_feed() {
+    let server = fixture_server();
+    server.serve("/broken.xml", MockResponse::MalformedXml);
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/broken.xml")]);
+
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid content"));
+}
+
+#[test]
+fn refresh_times_out() {
+    let server = fixture_server();
+    server.serve("/hanging.xml", MockResponse::Timeout);
+    let env = Env::new().with_config("timeout = 1");
+    env.run_ok(&["channel", "add", &server.url("/hanging.xml")]);
+
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn refresh_uses_proxy() {
+    let server = fixture_server();
+    server.serve(
+        "http://feed.example/feed.xml",
+        MockResponse::Body(FEED.to_string()),
+    );
+    let env = Env::new().with_config(&format!("proxy = \"{}\"", server.url("")));
+    env.run_ok(&["channel", "add", "http://feed.example/feed.xml"]);
+
+    env.run_ok(&["refresh"]);
+    assert_eq!(server.hits("http://feed.example/feed.xml"), 1);
+    assert_eq!(stored_items(&env).len(), 2);
+}
+
+#[test]
+fn refresh_waits_for_retry_after() {
+    let server = fixture_server();
+    server.serve(
+        "/limited.xml",
+        MockResponse::RateLimited {
+            retry_after: 1,
+            body: FEED.to_string(),
+        },
+    );
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/limited.xml")]);
+
+    let start = Instant::now();
+    env.run_ok(&["refresh"]);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.hits("/limited.xml"), 2);
+    assert_eq!(stored_items(&env).len(), 2);
+}
+
+#[test]
+fn refresh_fails_on_huge_retry_after() {
+    let server = fixture_server();
+    server.serve(
+        "/limited.xml",
+        MockResponse::RateLimited {
+            retry_after: u64::MAX,
+            body: FEED.to_string(),
+        },
+    );
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/limited.xml")]);
+
+    let start = Instant::now();
+    let output = env.run(&["refresh"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(!err.contains("panicked"));
+    assert!(err.contains("429 Too Many Requests"));
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(server.hits("/limited.xml"), 1);
+}
+
+#[test]
+fn greader_waits_for_retry_after() {
+    let server = sync_server();
+    let list = "/api/greader.php/reader/api/0/subscription/list";
+    server.serve(
+        list,
+        MockResponse::RateLimited {
+            retry_after: 1,
+            body: include_str!("fixtures/greader/subscriptions.json").to_string(),
+        },
+    );
+    let env = Env::new().with_config(&format!(
+        "[sync]\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        server.url("/api/greader.php")
+    ));
+
+    let start = Instant::now();
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.hits(&format!("{list}?output=json")), 2);
+}
+
+#[test]
+fn refresh_handles_slow_feed() {
+    let server = fixture_server();
+    server.serve(
+        "/slow.xml",
+        MockResponse::Slow {
+            body: FEED.to_string(),
+            chunks: 4,
+            delay: Duration::from_millis(50),
+        },
+    );
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/slow.xml")]);
+
+    env.run_ok(&["refresh"]);
+    assert_eq!(stored_items(&env).len(), 2);
+}
+
+#[test]
+fn read_exports_article() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["read", "1"]);
+    assert!(out.starts_with("# First post\n"));
+    assert!(out.contains("Content of the **first** post."));
+    assert!(out.contains("\n\nRoses are red,\\\nviolets are blue.\n\nE = mc² and H₂O, ~~old~~ new.\n\n---\n\nThe end.\n"));
+    let items = stored_items(&env);
+    assert_eq!(items[1]["read"], true);
+    assert_eq!(items[0]["read"], false);
+
+    let id = items[1]["id"].as_str().unwrap();
+    let out = env.run_ok(&["read", id, "--format", "text"]);
+    assert!(out.starts_with("First post\n"));
+
+    let path = env.path().join("first.txt");
+    env.run_ok(&["read", "1", "--output", path.to_str().unwrap()]);
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("First post\n"));
+    assert!(text.contains("Content of the first post."));
+    assert!(text.contains("Roses are red,\nviolets are blue."));
+    assert!(text.contains("E = mc² and H₂O, old new."));
+    assert!(!text.contains('#'));
+}
+
+#[test]
+fn export_html_groups_items_by_channel() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let path = env.path().join("digest.html");
+    let out = env.run_ok(&["export", "html", "--output", path.to_str().unwrap()]);
+    assert!(out.contains("Exported 2 items"));
+    let html = fs::read_to_string(&path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("<h2>Fixture Blog</h2>").count(), 1);
+    assert!(html.contains(&format!(
+        "<a href=\"{}\">Second post</a>",
+        server.url("/articles/second")
+    )));
+    assert!(html.contains("<p>Summary of the second post</p>"));
+    assert!(html.find("Second post").unwrap() < html.find("First post").unwrap());
+    assert!(!html.contains("<script"));
+
+    // Only web urls are links.
+    let feed = FEED
+        .replace("{base}/articles/first", "javascript:alert(1)")
+        .replace("{base}/articles/second#comments", "javascript:alert(2)");
+    server.serve("/feed.xml", MockResponse::Body(feed));
+    env.run_ok(&["refresh"]);
+    env.run_ok(&["export", "html", "--output", path.to_str().unwrap()]);
+    let html = fs::read_to_string(&path).unwrap();
+    assert!(html.contains("<h3>First post</h3>"));
+    assert!(html.contains("Comments: javascript:alert(2)"));
+    assert!(!html.contains("href=\"javascript"));
+
+    // Fixture items are from 2025.
+    let out = env.run_ok(&["export", "html", "--since", "7d", "--output", "other.html"]);
+    assert!(out.contains("No items to export!"));
+    assert!(
+        !env.run(&["export", "html", "--since", "7x", "-o", "x.html"])
+            .status
+            .success()
+    );
+    let output = env.run(&["export", "html", "--since", "999999999999w", "-o", "x.html"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("age is too large"));
+}
+
+#[test]
+fn item_unhide_shows_hidden_items() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let mut items = stored_items(&env);
+    items[1]["hidden"] = "always".into();
+    fs::write(env.data_file(), serde_json::to_string(&items).unwrap()).unwrap();
+
+    let out = env.run_ok(&["item", "list", "--hidden"]);
+    assert!(out.contains("First post"));
+    assert!(!out.contains("Second post"));
+
+    let out = env.run_ok(&["item", "unhide", "1"]);
+    assert!(out.contains("Unhid 1 items"));
+    assert!(stored_items(&env)[1].get("hidden").is_none());
+    let out = env.run_ok(&["item", "list", "--hidden"]);
+    assert!(out.contains("No items!"));
+}
+
+#[test]
+fn export_epub_bundles_starred_articles() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["export", "epub", "--starred", "-o", "none.epub"]);
+    assert!(out.contains("No items to export!"));
+
+    env.run_ok(&["item", "star", "1"]);
+    let out = env.run_ok(&["item", "list", "--starred"]);
+    assert!(out.contains("First post ★"));
+    assert!(!out.contains("Second post"));
+
+    // Starred status survives refreshes.
+    env.run_ok(&["refresh"]);
+    assert_eq!(stored_items(&env)[1]["starred"], true);
+
+    let path = env.path().join("articles.epub");
+    let out = env.run_ok(&["export", "epub", "--starred", "-o", path.to_str().unwrap()]);
+    assert!(out.contains("Exported 1 items"));
+
+    let mut book = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(book.by_index(0).unwrap().name(), "mimetype");
+    let mut read = |name: &str| {
+        let mut text = String::new();
+        io::Read::read_to_string(&mut book.by_name(name).unwrap(), &mut text).unwrap();
+        text
+    };
+    assert_eq!(read("mimetype"), "application/epub+zip");
+    let package = read("OEBPS/content.opf");
+    assert!(package.contains(r#"<itemref idref="article-1"/>"#));
+    // Language is taken from the article's page.
+    assert!(package.contains("<dc:language>sl</dc:language>"));
+    assert!(read("OEBPS/nav.xhtml").contains(">First post</a>"));
+    let chapter = read("OEBPS/article-1.xhtml");
+    assert!(chapter.contains("<h1>First post</h1>"));
+    assert!(chapter.contains("<p>Content of the first post.</p>"));
+    assert!(chapter.contains(r#"lang="sl-SI""#));
+
+    env.run_ok(&["item", "unstar", "1"]);
+    assert_eq!(stored_items(&env)[1]["starred"], false);
+    assert!(
+        !env.run(&["export", "epub", "-o", "x.epub"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn item_save_sends_to_wallabag() {
+    let server = fixture_server();
+    server.serve(
+        "/wallabag/oauth/v2/token",
+        MockResponse::Body(r#"{"access_token":"token","expires_in":3600}"#.to_string()),
+    );
+    server.serve(
+        "/wallabag/api/entries.json",
+        MockResponse::Body("{}".to_string()),
+    );
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["item", "save", "0"]);
+    assert!(out.contains("No read-later service is enabled"));
+
+    let env = env.with_config(&format!(
+        "[read_later.wallabag]\nenabled = true\nurl = \"{}\"\nclient_id = \"id\"\n\
+         client_secret = \"secret\"\nusername = \"user\"\npassword = \"password\"\n",
+        server.url("/wallabag/")
+    ));
+    let out = env.run_ok(&["item", "save", "0"]);
+    assert!(out.contains("Saved to wallabag: Second post"));
+    assert_eq!(server.hits("/wallabag/oauth/v2/token"), 1);
+    assert_eq!(server.hits("/wallabag/api/entries.json"), 1);
+
+    server.serve("/wallabag/api/entries.json", MockResponse::Status(500));
+    let output = env.run(&["item", "save", "0"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to save to wallabag"));
+}
+
+#[test]
+fn item_save_sends_to_instapaper() {
+    let server = fixture_server();
+    server.serve("/instapaper/api/add", MockResponse::Status(201));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["refresh"]);
+
+    let env = env.with_config(&format!(
+        "[read_later.instapaper]\nenabled = true\nurl = \"{}\"\nusername = \"me@example.com\"\n\
+         password = \"password\"\n",
+        server.url("/instapaper")
+    ));
+    let out = env.run_ok(&["item", "save", "1"]);
+    assert!(out.contains("Saved to Instapaper: First post"));
+    assert_eq!(server.hits("/instapaper/api/add"), 1);
+    let body = server.body("/instapaper/api/add").unwrap();
+    assert!(body.contains("title=First+post"));
+    assert!(
+        server
+            .header("/instapaper/api/add", "authorization")
+            .unwrap()
+            .starts_with("Basic ")
+    );
+
+    // Error says what went wrong.
+    server.serve("/instapaper/api/add", MockResponse::Status(403));
+    let output = env.run(&["item", "save", "1"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Failed to save to Instapaper"));
+    assert!(err.contains("403"));
+}
+
+#[test]
+fn stats_counts_items() {
+    let server = fixture_server();
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml"), "--name", "Blog"]);
+    env.run_ok(&["refresh"]);
+
+    let out = env.run_ok(&["stats"]);
+    let line = out.lines().find(|l| l.starts_with("Blog")).unwrap();
+    let columns: Vec<_> = line.split_whitespace().collect();
+    // Fixture items are published a day apart, in January 2025.
+    assert_eq!(columns[..4], ["Blog", "2", "2", "7.0/week"]);
+    assert_eq!(columns[5..], ["days", "0%", "0"]);
+
+    // Opened share is of the stored items, even if the history knows more of them.
+    env.run_ok(&["read", "1"]);
+    let path = env.data_file().with_file_name("history.json");
+    let mut history: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    for idx in 0..10 {
+        history["items"][format!("{}:removed-{idx}", server.url("/feed.xml"))] =
+            serde_json::json!({ "channel": server.url("/feed.xml"), "opens": 0 });
+    }
+    fs::write(&path, history.to_string()).unwrap();
+
+    let out = env.run_ok(&["stats"]);
+    let line = out.lines().find(|l| l.starts_with("Blog")).unwrap();
+    assert!(line.ends_with(" 50%       1"));
+    assert!(out.contains("Blog (1 of 12 items ever stored were opened)"));
+}
+
+#[test]
+fn stats_and_doctor_report_fetches() {
+    let server = fixture_server();
+    server.serve("/gone.xml", MockResponse::Status(404));
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml"), "--name", "Blog"]);
+    env.run_ok(&["channel", "add", &server.url("/gone.xml"), "--name", "Gone"]);
+    assert!(!env.run(&["refresh"]).status.success());
+    assert!(!env.run(&["refresh"]).status.success());
+
+    let out = env.run_ok(&["stats"]);
+    let fetches = &out[out.find("Avg. time").unwrap()..];
+    let blog = fetches.lines().find(|l| l.starts_with("Blog")).unwrap();
+    assert!(blog.ends_with(" 0/2"));
+    assert!(blog.contains(" B "));
+    let gone = fetches.lines().find(|l| l.starts_with("Gone")).unwrap();
+    assert!(gone.ends_with(" 2/2"));
+    assert!(out.contains("Often failing:\n  Gone (2 of the last 2 fetches, 2 of 2 in total)"));
+
+    let output = env.run(&["doctor"]);
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("Gone failed in 2 of the last 2 refreshes"));
+    assert!(!out.contains("Blog failed"));
+}
+
+/// Fixture feed with both items published the given number of days ago.
+fn feed_published_days_ago(days: i64) -> String {
+    let date = (Utc::now() - TimeDelta::days(days)).to_rfc2822();
+    FEED.replace("Mon, 06 Jan 2025 10:00:00 GMT", &date)
+        .replace("Tue, 07 Jan 2025 10:00:00 GMT", &date)
+}
+
+#[test]
+fn doctor_reports_problems() {
+    let server = fixture_server();
+    server.serve("/gone.xml", MockResponse::Status(404));
+    server.serve(
+        "/page.html",
+        MockResponse::Body("<html></html>".to_string()),
+    );
+    let env = Env::new();
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    env.run_ok(&["channel", "add", &server.url("/feed.xml/")]);
+    env.run_ok(&["channel", "add", &server.url("/gone.xml")]);
+    env.run_ok(&["channel", "add", &server.url("/page.html")]);
+    fs::write(env.data_file(), "not json").unwrap();
+
+    let output = env.run(&["doctor"]);
+    assert!(!output.status.success());
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("invalid JSON"));
+    assert!(out.contains("200, "));
+    assert!(out.contains("duplicate of channel 0"));
+    assert!(out.contains("404"));
+    assert!(out.contains("Not a feed"));
+
+    env.run_ok(&["channel", "remove", "3"]);
+    env.run_ok(&["channel", "remove", "2"]);
+    env.run_ok(&["channel", "remove", "1"]);
+    fs::remove_file(env.data_file()).unwrap();
+    server.serve("/feed.xml", MockResponse::Body(feed_published_days_ago(30)));
+    let out = env.run_ok(&["doctor"]);
+    assert!(!out.contains("feed looks dead"));
+
+    server.serve(
+        "/feed.xml",
+        MockResponse::Body(feed_published_days_ago(400)),
+    );
+    let out = env.run_ok(&["doctor"]);
+    assert!(out.contains("feed looks dead"));
+}
+
+#[cfg(unix)]
+#[test]
+fn remote_fails_without_running_tui() {
+    let env = Env::new();
+
+    let output = env.run(&["remote", "next-unread"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("simple-rss is not running"));
+
+    let output = env.run(&["remote", "jump"]);
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_serves_refresh_and_channel_changes() {
+    let server = fixture_server();
+    let env = Env::new();
+
+    let mut daemon = env.spawn(&["daemon"]);
+    let socket = env
+        .path()
+        .join("state")
+        .join("simple-rss")
+        .join("daemon.sock");
+    for _ in 0..50 {
+        if socket.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(socket.exists(), "daemon didn't start");
+
+    let output = env.run(&["daemon"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already running"));
+
+    // Daemon reloads the channels, so the refresh includes the new one.
+    env.run_ok(&["channel", "add", &server.url("/feed.xml")]);
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+
+    // Items are stored by the daemon.
+    assert_eq!(stored_items(&env).len(), 2);
+
+    // Changes are sent in one request and saved shortly after.
+    env.run_ok(&["item", "mark-read", "--all"]);
+    let all_read = || stored_items(&env).iter().all(|it| it["read"] == true);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !all_read() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(all_read());
+
+    env.run_ok(&["item", "star", "0", "1"]);
+    let all_starred = || stored_items(&env).iter().all(|it| it["starred"] == true);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !all_starred() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(all_starred());
+
+    // Moves made one after another keep their order, also after the daemon saves.
+    env.run_ok(&["channel", "add", "https://two.example/feed.xml"]);
+    env.run_ok(&["channel", "add", "https://three.example/feed.xml"]);
+    env.run_ok(&["channel", "move", "2", "0"]);
+    env.run_ok(&["channel", "move", "2", "1"]);
+    env.run(&["refresh"]);
+    let out = env.run_ok(&["channel", "list"]);
+    let order: Vec<_> = [
+        "https://three.example",
+        "https://two.example",
+        &server.url("/feed.xml"),
+    ]
+    .iter()
+    .map(|url| out.find(url).unwrap())
+    .collect();
+    assert!(order.is_sorted());
+
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_syncs_with_server() {
+    let server = sync_server();
+    let env = Env::new().with_config(&format!(
+        "[sync]\nurl = \"{}\"\nusername = \"user\"\npassword = \"secret\"\n",
+        server.url("/api/greader.php")
+    ));
+
+    let mut daemon = env.spawn(&["daemon"]);
+    let socket = env
+        .path()
+        .join("state")
+        .join("simple-rss")
+        .join("daemon.sock");
+    for _ in 0..50 {
+        if socket.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(socket.exists(), "daemon didn't start");
+
+    let out = env.run_ok(&["refresh"]);
+    assert!(out.contains("Refreshed 2 items!"));
+    assert_eq!(
+        stored_items(&env)[1]["id"],
+        "https://blog.example/feed.xml:000000000000001f"
+    );
+
+    // Daemon pushes the change to the server.
+    env.run_ok(&["item", "mark-read", "--all"]);
+    let edit_tag = "/api/greader.php/reader/api/0/edit-tag";
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while server.hits(edit_tag) == 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(server.hits(edit_tag), 1);
+
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+}
+
+#[test]
+fn auth_login_needs_username() {
+    let env = Env::new();
+
+    let output = env.run(&["auth", "login", "ttrss"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Pass --username"));
+}
+
+#[test]
+fn replay_prints_the_recorded_screen() {
+    let env = Env::new();
+    let item = |id: &str, title: &str| {
+        serde_json::json!({
+            "id": id, "channel_name": "Blog", "title": title, "description": null,
+            "pub_date": null, "link": format!("https://blog.example/{id}"), "read": false,
+        })
+    };
+    let start = serde_json::json!({"start": {
+        "width": 80, "height": 12, "tick_fps": 30,
+        "items": [item("1", "First post"), item("2", "Second post")],
+        "channels": [], "ui_state": {},
+    }});
+    let events = [
+        serde_json::json!({"event": {"at": 10, "event": {"Keyboard": "Down"}}}),
+        serde_json::json!({"event": {"at": 20, "event": {"Keyboard": "Help"}}}),
+    ];
+    let recording = env.path().join("session.jsonl");
+    let lines: Vec<_> = [start]
+        .iter()
+        .chain(&events)
+        .map(Value::to_string)
+        .collect();
+    fs::write(&recording, lines.join("\n")).unwrap();
+    let recording = recording.to_str().unwrap();
+
+    let out = env.run_ok(&["replay", recording]);
+    assert_eq!(out.lines().count(), 12);
+    assert!(out.contains("Show this help"));
+
+    let out = env.run_ok(&["replay", recording, "--events", "1"]);
+    assert!(out.contains("Second post"));
+    assert!(!out.contains("Show this help"));
+
+    let output = env.run(&["replay", env.path().to_str().unwrap()]);
+    assert!(!output.status.success());
+}